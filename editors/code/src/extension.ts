@@ -8,8 +8,10 @@ import {
 
 import * as Settings from "./settings";
 import { FppProject } from "./project";
+import { IncludeContextStatus } from "./include_context";
 import { locs, LocsQuickPickFile, LocsQuickPickItem, LocsQuickPickType } from "./locs";
-import { dumpSyntaxTree } from "./lsp_ext";
+import * as lc from "vscode-languageclient";
+import { dumpSyntaxTree, frameworkFile, topologyGraph } from "./lsp_ext";
 
 let extension: FppExtension;
 
@@ -20,6 +22,7 @@ class FppExtension implements vscode.Disposable {
 
     client?: LanguageClient;
     project: FppProject;
+    includeContext: IncludeContextStatus;
 
     constructor(
         private readonly context: vscode.ExtensionContext
@@ -27,6 +30,7 @@ class FppExtension implements vscode.Disposable {
         this.outputChannel = vscode.window.createOutputChannel("FPP");
         this.traceOutputChannel = vscode.window.createOutputChannel("FPP Trace", { log: true });
         this.project = new FppProject({ language: "fpp" });
+        this.includeContext = new IncludeContextStatus(() => this.client);
 
         this.subscriptions = [
             Settings.onLspServerPathChanged(() => {
@@ -34,6 +38,7 @@ class FppExtension implements vscode.Disposable {
             }),
             this.outputChannel,
             this.traceOutputChannel,
+            this.includeContext,
         ];
     }
 
@@ -114,6 +119,7 @@ class FppExtension implements vscode.Disposable {
     async reload() {
         if (this.client) {
             await this.project.reload(this.client);
+            await this.includeContext.refresh();
         }
     }
 
@@ -257,6 +263,9 @@ export async function activate(context: vscode.ExtensionContext) {
                 }
             });
         }),
+        vscode.commands.registerCommand('fpp.switchIncludeContext', async () => {
+            await extension.includeContext.switch();
+        }),
         vscode.commands.registerCommand('fpp.close', async () => {
             await extension.setProjectLocs(undefined);
         }),
@@ -285,6 +294,36 @@ export async function activate(context: vscode.ExtensionContext) {
         }),
         Settings.onLspServerLogLevelChanged(() => {
             extension.initializeClient();
+        }),
+        // Commands run by the code lenses of the language server
+        vscode.commands.registerCommand('fpp.showReferences', (uri: string, position: lc.Position, locations: lc.Location[]) => {
+            const client = extension.client;
+            if (!client) {
+                return;
+            }
+            return vscode.commands.executeCommand(
+                'editor.action.showReferences',
+                vscode.Uri.parse(uri),
+                client.protocol2CodeConverter.asPosition(position),
+                locations.map(client.protocol2CodeConverter.asLocation)
+            );
+        }),
+        vscode.commands.registerCommand('fpp.showTopologyGraph', async (topology: string) => {
+            if (!extension.client) {
+                return;
+            }
+            const content = await extension.client.sendRequest(topologyGraph, { topology });
+            const document = await vscode.workspace.openTextDocument({ language: "mermaid", content });
+            await vscode.window.showTextDocument(document, vscode.ViewColumn.Beside);
+        }),
+        // Definitions in the bundled F Prime framework live in virtual documents
+        vscode.workspace.registerTextDocumentContentProvider("fpp-framework", {
+            provideTextDocumentContent: async (uri: vscode.Uri) => {
+                if (!extension.client) {
+                    return undefined;
+                }
+                return extension.client.sendRequest(frameworkFile, { uri: uri.toString() });
+            }
         })
     );
 
@@ -0,0 +1,109 @@
+import * as vscode from 'vscode';
+import { LanguageClient } from 'vscode-languageclient/node';
+
+import { IncludeContext, includeContexts, setIncludeContext } from './lsp_ext';
+
+interface IncludeContextQuickPickItem extends vscode.QuickPickItem {
+    context: IncludeContext;
+}
+
+/**
+ * Shows which file an `.fppi` file included in several places is parsed
+ * and completed in, and lets the user switch to another includer
+ */
+export class IncludeContextStatus implements vscode.Disposable {
+    private item: vscode.LanguageStatusItem;
+    private subscriptions: vscode.Disposable[];
+
+    constructor(private readonly client: () => LanguageClient | undefined) {
+        this.item = vscode.languages.createLanguageStatusItem(
+            'fpp.includeContext',
+            { language: 'fpp', pattern: '**/*.fppi' }
+        );
+        this.item.name = "FPP Include Context";
+        this.item.command = { title: "Switch", command: "fpp.switchIncludeContext" };
+
+        this.subscriptions = [
+            this.item,
+            vscode.window.onDidChangeActiveTextEditor(() => this.refresh()),
+        ];
+
+        this.refresh();
+    }
+
+    private async contexts(document: vscode.TextDocument): Promise<IncludeContext[]> {
+        const client = this.client();
+        if (!client) {
+            return [];
+        }
+        return client.sendRequest(includeContexts, { uri: document.uri.toString() });
+    }
+
+    async refresh() {
+        const document = vscode.window.activeTextEditor?.document;
+        if (!document || document.languageId !== 'fpp') {
+            return;
+        }
+
+        let contexts: IncludeContext[] = [];
+        try {
+            contexts = await this.contexts(document);
+        } catch (e) {
+            console.error(e);
+        }
+
+        const active = contexts.find((context) => context.active);
+        if (!active) {
+            this.item.text = "Not included in the project";
+            this.item.detail = undefined;
+            this.item.severity = vscode.LanguageStatusSeverity.Warning;
+        } else {
+            this.item.text = `Included from ${active.label}`;
+            this.item.detail = contexts.length > 1 ? `${contexts.length} include contexts` : undefined;
+            this.item.severity = vscode.LanguageStatusSeverity.Information;
+        }
+    }
+
+    async switch() {
+        const client = this.client();
+        const document = vscode.window.activeTextEditor?.document;
+        if (!client || !document || document.languageId !== 'fpp') {
+            return;
+        }
+
+        const contexts = await this.contexts(document);
+        if (contexts.length === 0) {
+            vscode.window.showInformationMessage("This file is not included in the FPP project");
+            return;
+        }
+
+        const picked = await vscode.window.showQuickPick(
+            contexts.map((context) => ({
+                label: context.label,
+                description: context.active ? '(Active)' : undefined,
+                detail: context.includers
+                    .map((uri) => vscode.workspace.asRelativePath(vscode.Uri.parse(uri)))
+                    .join(' › '),
+                context
+            } as IncludeContextQuickPickItem)),
+            {
+                title: 'Select the file this file is analyzed as included from',
+                canPickMany: false,
+            }
+        );
+
+        if (picked) {
+            await client.sendRequest(setIncludeContext, {
+                uri: document.uri.toString(),
+                includers: picked.context.includers
+            });
+            await this.refresh();
+        }
+    }
+
+    dispose() {
+        for (const s of this.subscriptions) {
+            s.dispose();
+        }
+    }
+}
@@ -7,6 +7,25 @@ interface UriRequest {
 export const reloadWorkspace = new lc.RequestType0<void, void>("fpp/reloadWorkspace");
 export const setLocsWorkspace = new lc.RequestType<UriRequest, void, void>("fpp/setLocsWorkspace");
 export const setFullWorkspace = new lc.RequestType0<void, void>("fpp/setFullWorkspace");
+export const frameworkFile = new lc.RequestType<UriRequest, string, void>("fpp/frameworkFile");
+
+export type TopologyGraphParams = {
+    topology: string
+};
+export const topologyGraph = new lc.RequestType<TopologyGraphParams, string, void>("fpp/topologyGraph");
+
+export type IncludeContext = {
+    includers: lc.URI[],
+    label: string,
+    active: boolean
+};
+export const includeContexts = new lc.RequestType<UriRequest, IncludeContext[], void>("fpp/includeContexts");
+
+export type SetIncludeContextParams = {
+    uri: lc.URI,
+    includers: lc.URI[]
+};
+export const setIncludeContext = new lc.RequestType<SetIncludeContextParams, void, void>("fpp/setIncludeContext");
 
 export type DumpSyntaxTree = {
     uri: lc.URI
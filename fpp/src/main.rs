@@ -1,31 +1,544 @@
+use clap::{Parser, Subcommand, ValueEnum};
+use fpp_analysis::semantics::{LintConfig, LintLevel};
+use fpp_core::{DiagnosticEmitter, FileReader, SearchPaths, Spanned};
+use fpp_fs::{framework_files, FrameworkReader, FsReader, Manifest, FRAMEWORK_PREFIX};
 use std::io::Read;
+use std::path::{Path, PathBuf};
 use std::process::exit;
 
-fn compiler_main() -> String {
-    let mut stdin = String::new();
-    std::io::stdin()
-        .read_to_string(&mut stdin)
-        .expect("Failed to read input stream");
+#[derive(ValueEnum, Clone, Copy, Debug, Default)]
+enum ErrorFormat {
+    /// Rendered source snippets for a terminal
+    #[default]
+    Human,
+    /// One JSON object per diagnostic on each line of stderr
+    Json,
+    /// A SARIF 2.1.0 log written to stderr
+    Sarif,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, Default)]
+enum DocFormat {
+    /// Markdown pages
+    #[default]
+    Markdown,
+    /// Standalone HTML pages
+    Html,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, Default)]
+enum GraphFormat {
+    /// Graphviz DOT
+    #[default]
+    Dot,
+    /// Mermaid flowcharts and state diagrams
+    Mermaid,
+    /// The nodes and edges of each graph as JSON
+    Json,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, Default)]
+enum DiffFormat {
+    /// One line per change
+    #[default]
+    Text,
+    /// A JSON report with the number of breaking and compatible changes
+    Json,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Print a detailed explanation of a diagnostic code such as E0102
+    Explain { code: String },
+    /// Generate Rust modules for the types and ports in the input
+    ToRust,
+    /// Generate a Python module with the types in the input and the
+    /// dictionary identifiers of each topology
+    ToPython,
+    /// Write a documentation site with a page per module, component and topology
+    Doc {
+        /// Directory the pages are written to, `doc` by default
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        /// Format of the pages
+        #[arg(long, value_enum, default_value_t)]
+        format: DocFormat,
+    },
+    /// Write a graph of every topology and state machine
+    Graph {
+        /// Directory the graphs are written to, `graph` by default
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        /// Format of the graphs
+        #[arg(long, value_enum, default_value_t)]
+        format: GraphFormat,
+        /// Only draw the connections of the given connection graph, like `Health`
+        #[arg(long = "connections", value_name = "GRAPH")]
+        connections: Vec<String>,
+    },
+    /// Compare two versions of a model and report the changes that break
+    /// compatibility of the dictionaries and serialized data. Exits with an
+    /// error if there is a breaking change.
+    Diff {
+        /// Files of the old version of the model
+        #[arg(long, num_args = 1.., required = true)]
+        old: Vec<PathBuf>,
+        /// Files of the new version of the model
+        #[arg(long, num_args = 1.., required = true)]
+        new: Vec<PathBuf>,
+        /// Format of the report written to stdout
+        #[arg(long, value_enum, default_value_t)]
+        format: DiffFormat,
+    },
+    /// Translate F Prime XML components, ports, serializables, enums and
+    /// arrays into FPP. The translation is checked along with the imported
+    /// FPP files and written even if the check reports errors.
+    FromXml {
+        /// The `*Ai.xml` files to translate
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+        /// Directory the FPP files are written to, the current directory by default
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        /// FPP files defining what the XML refers to, the project files by default
+        #[arg(short, long, num_args = 1..)]
+        imports: Vec<PathBuf>,
+    },
+}
+
+#[derive(Parser, Debug)]
+#[command(version, author)]
+struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+    /// Format used to report diagnostics
+    #[arg(long, value_enum, default_value_t)]
+    error_format: ErrorFormat,
+    /// Do not report the given lint
+    #[arg(short = 'A', long = "allow", value_name = "LINT")]
+    allow: Vec<String>,
+    /// Report the given lint as a warning
+    #[arg(short = 'W', long = "warn", value_name = "LINT")]
+    warn: Vec<String>,
+    /// Report the given lint as an error
+    #[arg(short = 'D', long = "deny", value_name = "LINT")]
+    deny: Vec<String>,
+    /// Report ports whose arguments serialize to more than the given number of bytes
+    #[arg(long, value_name = "BYTES")]
+    max_port_buffer_size: Option<usize>,
+    /// Report definitions, instances and topologies that are never used
+    #[arg(long)]
+    check_unused: bool,
+    /// Check definitions against a style rule `<name>=<kind>:<check>`, like
+    /// `instance-case=instance:lowerCamelCase` or `command-docs=command:annotated`
+    #[arg(long = "style", value_name = "RULE")]
+    style: Vec<String>,
+    /// Search the given directory for included and located files not found
+    /// next to the file referring to them
+    #[arg(short = 'I', long = "include-path", value_name = "DIR")]
+    include_path: Vec<PathBuf>,
+    /// Replace the prefix of included and located paths by a directory
+    #[arg(long, value_name = "PREFIX=DIR", value_parser = parse_remap)]
+    remap: Vec<(String, PathBuf)>,
+    /// Add the F Prime framework definitions bundled with the tool to the
+    /// model, replacing the framework locations of the project
+    #[arg(long)]
+    builtin_framework: bool,
+    /// Only use the bundled framework definitions when none of the framework
+    /// locations of the project exist
+    #[arg(long)]
+    prefer_disk_framework: bool,
+    /// Project manifest to read the model, lint levels and output directories
+    /// from instead of reading the model from stdin
+    #[arg(long, value_name = "FPP_TOML")]
+    manifest: Option<PathBuf>,
+    /// The loaded project manifest
+    #[arg(skip)]
+    project: Option<Manifest>,
+}
+
+impl Args {
+    fn lint_config(&self) -> Result<LintConfig, String> {
+        let mut config = LintConfig::default();
+
+        // Levels given on the command line override the ones of the project
+        if let Some(project) = &self.project {
+            for (names, level) in [
+                (&project.lints.allow, LintLevel::Allow),
+                (&project.lints.warn, LintLevel::Warn),
+                (&project.lints.deny, LintLevel::Deny),
+            ] {
+                for name in names {
+                    config.set(name, level)?;
+                }
+            }
+
+            for rule in &project.lints.style {
+                config.style_rules.push(rule.parse()?);
+            }
+        }
+
+        for (names, level) in [
+            (&self.allow, LintLevel::Allow),
+            (&self.warn, LintLevel::Warn),
+            (&self.deny, LintLevel::Deny),
+        ] {
+            for name in names {
+                config.set(name, level)?;
+            }
+        }
+
+        for rule in &self.style {
+            config.style_rules.push(rule.parse()?);
+        }
+
+        Ok(config)
+    }
+
+    /// Where included and located files are searched, first the directories
+    /// given on the command line and then the ones of the project
+    fn search_paths(&self) -> SearchPaths {
+        let mut search_paths = SearchPaths {
+            include: self.include_path.clone(),
+            remap: self.remap.clone(),
+        };
 
-    let src = fpp_core::SourceFile::new("<stdin>", stdin);
-    let mut ast = fpp_parser::parse(src, |p| p.trans_unit(), None);
+        if let Some(project) = &self.project {
+            let project = project.search_paths();
+            search_paths.include.extend(project.include);
+            search_paths.remap.extend(project.remap);
+        }
 
+        search_paths
+    }
+
+    /// Whether the bundled framework definitions are added to the model
+    fn uses_builtin_framework(&self) -> bool {
+        match &self.project {
+            Some(project) => project.uses_builtin_framework(),
+            None => self.builtin_framework,
+        }
+    }
+
+    /// The output directory given on the command line, or else the one
+    /// configured in the project, or else `default`
+    fn output_dir(
+        &self,
+        given: &Option<PathBuf>,
+        configured: impl FnOnce(&Manifest) -> Option<PathBuf>,
+        default: &str,
+    ) -> PathBuf {
+        given
+            .clone()
+            .or_else(|| self.project.as_ref().and_then(configured))
+            .unwrap_or_else(|| PathBuf::from(default))
+    }
+}
+
+fn parse_remap(remap: &str) -> Result<(String, PathBuf), String> {
+    match remap.split_once('=') {
+        Some((prefix, dir)) if !prefix.is_empty() => Ok((prefix.to_string(), PathBuf::from(dir))),
+        _ => Err(format!("expected `PREFIX=DIR`, found `{}`", remap)),
+    }
+}
+
+/// Parse and analyze source files as one model
+fn analyze(
+    args: &Args,
+    lint_config: LintConfig,
+    sources: Vec<fpp_core::SourceFile>,
+) -> (fpp_analysis::Analysis, Vec<fpp_ast::TransUnit>) {
     let mut a = fpp_analysis::Analysis::new();
+    a.lint_config = lint_config;
+    a.max_port_buffer_size = args.max_port_buffer_size.or_else(|| {
+        args.project
+            .as_ref()
+            .and_then(|project| project.lints.max_port_buffer_size)
+    });
+    a.check_unused = args.check_unused
+        || args
+            .project
+            .as_ref()
+            .is_some_and(|project| project.lints.check_unused);
+    a.framework_dirs = std::iter::once(FRAMEWORK_PREFIX.to_string())
+        .chain(
+            args.project
+                .iter()
+                .flat_map(|project| &project.framework)
+                .map(|path| path.display().to_string()),
+        )
+        .collect();
 
-    let _ = fpp_analysis::resolve_includes(&mut a, fpp_fs::FsReader {}, &mut ast);
-    let _ = fpp_analysis::check_semantics(&mut a, vec![&ast]);
+    let mut sources = sources;
+    if args.uses_builtin_framework() {
+        let reader = FrameworkReader::new(FsReader::default());
+        for uri in framework_files() {
+            match reader.read(&uri) {
+                Ok(content) => sources.push(fpp_core::SourceFile::new(&uri, content)),
+                Err(err) => eprintln!("error: {}", err),
+            }
+        }
+    }
+
+    let mut asts = vec![];
+    for src in sources {
+        let mut ast = fpp_parser::parse(src, |p| p.trans_unit(), None);
+        let reader = FrameworkReader::new(FsReader::new(args.search_paths()));
+        let _ = fpp_analysis::resolve_includes(&mut a, reader, &mut ast);
+        asts.push(ast);
+    }
+
+    let _ = fpp_analysis::check_semantics(&mut a, asts.iter().collect());
+    (a, asts)
+}
 
-    format!("{:#?}", ast)
+fn read_sources(paths: &[PathBuf]) -> Vec<fpp_core::SourceFile> {
+    paths
+        .iter()
+        .map(|path| match std::fs::read_to_string(path) {
+            Ok(content) => fpp_core::SourceFile::new(&path.display().to_string(), content),
+            Err(err) => {
+                eprintln!("error: failed to read {}: {}", path.display(), err);
+                exit(1)
+            }
+        })
+        .collect()
+}
+
+/// The files of a project: the files under its source roots and framework
+/// locations, and the files located by its locs files
+fn project_sources(args: &Args, project: &Manifest) -> Vec<fpp_core::SourceFile> {
+    let mut paths = project.source_files();
+
+    // The same file may be both under a source root and located
+    let mut seen: Vec<PathBuf> = paths
+        .iter()
+        .map(|path| path.canonicalize().unwrap_or(path.clone()))
+        .collect();
+
+    let reader = FsReader::new(args.search_paths());
+    for locs in read_sources(&project.locs) {
+        let ast = fpp_parser::parse(locs, |p| p.trans_unit(), None);
+        for member in ast.0 {
+            let loc = match member {
+                fpp_ast::ModuleMember::SpecLoc(loc) => loc,
+                _ => continue,
+            };
+
+            match reader.find(locs, &loc.file.data) {
+                Ok((path, _)) => {
+                    let path = PathBuf::from(path);
+                    let canonical = path.canonicalize().unwrap_or(path.clone());
+                    if !seen.contains(&canonical) {
+                        seen.push(canonical);
+                        paths.push(path);
+                    }
+                }
+                Err(err) => err.diagnostic(loc.file.span(), &loc.file.data).emit(),
+            }
+        }
+    }
+
+    read_sources(&paths)
+}
+
+/// Run the command, returning its output and whether it failed
+fn compiler_main(args: &Args, lint_config: LintConfig) -> (String, bool) {
+    if let Some(Command::Diff { old, new, format }) = &args.command {
+        let (old, _old_ast) = analyze(args, lint_config.clone(), read_sources(old));
+        let (new, _new_ast) = analyze(args, lint_config, read_sources(new));
+        let changes = fpp_codegen::diff::diff(&old, &new);
+        let breaking = changes
+            .iter()
+            .any(|change| change.compatibility == fpp_codegen::diff::Compatibility::Breaking);
+        return (diff_report(&changes, *format), breaking);
+    }
+
+    if let Some(Command::FromXml {
+        files,
+        output,
+        imports,
+    }) = &args.command
+    {
+        let output = args.output_dir(output, |project| project.output.from_xml.clone(), ".");
+        let mut pages = vec![];
+        for (path, src) in files.iter().zip(read_sources(files)) {
+            if let Some(content) = fpp_codegen::xml::from_xml(src) {
+                pages.push(fpp_codegen::Page {
+                    path: fpp_codegen::xml::fpp_file_name(path),
+                    content,
+                });
+            }
+        }
+
+        let mut sources = match &args.project {
+            Some(project) if imports.is_empty() => project_sources(args, project),
+            _ => read_sources(imports),
+        };
+        sources.extend(pages.iter().map(|page| {
+            let path = output.join(&page.path);
+            fpp_core::SourceFile::new(&path.display().to_string(), page.content.clone())
+        }));
+        let _ = analyze(args, lint_config, sources);
+        return (write_pages(&output, pages), false);
+    }
+
+    let sources = match &args.project {
+        Some(project) => project_sources(args, project),
+        None => {
+            let mut stdin = String::new();
+            std::io::stdin()
+                .read_to_string(&mut stdin)
+                .expect("Failed to read input stream");
+
+            vec![fpp_core::SourceFile::new("<stdin>", stdin)]
+        }
+    };
+
+    let (a, asts) = analyze(args, lint_config, sources);
+    let ast: Vec<&fpp_ast::TransUnit> = asts.iter().collect();
+
+    let out = match &args.command {
+        Some(Command::ToRust) => fpp_codegen::rust::generate(&a, &ast),
+        Some(Command::ToPython) => fpp_codegen::python::generate(&a, &ast),
+        Some(Command::Doc { output, format }) => {
+            let output = args.output_dir(output, |project| project.output.doc.clone(), "doc");
+            let format = match format {
+                DocFormat::Markdown => fpp_codegen::doc::DocFormat::Markdown,
+                DocFormat::Html => fpp_codegen::doc::DocFormat::Html,
+            };
+            write_pages(&output, fpp_codegen::doc::generate(&a, &ast, format))
+        }
+        Some(Command::Graph {
+            output,
+            format,
+            connections,
+        }) => {
+            let output = args.output_dir(output, |project| project.output.graph.clone(), "graph");
+            let format = match format {
+                GraphFormat::Dot => fpp_codegen::graph::GraphFormat::Dot,
+                GraphFormat::Mermaid => fpp_codegen::graph::GraphFormat::Mermaid,
+                GraphFormat::Json => fpp_codegen::graph::GraphFormat::Json,
+            };
+            let pages = fpp_codegen::graph::generate(&a, &ast, format, connections);
+            write_pages(&output, pages)
+        }
+        _ => ast
+            .iter()
+            .map(|ast| format!("{:#?}", ast))
+            .collect::<Vec<_>>()
+            .join("\n"),
+    };
+
+    (out, false)
+}
+
+/// Render the changes between two versions of a model
+fn diff_report(changes: &[fpp_codegen::diff::Change], format: DiffFormat) -> String {
+    match format {
+        DiffFormat::Text => changes
+            .iter()
+            .map(|change| change.to_string())
+            .collect::<Vec<_>>()
+            .join("\n"),
+        DiffFormat::Json => fpp_codegen::diff::to_json(changes),
+    }
+}
+
+/// Write generated files into the `output` directory, returning the list of files written
+fn write_pages(output: &Path, pages: Vec<fpp_codegen::Page>) -> String {
+    if let Err(err) = std::fs::create_dir_all(output) {
+        eprintln!("error: failed to create {}: {}", output.display(), err);
+        exit(1)
+    }
+
+    let mut written = vec![];
+    for page in pages {
+        let path = output.join(&page.path);
+        if let Err(err) = std::fs::write(&path, page.content) {
+            eprintln!("error: failed to write {}: {}", path.display(), err);
+            exit(1)
+        }
+        written.push(path.display().to_string());
+    }
+
+    written.join("\n")
+}
+
+fn run<E: DiagnosticEmitter>(
+    diagnostics: E,
+    args: &Args,
+    lint_config: LintConfig,
+) -> (String, bool) {
+    let mut ctx = fpp_core::CompilerContext::new(diagnostics);
+    fpp_core::run(&mut ctx, || compiler_main(args, lint_config))
+}
+
+fn explain(code: &str) {
+    match fpp_errors::explain(code) {
+        Some(explanation) => print!("{}", explanation),
+        None => {
+            eprintln!("error: `{}` is not a valid diagnostic code", code);
+            exit(1)
+        }
+    }
 }
 
 fn main() {
-    let mut diagnostics = fpp_errors::ConsoleEmitter::color();
-    let mut ctx = fpp_core::CompilerContext::new(&mut diagnostics);
-    let out = fpp_core::run(&mut ctx, compiler_main);
+    let mut args = Args::parse();
+
+    if let Some(Command::Explain { code }) = &args.command {
+        return explain(code);
+    }
+
+    if let Some(path) = &args.manifest {
+        match Manifest::load(path) {
+            Ok(mut project) => {
+                project.builtin_framework |= args.builtin_framework;
+                project.prefer_disk_framework |= args.prefer_disk_framework;
+                args.project = Some(project)
+            }
+            Err(err) => {
+                eprintln!("error: {}", err);
+                exit(1)
+            }
+        }
+    }
 
-    if diagnostics.has_errors() {
+    let lint_config = match args.lint_config() {
+        Ok(lint_config) => lint_config,
+        Err(err) => {
+            eprintln!("error: {}", err);
+            exit(1)
+        }
+    };
+
+    let ((out, failed), has_errors) = match args.error_format {
+        ErrorFormat::Human => {
+            let mut diagnostics = fpp_errors::ConsoleEmitter::color();
+            let out = run(&mut diagnostics, &args, lint_config);
+            (out, diagnostics.has_errors())
+        }
+        ErrorFormat::Json => {
+            let mut diagnostics = fpp_errors::JsonEmitter::new(std::io::stderr());
+            let out = run(&mut diagnostics, &args, lint_config);
+            (out, diagnostics.has_errors())
+        }
+        ErrorFormat::Sarif => {
+            let mut diagnostics = fpp_errors::SarifEmitter::new(std::io::stderr());
+            let out = run(&mut diagnostics, &args, lint_config);
+            let has_errors = diagnostics.has_errors();
+            diagnostics.finish().expect("failed to write diagnostics");
+            (out, has_errors)
+        }
+    };
+
+    if has_errors {
         exit(1)
     }
 
     println!("{}", out);
+    if failed {
+        exit(1)
+    }
 }
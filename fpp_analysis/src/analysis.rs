@@ -1,6 +1,7 @@
 use crate::errors::SemanticResult;
 use crate::semantics::{
-    NameGroup, NestedScope, Scope, Symbol, SymbolInterface, Type, UseDefMatching, Value,
+    LintAttribute, LintConfig, NameGroup, NestedScope, ProjectLintConfig, Scope, Symbol,
+    SymbolInterface, Type, UseDefMatching, Value,
 };
 use fpp_core::SourceFile;
 use rustc_hash::{FxHashMap as HashMap, FxHashSet as HashSet};
@@ -37,6 +38,20 @@ pub struct Analysis {
     pub type_map: HashMap<fpp_core::Node, Arc<Type>>,
     /** The mapping from constant symbols and expressions to their values. */
     pub value_map: HashMap<fpp_core::Node, Value>,
+    /** The globally configured lint levels */
+    pub lint_config: LintConfig,
+    /** The lint settings of each project, replacing the global ones in
+     *  the files of the project */
+    pub project_lint_configs: Vec<ProjectLintConfig>,
+    /** The lint levels set by annotations on definitions */
+    pub lint_attributes: Vec<LintAttribute>,
+    /** The maximum serialized size of port arguments, if it should be checked */
+    pub max_port_buffer_size: Option<usize>,
+    /** Whether definitions that are never used should be reported */
+    pub check_unused: bool,
+    /** The URIs of the directories and files holding the F Prime framework,
+     *  whose definitions are never reported as unused */
+    pub framework_dirs: Vec<String>,
 }
 
 impl Analysis {
@@ -59,6 +74,12 @@ impl Analysis {
             include_context_map: Default::default(),
             type_map: Default::default(),
             value_map: Default::default(),
+            lint_config: Default::default(),
+            project_lint_configs: vec![],
+            lint_attributes: vec![],
+            max_port_buffer_size: None,
+            check_unused: false,
+            framework_dirs: vec![],
         }
     }
 
@@ -1,7 +1,7 @@
 use crate::analyzers::analyzer::Analyzer;
 use crate::analyzers::nested_analyzer::{NestedAnalyzer, NestedAnalyzerMode};
 use crate::analyzers::NestedScopeState;
-use crate::semantics::{ImpliedUse, QualifiedName};
+use crate::semantics::{special_port_name, ImpliedUse, QualifiedName};
 use fpp_ast::*;
 use std::collections::VecDeque;
 use std::marker::PhantomData;
@@ -188,22 +188,7 @@ impl<'ast, S: NestedScopeState, V: UseAnalysisPass<'ast, S>> Analyzer<'ast, V>
                 self.super_.visit(visitor, a, node)
             }
             Node::SpecSpecialPortInstance(pi) => {
-                let name = (match pi.kind {
-                    SpecialPortInstanceKind::CommandRecv => "Cmd",
-                    SpecialPortInstanceKind::CommandReg => "CmdReg",
-                    SpecialPortInstanceKind::CommandResp => "CmdResponse",
-                    SpecialPortInstanceKind::Event => "Log",
-                    SpecialPortInstanceKind::ParamGet => "PrmGet",
-                    SpecialPortInstanceKind::ParamSet => "PrmSet",
-                    SpecialPortInstanceKind::ProductGet => "DpGet",
-                    SpecialPortInstanceKind::ProductRecv => "DpResponse",
-                    SpecialPortInstanceKind::ProductRequest => "DpRequest",
-                    SpecialPortInstanceKind::ProductSend => "DpSend",
-                    SpecialPortInstanceKind::Telemetry => "Tlm",
-                    SpecialPortInstanceKind::TextEvent => "LogText",
-                    SpecialPortInstanceKind::TimeGet => "Time",
-                })
-                .to_string();
+                let name = special_port_name(&pi.kind).to_string();
 
                 let port_qi = ImpliedUse::new(vec!["Fw".to_string(), name].into(), pi.node_id)
                     .as_qual_ident();
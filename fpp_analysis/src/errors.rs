@@ -1,4 +1,4 @@
-use crate::semantics::TypeConversionError;
+use crate::semantics::{Suggestion, TypeConversionError};
 use fpp_core::{Diagnostic, Level, Span};
 
 #[derive(Debug)]
@@ -21,6 +21,8 @@ pub enum SemanticError {
         ng: String,
         name: String,
         loc: Span,
+        /// Definitions the user may have meant
+        suggestions: Vec<Suggestion>,
     },
     UseDefCycle {
         loc: Span,
@@ -31,6 +33,8 @@ pub enum SemanticError {
         msg: String,
         loc: Span,
         def_loc: Span,
+        /// Definitions the user may have meant
+        suggestions: Vec<Suggestion>,
     },
     InvalidType {
         loc: Span,
@@ -98,6 +102,30 @@ pub enum SemanticError {
 pub type SemanticResult<T = ()> = Result<T, SemanticError>;
 
 impl SemanticError {
+    /// Stable code identifying this kind of error, see `fpp explain`
+    pub fn code(&self) -> &'static str {
+        match self {
+            SemanticError::RedefinedSymbol { .. } => "E0101",
+            SemanticError::UndefinedSymbol { .. } => "E0102",
+            SemanticError::InvalidSymbol { .. } => "E0103",
+            SemanticError::UseDefCycle { .. } => "E0104",
+            SemanticError::InvalidType { .. } => "E0201",
+            SemanticError::DuplicateStructMember { .. } => "E0202",
+            SemanticError::TypeConversion { .. } => "E0203",
+            SemanticError::EmptyArray { .. } => "E0204",
+            SemanticError::ArrayDefaultMismatchedSize { .. } => "E0205",
+            SemanticError::EnumConstantShouldBeImplied { .. } => "E0301",
+            SemanticError::EnumConstantShouldBeExplicit { .. } => "E0302",
+            SemanticError::DuplicateEnumConstant { .. } => "E0303",
+            SemanticError::InvalidIntValue { .. } => "E0401",
+            SemanticError::DivisionByZero { .. } => "E0402",
+            SemanticError::InvalidTypeForMemberSelection { .. } => "E0403",
+            SemanticError::FormatStringMismatchLength { .. } => "E0501",
+            SemanticError::FormatStringInvalidReplacement { .. } => "E0502",
+            SemanticError::FormatStringInvalidPrecision { .. } => "E0503",
+        }
+    }
+
     pub fn emit(self) {
         Into::<Diagnostic>::into(self).emit();
     }
@@ -105,7 +133,8 @@ impl SemanticError {
 
 impl Into<Diagnostic> for SemanticError {
     fn into(self) -> Diagnostic {
-        match self {
+        let code = self.code();
+        let diag = match self {
             SemanticError::RedefinedSymbol {
                 name,
                 loc,
@@ -116,18 +145,30 @@ impl Into<Diagnostic> for SemanticError {
                 format!("redefinition of symbol {}", name),
             )
             .span_note(prev_loc, "previous definition is here"),
-            SemanticError::UndefinedSymbol { ng, name, loc } => Diagnostic::new(
+            SemanticError::UndefinedSymbol {
+                ng,
+                name,
                 loc,
-                Level::Error,
-                format!("cannot find {} `{}` in scope", ng, name),
+                suggestions,
+            } => Suggestion::annotate(
+                suggestions,
+                Diagnostic::new(
+                    loc,
+                    Level::Error,
+                    format!("cannot find {} `{}` in scope", ng, name),
+                ),
             ),
             SemanticError::InvalidSymbol {
                 symbol_name,
                 msg,
                 loc,
                 def_loc,
-            } => Diagnostic::new(loc, Level::Error, msg)
-                .span_note(def_loc, format!("{} defined here", symbol_name)),
+                suggestions,
+            } => Suggestion::annotate(
+                suggestions,
+                Diagnostic::new(loc, Level::Error, msg)
+                    .span_note(def_loc, format!("{} defined here", symbol_name)),
+            ),
             SemanticError::UseDefCycle { loc, cycle } => cycle.iter().enumerate().fold(
                 Diagnostic::new(loc, Level::Error, "encountered symbol use-definition cycle"),
                 |out, (i, suse)| match i {
@@ -246,6 +287,8 @@ impl Into<Diagnostic> for SemanticError {
             )
             .note(format!("value size `{}`", value_size))
             .span_note(size_loc, format!("array size `{}`", type_size)),
-        }
+        };
+
+        diag.code(code)
     }
 }
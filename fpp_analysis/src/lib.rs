@@ -2,7 +2,8 @@ mod analysis;
 mod errors;
 
 use crate::passes::{
-    CheckExprTypes, CheckTypeUses, CheckUseDefCycles, CheckUses, EnterSymbols, EvalConstantExprs,
+    CheckExprTypes, CheckLintAttributes, CheckPortBufferSizes, CheckStyle, CheckTypeUses,
+    CheckUnusedDefs, CheckUseDefCycles, CheckUses, EnterSymbols, EvalConstantExprs,
     EvalImpliedEnumConsts, FinalizeTypeDefs,
 };
 pub use analysis::*;
@@ -25,6 +26,9 @@ pub mod analyzers {
 }
 
 pub mod passes {
+    mod check_lint_attributes;
+    pub use check_lint_attributes::*;
+
     mod enter_symbols;
     pub use enter_symbols::*;
 
@@ -48,6 +52,15 @@ pub mod passes {
 
     mod finalize_type_defs;
     pub use finalize_type_defs::*;
+
+    mod check_port_buffer_sizes;
+    pub use check_port_buffer_sizes::*;
+
+    mod check_unused_defs;
+    pub use check_unused_defs::*;
+
+    mod check_style;
+    pub use check_style::*;
 }
 
 pub mod semantics {
@@ -78,6 +91,26 @@ pub mod semantics {
     mod format;
     pub use format::*;
 
+    mod serialize;
+    pub use serialize::*;
+
+    mod render;
+
+    mod dictionary;
+    pub use dictionary::*;
+
+    mod connections;
+    pub use connections::*;
+
+    mod suggestion;
+    pub use suggestion::*;
+
+    mod lint;
+    pub use lint::*;
+
+    mod style;
+    pub use style::*;
+
     mod generic_name_symbol_map;
     mod generic_nested_scope;
     mod generic_scope;
@@ -95,6 +128,7 @@ pub fn resolve_includes<Reader: FileReader>(
 }
 
 pub fn check_semantics(a: &mut Analysis, ast: Vec<&fpp_ast::TransUnit>) -> ControlFlow<()> {
+    CheckLintAttributes::new().visit_trans_units(a, ast.iter().cloned())?;
     EnterSymbols::new().visit_trans_units(a, ast.iter().cloned())?;
     CheckUses::new().visit_trans_units(a, ast.iter().cloned())?;
     CheckUseDefCycles::new().visit_trans_units(a, ast.iter().cloned())?;
@@ -103,6 +137,13 @@ pub fn check_semantics(a: &mut Analysis, ast: Vec<&fpp_ast::TransUnit>) -> Contr
     EvalImpliedEnumConsts::new().visit_trans_units(a, ast.iter().cloned())?;
     EvalConstantExprs::new().visit_trans_units(a, ast.iter().cloned())?;
     FinalizeTypeDefs::new().visit_trans_units(a, ast.iter().cloned())?;
+    CheckPortBufferSizes::new().visit_trans_units(a, ast.iter().cloned())?;
+    if a.has_unused_checks() {
+        CheckUnusedDefs::new(a).visit_trans_units(a, ast.iter().cloned())?;
+    }
+    if a.has_style_rules() {
+        CheckStyle::new().visit_trans_units(a, ast.iter().cloned())?;
+    }
 
     ControlFlow::Continue(())
 }
@@ -3,16 +3,16 @@ use crate::analyzers::basic_use_analyzer::UseAnalysisPass;
 use crate::analyzers::use_analyzer::UseAnalyzer;
 use crate::errors::SemanticError;
 use crate::semantics::{
-    AnonArrayType, AnonStructType, ArrayType, QualifiedName, StructType, Symbol, SymbolInterface,
-    Type, TypeConversionResult,
+    AnonArrayType, AnonStructType, ArrayType, NameGroup, QualifiedName, StructMembers, StructType,
+    Symbol, SymbolInterface, Type, TypeConversionResult,
 };
 use crate::Analysis;
 use fpp_ast::{
-    DefAliasType, DefArray, DefConstant, DefEnum, DefEnumConstant, DefStruct, Expr, ExprKind,
-    FloatKind, IntegerKind, Node, SpecCommand, SpecContainer, SpecEvent, SpecGeneralPortInstance,
-    SpecInit, SpecInternalPort, SpecParam, SpecRecord, SpecSpecialPortInstance,
-    SpecStateMachineInstance, SpecTlmChannel, SpecTlmPacket, StructTypeMember, TypeName,
-    TypeNameKind, Visitable, Visitor,
+    AstNode, DefAliasType, DefArray, DefConstant, DefEnum, DefEnumConstant, DefStruct, Expr,
+    ExprKind, FloatKind, IntegerKind, Node, SpecCommand, SpecContainer, SpecEvent,
+    SpecGeneralPortInstance, SpecInit, SpecInternalPort, SpecParam, SpecRecord,
+    SpecSpecialPortInstance, SpecStateMachineInstance, SpecTlmChannel, SpecTlmPacket,
+    StructTypeMember, TypeName, TypeNameKind, Visitable, Visitor,
 };
 use fpp_core::Spanned;
 use rustc_hash::FxHashMap as HashMap;
@@ -354,7 +354,7 @@ impl<'ast> Visitor<'ast> for CheckExprTypes<'ast> {
                 _ => {}
             },
             ExprKind::Struct(struct_expr) => {
-                let mut members_out = HashMap::default();
+                let mut members_out = StructMembers::default();
                 let mut member_locs = HashMap::default();
 
                 for member in struct_expr {
@@ -440,7 +440,7 @@ impl<'ast> Visitor<'ast> for CheckExprTypes<'ast> {
                             a,
                             every,
                             &Arc::new(Type::AnonStruct(AnonStructType {
-                                members: HashMap::from_iter([
+                                members: StructMembers::from_iter([
                                     (
                                         "seconds".to_string(),
                                         Arc::new(Type::PrimitiveInt(IntegerKind::U32)),
@@ -628,11 +628,17 @@ impl<'ast> UseAnalysisPass<'ast, Analysis> for CheckExprTypes<'ast> {
             // the enum definition, so it already has a type
             Symbol::EnumConstant(_) => {}
             _ => {
+                let qualifier = match &node.kind {
+                    ExprKind::Dot { e, .. } => a.use_def_map.get(&e.id()),
+                    _ => None,
+                };
+
                 SemanticError::InvalidSymbol {
                     symbol_name: symbol.name().data.clone(),
                     msg: "not a constant symbol".to_string(),
                     loc: node.span(),
                     def_loc: symbol.name().span(),
+                    suggestions: a.suggest(NameGroup::Value, &symbol.name().data, qualifier),
                 }
                 .emit();
                 return ControlFlow::Continue(());
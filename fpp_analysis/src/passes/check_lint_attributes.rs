@@ -0,0 +1,106 @@
+use crate::semantics::{edit_distance, Lint, LintAttribute, LintLevel, LINTS, UNKNOWN_LINTS};
+use crate::Analysis;
+use fpp_ast::{AstNode, MoveWalkable, Node, Visitor};
+use fpp_core::{Annotated, Span, Spanned};
+use std::ops::ControlFlow;
+
+/// Prefix of annotations that are interpreted as lint attributes
+pub(crate) const LINT_ATTRIBUTE_PREFIX: &str = "fpp:";
+
+/// Whether an annotation line is a lint attribute rather than documentation
+pub fn is_lint_attribute(line: &str) -> bool {
+    line.starts_with(LINT_ATTRIBUTE_PREFIX)
+}
+
+/// The annotation lines documenting `node`, leaving out its lint attributes
+pub fn doc_annotation<N: Annotated>(node: &N) -> Vec<String> {
+    node.pre_annotation()
+        .into_iter()
+        .chain(node.post_annotation())
+        .filter(|line| !is_lint_attribute(line))
+        .collect()
+}
+
+/// Collects the lint levels set with `@ fpp:allow(<lint>)`, `@ fpp:warn(<lint>)`
+/// and `@ fpp:deny(<lint>)` annotations on definitions
+#[derive(Default)]
+pub struct CheckLintAttributes {}
+
+impl CheckLintAttributes {
+    pub fn new() -> CheckLintAttributes {
+        CheckLintAttributes {}
+    }
+
+    /// Parse `<level>(<lint>, ...)` into the level and the list of lint names
+    fn parse(attribute: &str) -> Option<(LintLevel, Vec<&str>)> {
+        let (level, rest) = attribute.split_once('(')?;
+        let names = rest.strip_suffix(')')?;
+        let level = level.trim().parse().ok()?;
+
+        Some((level, names.split(',').map(|name| name.trim()).collect()))
+    }
+
+    fn check_annotation(&self, a: &mut Analysis, scope: Span, annotation: &str) {
+        let attribute = match annotation.strip_prefix(LINT_ATTRIBUTE_PREFIX) {
+            None => return,
+            Some(attribute) => attribute.trim(),
+        };
+
+        let (level, names) = match Self::parse(attribute) {
+            Some(parsed) => parsed,
+            None => {
+                if let Some(diag) = a.lint(
+                    &UNKNOWN_LINTS,
+                    scope,
+                    format!("malformed lint attribute `{}`", annotation),
+                ) {
+                    diag.note(
+                        "expected `fpp:allow(<lint>)`, `fpp:warn(<lint>)` or `fpp:deny(<lint>)`",
+                    )
+                    .emit();
+                }
+                return;
+            }
+        };
+
+        for name in names {
+            match Lint::find(name) {
+                Some(lint) => a.lint_attributes.push(LintAttribute {
+                    scope,
+                    lint: lint.name,
+                    level,
+                }),
+                None => {
+                    if let Some(diag) =
+                        a.lint(&UNKNOWN_LINTS, scope, format!("unknown lint `{}`", name))
+                    {
+                        let similar = LINTS
+                            .iter()
+                            .find(|lint| edit_distance(name, lint.name) <= name.len() / 3);
+
+                        match similar {
+                            None => diag.emit(),
+                            Some(lint) => {
+                                diag.note(format!("did you mean `{}`?", lint.name)).emit()
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl<'ast> Visitor<'ast> for CheckLintAttributes {
+    type Break = ();
+    type State = Analysis;
+
+    fn super_visit(&self, a: &mut Self::State, node: Node<'ast>) -> ControlFlow<Self::Break> {
+        let annotations = node.id().pre_annotation();
+        for annotation in &annotations {
+            self.check_annotation(a, node.span(), annotation);
+        }
+
+        node.walk(a, self)
+    }
+}
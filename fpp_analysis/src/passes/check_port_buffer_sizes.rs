@@ -0,0 +1,70 @@
+use crate::semantics::{SerializedSize, PORT_BUFFER_SIZE};
+use crate::Analysis;
+use fpp_ast::{DefPort, MoveWalkable, Node, Visitor};
+use fpp_core::Spanned;
+use std::ops::ControlFlow;
+
+/// Reports port definitions whose arguments may not fit in a port buffer
+/// of the configured maximum size
+#[derive(Default)]
+pub struct CheckPortBufferSizes {}
+
+impl CheckPortBufferSizes {
+    pub fn new() -> CheckPortBufferSizes {
+        CheckPortBufferSizes {}
+    }
+}
+
+impl<'ast> Visitor<'ast> for CheckPortBufferSizes {
+    type Break = ();
+    type State = Analysis;
+
+    fn super_visit(&self, a: &mut Self::State, node: Node<'ast>) -> ControlFlow<Self::Break> {
+        node.walk(a, self)
+    }
+
+    fn visit_def_port(&self, a: &mut Self::State, node: &'ast DefPort) -> ControlFlow<Self::Break> {
+        let max_size = match a.max_port_buffer_size_at(&node.name.span()) {
+            None => return ControlFlow::Continue(()),
+            Some(max_size) => max_size,
+        };
+
+        let mut param_sizes = vec![];
+        for param in &node.params {
+            match a
+                .type_map
+                .get(&param.type_name.node_id)
+                .and_then(|ty| ty.serialized_size())
+            {
+                // The size of abstract types is not known to FPP
+                None => return ControlFlow::Continue(()),
+                Some(size) => param_sizes.push((param, size)),
+            }
+        }
+
+        let total = param_sizes
+            .iter()
+            .fold(SerializedSize::fixed(0), |total, (_, size)| total + *size);
+
+        if total.max <= max_size {
+            return ControlFlow::Continue(());
+        }
+
+        if let Some(mut diag) = a.lint(
+            &PORT_BUFFER_SIZE,
+            node.name.span(),
+            format!(
+                "arguments of port `{}` serialize to {}, exceeding the maximum port buffer size of {} bytes",
+                node.name.data, total, max_size
+            ),
+        ) {
+            for (param, size) in param_sizes {
+                diag = diag.span_note(param.span(), format!("`{}` serializes to {}", param.name.data, size));
+            }
+
+            diag.emit();
+        }
+
+        ControlFlow::Continue(())
+    }
+}
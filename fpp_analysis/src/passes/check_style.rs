@@ -0,0 +1,132 @@
+use crate::passes::doc_annotation;
+use crate::semantics::{LintLevel, StyleCheck, StyleKind, StyleRule, STYLE};
+use crate::Analysis;
+use fpp_ast::{AstNode, MoveWalkable, Name, Node, Visitor};
+use fpp_core::{Diagnostic, Level, Spanned};
+use std::ops::ControlFlow;
+
+/// Checks definitions against the style rules of the lint configuration
+#[derive(Default)]
+pub struct CheckStyle {}
+
+impl CheckStyle {
+    pub fn new() -> CheckStyle {
+        CheckStyle {}
+    }
+
+    /// The style kind and name of a definition
+    fn definition<'ast>(node: Node<'ast>) -> Option<(StyleKind, &'ast Name)> {
+        let def = match node {
+            Node::DefAbsType(def) => (StyleKind::Type, &def.name),
+            Node::DefAction(def) => (StyleKind::Action, &def.name),
+            Node::DefAliasType(def) => (StyleKind::Type, &def.name),
+            Node::DefArray(def) => (StyleKind::Type, &def.name),
+            Node::DefChoice(def) => (StyleKind::Choice, &def.name),
+            Node::DefComponent(def) => (StyleKind::Component, &def.name),
+            Node::DefComponentInstance(def) => (StyleKind::Instance, &def.name),
+            Node::DefConstant(def) => (StyleKind::Constant, &def.name),
+            Node::DefEnum(def) => (StyleKind::Type, &def.name),
+            Node::DefEnumConstant(def) => (StyleKind::EnumConstant, &def.name),
+            Node::DefGuard(def) => (StyleKind::Guard, &def.name),
+            Node::DefInterface(def) => (StyleKind::Interface, &def.name),
+            Node::DefModule(def) => (StyleKind::Module, &def.name),
+            Node::DefPort(def) => (StyleKind::Port, &def.name),
+            Node::DefSignal(def) => (StyleKind::Signal, &def.name),
+            Node::DefState(def) => (StyleKind::State, &def.name),
+            Node::DefStateMachine(def) => (StyleKind::StateMachine, &def.name),
+            Node::DefStruct(def) => (StyleKind::Type, &def.name),
+            Node::DefTopology(def) => (StyleKind::Topology, &def.name),
+            Node::FormalParam(def) => (StyleKind::FormalParam, &def.name),
+            Node::SpecCommand(spec) => (StyleKind::Command, &spec.name),
+            Node::SpecContainer(spec) => (StyleKind::Container, &spec.name),
+            Node::SpecEvent(spec) => (StyleKind::Event, &spec.name),
+            Node::SpecInternalPort(spec) => (StyleKind::PortInstance, &spec.name),
+            Node::SpecParam(spec) => (StyleKind::Param, &spec.name),
+            // Port instances are checked through the general and special
+            // nodes, not the enum wrapping them, so they are checked once
+            Node::SpecGeneralPortInstance(spec) => (StyleKind::PortInstance, &spec.name),
+            Node::SpecSpecialPortInstance(spec) => (StyleKind::PortInstance, &spec.name),
+            Node::SpecRecord(spec) => (StyleKind::Record, &spec.name),
+            Node::SpecStateMachineInstance(spec) => (StyleKind::StateMachineInstance, &spec.name),
+            Node::SpecTlmChannel(spec) => (StyleKind::Telemetry, &spec.name),
+            Node::SpecTlmPacket(spec) => (StyleKind::Packet, &spec.name),
+            Node::StructTypeMember(member) => (StyleKind::StructMember, &member.name),
+            _ => return None,
+        };
+
+        Some(def)
+    }
+
+    /// The problem `rule` finds with a definition, if any
+    fn check_rule(rule: &StyleRule, node: Node, name: &Name) -> Option<String> {
+        let what = rule.kind.what();
+        match &rule.check {
+            StyleCheck::Case(styles) => {
+                if styles.iter().any(|style| style.matches(&name.data)) {
+                    return None;
+                }
+
+                let styles: Vec<String> = styles.iter().map(|style| style.to_string()).collect();
+                Some(format!(
+                    "{} `{}` should be written in {}",
+                    what,
+                    name.data,
+                    styles.join(" or ")
+                ))
+            }
+            StyleCheck::Pattern(pattern) => {
+                if pattern.is_match(&name.data) {
+                    return None;
+                }
+
+                Some(format!(
+                    "{} `{}` does not match the pattern `{}`",
+                    what,
+                    name.data,
+                    pattern.as_str()
+                ))
+            }
+            StyleCheck::Annotated => {
+                if !doc_annotation(&node.id()).is_empty() {
+                    return None;
+                }
+
+                Some(format!("{} `{}` has no annotation", what, name.data))
+            }
+        }
+    }
+}
+
+impl<'ast> Visitor<'ast> for CheckStyle {
+    type Break = ();
+    type State = Analysis;
+
+    fn super_visit(&self, a: &mut Self::State, node: Node<'ast>) -> ControlFlow<Self::Break> {
+        if let Some((kind, name)) = Self::definition(node) {
+            for rule in a
+                .lint_config_at(&name.span())
+                .style_rules
+                .iter()
+                .filter(|rule| rule.kind == kind)
+            {
+                let message = match Self::check_rule(rule, node, name) {
+                    None => continue,
+                    Some(message) => message,
+                };
+
+                // Rules are reported under their own name instead of the lint name
+                let level = match a.lint_level(&STYLE, name.span()) {
+                    LintLevel::Allow => continue,
+                    LintLevel::Warn => Level::Warning,
+                    LintLevel::Deny => Level::Error,
+                };
+
+                Diagnostic::new(name.span(), level, message)
+                    .code(rule.name.clone())
+                    .emit();
+            }
+        }
+
+        node.walk(a, self)
+    }
+}
@@ -3,8 +3,8 @@ use crate::analyzers::basic_use_analyzer::UseAnalysisPass;
 use crate::analyzers::use_analyzer::UseAnalyzer;
 use crate::errors::SemanticError;
 use crate::semantics::{
-    AbsType, AliasType, AnonArrayType, AnonStructType, ArrayType, EnumType, QualifiedName,
-    StructType, Symbol, SymbolInterface, Type,
+    AbsType, AliasType, AnonArrayType, AnonStructType, ArrayType, EnumType, NameGroup,
+    QualifiedName, StructType, Symbol, SymbolInterface, Type,
 };
 use crate::Analysis;
 use fpp_ast::*;
@@ -117,7 +117,9 @@ impl<'ast> Visitor<'ast> for CheckTypeUses<'ast> {
         }
 
         node.walk(a, self)?;
-        if node.constants.len() == 0 {
+
+        // Constants may have been dropped by the parser while recovering from a syntax error
+        if node.constants.len() == 0 && !node.is_recovered() {
             SemanticError::InvalidType {
                 loc: node.span(),
                 msg: "enum must define at least one constant".to_string(),
@@ -149,6 +151,7 @@ impl<'ast> Visitor<'ast> for CheckTypeUses<'ast> {
             node: node.clone(),
             rep_type,
             default: None,
+            constants: vec![],
         }));
 
         a.type_map.insert(node.node_id, ty.clone());
@@ -269,6 +272,11 @@ impl<'ast> UseAnalysisPass<'ast, Analysis> for CheckTypeUses<'ast> {
                     msg: "not a type symbol".to_string(),
                     loc: node.span(),
                     def_loc: symbol.name().span(),
+                    suggestions: a.suggest(
+                        NameGroup::Type,
+                        &symbol.name().data,
+                        a.qualifier_symbol(node),
+                    ),
                 }
                 .emit();
                 return ControlFlow::Continue(());
@@ -0,0 +1,233 @@
+use crate::semantics::{
+    span_contains, Lint, Symbol, SymbolInterface, UNUSED_DEFINITIONS, UNUSED_ENUM_CONSTANTS,
+    UNUSED_INSTANCES, UNUSED_TOPOLOGIES,
+};
+use crate::Analysis;
+use fpp_ast::*;
+use fpp_core::{uri_in_dir, Span, Spanned};
+use rustc_hash::FxHashSet;
+use std::ops::ControlFlow;
+
+/// Reports definitions that nothing else in the model refers to
+///
+/// Uses of a definition from inside itself do not count. For enum constants
+/// this covers the whole enum, so a constant only named by the enum default
+/// is still reported. Dictionary definitions are used by the ground system,
+/// root topologies are what gets deployed and the framework is shared by
+/// every model, so none of them are reported.
+///
+/// When no topology imports another one, every topology with members is a
+/// root. Otherwise the roots are the topologies importing other ones, and a
+/// topology that is never imported and imports nothing is reported.
+pub struct CheckUnusedDefs {
+    /// Symbols with at least one use outside of their own definition
+    used: FxHashSet<Symbol>,
+    /// Whether any topology imports another one
+    imports: bool,
+}
+
+impl CheckUnusedDefs {
+    pub fn new(a: &Analysis) -> CheckUnusedDefs {
+        let used = a
+            .use_def_map
+            .iter()
+            .filter(|(use_node, symbol)| !span_contains(&Self::scope(a, symbol), &use_node.span()))
+            .map(|(_, symbol)| symbol.clone())
+            .collect();
+        let imports = a
+            .use_def_map
+            .values()
+            .any(|symbol| matches!(symbol, Symbol::Topology(_)));
+
+        CheckUnusedDefs { used, imports }
+    }
+
+    fn is_root(&self, a: &Analysis, def: &DefTopology) -> bool {
+        let mut instances = def.members.iter().filter_map(|member| match member {
+            TopologyMember::SpecInstance(spec) => Some(spec),
+            _ => None,
+        });
+
+        if !self.imports {
+            return instances.next().is_some();
+        }
+
+        instances.any(|spec| {
+            matches!(
+                a.use_def_map.get(&spec.instance.id()),
+                Some(Symbol::Topology(_))
+            )
+        })
+    }
+
+    /// The span inside which uses of `symbol` do not count
+    fn scope(a: &Analysis, symbol: &Symbol) -> Span {
+        match (symbol, a.parent_symbol_map.get(symbol)) {
+            (Symbol::EnumConstant(_), Some(parent)) => parent.node().span(),
+            _ => symbol.node().span(),
+        }
+    }
+
+    fn check(&self, a: &Analysis, lint: &Lint, node: fpp_core::Node, message: String) {
+        let symbol = match a.symbol_map.get(&node) {
+            None => return,
+            Some(symbol) => symbol,
+        };
+
+        let span = symbol.name().span();
+        if self.used.contains(symbol) || !a.check_unused_at(&span) {
+            return;
+        }
+
+        let uri = span.file().uri();
+        if a.framework_dirs.iter().any(|dir| uri_in_dir(&uri, dir)) {
+            return;
+        }
+
+        if let Some(diag) = a.lint(lint, span, message) {
+            diag.emit();
+        }
+    }
+
+    fn check_def(
+        &self,
+        a: &Analysis,
+        node: fpp_core::Node,
+        is_dictionary_def: bool,
+        kind: &str,
+        name: &Name,
+    ) {
+        if !is_dictionary_def {
+            self.check(
+                a,
+                &UNUSED_DEFINITIONS,
+                node,
+                format!("{} `{}` is never used", kind, name.data),
+            );
+        }
+    }
+}
+
+impl<'ast> Visitor<'ast> for CheckUnusedDefs {
+    type Break = ();
+    type State = Analysis;
+
+    fn super_visit(&self, a: &mut Self::State, node: Node<'ast>) -> ControlFlow<Self::Break> {
+        node.walk(a, self)
+    }
+
+    fn visit_def_abs_type(
+        &self,
+        a: &mut Self::State,
+        def: &'ast DefAbsType,
+    ) -> ControlFlow<Self::Break> {
+        self.check_def(a, def.node_id, false, "type", &def.name);
+        ControlFlow::Continue(())
+    }
+
+    fn visit_def_alias_type(
+        &self,
+        a: &mut Self::State,
+        def: &'ast DefAliasType,
+    ) -> ControlFlow<Self::Break> {
+        self.check_def(a, def.node_id, def.is_dictionary_def, "type", &def.name);
+        ControlFlow::Continue(())
+    }
+
+    fn visit_def_array(
+        &self,
+        a: &mut Self::State,
+        def: &'ast DefArray,
+    ) -> ControlFlow<Self::Break> {
+        self.check_def(a, def.node_id, def.is_dictionary_def, "array", &def.name);
+        ControlFlow::Continue(())
+    }
+
+    fn visit_def_constant(
+        &self,
+        a: &mut Self::State,
+        def: &'ast DefConstant,
+    ) -> ControlFlow<Self::Break> {
+        self.check_def(a, def.node_id, def.is_dictionary_def, "constant", &def.name);
+        ControlFlow::Continue(())
+    }
+
+    fn visit_def_enum(&self, a: &mut Self::State, def: &'ast DefEnum) -> ControlFlow<Self::Break> {
+        self.check_def(a, def.node_id, def.is_dictionary_def, "enum", &def.name);
+        if def.is_dictionary_def {
+            return ControlFlow::Continue(());
+        }
+
+        for constant in &def.constants {
+            self.check(
+                a,
+                &UNUSED_ENUM_CONSTANTS,
+                constant.node_id,
+                format!(
+                    "enum constant `{}.{}` is never used outside of its enum",
+                    def.name.data, constant.name.data
+                ),
+            );
+        }
+
+        ControlFlow::Continue(())
+    }
+
+    fn visit_def_struct(
+        &self,
+        a: &mut Self::State,
+        def: &'ast DefStruct,
+    ) -> ControlFlow<Self::Break> {
+        self.check_def(a, def.node_id, def.is_dictionary_def, "struct", &def.name);
+        ControlFlow::Continue(())
+    }
+
+    fn visit_def_port(&self, a: &mut Self::State, def: &'ast DefPort) -> ControlFlow<Self::Break> {
+        self.check_def(a, def.node_id, false, "port", &def.name);
+        ControlFlow::Continue(())
+    }
+
+    fn visit_def_state_machine(
+        &self,
+        a: &mut Self::State,
+        def: &'ast DefStateMachine,
+    ) -> ControlFlow<Self::Break> {
+        self.check_def(a, def.node_id, false, "state machine", &def.name);
+        ControlFlow::Continue(())
+    }
+
+    fn visit_def_component_instance(
+        &self,
+        a: &mut Self::State,
+        def: &'ast DefComponentInstance,
+    ) -> ControlFlow<Self::Break> {
+        self.check(
+            a,
+            &UNUSED_INSTANCES,
+            def.node_id,
+            format!(
+                "component instance `{}` is not part of any topology",
+                def.name.data
+            ),
+        );
+        ControlFlow::Continue(())
+    }
+
+    fn visit_def_topology(
+        &self,
+        a: &mut Self::State,
+        def: &'ast DefTopology,
+    ) -> ControlFlow<Self::Break> {
+        if self.is_root(a, def) {
+            return ControlFlow::Continue(());
+        }
+
+        self.check(
+            a,
+            &UNUSED_TOPOLOGIES,
+            def.node_id,
+            format!("topology `{}` is never imported", def.name.data),
+        );
+        ControlFlow::Continue(())
+    }
+}
@@ -34,6 +34,7 @@ impl<'ast> CheckUses<'ast> {
                         msg: "not a qualifier".to_string(),
                         loc: qualifier.span(),
                         def_loc: qual_sym.node().span(),
+                        suggestions: vec![],
                     });
                 }
                 Some(scope) => scope,
@@ -45,6 +46,7 @@ impl<'ast> CheckUses<'ast> {
                 ng: ng.to_string(),
                 name: name.data.clone(),
                 loc: name.span(),
+                suggestions: a.suggest(ng, &name.data, Some(&qual_sym)),
             }),
             Some(sym) => {
                 a.use_def_map.insert(name.id(), sym.clone());
@@ -65,6 +67,7 @@ impl<'ast> CheckUses<'ast> {
                     ng: ng.to_string(),
                     name: name.data.clone(),
                     loc: name.span(),
+                    suggestions: a.suggest(ng, &name.data, None),
                 }),
                 Some(sym) => {
                     a.use_def_map.insert(name.id(), sym.clone());
@@ -153,6 +156,7 @@ impl<'ast> UseAnalysisPass<'ast, Analysis> for CheckUses<'ast> {
                                     ng: NameGroup::Value.to_string(),
                                     name: id.data.clone(),
                                     loc: id.span(),
+                                    suggestions: a.suggest(NameGroup::Value, &id.data, Some(qual)),
                                 }
                                 .emit();
                                 None
@@ -175,6 +179,7 @@ impl<'ast> UseAnalysisPass<'ast, Analysis> for CheckUses<'ast> {
                         ng: NameGroup::Value.to_string(),
                         name: id.clone(),
                         loc: node.span(),
+                        suggestions: a.suggest(NameGroup::Value, id, None),
                     }
                     .emit();
                 }
@@ -4,8 +4,8 @@ use crate::analyzers::use_analyzer::UseAnalyzer;
 use crate::errors::SemanticError;
 use crate::semantics::{
     AnonArrayValue, AnonStructValue, ArrayValue, BooleanValue, EnumConstantValue, FloatValue,
-    IntegerValue, MathError, PrimitiveIntegerValue, QualifiedName, StringValue, StructValue,
-    Symbol, SymbolInterface, Type, Value,
+    IntegerValue, MathError, PrimitiveIntegerValue, QualifiedName, StringValue, StructMembers,
+    StructValue, Symbol, SymbolInterface, Type, Value,
 };
 use crate::Analysis;
 use fpp_ast::{
@@ -332,12 +332,14 @@ impl<'ast> Visitor<'ast> for EvalConstantExprs<'ast> {
                 a.value_map.insert(
                     node.node_id,
                     Value::AnonStruct(AnonStructValue {
-                        members: HashMap::from_iter(struct_expr.iter().filter_map(|member| {
-                            Some((
-                                member.name.data.clone(),
-                                a.value_map.get(&member.value.node_id)?.clone(),
-                            ))
-                        })),
+                        members: StructMembers::from_iter(struct_expr.iter().filter_map(
+                            |member| {
+                                Some((
+                                    member.name.data.clone(),
+                                    a.value_map.get(&member.value.node_id)?.clone(),
+                                ))
+                            },
+                        )),
                     }),
                 );
             }
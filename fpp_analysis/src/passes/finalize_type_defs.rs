@@ -2,8 +2,8 @@ use crate::analyzers::analyzer::Analyzer;
 use crate::analyzers::nested_analyzer::{NestedAnalyzer, NestedAnalyzerMode};
 use crate::errors::SemanticError;
 use crate::semantics::{
-    AliasType, AnonArrayType, AnonStructType, ArrayType, Format, IntegerValue, StructType, Symbol,
-    Type, Value,
+    AliasType, AnonArrayType, AnonStructType, ArrayType, Format, IntegerValue, StructType,
+    StructValue, Symbol, SymbolInterface, Type, Value,
 };
 use crate::Analysis;
 use fpp_ast::{
@@ -50,6 +50,7 @@ impl<'ast> FinalizeTypeDefs<'ast> {
             TypeNameKind::QualIdent(q) => match a.use_def_map.get(&q.id()) {
                 None => {}
                 Some(symbol) => {
+                    let symbol = symbol.clone();
                     let _ = match symbol.clone() {
                         Symbol::AbsType(ty) => self.visit_def_abs_type(a, ty.deref()),
                         Symbol::AliasType(ty) => self.visit_def_alias_type(a, ty.deref()),
@@ -58,6 +59,11 @@ impl<'ast> FinalizeTypeDefs<'ast> {
                         Symbol::Struct(ty) => self.visit_def_struct(a, ty.deref()),
                         _ => ControlFlow::Continue(()),
                     };
+
+                    // Uses of the type refer to the finalized definition
+                    if let Some(def_ty) = a.type_map.get(&symbol.node()).cloned() {
+                        a.type_map.insert(node.node_id, def_ty);
+                    }
                 }
             },
             TypeNameKind::String(size) => match self.expr_as_integer_opt(a, size) {
@@ -108,6 +114,16 @@ impl<'ast> Visitor<'ast> for FinalizeTypeDefs<'ast> {
         ControlFlow::Continue(())
     }
 
+    // Type names outside of type definitions, e.g. port parameters
+    fn visit_type_name(
+        &self,
+        a: &mut Self::State,
+        node: &'ast TypeName,
+    ) -> ControlFlow<Self::Break> {
+        let _ = self.ty(a, node);
+        ControlFlow::Continue(())
+    }
+
     fn visit_trans_unit(
         &self,
         a: &mut Self::State,
@@ -259,6 +275,15 @@ impl<'ast> Visitor<'ast> for FinalizeTypeDefs<'ast> {
             },
         };
 
+        enum_ty.constants = node
+            .constants
+            .iter()
+            .filter_map(|constant| match a.value_map.get(&constant.node_id) {
+                Some(Value::EnumConstant(value)) => Some(value.value.clone()),
+                _ => None,
+            })
+            .collect();
+
         a.type_map
             .insert(node.node_id, Arc::new(Type::Enum(enum_ty)));
 
@@ -334,6 +359,23 @@ impl<'ast> Visitor<'ast> for FinalizeTypeDefs<'ast> {
             }
         }
 
+        // Compute the default value
+        let anon_struct_ty = Arc::new(Type::AnonStruct(ty.anon_struct.clone()));
+        let default = match &node.default {
+            None => anon_struct_ty.default_value(),
+            Some(default) => match a.value_map.get(&default.node_id) {
+                None => None,
+                Some(default_v) => default_v.convert(&anon_struct_ty),
+            },
+        };
+
+        ty.default = match default {
+            Some(Value::AnonStruct(v)) => {
+                Some(StructValue::new(v, Arc::new(Type::Struct(ty.clone()))))
+            }
+            _ => None,
+        };
+
         a.type_map.insert(node.node_id, Arc::new(Type::Struct(ty)));
         ControlFlow::Continue(())
     }
@@ -0,0 +1,317 @@
+use crate::semantics::Symbol;
+use crate::Analysis;
+use fpp_ast::{
+    AstNode, ComponentMember, ConnectionPatternKind, DefComponent, DefComponentInstance,
+    DefTopology, GeneralPortInstanceKind, InterfaceMember, PortInstanceIdentifier,
+    SpecPortInstance, SpecialPortInstanceKind, TopologyMember,
+};
+use std::collections::HashSet;
+use std::sync::Arc;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PortDirection {
+    Input,
+    Output,
+}
+
+/** A port instance of a component */
+#[derive(Debug, Clone)]
+pub struct PortInstance {
+    pub name: String,
+    /** The port instance specifier */
+    pub node: fpp_core::Node,
+    pub direction: PortDirection,
+    /** The qualified name of the port type, none for serial ports */
+    pub port_type: Option<String>,
+}
+
+/** One end of a connection */
+#[derive(Debug, Clone)]
+pub struct ConnectionEndpoint {
+    pub instance: Arc<DefComponentInstance>,
+    /** The name of the port instance */
+    pub port: String,
+    /** The port number given in the connection */
+    pub index: Option<i128>,
+}
+
+/** A connection of a topology, written in a direct graph or implied by a pattern */
+#[derive(Debug, Clone)]
+pub struct TopologyConnection {
+    /** The name of the connection graph, `Commands` for a command pattern */
+    pub graph: String,
+    pub from: ConnectionEndpoint,
+    pub to: ConnectionEndpoint,
+    /** The pattern implying the connection, if any */
+    pub pattern: Option<ConnectionPatternKind>,
+    /** The `Connection`, or the pattern graph specifier implying the connection */
+    pub node: fpp_core::Node,
+}
+
+/** The port of the framework a special port instance uses, relative to `Fw` */
+pub fn special_port_name(kind: &SpecialPortInstanceKind) -> &'static str {
+    match kind {
+        SpecialPortInstanceKind::CommandRecv => "Cmd",
+        SpecialPortInstanceKind::CommandReg => "CmdReg",
+        SpecialPortInstanceKind::CommandResp => "CmdResponse",
+        SpecialPortInstanceKind::Event => "Log",
+        SpecialPortInstanceKind::ParamGet => "PrmGet",
+        SpecialPortInstanceKind::ParamSet => "PrmSet",
+        SpecialPortInstanceKind::ProductGet => "DpGet",
+        SpecialPortInstanceKind::ProductRecv => "DpResponse",
+        SpecialPortInstanceKind::ProductRequest => "DpRequest",
+        SpecialPortInstanceKind::ProductSend => "DpSend",
+        SpecialPortInstanceKind::Telemetry => "Tlm",
+        SpecialPortInstanceKind::TextEvent => "LogText",
+        SpecialPortInstanceKind::TimeGet => "Time",
+    }
+}
+
+/** The name of the connection graph a pattern graph specifier generates */
+pub fn pattern_graph_name(kind: &ConnectionPatternKind) -> &'static str {
+    match kind {
+        ConnectionPatternKind::Command => "Commands",
+        ConnectionPatternKind::Event => "Events",
+        ConnectionPatternKind::Health => "Health",
+        ConnectionPatternKind::Param => "Parameters",
+        ConnectionPatternKind::Telemetry => "Telemetry",
+        ConnectionPatternKind::TextEvent => "TextEvents",
+        ConnectionPatternKind::Time => "Time",
+    }
+}
+
+/** Which end of a pattern connection the source instance is */
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum PatternSide {
+    /** The source sends to the target */
+    FromSource,
+    /** The target sends to the source */
+    ToSource,
+}
+
+/** The connections a pattern implies between its source and each target,
+ *  as the port type connecting them and the direction of the connection */
+fn pattern_ports(kind: &ConnectionPatternKind) -> &'static [(&'static str, PatternSide)] {
+    match kind {
+        ConnectionPatternKind::Command => &[
+            ("Fw.Cmd", PatternSide::FromSource),
+            ("Fw.CmdReg", PatternSide::ToSource),
+            ("Fw.CmdResponse", PatternSide::ToSource),
+        ],
+        ConnectionPatternKind::Event => &[("Fw.Log", PatternSide::ToSource)],
+        ConnectionPatternKind::Health => &[
+            ("Svc.Ping", PatternSide::FromSource),
+            ("Svc.Ping", PatternSide::ToSource),
+        ],
+        ConnectionPatternKind::Param => &[
+            ("Fw.PrmGet", PatternSide::ToSource),
+            ("Fw.PrmSet", PatternSide::ToSource),
+        ],
+        ConnectionPatternKind::Telemetry => &[("Fw.Tlm", PatternSide::ToSource)],
+        ConnectionPatternKind::TextEvent => &[("Fw.LogText", PatternSide::ToSource)],
+        ConnectionPatternKind::Time => &[("Fw.Time", PatternSide::ToSource)],
+    }
+}
+
+/** A component instance with its component and port instances */
+struct TopologyInstance {
+    instance: Arc<DefComponentInstance>,
+    ports: Vec<PortInstance>,
+}
+
+impl TopologyInstance {
+    fn port(&self, direction: PortDirection, port_type: &str) -> Option<&PortInstance> {
+        self.ports.iter().find(|port| {
+            port.direction == direction && port.port_type.as_deref() == Some(port_type)
+        })
+    }
+
+    fn endpoint(&self, port: &PortInstance) -> ConnectionEndpoint {
+        ConnectionEndpoint {
+            instance: self.instance.clone(),
+            port: port.name.clone(),
+            index: None,
+        }
+    }
+}
+
+impl Analysis {
+    /** The port instances of a component, including the ones of the
+     *  interfaces it imports */
+    pub fn component_ports(&self, def: &DefComponent) -> Vec<PortInstance> {
+        let mut ports = vec![];
+        for member in &def.members {
+            match member {
+                ComponentMember::SpecPortInstance(spec) => ports.push(self.port_instance(spec)),
+                ComponentMember::SpecInterfaceImport(spec) => {
+                    self.interface_ports(&mut ports, &spec.interface, &mut HashSet::new())
+                }
+                _ => {}
+            }
+        }
+
+        ports
+    }
+
+    fn interface_ports(
+        &self,
+        ports: &mut Vec<PortInstance>,
+        interface: &fpp_ast::QualIdent,
+        visited: &mut HashSet<fpp_core::Node>,
+    ) {
+        let def = match self.use_def_map.get(&interface.id()) {
+            Some(Symbol::Interface(def)) => def,
+            _ => return,
+        };
+
+        if !visited.insert(def.node_id) {
+            return;
+        }
+
+        for member in &def.members {
+            match member {
+                InterfaceMember::SpecPortInstance(spec) => ports.push(self.port_instance(spec)),
+                InterfaceMember::SpecInterfaceImport(spec) => {
+                    self.interface_ports(ports, &spec.interface, visited)
+                }
+            }
+        }
+    }
+
+    /** The port instance a specifier of a component or interface defines */
+    pub fn port_instance(&self, spec: &SpecPortInstance) -> PortInstance {
+        match spec {
+            SpecPortInstance::General(port) => PortInstance {
+                name: port.name.data.clone(),
+                node: port.node_id,
+                direction: match port.kind {
+                    GeneralPortInstanceKind::Input(_) => PortDirection::Input,
+                    GeneralPortInstanceKind::Output => PortDirection::Output,
+                },
+                port_type: port
+                    .port
+                    .as_ref()
+                    .and_then(|port| self.use_def_map.get(&port.id()))
+                    .map(|symbol| self.qualified_name(symbol)),
+            },
+            SpecPortInstance::Special(port) => PortInstance {
+                name: port.name.data.clone(),
+                node: port.node_id,
+                direction: match port.kind {
+                    SpecialPortInstanceKind::CommandRecv | SpecialPortInstanceKind::ProductRecv => {
+                        PortDirection::Input
+                    }
+                    _ => PortDirection::Output,
+                },
+                port_type: Some(format!("Fw.{}", special_port_name(&port.kind))),
+            },
+        }
+    }
+
+    /** The connections of a topology, excluding the ones of the topologies
+     *  it imports. Pattern graphs without targets connect every instance of
+     *  the topology, imported ones included, that has the ports of the pattern. */
+    pub fn topology_connections(&self, def: &DefTopology) -> Vec<TopologyConnection> {
+        let instances: Vec<TopologyInstance> = self
+            .topology_component_instances(def)
+            .into_iter()
+            .map(|instance| TopologyInstance {
+                ports: match self.use_def_map.get(&instance.component.id()) {
+                    Some(Symbol::Component(component)) => self.component_ports(component),
+                    _ => vec![],
+                },
+                instance,
+            })
+            .collect();
+
+        let mut connections = vec![];
+        for member in &def.members {
+            match member {
+                TopologyMember::SpecDirectConnectionGraph(spec) => {
+                    for connection in &spec.connections {
+                        let from = self.endpoint(&connection.from_port, &connection.from_index);
+                        let to = self.endpoint(&connection.to_port, &connection.to_index);
+                        if let (Some(from), Some(to)) = (from, to) {
+                            connections.push(TopologyConnection {
+                                graph: spec.name.data.clone(),
+                                from,
+                                to,
+                                pattern: None,
+                                node: connection.node_id,
+                            });
+                        }
+                    }
+                }
+                TopologyMember::SpecPatternConnectionGraph(spec) => {
+                    let source = match self.use_def_map.get(&spec.source.id()) {
+                        Some(Symbol::ComponentInstance(source)) => source,
+                        _ => continue,
+                    };
+                    let source = match instances
+                        .iter()
+                        .find(|instance| instance.instance.node_id == source.node_id)
+                    {
+                        Some(source) => source,
+                        None => continue,
+                    };
+
+                    let targets: Vec<&TopologyInstance> = if spec.targets.is_empty() {
+                        instances
+                            .iter()
+                            .filter(|instance| instance.instance.node_id != source.instance.node_id)
+                            .collect()
+                    } else {
+                        spec.targets
+                            .iter()
+                            .filter_map(|target| match self.use_def_map.get(&target.id()) {
+                                Some(Symbol::ComponentInstance(target)) => instances
+                                    .iter()
+                                    .find(|instance| instance.instance.node_id == target.node_id),
+                                _ => None,
+                            })
+                            .collect()
+                    };
+
+                    for target in targets {
+                        for (port_type, side) in pattern_ports(&spec.kind) {
+                            let (from, to) = match side {
+                                PatternSide::FromSource => (source, target),
+                                PatternSide::ToSource => (target, source),
+                            };
+
+                            let from_port = from.port(PortDirection::Output, port_type);
+                            let to_port = to.port(PortDirection::Input, port_type);
+                            if let (Some(from_port), Some(to_port)) = (from_port, to_port) {
+                                connections.push(TopologyConnection {
+                                    graph: pattern_graph_name(&spec.kind).to_string(),
+                                    from: from.endpoint(from_port),
+                                    to: to.endpoint(to_port),
+                                    pattern: Some(spec.kind.clone()),
+                                    node: spec.node_id,
+                                });
+                            }
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        connections
+    }
+
+    fn endpoint(
+        &self,
+        port: &PortInstanceIdentifier,
+        index: &Option<fpp_ast::Expr>,
+    ) -> Option<ConnectionEndpoint> {
+        match self.use_def_map.get(&port.interface_instance.id())? {
+            Symbol::ComponentInstance(instance) => Some(ConnectionEndpoint {
+                instance: instance.clone(),
+                port: port.port_name.data.clone(),
+                index: index.as_ref().and_then(|index| self.integer_value(index)),
+            }),
+            _ => None,
+        }
+    }
+}
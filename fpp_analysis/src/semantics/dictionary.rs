@@ -0,0 +1,230 @@
+use crate::semantics::{IntegerValue, Symbol, Type, Value};
+use crate::Analysis;
+use fpp_ast::{
+    AstNode, ComponentMember, DefComponent, DefComponentInstance, DefTopology, Expr, SpecCommand,
+    SpecEvent, SpecParam, SpecTlmChannel, TopologyMember,
+};
+use std::collections::HashSet;
+use std::sync::Arc;
+
+/** The definition giving a command in a component dictionary */
+#[derive(Debug, Clone)]
+pub enum CommandKind {
+    Command(SpecCommand),
+    /** The command setting a parameter */
+    ParamSet(SpecParam),
+    /** The command saving a parameter */
+    ParamSave(SpecParam),
+}
+
+#[derive(Debug, Clone)]
+pub struct DictionaryCommand {
+    pub name: String,
+    pub opcode: i128,
+    pub kind: CommandKind,
+}
+
+#[derive(Debug, Clone)]
+pub struct DictionaryEvent {
+    pub name: String,
+    pub id: i128,
+    pub node: SpecEvent,
+}
+
+#[derive(Debug, Clone)]
+pub struct DictionaryChannel {
+    pub name: String,
+    pub id: i128,
+    pub node: SpecTlmChannel,
+}
+
+#[derive(Debug, Clone)]
+pub struct DictionaryParam {
+    pub name: String,
+    pub id: i128,
+    pub node: SpecParam,
+}
+
+/** The commands, events, telemetry channels and parameters of a component
+ *  with their component-local identifiers. Identifiers that are not given
+ *  explicitly follow the previous identifier of the same kind, starting at 0. */
+#[derive(Debug, Clone, Default)]
+pub struct ComponentDictionary {
+    pub commands: Vec<DictionaryCommand>,
+    pub events: Vec<DictionaryEvent>,
+    pub channels: Vec<DictionaryChannel>,
+    pub params: Vec<DictionaryParam>,
+}
+
+/** A component instance used by a topology */
+#[derive(Debug, Clone)]
+pub struct DictionaryInstance {
+    /** The fully qualified name of the instance */
+    pub name: String,
+    pub node: Arc<DefComponentInstance>,
+    pub component: Arc<DefComponent>,
+    pub base_id: i128,
+    /** The dictionary of the component, identifiers are relative to `base_id` */
+    pub dictionary: ComponentDictionary,
+}
+
+/** Assigns implied identifiers in definition order */
+struct IdCounter(i128);
+
+impl IdCounter {
+    fn next(&mut self, explicit: Option<i128>) -> i128 {
+        let id = explicit.unwrap_or(self.0);
+        self.0 = id + 1;
+        id
+    }
+}
+
+impl Analysis {
+    /** The evaluated value of an integer expression */
+    pub fn integer_value(&self, e: &Expr) -> Option<i128> {
+        match self
+            .value_map
+            .get(&e.node_id)?
+            .convert(&Arc::new(Type::Integer))
+        {
+            Some(Value::Integer(IntegerValue(v))) => Some(v),
+            _ => None,
+        }
+    }
+
+    fn integer_value_opt(&self, e: &Option<Expr>) -> Option<i128> {
+        e.as_ref().and_then(|e| self.integer_value(e))
+    }
+
+    pub fn component_dictionary(&self, def: &DefComponent) -> ComponentDictionary {
+        let mut dictionary = ComponentDictionary::default();
+        let mut opcodes = IdCounter(0);
+        let mut event_ids = IdCounter(0);
+        let mut channel_ids = IdCounter(0);
+        let mut param_ids = IdCounter(0);
+
+        for member in &def.members {
+            match member {
+                ComponentMember::SpecCommand(command) => {
+                    dictionary.commands.push(DictionaryCommand {
+                        name: command.name.data.clone(),
+                        opcode: opcodes.next(self.integer_value_opt(&command.opcode)),
+                        kind: CommandKind::Command(command.clone()),
+                    })
+                }
+                ComponentMember::SpecEvent(event) => dictionary.events.push(DictionaryEvent {
+                    name: event.name.data.clone(),
+                    id: event_ids.next(self.integer_value_opt(&event.id)),
+                    node: event.clone(),
+                }),
+                ComponentMember::SpecTlmChannel(channel) => {
+                    dictionary.channels.push(DictionaryChannel {
+                        name: channel.name.data.clone(),
+                        id: channel_ids.next(self.integer_value_opt(&channel.id)),
+                        node: channel.clone(),
+                    })
+                }
+                ComponentMember::SpecParam(param) => {
+                    // Each parameter implies a set and a save command
+                    let name = param.name.data.to_uppercase();
+                    dictionary.commands.push(DictionaryCommand {
+                        name: format!("{}_PRM_SET", name),
+                        opcode: opcodes.next(self.integer_value_opt(&param.set_opcode)),
+                        kind: CommandKind::ParamSet(param.clone()),
+                    });
+                    dictionary.commands.push(DictionaryCommand {
+                        name: format!("{}_PRM_SAVE", name),
+                        opcode: opcodes.next(self.integer_value_opt(&param.save_opcode)),
+                        kind: CommandKind::ParamSave(param.clone()),
+                    });
+                    dictionary.params.push(DictionaryParam {
+                        name: param.name.data.clone(),
+                        id: param_ids.next(self.integer_value_opt(&param.id)),
+                        node: param.clone(),
+                    });
+                }
+                _ => {}
+            }
+        }
+
+        dictionary
+    }
+
+    /** The component instances of a topology in the order they are listed,
+     *  the ones of an imported topology in place of its import */
+    pub fn topology_instances(&self, def: &DefTopology) -> Vec<DictionaryInstance> {
+        self.topology_component_instances(def)
+            .into_iter()
+            .filter_map(|instance| {
+                let component = match self.use_def_map.get(&instance.component.id())? {
+                    Symbol::Component(component) => component.clone(),
+                    _ => return None,
+                };
+
+                let symbol = Symbol::ComponentInstance(instance.clone());
+                Some(DictionaryInstance {
+                    name: self.qualified_name(&symbol),
+                    base_id: self.integer_value(&instance.base_id)?,
+                    dictionary: self.component_dictionary(&component),
+                    node: instance,
+                    component,
+                })
+            })
+            .collect()
+    }
+
+    /** The component instances of a topology and of the topologies it
+     *  imports, each listed once in the order they are listed */
+    pub fn topology_component_instances(
+        &self,
+        def: &DefTopology,
+    ) -> Vec<Arc<DefComponentInstance>> {
+        let mut instances = vec![];
+        self.collect_topology(def, &mut instances, &mut vec![], &mut HashSet::new());
+        instances
+    }
+
+    /** The topologies a topology imports, directly or through the topologies
+     *  it imports, each listed once */
+    pub fn imported_topologies(&self, def: &DefTopology) -> Vec<Arc<DefTopology>> {
+        let mut topologies = vec![];
+        self.collect_topology(def, &mut vec![], &mut topologies, &mut HashSet::new());
+        topologies
+    }
+
+    fn collect_topology(
+        &self,
+        def: &DefTopology,
+        instances: &mut Vec<Arc<DefComponentInstance>>,
+        topologies: &mut Vec<Arc<DefTopology>>,
+        visited: &mut HashSet<fpp_core::Node>,
+    ) {
+        if !visited.insert(def.node_id) {
+            return;
+        }
+
+        for member in &def.members {
+            let spec = match member {
+                TopologyMember::SpecInstance(spec) => spec,
+                _ => continue,
+            };
+
+            match self.use_def_map.get(&spec.instance.id()) {
+                Some(Symbol::Topology(def)) => {
+                    if !visited.contains(&def.node_id) {
+                        topologies.push(def.clone());
+                    }
+                    self.collect_topology(def, instances, topologies, visited)
+                }
+                Some(Symbol::ComponentInstance(instance))
+                    if instances
+                        .iter()
+                        .all(|other| other.node_id != instance.node_id) =>
+                {
+                    instances.push(instance.clone())
+                }
+                _ => {}
+            }
+        }
+    }
+}
@@ -381,6 +381,7 @@ impl<'a> FormatParser<'a> {
             fpp_core::Level::Error,
             msg,
         )
+        .code("E0504")
     }
 
     /// Peeks the next symbol from the input stream without consuming it.
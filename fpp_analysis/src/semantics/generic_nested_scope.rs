@@ -36,6 +36,11 @@ impl<NG: Copy, S: SymbolInterface, M: EnumMap<NG, GenericNameSymbolMap<S>>>
         self.0.iter().rev().find_map(predicate)
     }
 
+    /// Iterate over the scopes from the innermost to the outermost
+    pub fn iter(&self) -> impl Iterator<Item = &Option<S>> {
+        self.0.iter().rev()
+    }
+
     pub fn current(&self) -> &Option<S> {
         self.0.last().unwrap()
     }
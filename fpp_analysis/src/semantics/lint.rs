@@ -0,0 +1,279 @@
+use crate::semantics::StyleRule;
+use crate::Analysis;
+use fpp_core::{uri_in_dir, Diagnostic, Level, Span};
+use rustc_hash::FxHashMap;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// How diagnostics reported by a lint are handled
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LintLevel {
+    /// The lint is not reported
+    Allow,
+    /// The lint is reported as a warning
+    Warn,
+    /// The lint is reported as an error
+    Deny,
+}
+
+impl Display for LintLevel {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            LintLevel::Allow => "allow",
+            LintLevel::Warn => "warn",
+            LintLevel::Deny => "deny",
+        })
+    }
+}
+
+impl FromStr for LintLevel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "allow" => Ok(LintLevel::Allow),
+            "warn" => Ok(LintLevel::Warn),
+            "deny" => Ok(LintLevel::Deny),
+            _ => Err(format!(
+                "invalid lint level `{}`, expected `allow`, `warn` or `deny`",
+                s
+            )),
+        }
+    }
+}
+
+/// A named check that reports questionable but valid FPP
+#[derive(Debug)]
+pub struct Lint {
+    /// Name used to configure the lint and shown with its diagnostics
+    pub name: &'static str,
+    /// Level used when the lint is not configured
+    pub default_level: LintLevel,
+    pub description: &'static str,
+    /// Whether the lint reports code that can be removed, which editors show faded
+    pub unnecessary: bool,
+}
+
+pub const UNKNOWN_LINTS: Lint = Lint {
+    name: "unknown_lints",
+    default_level: LintLevel::Warn,
+    description: "lint attributes that are malformed or refer to a lint that does not exist",
+    unnecessary: false,
+};
+
+pub const PORT_BUFFER_SIZE: Lint = Lint {
+    name: "port_buffer_size",
+    default_level: LintLevel::Warn,
+    description: "port arguments that may not fit in the configured maximum port buffer size",
+    unnecessary: false,
+};
+
+pub const UNUSED_DEFINITIONS: Lint = Lint {
+    name: "unused_definitions",
+    default_level: LintLevel::Warn,
+    description: "constants, types, ports and state machines that are never used",
+    unnecessary: true,
+};
+
+pub const UNUSED_INSTANCES: Lint = Lint {
+    name: "unused_instances",
+    default_level: LintLevel::Warn,
+    description: "component instances that are not part of any topology",
+    unnecessary: true,
+};
+
+pub const UNUSED_TOPOLOGIES: Lint = Lint {
+    name: "unused_topologies",
+    default_level: LintLevel::Warn,
+    description: "topologies that are never imported into another topology",
+    unnecessary: true,
+};
+
+pub const UNUSED_ENUM_CONSTANTS: Lint = Lint {
+    name: "unused_enum_constants",
+    default_level: LintLevel::Warn,
+    description: "enum constants that are never used outside of their enum",
+    unnecessary: true,
+};
+
+pub const STYLE: Lint = Lint {
+    name: "style",
+    default_level: LintLevel::Warn,
+    description: "definitions that break one of the configured style rules",
+    unnecessary: false,
+};
+
+/// Every lint known to the analysis
+pub const LINTS: &[&Lint] = &[
+    &UNKNOWN_LINTS,
+    &PORT_BUFFER_SIZE,
+    &UNUSED_DEFINITIONS,
+    &UNUSED_INSTANCES,
+    &UNUSED_TOPOLOGIES,
+    &UNUSED_ENUM_CONSTANTS,
+    &STYLE,
+];
+
+impl Lint {
+    pub fn find(name: &str) -> Option<&'static Lint> {
+        LINTS.iter().find(|lint| lint.name == name).copied()
+    }
+}
+
+/// Lint levels configured for the whole analysis
+#[derive(Debug, Clone, Default)]
+pub struct LintConfig {
+    levels: FxHashMap<&'static str, LintLevel>,
+    /// Rules checked by the `style` lint
+    pub style_rules: Vec<StyleRule>,
+}
+
+impl LintConfig {
+    /// Override the level of the lint named `name`
+    pub fn set(&mut self, name: &str, level: LintLevel) -> Result<(), String> {
+        match Lint::find(name) {
+            Some(lint) => {
+                self.levels.insert(lint.name, level);
+                Ok(())
+            }
+            None => Err(format!("unknown lint `{}`", name)),
+        }
+    }
+
+    pub fn level(&self, lint: &Lint) -> LintLevel {
+        self.levels
+            .get(lint.name)
+            .copied()
+            .unwrap_or(lint.default_level)
+    }
+}
+
+/// Lint settings of one of several projects analyzed together
+#[derive(Debug, Clone, Default)]
+pub struct ProjectLintConfig {
+    /// URIs of the directories and files holding the files of the project
+    pub sources: Vec<String>,
+    pub lint_config: LintConfig,
+    /// The maximum serialized size of port arguments, if it should be checked
+    pub max_port_buffer_size: Option<usize>,
+    /// Whether definitions that are never used should be reported
+    pub check_unused: bool,
+}
+
+impl ProjectLintConfig {
+    /// Length of the longest source URI containing `uri`
+    fn matches(&self, uri: &str) -> Option<usize> {
+        self.sources
+            .iter()
+            .filter(|source| uri_in_dir(uri, source))
+            .map(|source| source.len())
+            .max()
+    }
+}
+
+/// A lint level set with an `@ fpp:<level>(<lint>)` annotation on a definition
+#[derive(Debug, Clone)]
+pub struct LintAttribute {
+    /// Span of the annotated definition the level applies to
+    pub scope: Span,
+    pub lint: &'static str,
+    pub level: LintLevel,
+}
+
+pub(crate) fn span_contains(outer: &Span, inner: &Span) -> bool {
+    outer.file() == inner.file()
+        && outer.start().pos() <= inner.start().pos()
+        && inner.end().pos() <= outer.end().pos()
+}
+
+impl Analysis {
+    /// The level of `lint` at `span`
+    ///
+    /// The innermost definition enclosing `span` with a lint attribute for `lint`
+    /// takes precedence over the globally configured level. Definitions enclosing
+    /// include specifiers apply to the included definitions.
+    pub fn lint_level(&self, lint: &Lint, span: Span) -> LintLevel {
+        let mut current = Some(span);
+        while let Some(span) = current {
+            let innermost = self
+                .lint_attributes
+                .iter()
+                .filter(|attr| attr.lint == lint.name && span_contains(&attr.scope, &span))
+                .min_by_key(|attr| attr.scope.len());
+
+            if let Some(attr) = innermost {
+                return attr.level;
+            }
+
+            current = span.including_span();
+        }
+
+        self.lint_config_at(&span).level(lint)
+    }
+
+    /// The project holding the file of `span`, or the file including it
+    ///
+    /// Nested projects take precedence over the projects enclosing them.
+    fn project_lint_config(&self, span: &Span) -> Option<&ProjectLintConfig> {
+        if self.project_lint_configs.is_empty() {
+            return None;
+        }
+
+        let uri = span.file().top_file().uri();
+        self.project_lint_configs
+            .iter()
+            .filter_map(|project| project.matches(&uri).map(|len| (len, project)))
+            .max_by_key(|(len, _)| *len)
+            .map(|(_, project)| project)
+    }
+
+    /// The lint levels and style rules configured for the file of `span`
+    pub fn lint_config_at(&self, span: &Span) -> &LintConfig {
+        self.project_lint_config(span)
+            .map_or(&self.lint_config, |project| &project.lint_config)
+    }
+
+    /// The maximum port buffer size configured for the file of `span`
+    pub fn max_port_buffer_size_at(&self, span: &Span) -> Option<usize> {
+        self.project_lint_config(span)
+            .map_or(self.max_port_buffer_size, |project| {
+                project.max_port_buffer_size
+            })
+    }
+
+    /// Whether unused definitions are reported in the file of `span`
+    pub fn check_unused_at(&self, span: &Span) -> bool {
+        self.project_lint_config(span)
+            .map_or(self.check_unused, |project| project.check_unused)
+    }
+
+    /// Whether unused definitions are reported in any file
+    pub fn has_unused_checks(&self) -> bool {
+        self.check_unused
+            || self
+                .project_lint_configs
+                .iter()
+                .any(|project| project.check_unused)
+    }
+
+    /// Whether any file is checked against style rules
+    pub fn has_style_rules(&self) -> bool {
+        !self.lint_config.style_rules.is_empty()
+            || self
+                .project_lint_configs
+                .iter()
+                .any(|project| !project.lint_config.style_rules.is_empty())
+    }
+
+    /// Create a diagnostic for `lint` at `span`
+    /// Returns `None` if the lint is allowed at this location
+    pub fn lint<T: Into<String>>(&self, lint: &Lint, span: Span, message: T) -> Option<Diagnostic> {
+        let level = match self.lint_level(lint, span) {
+            LintLevel::Allow => return None,
+            LintLevel::Warn => Level::Warning,
+            LintLevel::Deny => Level::Error,
+        };
+
+        Some(Diagnostic::new(span, level, message).code(lint.name))
+    }
+}
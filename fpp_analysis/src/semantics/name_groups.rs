@@ -1,7 +1,7 @@
 use fpp_macros::EnumMap;
 use std::fmt::{Display, Formatter};
 
-#[derive(EnumMap, Copy, Clone, Debug)]
+#[derive(EnumMap, Copy, Clone, Debug, PartialEq, Eq)]
 pub enum NameGroup {
     Component,
     Port,
@@ -0,0 +1,279 @@
+use crate::semantics::{
+    AnonArrayValue, AnonStructType, ArrayValue, BooleanValue, EnumConstantValue, FloatValue,
+    Format, FormatPart, FormatReplacementKind, IntegerFormatKind, IntegerValue,
+    PrimitiveIntegerValue, PrimitiveType, RationalFormatKind, StringValue, StructMembers,
+    StructValue, Type, Value,
+};
+use rustc_hash::FxHashMap as HashMap;
+use std::ops::Deref;
+use std::sync::Arc;
+
+/** Precision used by `%e`, `%f` and `%g` when none is given */
+const DEFAULT_PRECISION: usize = 6;
+
+/** Format a non-finite float the way C's printf does */
+fn non_finite(v: f64) -> Option<String> {
+    if v.is_nan() {
+        Some("nan".to_string())
+    } else if v.is_infinite() {
+        Some(if v < 0.0 { "-inf" } else { "inf" }.to_string())
+    } else {
+        None
+    }
+}
+
+/** `%.{precision}f` */
+fn render_fixed(v: f64, precision: usize) -> String {
+    non_finite(v).unwrap_or_else(|| format!("{:.*}", precision, v))
+}
+
+/** `%.{precision}e` with C's signed two digit exponent */
+fn render_exponent(v: f64, precision: usize) -> String {
+    if let Some(s) = non_finite(v) {
+        return s;
+    }
+
+    let s = format!("{:.*e}", precision, v);
+    let (mantissa, exponent) = s.split_once('e').unwrap();
+    let exponent: i32 = exponent.parse().unwrap();
+    format!(
+        "{}e{}{:02}",
+        mantissa,
+        if exponent < 0 { '-' } else { '+' },
+        exponent.abs()
+    )
+}
+
+/** `%.{precision}g` */
+fn render_general(v: f64, precision: usize) -> String {
+    if let Some(s) = non_finite(v) {
+        return s;
+    }
+
+    let precision = precision.max(1);
+
+    // The exponent after rounding to the requested number of significant digits
+    let exponent: i32 = format!("{:.*e}", precision - 1, v)
+        .split_once('e')
+        .unwrap()
+        .1
+        .parse()
+        .unwrap();
+
+    let s = if exponent < -4 || exponent >= precision as i32 {
+        render_exponent(v, precision - 1)
+    } else {
+        render_fixed(v, (precision as i32 - 1 - exponent) as usize)
+    };
+
+    // Trailing zeros in the fraction are removed
+    match s.split_once('e') {
+        None => trim_fraction(&s).to_string(),
+        Some((mantissa, exponent)) => format!("{}e{}", trim_fraction(mantissa), exponent),
+    }
+}
+
+fn trim_fraction(s: &str) -> &str {
+    if s.contains('.') {
+        s.trim_end_matches('0').trim_end_matches('.')
+    } else {
+        s
+    }
+}
+
+/** The bits of `v` as an unsigned integer of the type's width */
+fn unsigned_bits(v: i128, ty: &Type) -> u128 {
+    let width = match ty {
+        Type::PrimitiveInt(kind) => kind.bit_width(),
+        Type::Enum(ty) => ty.rep_type.bit_width(),
+        _ => 64,
+    };
+
+    (v as u128) & ((1u128 << width) - 1)
+}
+
+fn integer_value(value: &Value) -> Option<i128> {
+    match value {
+        Value::PrimitiveInteger(PrimitiveIntegerValue { value, .. })
+        | Value::Integer(IntegerValue(value))
+        | Value::EnumConstant(EnumConstantValue {
+            value: (_, value), ..
+        }) => Some(*value),
+        _ => None,
+    }
+}
+
+fn float_value(value: &Value) -> Option<f64> {
+    match value {
+        Value::Float(FloatValue { value, .. }) => Some(*value),
+        _ => integer_value(value).map(|v| v as f64),
+    }
+}
+
+impl Format {
+    /** Render the format string with one value for each replacement field,
+     *  e.g. the arguments of an event. Returns `None` if the number of values
+     *  does not match the number of fields. */
+    pub fn render(&self, values: &[(Value, Arc<Type>)]) -> Option<String> {
+        if values.len() != self.len() {
+            return None;
+        }
+
+        let mut values = values.iter();
+        let mut out = String::new();
+        for part in &self.0 {
+            match part {
+                FormatPart::Literal(s) => out.push_str(s),
+                FormatPart::FormatReplacement(field) => {
+                    let (value, ty) = values.next().unwrap();
+                    out.push_str(&field.kind.render(value, ty));
+                }
+            }
+        }
+
+        Some(out)
+    }
+}
+
+impl FormatReplacementKind {
+    /** Render a single value of `ty` through this replacement field */
+    pub fn render(&self, value: &Value, ty: &Arc<Type>) -> String {
+        let value = value.convert(ty).unwrap_or_else(|| value.clone());
+        let ty = Type::underlying_type(ty);
+
+        match self {
+            FormatReplacementKind::Default => value.render(&ty),
+            FormatReplacementKind::Integer(kind) => match integer_value(&value) {
+                None => value.render(&ty),
+                Some(v) => match kind {
+                    IntegerFormatKind::Character => {
+                        (unsigned_bits(v, &ty) as u8 as char).to_string()
+                    }
+                    IntegerFormatKind::Decimal => v.to_string(),
+                    IntegerFormatKind::Hexadecimal => format!("{:x}", unsigned_bits(v, &ty)),
+                    IntegerFormatKind::Octal => format!("{:o}", unsigned_bits(v, &ty)),
+                },
+            },
+            FormatReplacementKind::Rational { precision, kind } => match float_value(&value) {
+                None => value.render(&ty),
+                Some(v) => {
+                    let precision = precision.map_or(DEFAULT_PRECISION, |p| p as usize);
+                    match kind {
+                        RationalFormatKind::Exponent => render_exponent(v, precision),
+                        RationalFormatKind::Fixed => render_fixed(v, precision),
+                        RationalFormatKind::General => render_general(v, precision),
+                    }
+                }
+            },
+        }
+    }
+}
+
+impl Value {
+    /** Render this value of `ty` like the `toString` function F Prime generates */
+    pub fn render(&self, ty: &Arc<Type>) -> String {
+        let value = self.convert(ty).unwrap_or_else(|| self.clone());
+        let ty = Type::underlying_type(ty);
+
+        match (&value, ty.deref()) {
+            (_, Type::Array(array_ty)) => {
+                Self::render_elements(&value, &array_ty.anon_array.elt_type, &array_ty.format)
+            }
+            (_, Type::AnonArray(array_ty)) => {
+                Self::render_elements(&value, &array_ty.elt_type, &None)
+            }
+            (
+                Value::Struct(StructValue { anon_struct, .. }) | Value::AnonStruct(anon_struct),
+                _,
+            ) => match ty.deref() {
+                Type::Struct(struct_ty) => Self::render_members(
+                    &anon_struct.members,
+                    &struct_ty.anon_struct,
+                    &struct_ty.sizes,
+                    &struct_ty.formats,
+                ),
+                Type::AnonStruct(struct_ty) => Self::render_members(
+                    &anon_struct.members,
+                    struct_ty,
+                    &HashMap::default(),
+                    &HashMap::default(),
+                ),
+                _ => value.to_string(),
+            },
+            (Value::PrimitiveInteger(_) | Value::Integer(_), _) => {
+                integer_value(&value).unwrap().to_string()
+            }
+            (Value::Float(FloatValue { value, .. }), _) => render_fixed(*value, DEFAULT_PRECISION),
+            (Value::Boolean(BooleanValue(v)), _) => v.to_string(),
+            (Value::String(StringValue(s)), _) => s.clone(),
+            (Value::EnumConstant(EnumConstantValue { value, .. }), _) => value.0.clone(),
+            _ => value.to_string(),
+        }
+    }
+
+    fn render_elements(value: &Value, elt_type: &Arc<Type>, format: &Option<Format>) -> String {
+        let elements = match value {
+            Value::AnonArray(AnonArrayValue { elements })
+            | Value::Array(ArrayValue {
+                anon_array: AnonArrayValue { elements },
+                ..
+            }) => elements,
+            _ => return value.to_string(),
+        };
+
+        let elements: Vec<String> = elements
+            .iter()
+            .map(|element| Self::render_with(element, elt_type, format))
+            .collect();
+
+        format!("[ {} ]", elements.join(", "))
+    }
+
+    fn render_members(
+        members: &StructMembers<Value>,
+        struct_ty: &AnonStructType,
+        sizes: &HashMap<String, u32>,
+        formats: &HashMap<String, Format>,
+    ) -> String {
+        let members: Vec<String> = struct_ty
+            .members
+            .iter()
+            .map(|(name, member_ty)| {
+                let member = members
+                    .get(name)
+                    .cloned()
+                    .or_else(|| member_ty.default_value());
+
+                let format = formats.get(name).cloned();
+                let rendered = match (member, sizes.get(name)) {
+                    (None, _) => String::new(),
+                    (Some(member), None) => Self::render_with(&member, member_ty, &format),
+                    // Members with a size are arrays of the member type
+                    (Some(member @ (Value::AnonArray(_) | Value::Array(_))), Some(_)) => {
+                        Self::render_elements(&member, member_ty, &format)
+                    }
+                    (Some(member), Some(size)) => Self::render_elements(
+                        &Value::AnonArray(AnonArrayValue {
+                            elements: vec![member; *size as usize],
+                        }),
+                        member_ty,
+                        &format,
+                    ),
+                };
+
+                format!("{} = {}", name, rendered)
+            })
+            .collect();
+
+        format!("( {} )", members.join(", "))
+    }
+
+    fn render_with(value: &Value, ty: &Arc<Type>, format: &Option<Format>) -> String {
+        match format {
+            None => value.render(ty),
+            Some(format) => format
+                .render(&[(value.clone(), ty.clone())])
+                .unwrap_or_else(|| value.render(ty)),
+        }
+    }
+}
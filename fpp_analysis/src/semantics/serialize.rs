@@ -0,0 +1,425 @@
+use crate::semantics::{
+    int_kind_signedness, AnonArrayType, AnonArrayValue, AnonStructType, AnonStructValue,
+    ArrayValue, BooleanValue, EnumConstantValue, FloatValue, PrimitiveIntSignedness,
+    PrimitiveIntegerValue, PrimitiveType, StringValue, StructMembers, StructValue, Type, Value,
+    DEFAULT_STRING_SIZE, STRING_LENGTH_PREFIX_SIZE,
+};
+use fpp_ast::{FloatKind, IntegerKind};
+use rustc_hash::FxHashMap as HashMap;
+use std::fmt::{Display, Formatter};
+use std::ops::Deref;
+use std::sync::Arc;
+
+/** Serialized form of `true` */
+const TRUE_VALUE: u8 = 0xFF;
+
+/** Serialized form of `false` */
+const FALSE_VALUE: u8 = 0x00;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum EncodeError {
+    /** The value cannot be converted to the type */
+    Mismatch {
+        value: String,
+        ty: String,
+    },
+    /** Values of the type have no serialized form */
+    UnsupportedType(String),
+    IntegerOutOfRange {
+        value: i128,
+        kind: IntegerKind,
+    },
+    StringTooLong {
+        len: usize,
+        max: usize,
+    },
+    ArraySizeMismatch {
+        expected: usize,
+        actual: usize,
+    },
+}
+
+impl Display for EncodeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EncodeError::Mismatch { value, ty } => {
+                write!(f, "value {} cannot be converted to {}", value, ty)
+            }
+            EncodeError::UnsupportedType(ty) => write!(f, "{} cannot be serialized", ty),
+            EncodeError::IntegerOutOfRange { value, kind } => {
+                write!(f, "{} is out of range for {:?}", value, kind)
+            }
+            EncodeError::StringTooLong { len, max } => write!(
+                f,
+                "string of {} bytes exceeds the maximum size of {} bytes",
+                len, max
+            ),
+            EncodeError::ArraySizeMismatch { expected, actual } => write!(
+                f,
+                "expected {} array elements but found {}",
+                expected, actual
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DecodeError {
+    /** Values of the type have no serialized form */
+    UnsupportedType(String),
+    UnexpectedEnd {
+        needed: usize,
+        remaining: usize,
+    },
+    InvalidBoolean(u8),
+    InvalidEnumValue {
+        value: i128,
+        ty: String,
+    },
+    StringTooLong {
+        len: usize,
+        max: usize,
+    },
+    InvalidUtf8,
+}
+
+impl Display for DecodeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DecodeError::UnsupportedType(ty) => write!(f, "{} cannot be deserialized", ty),
+            DecodeError::UnexpectedEnd { needed, remaining } => write!(
+                f,
+                "expected {} more bytes but only {} remain",
+                needed, remaining
+            ),
+            DecodeError::InvalidBoolean(byte) => {
+                write!(f, "0x{:02X} is not a serialized boolean", byte)
+            }
+            DecodeError::InvalidEnumValue { value, ty } => {
+                write!(f, "{} is not a constant of enum {}", value, ty)
+            }
+            DecodeError::StringTooLong { len, max } => write!(
+                f,
+                "string of {} bytes exceeds the maximum size of {} bytes",
+                len, max
+            ),
+            DecodeError::InvalidUtf8 => f.write_str("string is not valid UTF-8"),
+        }
+    }
+}
+
+pub type EncodeResult = Result<(), EncodeError>;
+pub type DecodeResult = Result<Value, DecodeError>;
+
+fn string_max_size(size: &Option<i128>) -> usize {
+    match size {
+        None => DEFAULT_STRING_SIZE,
+        Some(size) => usize::try_from(*size).unwrap_or(usize::MAX),
+    }
+}
+
+fn encode_integer(value: i128, kind: IntegerKind, out: &mut Vec<u8>) -> EncodeResult {
+    let width = kind.bit_width();
+    let (min, max) = match int_kind_signedness(kind) {
+        PrimitiveIntSignedness::Signed => (-(1i128 << (width - 1)), (1i128 << (width - 1)) - 1),
+        PrimitiveIntSignedness::Unsigned => (0, (1i128 << width) - 1),
+    };
+
+    if value < min || value > max {
+        return Err(EncodeError::IntegerOutOfRange { value, kind });
+    }
+
+    // The low bytes of the two's complement representation
+    let bytes = value.to_be_bytes();
+    out.extend_from_slice(&bytes[bytes.len() - width as usize / 8..]);
+    Ok(())
+}
+
+fn take<'a>(bytes: &mut &'a [u8], needed: usize) -> Result<&'a [u8], DecodeError> {
+    if bytes.len() < needed {
+        return Err(DecodeError::UnexpectedEnd {
+            needed,
+            remaining: bytes.len(),
+        });
+    }
+
+    let (head, tail) = bytes.split_at(needed);
+    *bytes = tail;
+    Ok(head)
+}
+
+fn decode_integer(kind: IntegerKind, bytes: &mut &[u8]) -> Result<i128, DecodeError> {
+    let width = kind.bit_width() as usize / 8;
+    let raw = take(bytes, width)?;
+
+    let negative =
+        int_kind_signedness(kind) == PrimitiveIntSignedness::Signed && raw[0] & 0x80 != 0;
+    let mut buf = if negative { [0xFF; 16] } else { [0; 16] };
+    buf[16 - width..].copy_from_slice(raw);
+    Ok(i128::from_be_bytes(buf))
+}
+
+impl Value {
+    /** Serialize this value as `ty` following the F Prime serialization rules */
+    pub fn encode(&self, ty: &Arc<Type>, out: &mut Vec<u8>) -> EncodeResult {
+        let ty = Type::underlying_type(ty);
+        match ty.deref() {
+            Type::Array(array_ty) => self.encode_array(&ty, &array_ty.anon_array, out),
+            Type::AnonArray(array_ty) => self.encode_array(&ty, array_ty, out),
+            Type::Struct(struct_ty) => {
+                self.encode_struct(&ty, &struct_ty.anon_struct, &struct_ty.sizes, out)
+            }
+            Type::AnonStruct(struct_ty) => {
+                self.encode_struct(&ty, struct_ty, &HashMap::default(), out)
+            }
+            Type::Integer | Type::AbsType(_) => Err(EncodeError::UnsupportedType(ty.to_string())),
+            _ => self.encode_scalar(&ty, out),
+        }
+    }
+
+    fn mismatch(&self, ty: &Type) -> EncodeError {
+        EncodeError::Mismatch {
+            value: self.to_string(),
+            ty: ty.to_string(),
+        }
+    }
+
+    fn encode_scalar(&self, ty: &Arc<Type>, out: &mut Vec<u8>) -> EncodeResult {
+        let value = self.convert(ty).ok_or_else(|| self.mismatch(ty))?;
+        match (ty.deref(), value) {
+            (Type::PrimitiveInt(kind), Value::PrimitiveInteger(v)) => {
+                encode_integer(v.value, *kind, out)
+            }
+            (Type::Float(FloatKind::F32), Value::Float(v)) => {
+                out.extend_from_slice(&(v.value as f32).to_be_bytes());
+                Ok(())
+            }
+            (Type::Float(FloatKind::F64), Value::Float(v)) => {
+                out.extend_from_slice(&v.value.to_be_bytes());
+                Ok(())
+            }
+            (Type::Boolean, Value::Boolean(BooleanValue(v))) => {
+                out.push(if v { TRUE_VALUE } else { FALSE_VALUE });
+                Ok(())
+            }
+            (Type::String(size), Value::String(StringValue(s))) => {
+                let max = string_max_size(size).min(u16::MAX as usize);
+                if s.len() > max {
+                    return Err(EncodeError::StringTooLong { len: s.len(), max });
+                }
+
+                out.extend_from_slice(&(s.len() as u16).to_be_bytes());
+                out.extend_from_slice(s.as_bytes());
+                Ok(())
+            }
+            (Type::Enum(enum_ty), Value::EnumConstant(v)) => {
+                encode_integer(v.value.1, enum_ty.rep_type, out)
+            }
+            _ => Err(EncodeError::UnsupportedType(ty.to_string())),
+        }
+    }
+
+    fn encode_array(
+        &self,
+        ty: &Arc<Type>,
+        array_ty: &AnonArrayType,
+        out: &mut Vec<u8>,
+    ) -> EncodeResult {
+        let size = match array_ty.size {
+            None => return Err(EncodeError::UnsupportedType(ty.to_string())),
+            Some(size) => size,
+        };
+
+        let elements = match self {
+            Value::AnonArray(v) | Value::Array(ArrayValue { anon_array: v, .. }) => &v.elements,
+            // Promote a single value to an array
+            _ => {
+                return self
+                    .convert(ty)
+                    .ok_or_else(|| self.mismatch(ty))?
+                    .encode_array(ty, array_ty, out);
+            }
+        };
+
+        if elements.len() != size {
+            return Err(EncodeError::ArraySizeMismatch {
+                expected: size,
+                actual: elements.len(),
+            });
+        }
+
+        for element in elements {
+            element.encode(&array_ty.elt_type, out)?;
+        }
+
+        Ok(())
+    }
+
+    fn encode_struct(
+        &self,
+        ty: &Arc<Type>,
+        struct_ty: &AnonStructType,
+        sizes: &HashMap<String, u32>,
+        out: &mut Vec<u8>,
+    ) -> EncodeResult {
+        let members = match self {
+            Value::AnonStruct(v) | Value::Struct(StructValue { anon_struct: v, .. }) => &v.members,
+            // Promote a single value to a struct
+            _ => {
+                return self
+                    .convert(ty)
+                    .ok_or_else(|| self.mismatch(ty))?
+                    .encode_struct(ty, struct_ty, sizes, out);
+            }
+        };
+
+        for (name, member_ty) in &struct_ty.members {
+            let member = match members.get(name) {
+                Some(member) => member.clone(),
+                None => member_ty
+                    .default_value()
+                    .ok_or_else(|| EncodeError::UnsupportedType(member_ty.to_string()))?,
+            };
+
+            match (sizes.get(name), &member) {
+                (None, _) => member.encode(member_ty, out)?,
+                // Members with a size are arrays of the member type
+                (
+                    Some(size),
+                    Value::AnonArray(v) | Value::Array(ArrayValue { anon_array: v, .. }),
+                ) => {
+                    if v.elements.len() != *size as usize {
+                        return Err(EncodeError::ArraySizeMismatch {
+                            expected: *size as usize,
+                            actual: v.elements.len(),
+                        });
+                    }
+
+                    for element in &v.elements {
+                        element.encode(member_ty, out)?;
+                    }
+                }
+                (Some(size), _) => {
+                    for _ in 0..*size {
+                        member.encode(member_ty, out)?;
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /** Deserialize a value of `ty` from the front of `bytes`, advancing past it */
+    pub fn decode(ty: &Arc<Type>, bytes: &mut &[u8]) -> DecodeResult {
+        let ty = Type::underlying_type(ty);
+        match ty.deref() {
+            Type::PrimitiveInt(kind) => Ok(Value::PrimitiveInteger(PrimitiveIntegerValue {
+                value: decode_integer(*kind, bytes)?,
+                kind: *kind,
+            })),
+            Type::Float(kind) => {
+                let value = match kind {
+                    FloatKind::F32 => {
+                        f32::from_be_bytes(take(bytes, 4)?.try_into().unwrap()) as f64
+                    }
+                    FloatKind::F64 => f64::from_be_bytes(take(bytes, 8)?.try_into().unwrap()),
+                };
+
+                Ok(Value::Float(FloatValue { value, kind: *kind }))
+            }
+            Type::Boolean => match take(bytes, 1)?[0] {
+                TRUE_VALUE => Ok(Value::Boolean(BooleanValue(true))),
+                FALSE_VALUE => Ok(Value::Boolean(BooleanValue(false))),
+                byte => Err(DecodeError::InvalidBoolean(byte)),
+            },
+            Type::String(size) => {
+                let prefix = take(bytes, STRING_LENGTH_PREFIX_SIZE)?;
+                let len = u16::from_be_bytes(prefix.try_into().unwrap()) as usize;
+                let max = string_max_size(size);
+                if len > max {
+                    return Err(DecodeError::StringTooLong { len, max });
+                }
+
+                match String::from_utf8(take(bytes, len)?.to_vec()) {
+                    Ok(s) => Ok(Value::String(StringValue(s))),
+                    Err(_) => Err(DecodeError::InvalidUtf8),
+                }
+            }
+            Type::Enum(enum_ty) => {
+                let value = decode_integer(enum_ty.rep_type, bytes)?;
+                match enum_ty.constants.iter().find(|(_, v)| *v == value) {
+                    Some((name, _)) => Ok(Value::EnumConstant(EnumConstantValue::new(
+                        name.clone(),
+                        value,
+                        ty.clone(),
+                    ))),
+                    None => Err(DecodeError::InvalidEnumValue {
+                        value,
+                        ty: ty.to_string(),
+                    }),
+                }
+            }
+            Type::Array(array_ty) => Ok(Value::Array(ArrayValue {
+                anon_array: Self::decode_array(&ty, &array_ty.anon_array, bytes)?,
+                ty: ty.clone(),
+            })),
+            Type::AnonArray(array_ty) => {
+                Ok(Value::AnonArray(Self::decode_array(&ty, array_ty, bytes)?))
+            }
+            Type::Struct(struct_ty) => Ok(Value::Struct(StructValue::new(
+                Self::decode_struct(&struct_ty.anon_struct, &struct_ty.sizes, bytes)?,
+                ty.clone(),
+            ))),
+            Type::AnonStruct(struct_ty) => Ok(Value::AnonStruct(Self::decode_struct(
+                struct_ty,
+                &HashMap::default(),
+                bytes,
+            )?)),
+            _ => Err(DecodeError::UnsupportedType(ty.to_string())),
+        }
+    }
+
+    fn decode_array(
+        ty: &Type,
+        array_ty: &AnonArrayType,
+        bytes: &mut &[u8],
+    ) -> Result<AnonArrayValue, DecodeError> {
+        let size = match array_ty.size {
+            None => return Err(DecodeError::UnsupportedType(ty.to_string())),
+            Some(size) => size,
+        };
+
+        let mut elements = Vec::with_capacity(size);
+        for _ in 0..size {
+            elements.push(Value::decode(&array_ty.elt_type, bytes)?);
+        }
+
+        Ok(AnonArrayValue { elements })
+    }
+
+    fn decode_struct(
+        struct_ty: &AnonStructType,
+        sizes: &HashMap<String, u32>,
+        bytes: &mut &[u8],
+    ) -> Result<AnonStructValue, DecodeError> {
+        let mut members = StructMembers::default();
+        for (name, member_ty) in &struct_ty.members {
+            let member = match sizes.get(name) {
+                None => Value::decode(member_ty, bytes)?,
+                Some(size) => {
+                    let mut elements = Vec::with_capacity(*size as usize);
+                    for _ in 0..*size {
+                        elements.push(Value::decode(member_ty, bytes)?);
+                    }
+
+                    Value::AnonArray(AnonArrayValue { elements })
+                }
+            };
+
+            members.insert(name.clone(), member);
+        }
+
+        Ok(AnonStructValue { members })
+    }
+}
@@ -0,0 +1,229 @@
+use regex::Regex;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// Capitalization convention of an identifier
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CaseStyle {
+    /// `UpperCamelCase`
+    UpperCamel,
+    /// `lowerCamelCase`
+    LowerCamel,
+    /// `SCREAMING_SNAKE_CASE`
+    ScreamingSnake,
+    /// `snake_case`
+    Snake,
+}
+
+impl CaseStyle {
+    pub fn matches(&self, name: &str) -> bool {
+        let mut chars = name.chars();
+        let first = match chars.next() {
+            None => return false,
+            Some(first) => first,
+        };
+
+        match self {
+            CaseStyle::UpperCamel => {
+                first.is_ascii_uppercase() && chars.all(|c| c.is_ascii_alphanumeric())
+            }
+            CaseStyle::LowerCamel => {
+                first.is_ascii_lowercase() && chars.all(|c| c.is_ascii_alphanumeric())
+            }
+            CaseStyle::ScreamingSnake => {
+                first.is_ascii_uppercase()
+                    && name.split('_').all(|word| {
+                        !word.is_empty()
+                            && word
+                                .chars()
+                                .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit())
+                    })
+            }
+            CaseStyle::Snake => {
+                first.is_ascii_lowercase()
+                    && name.split('_').all(|word| {
+                        !word.is_empty()
+                            && word
+                                .chars()
+                                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit())
+                    })
+            }
+        }
+    }
+}
+
+impl Display for CaseStyle {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            CaseStyle::UpperCamel => "UpperCamelCase",
+            CaseStyle::LowerCamel => "lowerCamelCase",
+            CaseStyle::ScreamingSnake => "SCREAMING_SNAKE_CASE",
+            CaseStyle::Snake => "snake_case",
+        })
+    }
+}
+
+impl FromStr for CaseStyle {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "UpperCamelCase" => Ok(CaseStyle::UpperCamel),
+            "lowerCamelCase" => Ok(CaseStyle::LowerCamel),
+            "SCREAMING_SNAKE_CASE" => Ok(CaseStyle::ScreamingSnake),
+            "snake_case" => Ok(CaseStyle::Snake),
+            _ => Err(format!(
+                "invalid case style `{}`, expected `UpperCamelCase`, `lowerCamelCase`, \
+                 `SCREAMING_SNAKE_CASE` or `snake_case`",
+                s
+            )),
+        }
+    }
+}
+
+macro_rules! style_kinds {
+    ($(($variant:ident, $name:literal, $what:literal)),* $(,)?) => {
+        /// Kind of definition a style rule applies to
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub enum StyleKind {
+            $($variant,)*
+        }
+
+        impl StyleKind {
+            /// How definitions of this kind are referred to in messages
+            pub fn what(&self) -> &'static str {
+                match self {
+                    $(StyleKind::$variant => $what,)*
+                }
+            }
+        }
+
+        impl Display for StyleKind {
+            fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+                f.write_str(match self {
+                    $(StyleKind::$variant => $name,)*
+                })
+            }
+        }
+
+        impl FromStr for StyleKind {
+            type Err = String;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                match s {
+                    $($name => Ok(StyleKind::$variant),)*
+                    _ => Err(format!(
+                        "invalid definition kind `{}`, expected one of {}",
+                        s,
+                        [$(concat!("`", $name, "`")),*].join(", ")
+                    )),
+                }
+            }
+        }
+    };
+}
+
+style_kinds!(
+    (Action, "action", "action"),
+    (Choice, "choice", "choice"),
+    (Command, "command", "command"),
+    (Component, "component", "component"),
+    (Constant, "constant", "constant"),
+    (Container, "container", "container"),
+    (EnumConstant, "enum_constant", "enum constant"),
+    (Event, "event", "event"),
+    (FormalParam, "formal_param", "formal parameter"),
+    (Guard, "guard", "guard"),
+    (Instance, "instance", "component instance"),
+    (Interface, "interface", "interface"),
+    (Module, "module", "module"),
+    (Packet, "packet", "telemetry packet"),
+    (Param, "param", "parameter"),
+    (Port, "port", "port"),
+    (PortInstance, "port_instance", "port instance"),
+    (Record, "record", "record"),
+    (Signal, "signal", "signal"),
+    (State, "state", "state"),
+    (StateMachine, "state_machine", "state machine"),
+    (
+        StateMachineInstance,
+        "state_machine_instance",
+        "state machine instance"
+    ),
+    (StructMember, "struct_member", "struct member"),
+    (Telemetry, "telemetry", "telemetry channel"),
+    (Topology, "topology", "topology"),
+    (Type, "type", "type"),
+);
+
+/// What a style rule requires of a definition
+#[derive(Debug, Clone)]
+pub enum StyleCheck {
+    /// The name is written in one of the case styles
+    Case(Vec<CaseStyle>),
+    /// The name matches the regular expression
+    Pattern(Regex),
+    /// The definition has an annotation other than a lint attribute
+    Annotated,
+}
+
+/// A rule of a coding standard checked on every definition of a kind
+///
+/// Rules are written as `<name>=<kind>:<check>` where the check is one or
+/// more case styles separated by `|`, a regular expression in slashes like
+/// `/^[a-z]+$/` or `annotated`. For example
+/// `command-case=command:SCREAMING_SNAKE_CASE|snake_case`.
+#[derive(Debug, Clone)]
+pub struct StyleRule {
+    /// Identifier reported as the code of the diagnostics of the rule
+    pub name: String,
+    pub kind: StyleKind,
+    pub check: StyleCheck,
+}
+
+impl FromStr for StyleRule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, rest) = s.split_once('=').ok_or_else(|| {
+            format!(
+                "invalid style rule `{}`, expected `<name>=<kind>:<check>`",
+                s
+            )
+        })?;
+        let (kind, check) = rest.split_once(':').ok_or_else(|| {
+            format!(
+                "invalid style rule `{}`, expected `<name>=<kind>:<check>`",
+                s
+            )
+        })?;
+
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(format!("style rule `{}` has no name", s));
+        }
+
+        let check = check.trim();
+        let check = if check == "annotated" {
+            StyleCheck::Annotated
+        } else if let Some(pattern) = check.strip_prefix('/').and_then(|c| c.strip_suffix('/')) {
+            StyleCheck::Pattern(
+                Regex::new(pattern)
+                    .map_err(|err| format!("invalid pattern in style rule `{}`: {}", name, err))?,
+            )
+        } else {
+            StyleCheck::Case(
+                check
+                    .split('|')
+                    .map(|style| style.trim().parse())
+                    .collect::<Result<_, _>>()?,
+            )
+        };
+
+        Ok(StyleRule {
+            name: name.to_string(),
+            kind: kind.trim().parse()?,
+            check,
+        })
+    }
+}
@@ -0,0 +1,221 @@
+use crate::semantics::{NameGroup, Scope, Symbol, SymbolInterface};
+use crate::Analysis;
+use fpp_ast::{AstNode, QualIdent};
+use fpp_core::{Diagnostic, Span, Spanned};
+
+/// Maximum number of candidates of each kind attached to a diagnostic
+const MAX_CANDIDATES: usize = 3;
+
+/// A symbol the user may have meant when a name failed to resolve
+#[derive(Debug, Clone)]
+pub struct Suggestion {
+    pub msg: String,
+    pub loc: Span,
+}
+
+impl Suggestion {
+    pub fn annotate(suggestions: Vec<Suggestion>, diagnostic: Diagnostic) -> Diagnostic {
+        suggestions
+            .into_iter()
+            .fold(diagnostic, |diag, s| diag.span_note(s.loc, s.msg))
+    }
+}
+
+impl Symbol {
+    /// The human readable kind of definition of this symbol
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Symbol::AbsType(_) => "abstract type",
+            Symbol::AliasType(_) => "alias type",
+            Symbol::Array(_) => "array",
+            Symbol::Component(_) => "component",
+            Symbol::ComponentInstance(_) => "component instance",
+            Symbol::Constant(_) => "constant",
+            Symbol::Enum(_) => "enum",
+            Symbol::EnumConstant(_) => "enum constant",
+            Symbol::Interface(_) => "port interface",
+            Symbol::Module(_) => "module",
+            Symbol::Port(_) => "port",
+            Symbol::StateMachine(_) => "state machine",
+            Symbol::Struct(_) => "struct",
+            Symbol::Topology(_) => "topology",
+        }
+    }
+}
+
+impl NameGroup {
+    /// Check if the symbol is a definition of this name group
+    /// Modules, components and enums are also entered into other name groups
+    /// so they may be used as qualifiers, these are not suggested
+    pub fn is_definition(&self, symbol: &Symbol) -> bool {
+        matches!(
+            (self, symbol),
+            (NameGroup::Component, Symbol::Component(_))
+                | (NameGroup::Port, Symbol::Port(_))
+                | (NameGroup::StateMachine, Symbol::StateMachine(_))
+                | (
+                    NameGroup::PortInterfaceInstance,
+                    Symbol::ComponentInstance(_) | Symbol::Topology(_)
+                )
+                | (NameGroup::PortInterface, Symbol::Interface(_))
+                | (
+                    NameGroup::Type,
+                    Symbol::AbsType(_)
+                        | Symbol::AliasType(_)
+                        | Symbol::Array(_)
+                        | Symbol::Enum(_)
+                        | Symbol::Struct(_)
+                )
+                | (
+                    NameGroup::Value,
+                    Symbol::Constant(_) | Symbol::EnumConstant(_)
+                )
+        )
+    }
+}
+
+/// Number of single character edits needed to turn `a` into `b`
+pub(crate) fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = prev[j] + if ca == *cb { 0 } else { 1 };
+            current[j + 1] = substitution.min(prev[j + 1] + 1).min(current[j] + 1);
+        }
+
+        prev = current;
+    }
+
+    prev[b.len()]
+}
+
+impl Analysis {
+    /// Compute the fully qualified name of a symbol by following its parent symbols
+    pub fn qualified_name(&self, symbol: &Symbol) -> String {
+        let mut idents = vec![symbol.name().data.clone()];
+        let mut current = symbol;
+        while let Some(parent) = self.parent_symbol_map.get(current) {
+            idents.push(parent.name().data.clone());
+            current = parent;
+        }
+
+        idents.reverse();
+        idents.join(".")
+    }
+
+    /// The symbol the qualifier of a qualified identifier resolved to
+    pub fn qualifier_symbol(&self, node: &QualIdent) -> Option<&Symbol> {
+        match node {
+            QualIdent::Unqualified(_) => None,
+            QualIdent::Qualified(qualified) => {
+                let last = match qualified.qualifier.as_ref() {
+                    QualIdent::Unqualified(name) => name,
+                    QualIdent::Qualified(qualifier) => &qualifier.name,
+                };
+
+                self.use_def_map.get(&last.id())
+            }
+        }
+    }
+
+    /// Suggest definitions for a `name` that did not resolve to a valid symbol in `ng`
+    ///
+    /// When a `qualifier` is given only its scope is searched for similar names,
+    /// otherwise all the scopes visible from the current nested scope are searched.
+    pub fn suggest(
+        &self,
+        ng: NameGroup,
+        name: &str,
+        qualifier: Option<&Symbol>,
+    ) -> Vec<Suggestion> {
+        let scopes: Vec<&Scope> = match qualifier {
+            Some(qualifier) => self.symbol_scope_map.get(qualifier).into_iter().collect(),
+            None => self
+                .nested_scope
+                .iter()
+                .map(|s| self.get_scope(s))
+                .collect(),
+        };
+
+        let mut out = vec![];
+
+        // Names within a small edit distance in the same name group
+        // Short names are similar to too many other names to be useful
+        let max_distance = name.chars().count() / 3;
+        let mut similar: Vec<(usize, Symbol)> = scopes
+            .iter()
+            .flat_map(|scope| scope.get_group(ng).iter())
+            .filter(|(_, symbol)| ng.is_definition(symbol))
+            .filter_map(|(candidate, symbol)| {
+                let distance = edit_distance(name, candidate);
+                if distance > 0 && distance <= max_distance {
+                    Some((distance, symbol.clone()))
+                } else {
+                    None
+                }
+            })
+            .collect();
+
+        similar.sort_by(|(d1, s1), (d2, s2)| {
+            d1.cmp(d2).then_with(|| s1.name().data.cmp(&s2.name().data))
+        });
+        similar.dedup_by(|(_, s1), (_, s2)| s1 == s2);
+        out.extend(
+            similar
+                .into_iter()
+                .take(MAX_CANDIDATES)
+                .map(|(_, symbol)| Suggestion {
+                    msg: format!("did you mean `{}`?", symbol.name().data),
+                    loc: symbol.name().span(),
+                }),
+        );
+
+        // The same name defined in another scope, this needs to be qualified
+        let visible = scopes.iter().find_map(|scope| scope.get(ng, name));
+        let mut elsewhere: Vec<(String, Symbol)> = std::iter::once(&self.global_scope)
+            .chain(self.symbol_scope_map.values())
+            .filter_map(|scope| scope.get(ng, name))
+            .filter(|symbol| ng.is_definition(symbol) && Some(symbol) != visible.as_ref())
+            .map(|symbol| (self.qualified_name(&symbol), symbol))
+            // An unqualified use cannot reach a definition hidden by the visible scopes
+            .filter(|(qualified_name, _)| qualifier.is_some() || qualified_name != name)
+            .collect();
+
+        elsewhere.sort_by(|(n1, _), (n2, _)| n1.cmp(n2));
+        elsewhere.dedup_by(|(n1, _), (n2, _)| n1 == n2);
+        out.extend(
+            elsewhere
+                .into_iter()
+                .take(MAX_CANDIDATES)
+                .map(|(qualified_name, symbol)| Suggestion {
+                    msg: format!("did you mean `{}`?", qualified_name),
+                    loc: symbol.name().span(),
+                }),
+        );
+
+        // The same name in a different name group
+        // A symbol that was found but is invalid is already reported with the error
+        let mut other_groups: Vec<Symbol> = vec![];
+        for other_ng in NameGroup::all().filter(|other_ng| *other_ng != ng) {
+            match scopes.iter().find_map(|scope| scope.get(other_ng, name)) {
+                Some(symbol)
+                    if other_ng.is_definition(&symbol)
+                        && Some(&symbol) != visible.as_ref()
+                        && !other_groups.contains(&symbol) =>
+                {
+                    other_groups.push(symbol)
+                }
+                _ => {}
+            }
+        }
+
+        out.extend(other_groups.into_iter().map(|symbol| Suggestion {
+            msg: format!("{} `{}` is not a {}", symbol.kind(), symbol.name().data, ng),
+            loc: symbol.name().span(),
+        }));
+
+        out
+    }
+}
@@ -4,7 +4,8 @@ use crate::semantics::{
 };
 use fpp_ast::{FloatKind, IntegerKind};
 use fpp_core::Diagnostic;
-use rustc_hash::FxHashMap as HashMap;
+use indexmap::IndexMap;
+use rustc_hash::{FxBuildHasher, FxHashMap as HashMap};
 use std::fmt::{Debug, Display, Formatter};
 use std::ops::Deref;
 use std::sync::Arc;
@@ -63,7 +64,7 @@ impl Type {
                 }
 
                 Some(Value::AnonStruct(AnonStructValue {
-                    members: HashMap::from_iter(members.into_iter()),
+                    members: StructMembers::from_iter(members.into_iter()),
                 }))
             }
         }
@@ -79,6 +80,49 @@ impl Type {
         }
     }
 
+    /** Get the serialized size, if it can be computed */
+    pub fn serialized_size(&self) -> Option<SerializedSize> {
+        match self {
+            Type::PrimitiveInt(kind) => Some(SerializedSize::fixed(kind.bit_width() as usize / 8)),
+            Type::Float(kind) => Some(SerializedSize::fixed(kind.bit_width() as usize / 8)),
+            Type::String(size) => {
+                let size = match size {
+                    None => DEFAULT_STRING_SIZE,
+                    Some(size) => usize::try_from(*size).ok()?,
+                };
+
+                Some(SerializedSize {
+                    max: STRING_LENGTH_PREFIX_SIZE + size,
+                    fixed: false,
+                })
+            }
+            Type::Boolean => Some(SerializedSize::fixed(1)),
+            Type::Integer => None,
+            Type::AbsType(_) => None,
+            Type::AliasType(ty) => ty.alias_type.serialized_size(),
+            Type::Array(ty) => ty.anon_array.serialized_size(),
+            Type::AnonArray(ty) => ty.serialized_size(),
+            Type::Enum(ty) => Some(SerializedSize::fixed(ty.rep_type.bit_width() as usize / 8)),
+            Type::Struct(ty) => {
+                let mut total = SerializedSize::fixed(0);
+                for (name, member_ty) in &ty.anon_struct.members {
+                    let count = ty.sizes.get(name).copied().unwrap_or(1) as usize;
+                    total = total + member_ty.serialized_size()?.repeat(count);
+                }
+
+                Some(total)
+            }
+            Type::AnonStruct(ty) => {
+                let mut total = SerializedSize::fixed(0);
+                for member_ty in ty.members.values() {
+                    total = total + member_ty.serialized_size()?;
+                }
+
+                Some(total)
+            }
+        }
+    }
+
     /** Get the definition node identifier, if any */
     pub fn def_node_id(&self) -> Option<fpp_core::Node> {
         match self {
@@ -511,7 +555,7 @@ impl Type {
                 // - If the member only exists in t2, bring it in unchanged
                 // - If the member exists in _both_, find the common type of the member on both
                 //    - If there is no common type, return None
-                let mut out_members = HashMap::default();
+                let mut out_members = StructMembers::default();
 
                 for (name, t1_ty) in &t1_struct.members {
                     match t2_struct.members.get(name) {
@@ -558,7 +602,7 @@ impl Type {
                 if other.is_promotable_to_struct() {
                     // Build a new struct with the same members as the old one while trying
                     // to find the common type between the single element and all the members
-                    let mut out_members = HashMap::default();
+                    let mut out_members = StructMembers::default();
                     let other_rc = Arc::new(other.clone());
 
                     for (name, in_member_ty) in &str.members {
@@ -670,6 +714,62 @@ impl TypeConversionError {
 
 pub type TypeConversionResult = Result<(), TypeConversionError>;
 
+/** Size of strings declared without an explicit `size` */
+pub const DEFAULT_STRING_SIZE: usize = 80;
+
+/** Size of the length prefix serialized before the characters of a string */
+pub const STRING_LENGTH_PREFIX_SIZE: usize = 2;
+
+/** The serialized size of a type in bytes */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SerializedSize {
+    /** The largest number of bytes a value of the type serializes to */
+    pub max: usize,
+    /** Whether every value of the type serializes to exactly `max` bytes */
+    pub fixed: bool,
+}
+
+impl SerializedSize {
+    pub fn fixed(size: usize) -> SerializedSize {
+        SerializedSize {
+            max: size,
+            fixed: true,
+        }
+    }
+
+    /** The size of `count` consecutive values of this size */
+    pub fn repeat(self, count: usize) -> SerializedSize {
+        SerializedSize {
+            max: self.max.saturating_mul(count),
+            fixed: self.fixed,
+        }
+    }
+}
+
+impl std::ops::Add for SerializedSize {
+    type Output = SerializedSize;
+
+    fn add(self, rhs: SerializedSize) -> SerializedSize {
+        SerializedSize {
+            max: self.max.saturating_add(rhs.max),
+            fixed: self.fixed && rhs.fixed,
+        }
+    }
+}
+
+impl Display for SerializedSize {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if !self.fixed {
+            f.write_str("at most ")?;
+        }
+
+        match self.max {
+            1 => f.write_str("1 byte"),
+            max => write!(f, "{} bytes", max),
+        }
+    }
+}
+
 pub trait PrimitiveType {
     fn bit_width(&self) -> u32;
 }
@@ -754,6 +854,13 @@ pub struct AnonArrayType {
     pub elt_type: Arc<Type>,
 }
 
+impl AnonArrayType {
+    /** Get the serialized size, if the array size is known */
+    pub fn serialized_size(&self) -> Option<SerializedSize> {
+        Some(self.elt_type.serialized_size()?.repeat(self.size?))
+    }
+}
+
 /** An enum type */
 #[derive(Debug, Clone)]
 pub struct EnumType {
@@ -763,6 +870,8 @@ pub struct EnumType {
     pub rep_type: IntegerKind,
     /** The default value */
     pub default: Option<Value>,
+    /** The names and values of the constants */
+    pub constants: Vec<(String, i128)>,
 }
 
 /** A named struct type */
@@ -780,9 +889,12 @@ pub struct StructType {
     pub formats: HashMap<String, Format>,
 }
 
+/** Struct members in declaration order */
+pub type StructMembers<T> = IndexMap<String, T, FxBuildHasher>;
+
 /** An anonymous struct type */
 #[derive(Debug, Clone)]
 pub struct AnonStructType {
     /** The members */
-    pub members: HashMap<String, Arc<Type>>,
+    pub members: StructMembers<Arc<Type>>,
 }
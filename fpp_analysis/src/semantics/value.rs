@@ -1,6 +1,5 @@
-use crate::semantics::{ArrayType, EnumType, StructType, Type};
+use crate::semantics::{ArrayType, EnumType, StructMembers, StructType, Type};
 use fpp_ast::FloatKind;
-use rustc_hash::FxHashMap as HashMap;
 use std::fmt;
 use std::fmt::Formatter;
 use std::ops::Deref;
@@ -60,7 +59,7 @@ impl Value {
                         }))
                     }
                     Type::Struct(struct_ty) => {
-                        let mut out_value = HashMap::default();
+                        let mut out_value = StructMembers::default();
                         for (name, member_ty) in &struct_ty.anon_struct.members {
                             out_value.insert(name.clone(), self.clone().convert(member_ty)?);
                         }
@@ -71,7 +70,7 @@ impl Value {
                         }))
                     }
                     Type::AnonStruct(struct_ty) => {
-                        let mut out_value = HashMap::default();
+                        let mut out_value = StructMembers::default();
                         for (name, member_ty) in &struct_ty.members {
                             out_value.insert(name.clone(), self.clone().convert(member_ty)?);
                         }
@@ -193,7 +192,7 @@ impl Value {
             }
 
             Value::AnonStruct(anon_struct) | Value::Struct(StructValue { anon_struct, .. }) => {
-                let mut members = HashMap::default();
+                let mut members = StructMembers::default();
 
                 let to_ty = match ty.deref() {
                     // TODO(tumbar) default values need to come from struct type?
@@ -470,7 +469,7 @@ impl EnumConstantValue {
 
 #[derive(Debug, Clone)]
 pub struct AnonStructValue {
-    pub members: HashMap<String, Value>,
+    pub members: StructMembers<Value>,
 }
 
 #[derive(Debug, Clone)]
@@ -479,6 +478,19 @@ pub struct StructValue {
     ty: Arc<Type>,
 }
 
+impl StructValue {
+    pub fn new(anon_struct: AnonStructValue, ty: Arc<Type>) -> StructValue {
+        match ty.deref() {
+            Type::Struct(_) => (),
+            _ => {
+                panic!("expected struct type")
+            }
+        }
+
+        StructValue { anon_struct, ty }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct AbsTypeValue {
     ty: Arc<Type>,
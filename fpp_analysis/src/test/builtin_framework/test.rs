@@ -0,0 +1,11 @@
+use crate::test::run_framework_test;
+
+#[test]
+fn ok() {
+    run_framework_test("builtin_framework/ok")
+}
+
+#[test]
+fn redefined() {
+    run_framework_test("builtin_framework/redefined")
+}
@@ -0,0 +1,148 @@
+use crate::semantics::{Symbol, TopologyConnection};
+use crate::test::with_analysis;
+use crate::Analysis;
+
+const DEFS: &str = "
+module Fw {
+  port Cmd
+  port CmdReg
+  port CmdResponse
+  port Log
+}
+module Svc {
+  port Ping
+  port Sched
+}
+interface Pingable {
+  async input port pingIn: Svc.Ping
+  output port pingOut: Svc.Ping
+}
+module M {
+  active component Dispatcher {
+    output port cmdOut: [2] Fw.Cmd
+    sync input port cmdRegIn: Fw.CmdReg
+    sync input port cmdResponseIn: Fw.CmdResponse
+    async input port pingIn: Svc.Ping
+    output port pingOut: Svc.Ping
+    output port schedOut: [4] Svc.Sched
+  }
+  active component Worker {
+    command recv port cmdIn
+    command reg port cmdRegOut
+    command resp port cmdResponseOut
+    event port logOut
+    async input port schedIn: Svc.Sched
+    import Pingable
+  }
+  instance dispatcher: Dispatcher base id 0x100 queue size 10
+  instance a: Worker base id 0x200 queue size 10
+  instance b: Worker base id 0x300 queue size 10
+  topology Sub {
+    instance b
+  }
+  topology T {
+    import Sub
+    instance dispatcher
+    instance a
+    command connections instance dispatcher
+    health connections instance dispatcher { a }
+    connections Rate {
+      dispatcher.schedOut[1] -> a.schedIn
+      dispatcher.schedOut[1 + 2] -> b.schedIn
+    }
+  }
+}
+";
+
+fn connections(a: &Analysis, name: &str) -> Vec<String> {
+    let def = a
+        .symbol_map
+        .values()
+        .find_map(|symbol| match symbol {
+            Symbol::Topology(def) if def.name.data == name => Some(def.clone()),
+            _ => None,
+        })
+        .unwrap_or_else(|| panic!("no topology named {}", name));
+
+    a.topology_connections(&def)
+        .iter()
+        .map(|c: &TopologyConnection| {
+            let index = |index: Option<i128>| index.map_or(String::new(), |i| format!("[{}]", i));
+            format!(
+                "{}: {}.{}{} -> {}.{}{}{}",
+                c.graph,
+                c.from.instance.name.data,
+                c.from.port,
+                index(c.from.index),
+                c.to.instance.name.data,
+                c.to.port,
+                index(c.to.index),
+                if c.pattern.is_some() {
+                    " (pattern)"
+                } else {
+                    ""
+                },
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn topology_connections() {
+    with_analysis(DEFS, |a| {
+        assert_eq!(
+            connections(a, "T"),
+            vec![
+                "Commands: dispatcher.cmdOut -> b.cmdIn (pattern)",
+                "Commands: b.cmdRegOut -> dispatcher.cmdRegIn (pattern)",
+                "Commands: b.cmdResponseOut -> dispatcher.cmdResponseIn (pattern)",
+                "Commands: dispatcher.cmdOut -> a.cmdIn (pattern)",
+                "Commands: a.cmdRegOut -> dispatcher.cmdRegIn (pattern)",
+                "Commands: a.cmdResponseOut -> dispatcher.cmdResponseIn (pattern)",
+                "Health: dispatcher.pingOut -> a.pingIn (pattern)",
+                "Health: a.pingOut -> dispatcher.pingIn (pattern)",
+                "Rate: dispatcher.schedOut[1] -> a.schedIn",
+                "Rate: dispatcher.schedOut[3] -> b.schedIn",
+            ]
+        );
+
+        // Connections of imported topologies are not repeated
+        assert!(connections(a, "Sub").is_empty());
+    });
+}
+
+#[test]
+fn component_ports() {
+    with_analysis(DEFS, |a| {
+        let worker = a
+            .symbol_map
+            .values()
+            .find_map(|symbol| match symbol {
+                Symbol::Component(def) if def.name.data == "Worker" => Some(def.clone()),
+                _ => None,
+            })
+            .unwrap();
+
+        let ports: Vec<(String, Option<String>)> = a
+            .component_ports(&worker)
+            .into_iter()
+            .map(|port| (port.name, port.port_type))
+            .collect();
+
+        assert_eq!(
+            ports,
+            vec![
+                ("cmdIn".to_string(), Some("Fw.Cmd".to_string())),
+                ("cmdRegOut".to_string(), Some("Fw.CmdReg".to_string())),
+                (
+                    "cmdResponseOut".to_string(),
+                    Some("Fw.CmdResponse".to_string())
+                ),
+                ("logOut".to_string(), Some("Fw.Log".to_string())),
+                ("schedIn".to_string(), Some("Svc.Sched".to_string())),
+                ("pingIn".to_string(), Some("Svc.Ping".to_string())),
+                ("pingOut".to_string(), Some("Svc.Ping".to_string())),
+            ]
+        );
+    });
+}
@@ -1,6 +1,20 @@
-use crate::test::run_test;
+use crate::semantics::{Symbol, SymbolInterface};
+use crate::test::{run_test, with_analysis};
 
 #[test]
 fn ok() {
     run_test("defs/ok")
 }
+
+#[test]
+fn component_instance_symbol() {
+    let src = "passive component C {}\ninstance c: C base id 0x100";
+    with_analysis(src, |a| {
+        let symbol = a
+            .symbol_map
+            .values()
+            .find(|symbol| symbol.name().data == "c")
+            .expect("instance c is not in the symbol map");
+        assert!(matches!(symbol, Symbol::ComponentInstance(_)));
+    })
+}
@@ -0,0 +1,74 @@
+use fpp_core::{DiagnosticData, DiagnosticEmitter, Level, SourceFile};
+
+/// Collects the codes of all emitted errors
+struct CodeCollector(Vec<Option<String>>);
+
+impl DiagnosticEmitter for &mut CodeCollector {
+    fn emit(&mut self, diagnostic: DiagnosticData) {
+        if diagnostic.level == Level::Error {
+            self.0.push(diagnostic.code.map(|code| code.into_owned()));
+        }
+    }
+}
+
+fn error_codes(src: &str) -> Vec<Option<String>> {
+    let mut codes = CodeCollector(vec![]);
+    let mut ctx = fpp_core::CompilerContext::new(&mut codes);
+
+    fpp_core::run(&mut ctx, || {
+        let src = SourceFile::new("example.fpp", src.to_string());
+        let ast = fpp_parser::parse(src, |p| p.trans_unit(), None);
+        let mut a = crate::Analysis::new();
+        let _ = crate::check_semantics(&mut a, vec![&ast]);
+    });
+
+    codes.0
+}
+
+/// Extract the FPP examples that can be checked from an explanation
+fn examples(explanation: &str) -> Vec<String> {
+    let mut out = vec![];
+    let mut current: Option<String> = None;
+    let mut ignored = false;
+    for line in explanation.lines() {
+        match (&mut current, line) {
+            (None, "```fpp") => current = Some(String::new()),
+            (None, "```fpp,ignore") => ignored = true,
+            (Some(_), "```") => out.push(current.take().unwrap()),
+            (Some(example), line) => {
+                example.push_str(line);
+                example.push('\n');
+            }
+            _ => {}
+        }
+    }
+
+    assert!(
+        ignored || out.len() == 2,
+        "expected an erroneous and a corrected example"
+    );
+    out
+}
+
+#[test]
+fn explanation_examples() {
+    let mut failures = vec![];
+    for (code, explanation) in fpp_errors::DIAGNOSTIC_CODES {
+        let examples = examples(explanation);
+        if examples.is_empty() {
+            continue;
+        }
+
+        let erroneous = error_codes(&examples[0]);
+        if !erroneous.contains(&Some(code.to_string())) {
+            failures.push(format!("{code}: erroneous example reported {erroneous:?}"));
+        }
+
+        let corrected = error_codes(&examples[1]);
+        if !corrected.is_empty() {
+            failures.push(format!("{code}: corrected example reported {corrected:?}"));
+        }
+    }
+
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}
@@ -0,0 +1,21 @@
+use crate::test::run_test;
+
+#[test]
+fn unknown_lint() {
+    run_test("lints/unknown_lint")
+}
+
+#[test]
+fn malformed_attribute() {
+    run_test("lints/malformed_attribute")
+}
+
+#[test]
+fn allowed() {
+    run_test("lints/allowed")
+}
+
+#[test]
+fn deny() {
+    run_test("lints/deny")
+}
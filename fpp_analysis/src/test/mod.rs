@@ -1,10 +1,26 @@
+use crate::semantics::{SymbolInterface, Type};
 use fpp_core::{FileReader, SourceFile};
-use fpp_fs::FsReader;
+use fpp_fs::{framework_file, framework_files, FsReader};
 use pretty_assertions::assert_eq;
 use std::path::PathBuf;
+use std::sync::Arc;
 use std::{env, fs};
 
 pub(crate) fn run_test(file_path: &str) {
+    run_test_with(file_path, |_| {})
+}
+
+/// Run a test after configuring the analysis with `configure`
+pub(crate) fn run_test_with<F: FnOnce(&mut crate::Analysis)>(file_path: &str, configure: F) {
+    run_test_impl(file_path, false, configure)
+}
+
+/// Run a test on a model using the bundled F Prime framework definitions
+pub(crate) fn run_framework_test(file_path: &str) {
+    run_test_impl(file_path, true, |_| {})
+}
+
+fn run_test_impl<F: FnOnce(&mut crate::Analysis)>(file_path: &str, framework: bool, configure: F) {
     // Compute the path to the FPP input and .ref.txt output
     let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
     path.push("src/test");
@@ -17,7 +33,7 @@ pub(crate) fn run_test(file_path: &str) {
     ref_file.push(file_path);
     ref_file.set_extension("ref.txt");
 
-    let file_reader = FsReader {};
+    let file_reader = FsReader::default();
 
     // Set up the compiler context to capture diagnostic messages into a buffer
     let mut diagnostics_str = vec![];
@@ -34,8 +50,18 @@ pub(crate) fn run_test(file_path: &str) {
 
         let mut ast = fpp_parser::parse(src, |p| p.trans_unit(), None);
         let mut a = crate::Analysis::new();
+        configure(&mut a);
         let _ = crate::resolve_includes(&mut a, file_reader, &mut ast);
-        let _ = crate::check_semantics(&mut a, vec![&ast]);
+
+        let mut asts = vec![ast];
+        if framework {
+            for uri in framework_files() {
+                let src = SourceFile::new(&uri, framework_file(&uri).unwrap().to_string());
+                asts.push(fpp_parser::parse(src, |p| p.trans_unit(), None));
+            }
+        }
+
+        let _ = crate::check_semantics(&mut a, asts.iter().collect());
     });
 
     let output = String::from_utf8(diagnostics_str)
@@ -56,6 +82,44 @@ pub(crate) fn run_test(file_path: &str) {
     }
 }
 
+/// Analyze `src`, which must not report any diagnostics, and pass the analysis to `f`
+pub(crate) fn with_analysis<F: FnOnce(&crate::Analysis)>(src: &str, f: F) {
+    let mut diagnostics = vec![];
+    let mut ctx = fpp_core::CompilerContext::new(fpp_errors::WriteEmitter::new(&mut diagnostics));
+
+    fpp_core::run(&mut ctx, || {
+        let src = SourceFile::new("defs.fpp", src.to_string());
+        let ast = fpp_parser::parse(src, |p| p.trans_unit(), None);
+        let mut a = crate::Analysis::new();
+        let _ = crate::check_semantics(&mut a, vec![&ast]);
+
+        f(&a)
+    });
+
+    assert!(
+        diagnostics.is_empty(),
+        "{}",
+        String::from_utf8_lossy(&diagnostics)
+    );
+}
+
+/// Analyze `src`, which must not report any diagnostics, and pass a function
+/// looking up the type of a definition by name to `f`
+pub(crate) fn with_types<F: FnOnce(&dyn Fn(&str) -> Arc<Type>)>(src: &str, f: F) {
+    with_analysis(src, |a| {
+        let lookup = |name: &str| -> Arc<Type> {
+            let symbol = a
+                .symbol_map
+                .values()
+                .find(|symbol| symbol.name().data == name)
+                .unwrap_or_else(|| panic!("no definition named {}", name));
+            a.type_map.get(&symbol.node()).unwrap().clone()
+        };
+
+        f(&lookup)
+    })
+}
+
 mod cycles {
     mod test;
 }
@@ -140,10 +204,34 @@ mod invalid_symbols {
     mod test;
 }
 
+mod suggestions {
+    mod test;
+}
+
+mod explain;
+
+mod serialize;
+
+mod connections;
+
+mod render;
+
+mod lints {
+    mod test;
+}
+
 mod redef {
     mod test;
 }
 
+mod port_buffer_size {
+    mod test;
+}
+
+mod unused {
+    mod test;
+}
+
 // mod unconnected {
 //     mod test;
 // }
@@ -225,3 +313,11 @@ mod redef {
 //         mod test;
 //     }
 // }
+
+mod builtin_framework {
+    mod test;
+}
+
+mod style {
+    mod test;
+}
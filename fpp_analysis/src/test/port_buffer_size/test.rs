@@ -0,0 +1,30 @@
+use crate::test::run_test_with;
+
+/// Port buffer size used by all the tests in this directory
+const MAX_PORT_BUFFER_SIZE: usize = 64;
+
+fn run_test(file_path: &str) {
+    run_test_with(file_path, |a| {
+        a.max_port_buffer_size = Some(MAX_PORT_BUFFER_SIZE)
+    })
+}
+
+#[test]
+fn exceeded() {
+    run_test("port_buffer_size/exceeded")
+}
+
+#[test]
+fn within_limit() {
+    run_test("port_buffer_size/within_limit")
+}
+
+#[test]
+fn allowed() {
+    run_test("port_buffer_size/allowed")
+}
+
+#[test]
+fn abs_type() {
+    run_test("port_buffer_size/abs_type")
+}
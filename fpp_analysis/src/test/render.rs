@@ -0,0 +1,162 @@
+use crate::semantics::{
+    FloatValue, Format, FormatReplacementKind, IntegerFormatKind, IntegerValue,
+    PrimitiveIntegerValue, RationalFormatKind, StringValue, Type, Value,
+};
+use crate::test::with_types;
+use fpp_ast::{FloatKind, IntegerKind};
+use std::sync::Arc;
+
+const DEFS: &str = "
+enum Mode { OFF = 2, ON = 5 }
+array Volts = [3] F32 format \"{.2f} V\"
+array Counts = [2] I8 default [ 10, -1 ] format \"{x}\"
+struct Point {
+  x: I32 format \"{d}\"
+  y: [2] F64 format \"{.1e}\"
+  label: string
+  mode: Mode
+} default { x = -1, label = \"origin\" }
+array Points = [2] Point
+struct Flags {
+  ok: bool
+  c: U8 format \"{c}\"
+  o: U16 format \"{o}\"
+} default { c = 65, o = 8 }
+type Alias = Flags
+";
+
+fn render_default(ty: &Arc<Type>) -> String {
+    ty.default_value().unwrap().render(ty)
+}
+
+#[test]
+fn defaults() {
+    with_types(DEFS, |lookup| {
+        assert_eq!(render_default(&lookup("Mode")), "OFF");
+        assert_eq!(
+            render_default(&lookup("Volts")),
+            "[ 0.00 V, 0.00 V, 0.00 V ]"
+        );
+        assert_eq!(render_default(&lookup("Counts")), "[ a, ff ]");
+        assert_eq!(
+            render_default(&lookup("Point")),
+            "( x = -1, y = [ 0.0e+00, 0.0e+00 ], label = origin, mode = OFF )"
+        );
+        assert_eq!(
+            render_default(&lookup("Points")),
+            "[ ( x = -1, y = [ 0.0e+00, 0.0e+00 ], label = origin, mode = OFF ), \
+             ( x = -1, y = [ 0.0e+00, 0.0e+00 ], label = origin, mode = OFF ) ]"
+        );
+        assert_eq!(
+            render_default(&lookup("Alias")),
+            "( ok = false, c = A, o = 10 )"
+        );
+    })
+}
+
+#[test]
+fn format_fields() {
+    let f64_ty = Arc::new(Type::Float(FloatKind::F64));
+    let float = |value: f64| {
+        Value::Float(FloatValue {
+            value,
+            kind: FloatKind::F64,
+        })
+    };
+    let rational = |precision: Option<i32>, kind: RationalFormatKind, value: f64| {
+        FormatReplacementKind::Rational { precision, kind }.render(&float(value), &f64_ty)
+    };
+
+    assert_eq!(
+        rational(None, RationalFormatKind::Fixed, 1.0 / 3.0),
+        "0.333333"
+    );
+    assert_eq!(rational(Some(1), RationalFormatKind::Fixed, 2.25), "2.2");
+    assert_eq!(
+        rational(None, RationalFormatKind::Exponent, 12345.678),
+        "1.234568e+04"
+    );
+    assert_eq!(
+        rational(Some(2), RationalFormatKind::Exponent, -0.000123),
+        "-1.23e-04"
+    );
+    assert_eq!(
+        rational(None, RationalFormatKind::General, 100000.0),
+        "100000"
+    );
+    assert_eq!(rational(None, RationalFormatKind::General, 1e6), "1e+06");
+    assert_eq!(
+        rational(None, RationalFormatKind::General, 0.0001),
+        "0.0001"
+    );
+    assert_eq!(
+        rational(Some(3), RationalFormatKind::General, 1234.5678),
+        "1.23e+03"
+    );
+    assert_eq!(rational(None, RationalFormatKind::General, 2.5), "2.5");
+    assert_eq!(
+        rational(None, RationalFormatKind::Fixed, f64::NEG_INFINITY),
+        "-inf"
+    );
+
+    let i16_ty = Arc::new(Type::PrimitiveInt(IntegerKind::I16));
+    let integer = |kind: IntegerFormatKind, value: i128| {
+        FormatReplacementKind::Integer(kind).render(&Value::Integer(IntegerValue(value)), &i16_ty)
+    };
+
+    assert_eq!(integer(IntegerFormatKind::Decimal, -42), "-42");
+    assert_eq!(integer(IntegerFormatKind::Hexadecimal, -2), "fffe");
+    assert_eq!(integer(IntegerFormatKind::Octal, 64), "100");
+    assert_eq!(integer(IntegerFormatKind::Character, 0x41), "A");
+
+    // Integers are rendered as floats by rational fields
+    assert_eq!(
+        FormatReplacementKind::Rational {
+            precision: Some(1),
+            kind: RationalFormatKind::Fixed,
+        }
+        .render(
+            &Value::PrimitiveInteger(PrimitiveIntegerValue {
+                value: 3,
+                kind: IntegerKind::U8,
+            }),
+            &Arc::new(Type::PrimitiveInt(IntegerKind::U8)),
+        ),
+        "3.0"
+    );
+}
+
+#[test]
+fn event_format() {
+    with_types(DEFS, |lookup| {
+        let format_of = |name: &str| match lookup(name).as_ref() {
+            Type::Array(array) => array.format.clone().unwrap(),
+            _ => panic!("expected an array"),
+        };
+
+        // A format with several fields, like the format of an event
+        let format = Format([format_of("Volts").0, format_of("Counts").0].concat());
+        let values = [
+            (
+                Value::Integer(IntegerValue(12)),
+                Arc::new(Type::Float(FloatKind::F32)),
+            ),
+            (
+                Value::Integer(IntegerValue(-128)),
+                Arc::new(Type::PrimitiveInt(IntegerKind::I8)),
+            ),
+        ];
+
+        assert_eq!(format.render(&values), Some("12.00 V80".to_string()));
+        assert_eq!(format.render(&values[..1]), None);
+
+        let string = (
+            Value::String(StringValue("text".to_string())),
+            Arc::new(Type::String(None)),
+        );
+        assert_eq!(
+            format_of("Volts").render(&[string]),
+            Some("text V".to_string())
+        );
+    })
+}
@@ -0,0 +1,348 @@
+use crate::semantics::{
+    int_kind_signedness, AnonArrayType, AnonArrayValue, AnonStructType, AnonStructValue,
+    ArrayValue, BooleanValue, DecodeError, EncodeError, EnumConstantValue, FloatValue,
+    PrimitiveIntSignedness, PrimitiveIntegerValue, PrimitiveType, StringValue, StructMembers,
+    StructValue, Type, Value, DEFAULT_STRING_SIZE,
+};
+use crate::test::with_types;
+use fpp_ast::{FloatKind, IntegerKind};
+use proptest::prelude::*;
+use proptest::test_runner::TestRunner;
+use rustc_hash::FxHashMap as HashMap;
+use std::ops::Deref;
+use std::sync::Arc;
+
+/// Definitions covering every serializable type
+const DEFS: &str = "
+enum E : I16 { A = -3, B = 0, C = 1000 }
+array Ints = [3] U64
+array Floats = [2] F32
+struct S {
+  a: I8
+  b: [2] U16
+  c: string size 6
+  d: bool
+  e: F64
+}
+type T = S
+struct Outer {
+  e: E
+  ints: Ints
+  floats: Floats
+  t: T
+  name: string
+  small: [4] I32
+  big: I64
+  tiny: U8
+  word: U32
+}
+type Abs
+";
+
+fn anon_types() -> Vec<Arc<Type>> {
+    let elt_type = Arc::new(Type::PrimitiveInt(IntegerKind::I32));
+    let mut members = StructMembers::default();
+    members.insert("z".to_string(), Arc::new(Type::Boolean));
+    members.insert("a".to_string(), Arc::new(Type::String(Some(3))));
+    members.insert("m".to_string(), Arc::new(Type::Float(FloatKind::F64)));
+
+    vec![
+        Arc::new(Type::AnonArray(AnonArrayType {
+            size: Some(5),
+            elt_type,
+        })),
+        Arc::new(Type::AnonStruct(AnonStructType { members })),
+    ]
+}
+
+/// Generate arbitrary values of `ty`
+fn value_strategy(ty: &Arc<Type>) -> BoxedStrategy<Value> {
+    let ty = Type::underlying_type(ty);
+    match ty.deref() {
+        Type::PrimitiveInt(kind) => {
+            let kind = *kind;
+            let width = kind.bit_width();
+            let range = match int_kind_signedness(kind) {
+                PrimitiveIntSignedness::Signed => {
+                    -(1i128 << (width - 1))..=(1i128 << (width - 1)) - 1
+                }
+                PrimitiveIntSignedness::Unsigned => 0..=(1i128 << width) - 1,
+            };
+
+            range
+                .prop_map(move |value| {
+                    Value::PrimitiveInteger(PrimitiveIntegerValue { value, kind })
+                })
+                .boxed()
+        }
+        Type::Float(FloatKind::F32) => any::<f32>()
+            .prop_filter("NaN does not compare equal", |v| !v.is_nan())
+            .prop_map(|v| {
+                Value::Float(FloatValue {
+                    value: v as f64,
+                    kind: FloatKind::F32,
+                })
+            })
+            .boxed(),
+        Type::Float(FloatKind::F64) => any::<f64>()
+            .prop_filter("NaN does not compare equal", |v| !v.is_nan())
+            .prop_map(|value| {
+                Value::Float(FloatValue {
+                    value,
+                    kind: FloatKind::F64,
+                })
+            })
+            .boxed(),
+        Type::Boolean => any::<bool>()
+            .prop_map(|v| Value::Boolean(BooleanValue(v)))
+            .boxed(),
+        Type::String(size) => {
+            // Characters are at most 4 bytes in UTF-8
+            let max_chars = size.map_or(DEFAULT_STRING_SIZE, |size| size as usize) / 4;
+            proptest::collection::vec(any::<char>(), 0..=max_chars)
+                .prop_map(|chars| Value::String(StringValue(chars.into_iter().collect())))
+                .boxed()
+        }
+        Type::Enum(enum_ty) => {
+            let ty = ty.clone();
+            proptest::sample::select(enum_ty.constants.clone())
+                .prop_map(move |(name, value)| {
+                    Value::EnumConstant(EnumConstantValue::new(name, value, ty.clone()))
+                })
+                .boxed()
+        }
+        Type::Array(array_ty) => {
+            let ty = ty.clone();
+            proptest::collection::vec(
+                value_strategy(&array_ty.anon_array.elt_type),
+                array_ty.anon_array.size.unwrap(),
+            )
+            .prop_map(move |elements| {
+                Value::Array(ArrayValue {
+                    anon_array: AnonArrayValue { elements },
+                    ty: ty.clone(),
+                })
+            })
+            .boxed()
+        }
+        Type::AnonArray(array_ty) => {
+            proptest::collection::vec(value_strategy(&array_ty.elt_type), array_ty.size.unwrap())
+                .prop_map(|elements| Value::AnonArray(AnonArrayValue { elements }))
+                .boxed()
+        }
+        Type::Struct(struct_ty) => {
+            let ty = ty.clone();
+            members_strategy(&struct_ty.anon_struct, &struct_ty.sizes)
+                .prop_map(move |anon_struct| {
+                    Value::Struct(StructValue::new(anon_struct, ty.clone()))
+                })
+                .boxed()
+        }
+        Type::AnonStruct(struct_ty) => members_strategy(struct_ty, &HashMap::default())
+            .prop_map(Value::AnonStruct)
+            .boxed(),
+        _ => panic!("{} is not serializable", ty),
+    }
+}
+
+fn members_strategy(
+    struct_ty: &AnonStructType,
+    sizes: &HashMap<String, u32>,
+) -> BoxedStrategy<AnonStructValue> {
+    let names: Vec<String> = struct_ty.members.keys().cloned().collect();
+    let members: Vec<BoxedStrategy<Value>> = struct_ty
+        .members
+        .iter()
+        .map(|(name, member_ty)| match sizes.get(name) {
+            None => value_strategy(member_ty),
+            Some(size) => proptest::collection::vec(value_strategy(member_ty), *size as usize)
+                .prop_map(|elements| Value::AnonArray(AnonArrayValue { elements }))
+                .boxed(),
+        })
+        .collect();
+
+    members
+        .prop_map(move |values| AnonStructValue {
+            members: names.iter().cloned().zip(values).collect(),
+        })
+        .boxed()
+}
+
+fn encode(value: &Value, ty: &Arc<Type>) -> Vec<u8> {
+    let mut out = vec![];
+    value
+        .encode(ty, &mut out)
+        .unwrap_or_else(|err| panic!("failed to encode {}: {}", ty, err));
+    out
+}
+
+fn check_round_trip(ty: &Arc<Type>) {
+    let size = ty.serialized_size().unwrap();
+    let mut runner = TestRunner::default();
+
+    runner
+        .run(&value_strategy(ty), |value| {
+            let bytes = encode(&value, ty);
+            if size.fixed {
+                prop_assert_eq!(bytes.len(), size.max);
+            } else {
+                prop_assert!(bytes.len() <= size.max);
+            }
+
+            let mut remaining = bytes.as_slice();
+            let decoded = Value::decode(ty, &mut remaining);
+            prop_assert!(decoded.is_ok(), "{}: {:?}", ty, decoded);
+            prop_assert!(remaining.is_empty(), "{}: trailing bytes", ty);
+            prop_assert_eq!(encode(&decoded.unwrap(), ty), bytes);
+            Ok(())
+        })
+        .unwrap_or_else(|err| panic!("{}: {}", ty, err));
+}
+
+#[test]
+fn round_trip() {
+    with_types(DEFS, |lookup| {
+        let mut types = vec![
+            Arc::new(Type::Boolean),
+            Arc::new(Type::String(None)),
+            Arc::new(Type::String(Some(0))),
+            Arc::new(Type::Float(FloatKind::F32)),
+            Arc::new(Type::Float(FloatKind::F64)),
+        ];
+
+        for kind in [
+            IntegerKind::I8,
+            IntegerKind::I16,
+            IntegerKind::I32,
+            IntegerKind::I64,
+            IntegerKind::U8,
+            IntegerKind::U16,
+            IntegerKind::U32,
+            IntegerKind::U64,
+        ] {
+            types.push(Arc::new(Type::PrimitiveInt(kind)));
+        }
+
+        for name in ["E", "Ints", "Floats", "S", "T", "Outer"] {
+            types.push(lookup(name));
+        }
+
+        types.extend(anon_types());
+
+        for ty in &types {
+            check_round_trip(ty);
+        }
+    })
+}
+
+#[test]
+fn encoding() {
+    with_types(DEFS, |lookup| {
+        let s = lookup("S");
+
+        // Members are serialized in declaration order, big-endian, with
+        // booleans as 0xFF and strings prefixed with their length.
+        // Missing members take their default value.
+        let mut members = StructMembers::default();
+        members.insert("d".to_string(), Value::Boolean(BooleanValue(true)));
+        members.insert(
+            "c".to_string(),
+            Value::String(StringValue("abc".to_string())),
+        );
+        members.insert(
+            "b".to_string(),
+            Value::PrimitiveInteger(PrimitiveIntegerValue {
+                value: 0x1234,
+                kind: IntegerKind::U16,
+            }),
+        );
+        let value = Value::AnonStruct(AnonStructValue { members });
+        assert_eq!(
+            encode(&value, &s),
+            vec![
+                0x00, // a
+                0x12, 0x34, 0x12, 0x34, // b
+                0x00, 0x03, b'a', b'b', b'c', // c
+                0xFF, // d
+                0, 0, 0, 0, 0, 0, 0, 0, // e
+            ]
+        );
+
+        let e = lookup("E");
+        let c = Value::decode(&e, &mut [0x03, 0xE8].as_slice()).unwrap();
+        assert_eq!(encode(&c, &e), vec![0x03, 0xE8]);
+        assert_eq!(
+            encode(
+                &Value::Boolean(BooleanValue(true)),
+                &Arc::new(Type::Boolean)
+            ),
+            [0xFF]
+        );
+        assert_eq!(
+            encode(
+                &Value::PrimitiveInteger(PrimitiveIntegerValue {
+                    value: -2,
+                    kind: IntegerKind::I32,
+                }),
+                &Arc::new(Type::PrimitiveInt(IntegerKind::I32))
+            ),
+            vec![0xFF, 0xFF, 0xFF, 0xFE]
+        );
+    })
+}
+
+#[test]
+fn errors() {
+    with_types(DEFS, |lookup| {
+        let u8_ty = Arc::new(Type::PrimitiveInt(IntegerKind::U8));
+        let mut out = vec![];
+
+        assert_eq!(
+            Value::PrimitiveInteger(PrimitiveIntegerValue {
+                value: 256,
+                kind: IntegerKind::U8,
+            })
+            .encode(&u8_ty, &mut out),
+            Err(EncodeError::IntegerOutOfRange {
+                value: 256,
+                kind: IntegerKind::U8
+            })
+        );
+
+        assert_eq!(
+            Value::String(StringValue("too long".to_string()))
+                .encode(&Arc::new(Type::String(Some(4))), &mut out),
+            Err(EncodeError::StringTooLong { len: 8, max: 4 })
+        );
+
+        let abs = lookup("Abs");
+        assert!(matches!(
+            Value::Boolean(BooleanValue(true)).encode(&abs, &mut out),
+            Err(EncodeError::UnsupportedType(_))
+        ));
+        assert!(matches!(
+            Value::decode(&Arc::new(Type::Integer), &mut [0u8; 8].as_slice()),
+            Err(DecodeError::UnsupportedType(_))
+        ));
+
+        assert_eq!(
+            Value::decode(&Arc::new(Type::Boolean), &mut [0x01].as_slice()).err(),
+            Some(DecodeError::InvalidBoolean(0x01))
+        );
+        assert_eq!(
+            Value::decode(&lookup("E"), &mut [0x00, 0x02].as_slice()).err(),
+            Some(DecodeError::InvalidEnumValue {
+                value: 2,
+                ty: "E".to_string()
+            })
+        );
+        assert_eq!(
+            Value::decode(&lookup("Ints"), &mut [0u8; 20].as_slice()).err(),
+            Some(DecodeError::UnexpectedEnd {
+                needed: 8,
+                remaining: 4
+            })
+        );
+        assert!(out.is_empty());
+    })
+}
@@ -0,0 +1,125 @@
+use crate::semantics::{CaseStyle, LintConfig, ProjectLintConfig, StyleRule};
+use crate::test::run_test_with;
+
+fn run_test(file_path: &str, rules: &[&str]) {
+    run_test_with(file_path, |a| {
+        a.lint_config.style_rules = rules.iter().map(|rule| rule.parse().unwrap()).collect()
+    })
+}
+
+#[test]
+fn case() {
+    run_test(
+        "style/case",
+        &[
+            "type-case=type:UpperCamelCase",
+            "component-case=component:UpperCamelCase",
+            "instance-case=instance:lowerCamelCase",
+            "port-instance-case=port_instance:lowerCamelCase",
+            "command-case=command:SCREAMING_SNAKE_CASE|snake_case",
+        ],
+    )
+}
+
+#[test]
+fn pattern() {
+    run_test(
+        "style/pattern",
+        &[
+            "constant-prefix=constant:/^k[A-Z]/",
+            "enum-constant-case=enum_constant:/^[A-Z][A-Z0-9_]*$/",
+        ],
+    )
+}
+
+#[test]
+fn annotated() {
+    run_test(
+        "style/annotated",
+        &[
+            "command-docs=command:annotated",
+            "event-docs=event:annotated",
+            "telemetry-docs=telemetry:annotated",
+        ],
+    )
+}
+
+#[test]
+fn allowed() {
+    run_test(
+        "style/allowed",
+        &[
+            "type-case=type:UpperCamelCase",
+            "command-docs=command:annotated",
+        ],
+    )
+}
+
+fn project(dir: &str, rules: &[&str]) -> ProjectLintConfig {
+    let mut lint_config = LintConfig::default();
+    lint_config.style_rules = rules.iter().map(|rule| rule.parse().unwrap()).collect();
+    ProjectLintConfig {
+        sources: vec![format!("{}/src/test/{}", env!("CARGO_MANIFEST_DIR"), dir)],
+        lint_config,
+        max_port_buffer_size: None,
+        check_unused: false,
+    }
+}
+
+#[test]
+fn projects() {
+    // The innermost project holding the file replaces the global rules
+    run_test_with("style/projects", |a| {
+        a.lint_config.style_rules = vec!["type-case=type:UpperCamelCase".parse().unwrap()];
+        a.project_lint_configs = vec![
+            project("", &["type-case=type:UpperCamelCase"]),
+            project("style", &["constant-case=constant:UpperCamelCase"]),
+            project("styl", &["type-case=type:UpperCamelCase"]),
+            project("lints", &["type-case=type:UpperCamelCase"]),
+        ];
+    })
+}
+
+#[test]
+fn case_styles() {
+    assert!(CaseStyle::UpperCamel.matches("CmdDispatcher"));
+    assert!(!CaseStyle::UpperCamel.matches("cmdDispatcher"));
+    assert!(CaseStyle::LowerCamel.matches("cmdDisp2"));
+    assert!(!CaseStyle::LowerCamel.matches("cmd_disp"));
+    assert!(CaseStyle::ScreamingSnake.matches("NO_OP_2"));
+    assert!(!CaseStyle::ScreamingSnake.matches("NO__OP"));
+    assert!(CaseStyle::Snake.matches("no_op"));
+    assert!(!CaseStyle::Snake.matches("no_op_"));
+    assert!(!CaseStyle::Snake.matches(""));
+}
+
+#[test]
+fn invalid_rules() {
+    for (rule, error) in [
+        (
+            "command",
+            "invalid style rule `command`, expected `<name>=<kind>:<check>`",
+        ),
+        (
+            "=command:annotated",
+            "style rule `=command:annotated` has no name",
+        ),
+        (
+            "c=command:kebab-case",
+            "invalid case style `kebab-case`, expected `UpperCamelCase`, `lowerCamelCase`, \
+             `SCREAMING_SNAKE_CASE` or `snake_case`",
+        ),
+        (
+            "c=commands:annotated",
+            "invalid definition kind `commands`, expected one of `action`, `choice`, \
+             `command`, `component`, `constant`, `container`, `enum_constant`, `event`, \
+             `formal_param`, `guard`, `instance`, `interface`, `module`, `packet`, `param`, \
+             `port`, `port_instance`, `record`, `signal`, `state`, `state_machine`, \
+             `state_machine_instance`, `struct_member`, `telemetry`, `topology`, `type`",
+        ),
+    ] {
+        assert_eq!(rule.parse::<StyleRule>().unwrap_err(), error);
+    }
+
+    assert!("c=constant:/(/".parse::<StyleRule>().is_err());
+}
@@ -0,0 +1,16 @@
+use crate::test::run_test;
+
+#[test]
+fn similar_name() {
+    run_test("suggestions/similar_name")
+}
+
+#[test]
+fn qualified_similar_name() {
+    run_test("suggestions/qualified_similar_name")
+}
+
+#[test]
+fn other_module() {
+    run_test("suggestions/other_module")
+}
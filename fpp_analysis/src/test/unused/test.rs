@@ -0,0 +1,75 @@
+use crate::semantics::ProjectLintConfig;
+use crate::test::run_test_with;
+
+fn run_test(file_path: &str) {
+    run_test_with(file_path, |a| a.check_unused = true)
+}
+
+#[test]
+fn definitions() {
+    run_test("unused/definitions")
+}
+
+#[test]
+fn enum_constants() {
+    run_test("unused/enum_constants")
+}
+
+#[test]
+fn instances() {
+    run_test("unused/instances")
+}
+
+#[test]
+fn topologies() {
+    run_test("unused/topologies")
+}
+
+#[test]
+fn roots() {
+    // Topologies importing others are the roots, the other ones must be imported
+    run_test("unused/roots")
+}
+
+#[test]
+fn deployments() {
+    // Without imports every topology with members is a deployment
+    run_test("unused/deployments")
+}
+
+#[test]
+fn allowed() {
+    run_test("unused/allowed")
+}
+
+fn project(dir: &str, check_unused: bool) -> ProjectLintConfig {
+    ProjectLintConfig {
+        sources: vec![format!("{}/src/test/{}", env!("CARGO_MANIFEST_DIR"), dir)],
+        lint_config: Default::default(),
+        max_port_buffer_size: None,
+        check_unused,
+    }
+}
+
+#[test]
+fn projects() {
+    // The innermost project holding the file decides whether it is checked
+    run_test_with("unused/projects", |a| {
+        a.project_lint_configs = vec![
+            project("", false),
+            project("unused", true),
+            project("lints", false),
+        ];
+    })
+}
+
+#[test]
+fn framework() {
+    run_test_with("unused/framework", |a| {
+        a.check_unused = true;
+        a.framework_dirs = vec![format!(
+            "{}/src/test/unused/framework",
+            env!("CARGO_MANIFEST_DIR")
+        )];
+    })
+}
@@ -2,6 +2,7 @@ pub mod component;
 pub mod node;
 pub mod state_machine;
 pub mod topology;
+pub mod unparse;
 pub mod visit;
 
 use std::fmt::Debug;
@@ -17,6 +18,11 @@ pub use visit::*;
 
 pub trait AstNode: fpp_core::Spanned + Sized {
     fn id(&self) -> fpp_core::Node;
+
+    /// Check if the parser recovered from a syntax error inside this node
+    fn is_recovered(&self) -> bool {
+        self.id().is_recovered()
+    }
 }
 
 #[derive(Debug, Clone, VisitorWalkable)]
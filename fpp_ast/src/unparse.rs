@@ -0,0 +1,949 @@
+//! Printing of FPP source from the AST
+//!
+//! Parsing the printed source yields the same AST, annotations included.
+//! Parentheses are added where the shape of an expression tree requires
+//! them, so ASTs built by hand print correctly as well. The AST does not
+//! record whether a topology member was written with `import` or `instance`,
+//! so both print as `instance`.
+
+use crate::*;
+use fpp_core::Annotated;
+
+const INDENT: &str = "  ";
+
+/// Print a translation unit as FPP source
+pub fn trans_unit(ast: &TransUnit) -> String {
+    let members: Vec<String> = ast.0.iter().map(module_member).collect();
+    let mut out = members.join("\n\n");
+    if !out.is_empty() {
+        out.push('\n');
+    }
+
+    out
+}
+
+/// Attach the annotations of `node` to its printed `text`
+fn annotate<T: Annotated>(node: &T, text: String) -> String {
+    let mut out = String::new();
+    for line in node.pre_annotation() {
+        out.push_str(&annotation("@", &line));
+        out.push('\n');
+    }
+
+    out.push_str(&text);
+    for (i, line) in node.post_annotation().iter().enumerate() {
+        out.push(if i == 0 { ' ' } else { '\n' });
+        out.push_str(&annotation("@<", line));
+    }
+
+    out
+}
+
+fn annotation(marker: &str, line: &str) -> String {
+    if line.is_empty() {
+        marker.to_string()
+    } else {
+        format!("{} {}", marker, line)
+    }
+}
+
+fn indent(text: &str) -> String {
+    text.lines()
+        .map(|line| {
+            if line.is_empty() {
+                String::new()
+            } else {
+                format!("{}{}", INDENT, line)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// A definition with a body of `members` in curly braces
+fn body(header: String, members: Vec<String>, separator: &str) -> String {
+    if members.is_empty() {
+        format!("{} {{}}", header)
+    } else {
+        format!("{} {{\n{}\n}}", header, indent(&members.join(separator)))
+    }
+}
+
+fn dictionary(is_dictionary_def: bool) -> &'static str {
+    if is_dictionary_def { "dictionary " } else { "" }
+}
+
+/// An optional clause such as ` priority 10`
+fn opt_expr(keyword: &str, e: &Option<Expr>) -> String {
+    match e {
+        None => String::new(),
+        Some(e) => format!(" {} {}", keyword, expr(e)),
+    }
+}
+
+fn opt_format(format: &Option<LitString>) -> String {
+    match format {
+        None => String::new(),
+        Some(format) => format!(" format {}", lit_string(format)),
+    }
+}
+
+fn opt_queue_full(queue_full: &Option<QueueFull>) -> String {
+    match queue_full {
+        None => "",
+        Some(QueueFull::Assert) => " assert",
+        Some(QueueFull::Block) => " block",
+        Some(QueueFull::Drop) => " drop",
+        Some(QueueFull::Hook) => " hook",
+    }
+    .to_string()
+}
+
+/// An identifier, escaped with `$` if it is spelled like a keyword
+pub fn ident(name: &str) -> String {
+    match fpp_lexer::keyword(name) {
+        Some(_) => format!("${}", name),
+        None => name.to_string(),
+    }
+}
+
+pub fn qual_ident(q: &QualIdent) -> String {
+    match q {
+        QualIdent::Unqualified(name) => ident(&name.data),
+        QualIdent::Qualified(q) => format!("{}.{}", qual_ident(&q.qualifier), ident(&q.name.data)),
+    }
+}
+
+/// A string literal. String data is kept as written in the source, so it
+/// only needs quoting. Data that spans several lines or holds a bare quote
+/// must have come from a multi-line string.
+fn string(data: &str) -> String {
+    let mut escaped = false;
+    let bare_quote = data.chars().any(|c| {
+        let bare = c == '"' && !escaped;
+        escaped = c == '\\' && !escaped;
+        bare
+    });
+
+    if bare_quote || data.contains('\n') {
+        format!("\"\"\"{}\"\"\"", data)
+    } else {
+        format!("\"{}\"", data)
+    }
+}
+
+fn lit_string(s: &LitString) -> String {
+    string(&s.data)
+}
+
+pub fn type_name(type_name: &TypeName) -> String {
+    match &type_name.kind {
+        TypeNameKind::Bool => "bool".to_string(),
+        TypeNameKind::Floating(FloatKind::F32) => "F32".to_string(),
+        TypeNameKind::Floating(FloatKind::F64) => "F64".to_string(),
+        TypeNameKind::Integer(kind) => format!("{:?}", kind),
+        TypeNameKind::QualIdent(q) => qual_ident(q),
+        TypeNameKind::String(None) => "string".to_string(),
+        TypeNameKind::String(Some(size)) => format!("string size {}", expr(size)),
+    }
+}
+
+pub fn expr(e: &Expr) -> String {
+    expr_prec(e, 0)
+}
+
+/// Print `e` in a position where only expressions binding at least as
+/// tightly as `min` can appear without parentheses. Sums bind at 1,
+/// products at 2, negation at 3 and everything else at 4.
+fn expr_prec(e: &Expr, min: u8) -> String {
+    let (prec, text) = match &e.kind {
+        ExprKind::Array(elements) => (
+            4,
+            format!(
+                "[{}]",
+                elements.iter().map(expr).collect::<Vec<_>>().join(", ")
+            ),
+        ),
+        ExprKind::ArraySubscript { e1, e2 } => (4, format!("{}[{}]", expr_prec(e1, 4), expr(e2))),
+        ExprKind::Binop { left, op, right } => {
+            let (prec, symbol) = match op {
+                Binop::Add => (1, "+"),
+                Binop::Sub => (1, "-"),
+                Binop::Mul => (2, "*"),
+                Binop::Div => (2, "/"),
+            };
+
+            (
+                prec,
+                format!(
+                    "{} {} {}",
+                    expr_prec(left, prec),
+                    symbol,
+                    expr_prec(right, prec + 1)
+                ),
+            )
+        }
+        ExprKind::Dot { e, id } => (4, format!("{}.{}", expr_prec(e, 4), ident(&id.data))),
+        ExprKind::Ident(name) => (4, ident(name)),
+        ExprKind::LiteralBool(value) => (4, value.to_string()),
+        ExprKind::LiteralInt(value) | ExprKind::LiteralFloat(value) => (4, value.clone()),
+        ExprKind::LiteralString(value) => (4, string(value)),
+        ExprKind::Paren(e) => (4, format!("({})", expr(e))),
+        ExprKind::Struct(members) if members.is_empty() => (4, "{}".to_string()),
+        ExprKind::Struct(members) => (
+            4,
+            format!(
+                "{{ {} }}",
+                members
+                    .iter()
+                    .map(|m| format!("{} = {}", ident(&m.name.data), expr(&m.value)))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        ),
+        ExprKind::Unop { op: Unop::Minus, e } => (3, format!("-{}", expr_prec(e, 4))),
+    };
+
+    if prec < min {
+        format!("({})", text)
+    } else {
+        text
+    }
+}
+
+/// A formal parameter list, one parameter per line if any are annotated
+fn formal_params(params: &[FormalParam]) -> String {
+    if params.is_empty() {
+        return String::new();
+    }
+
+    let annotated = params
+        .iter()
+        .any(|p| !p.pre_annotation().is_empty() || !p.post_annotation().is_empty());
+
+    if annotated {
+        let params: Vec<String> = params
+            .iter()
+            .map(|p| annotate(p, formal_param(p)))
+            .collect();
+        format!("(\n{}\n)", indent(&params.join("\n")))
+    } else {
+        format!(
+            "({})",
+            params
+                .iter()
+                .map(formal_param)
+                .collect::<Vec<_>>()
+                .join(", ")
+        )
+    }
+}
+
+fn formal_param(p: &FormalParam) -> String {
+    let kind = match p.kind {
+        FormalParamKind::Ref => "ref ",
+        FormalParamKind::Value => "",
+    };
+
+    format!(
+        "{}{}: {}",
+        kind,
+        ident(&p.name.data),
+        type_name(&p.type_name)
+    )
+}
+
+/// Print a module member along with its annotations
+pub fn module_member(member: &ModuleMember) -> String {
+    let text = match member {
+        ModuleMember::DefAbsType(def) => def_abs_type(def),
+        ModuleMember::DefAliasType(def) => def_alias_type(def),
+        ModuleMember::DefArray(def) => def_array(def),
+        ModuleMember::DefComponent(def) => def_component(def),
+        ModuleMember::DefConstant(def) => def_constant(def),
+        ModuleMember::DefEnum(def) => def_enum(def),
+        ModuleMember::DefInterface(def) => def_interface(def),
+        ModuleMember::DefModule(def) => body(
+            format!("module {}", ident(&def.name.data)),
+            def.members.iter().map(module_member).collect(),
+            "\n\n",
+        ),
+        ModuleMember::DefPort(def) => format!(
+            "port {}{}{}",
+            ident(&def.name.data),
+            formal_params(&def.params),
+            match &def.return_type {
+                None => String::new(),
+                Some(ty) => format!(" -> {}", type_name(ty)),
+            }
+        ),
+        ModuleMember::DefStruct(def) => def_struct(def),
+        ModuleMember::SpecInclude(spec) => spec_include(spec),
+        ModuleMember::SpecLoc(spec) => spec_loc(spec),
+        ModuleMember::DefComponentInstance(def) => def_component_instance(def),
+        ModuleMember::DefStateMachine(def) => def_state_machine(def),
+        ModuleMember::DefTopology(def) => def_topology(def),
+    };
+
+    annotate(member, text)
+}
+
+fn def_abs_type(def: &DefAbsType) -> String {
+    format!("type {}", ident(&def.name.data))
+}
+
+fn def_alias_type(def: &DefAliasType) -> String {
+    format!(
+        "{}type {} = {}",
+        dictionary(def.is_dictionary_def),
+        ident(&def.name.data),
+        type_name(&def.type_name)
+    )
+}
+
+fn def_array(def: &DefArray) -> String {
+    format!(
+        "{}array {} = [{}] {}{}{}",
+        dictionary(def.is_dictionary_def),
+        ident(&def.name.data),
+        expr(&def.size),
+        type_name(&def.elt_type),
+        opt_expr("default", &def.default),
+        opt_format(&def.format)
+    )
+}
+
+fn def_constant(def: &DefConstant) -> String {
+    format!(
+        "{}constant {} = {}",
+        dictionary(def.is_dictionary_def),
+        ident(&def.name.data),
+        expr(&def.value)
+    )
+}
+
+fn def_enum(def: &DefEnum) -> String {
+    let header = format!(
+        "{}enum {}{}",
+        dictionary(def.is_dictionary_def),
+        ident(&def.name.data),
+        match &def.type_name {
+            None => String::new(),
+            Some(ty) => format!(": {}", type_name(ty)),
+        }
+    );
+
+    let constants = def
+        .constants
+        .iter()
+        .map(|constant| {
+            let text = match &constant.value {
+                None => ident(&constant.name.data),
+                Some(value) => format!("{} = {}", ident(&constant.name.data), expr(value)),
+            };
+            annotate(constant, text)
+        })
+        .collect();
+
+    format!(
+        "{}{}",
+        body(header, constants, "\n"),
+        opt_expr("default", &def.default)
+    )
+}
+
+fn def_struct(def: &DefStruct) -> String {
+    let header = format!(
+        "{}struct {}",
+        dictionary(def.is_dictionary_def),
+        ident(&def.name.data)
+    );
+
+    let members = def
+        .members
+        .iter()
+        .map(|member| {
+            let size = match &member.size {
+                None => String::new(),
+                Some(size) => format!("[{}] ", expr(size)),
+            };
+            let text = format!(
+                "{}: {}{}{}",
+                ident(&member.name.data),
+                size,
+                type_name(&member.type_name),
+                opt_format(&member.format)
+            );
+            annotate(member, text)
+        })
+        .collect();
+
+    format!(
+        "{}{}",
+        body(header, members, "\n"),
+        opt_expr("default", &def.default)
+    )
+}
+
+fn def_interface(def: &DefInterface) -> String {
+    let members = def
+        .members
+        .iter()
+        .map(|member| {
+            let text = match member {
+                InterfaceMember::SpecPortInstance(spec) => spec_port_instance(spec),
+                InterfaceMember::SpecInterfaceImport(spec) => spec_interface_import(spec),
+            };
+            annotate(member, text)
+        })
+        .collect();
+
+    body(
+        format!("interface {}", ident(&def.name.data)),
+        members,
+        "\n\n",
+    )
+}
+
+fn def_component(def: &DefComponent) -> String {
+    let kind = match def.kind {
+        ComponentKind::Active => "active",
+        ComponentKind::Passive => "passive",
+        ComponentKind::Queued => "queued",
+    };
+
+    body(
+        format!("{} component {}", kind, ident(&def.name.data)),
+        def.members.iter().map(component_member).collect(),
+        "\n\n",
+    )
+}
+
+/// Print a component member along with its annotations
+pub fn component_member(member: &ComponentMember) -> String {
+    let text = match member {
+        ComponentMember::DefAbsType(def) => def_abs_type(def),
+        ComponentMember::DefAliasType(def) => def_alias_type(def),
+        ComponentMember::DefArray(def) => def_array(def),
+        ComponentMember::DefConstant(def) => def_constant(def),
+        ComponentMember::DefEnum(def) => def_enum(def),
+        ComponentMember::DefStruct(def) => def_struct(def),
+        ComponentMember::SpecCommand(spec) => {
+            let kind = match spec.kind {
+                InputPortKind::Async => "async",
+                InputPortKind::Guarded => "guarded",
+                InputPortKind::Sync => "sync",
+            };
+            format!(
+                "{} command {}{}{}{}{}",
+                kind,
+                ident(&spec.name.data),
+                formal_params(&spec.params),
+                opt_expr("opcode", &spec.opcode),
+                opt_expr("priority", &spec.priority),
+                opt_queue_full(&spec.queue_full)
+            )
+        }
+        ComponentMember::SpecContainer(spec) => format!(
+            "product container {}{}{}",
+            ident(&spec.name.data),
+            opt_expr("id", &spec.id),
+            opt_expr("default priority", &spec.default_priority)
+        ),
+        ComponentMember::SpecEvent(spec) => {
+            let severity = match spec.severity {
+                EventSeverity::ActivityHigh => "activity high",
+                EventSeverity::ActivityLow => "activity low",
+                EventSeverity::Command => "command",
+                EventSeverity::Diagnostic => "diagnostic",
+                EventSeverity::Fatal => "fatal",
+                EventSeverity::WarningHigh => "warning high",
+                EventSeverity::WarningLow => "warning low",
+            };
+            let throttle = match &spec.throttle {
+                None => String::new(),
+                Some(throttle) => format!(
+                    " throttle {}{}",
+                    expr(&throttle.count),
+                    opt_expr("every", &throttle.every)
+                ),
+            };
+            format!(
+                "event {}{} severity {}{} format {}{}",
+                ident(&spec.name.data),
+                formal_params(&spec.params),
+                severity,
+                opt_expr("id", &spec.id),
+                lit_string(&spec.format),
+                throttle
+            )
+        }
+        ComponentMember::SpecInclude(spec) => spec_include(spec),
+        ComponentMember::SpecInternalPort(spec) => format!(
+            "internal port {}{}{}{}",
+            ident(&spec.name.data),
+            formal_params(&spec.params),
+            opt_expr("priority", &spec.priority),
+            opt_queue_full(&spec.queue_full)
+        ),
+        ComponentMember::SpecParam(spec) => format!(
+            "{}param {}: {}{}{}{}{}",
+            if spec.is_external { "external " } else { "" },
+            ident(&spec.name.data),
+            type_name(&spec.type_name),
+            opt_expr("default", &spec.default),
+            opt_expr("id", &spec.id),
+            opt_expr("set opcode", &spec.set_opcode),
+            opt_expr("save opcode", &spec.save_opcode)
+        ),
+        ComponentMember::SpecPortInstance(spec) => spec_port_instance(spec),
+        ComponentMember::SpecPortMatching(spec) => format!(
+            "match {} with {}",
+            ident(&spec.port1.data),
+            ident(&spec.port2.data)
+        ),
+        ComponentMember::SpecRecord(spec) => format!(
+            "product record {}: {}{}{}",
+            ident(&spec.name.data),
+            type_name(&spec.record_type),
+            if spec.is_array { " array" } else { "" },
+            opt_expr("id", &spec.id)
+        ),
+        ComponentMember::SpecStateMachineInstance(spec) => format!(
+            "state machine instance {}: {}{}{}",
+            ident(&spec.name.data),
+            qual_ident(&spec.state_machine),
+            opt_expr("priority", &spec.priority),
+            opt_queue_full(&spec.queue_full)
+        ),
+        ComponentMember::SpecTlmChannel(spec) => {
+            let update = match spec.update {
+                None => "",
+                Some(TlmChannelUpdate::Always) => " update always",
+                Some(TlmChannelUpdate::OnChange) => " update on change",
+            };
+            format!(
+                "telemetry {}: {}{}{}{}{}{}",
+                ident(&spec.name.data),
+                type_name(&spec.type_name),
+                opt_expr("id", &spec.id),
+                update,
+                opt_format(&spec.format),
+                tlm_limits("low", &spec.low),
+                tlm_limits("high", &spec.high)
+            )
+        }
+        ComponentMember::SpecInterfaceImport(spec) => spec_interface_import(spec),
+        ComponentMember::DefStateMachine(def) => def_state_machine(def),
+    };
+
+    annotate(member, text)
+}
+
+fn tlm_limits(keyword: &str, limits: &[TlmChannelLimit]) -> String {
+    if limits.is_empty() {
+        return String::new();
+    }
+
+    let limits: Vec<String> = limits
+        .iter()
+        .map(|limit| {
+            let kind = match limit.kind {
+                TlmChannelLimitKind::Red => "red",
+                TlmChannelLimitKind::Orange => "orange",
+                TlmChannelLimitKind::Yellow => "yellow",
+            };
+            format!("{} {}", kind, expr(&limit.value))
+        })
+        .collect();
+
+    format!(" {} {{ {} }}", keyword, limits.join(", "))
+}
+
+fn spec_port_instance(spec: &SpecPortInstance) -> String {
+    match spec {
+        SpecPortInstance::General(spec) => {
+            let kind = match &spec.kind {
+                GeneralPortInstanceKind::Input(InputPortKind::Async) => "async input",
+                GeneralPortInstanceKind::Input(InputPortKind::Guarded) => "guarded input",
+                GeneralPortInstanceKind::Input(InputPortKind::Sync) => "sync input",
+                GeneralPortInstanceKind::Output => "output",
+            };
+            let size = match &spec.size {
+                None => String::new(),
+                Some(size) => format!("[{}] ", expr(size)),
+            };
+            let port = match &spec.port {
+                None => "serial".to_string(),
+                Some(port) => qual_ident(port),
+            };
+            format!(
+                "{} port {}: {}{}{}{}",
+                kind,
+                ident(&spec.name.data),
+                size,
+                port,
+                opt_expr("priority", &spec.priority),
+                opt_queue_full(&spec.queue_full)
+            )
+        }
+        SpecPortInstance::Special(spec) => {
+            let input_kind = match spec.input_kind {
+                None => "",
+                Some(InputPortKind::Async) => "async ",
+                Some(InputPortKind::Guarded) => "guarded ",
+                Some(InputPortKind::Sync) => "sync ",
+            };
+            let kind = match spec.kind {
+                SpecialPortInstanceKind::CommandRecv => "command recv",
+                SpecialPortInstanceKind::CommandReg => "command reg",
+                SpecialPortInstanceKind::CommandResp => "command resp",
+                SpecialPortInstanceKind::Event => "event",
+                SpecialPortInstanceKind::ParamGet => "param get",
+                SpecialPortInstanceKind::ParamSet => "param set",
+                SpecialPortInstanceKind::ProductGet => "product get",
+                SpecialPortInstanceKind::ProductRecv => "product recv",
+                SpecialPortInstanceKind::ProductRequest => "product request",
+                SpecialPortInstanceKind::ProductSend => "product send",
+                SpecialPortInstanceKind::Telemetry => "telemetry",
+                SpecialPortInstanceKind::TextEvent => "text event",
+                SpecialPortInstanceKind::TimeGet => "time get",
+            };
+            format!(
+                "{}{} port {}{}{}",
+                input_kind,
+                kind,
+                ident(&spec.name.data),
+                opt_expr("priority", &spec.priority),
+                opt_queue_full(&spec.queue_full)
+            )
+        }
+    }
+}
+
+fn spec_interface_import(spec: &SpecInterfaceImport) -> String {
+    format!("import {}", qual_ident(&spec.interface))
+}
+
+fn spec_include(spec: &SpecInclude) -> String {
+    format!("include {}", lit_string(&spec.file))
+}
+
+fn spec_loc(spec: &SpecLoc) -> String {
+    let kind = match spec.kind {
+        SpecLocKind::Component => "component",
+        SpecLocKind::Instance => "instance",
+        SpecLocKind::Constant => "constant",
+        SpecLocKind::Port => "port",
+        SpecLocKind::StateMachine => "state machine",
+        SpecLocKind::Type => "type",
+        SpecLocKind::Interface => "interface",
+    };
+
+    format!(
+        "locate {}{} {} at {}",
+        dictionary(spec.is_dictionary_def),
+        kind,
+        qual_ident(&spec.symbol),
+        lit_string(&spec.file)
+    )
+}
+
+fn def_component_instance(def: &DefComponentInstance) -> String {
+    let opt_string = |keyword: &str, s: &Option<LitString>| match s {
+        None => String::new(),
+        Some(s) => format!(" {} {}", keyword, lit_string(s)),
+    };
+
+    let header = format!(
+        "instance {}: {} base id {}{}{}{}{}{}{}",
+        ident(&def.name.data),
+        qual_ident(&def.component),
+        expr(&def.base_id),
+        opt_string("type", &def.impl_type),
+        opt_string("at", &def.file),
+        opt_expr("queue size", &def.queue_size),
+        opt_expr("stack size", &def.stack_size),
+        opt_expr("priority", &def.priority),
+        opt_expr("cpu", &def.cpu)
+    );
+
+    if def.init_specs.is_empty() {
+        return header;
+    }
+
+    let init_specs = def
+        .init_specs
+        .iter()
+        .map(|spec| {
+            let text = format!("phase {} {}", expr(&spec.phase), lit_string(&spec.code));
+            annotate(spec, text)
+        })
+        .collect();
+
+    body(header, init_specs, "\n\n")
+}
+
+fn def_topology(def: &DefTopology) -> String {
+    let implements = if def.implements.is_empty() {
+        String::new()
+    } else {
+        format!(
+            " implements {}",
+            def.implements
+                .iter()
+                .map(qual_ident)
+                .collect::<Vec<_>>()
+                .join(", ")
+        )
+    };
+
+    body(
+        format!("topology {}{}", ident(&def.name.data), implements),
+        def.members.iter().map(topology_member).collect(),
+        "\n\n",
+    )
+}
+
+/// Print a topology member along with its annotations
+pub fn topology_member(member: &TopologyMember) -> String {
+    let text = match member {
+        TopologyMember::SpecInstance(spec) => format!("instance {}", qual_ident(&spec.instance)),
+        TopologyMember::SpecDirectConnectionGraph(spec) => body(
+            format!("connections {}", ident(&spec.name.data)),
+            spec.connections.iter().map(connection).collect(),
+            "\n",
+        ),
+        TopologyMember::SpecPatternConnectionGraph(spec) => {
+            let kind = match spec.kind {
+                ConnectionPatternKind::Command => "command",
+                ConnectionPatternKind::Event => "event",
+                ConnectionPatternKind::Health => "health",
+                ConnectionPatternKind::Param => "param",
+                ConnectionPatternKind::Telemetry => "telemetry",
+                ConnectionPatternKind::TextEvent => "text event",
+                ConnectionPatternKind::Time => "time",
+            };
+            let targets = if spec.targets.is_empty() {
+                String::new()
+            } else {
+                format!(
+                    " {{ {} }}",
+                    spec.targets
+                        .iter()
+                        .map(qual_ident)
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )
+            };
+            format!(
+                "{} connections instance {}{}",
+                kind,
+                qual_ident(&spec.source),
+                targets
+            )
+        }
+        TopologyMember::SpecInclude(spec) => spec_include(spec),
+        TopologyMember::SpecTopPort(spec) => format!(
+            "port {} = {}",
+            ident(&spec.name.data),
+            port_instance_identifier(&spec.underlying_port)
+        ),
+        TopologyMember::SpecTlmPacketSet(spec) => spec_tlm_packet_set(spec),
+    };
+
+    annotate(member, text)
+}
+
+fn connection(c: &Connection) -> String {
+    let index = |e: &Option<Expr>| match e {
+        None => String::new(),
+        Some(e) => format!("[{}]", expr(e)),
+    };
+
+    format!(
+        "{}{}{} -> {}{}",
+        if c.is_unmatched { "unmatched " } else { "" },
+        port_instance_identifier(&c.from_port),
+        index(&c.from_index),
+        port_instance_identifier(&c.to_port),
+        index(&c.to_index)
+    )
+}
+
+fn port_instance_identifier(port: &PortInstanceIdentifier) -> String {
+    format!(
+        "{}.{}",
+        qual_ident(&port.interface_instance),
+        ident(&port.port_name.data)
+    )
+}
+
+fn tlm_channel_identifier(channel: &TlmChannelIdentifier) -> String {
+    format!(
+        "{}.{}",
+        qual_ident(&channel.component_instance),
+        ident(&channel.channel_name.data)
+    )
+}
+
+fn spec_tlm_packet_set(spec: &SpecTlmPacketSet) -> String {
+    let members = spec.members.iter().map(tlm_packet_set_member).collect();
+
+    let packets = body(
+        format!("telemetry packets {}", ident(&spec.name.data)),
+        members,
+        "\n\n",
+    );
+
+    if spec.omitted.is_empty() {
+        packets
+    } else {
+        body(
+            format!("{} omit", packets),
+            spec.omitted.iter().map(tlm_channel_identifier).collect(),
+            "\n",
+        )
+    }
+}
+
+/// Print a telemetry packet set member along with its annotations
+pub fn tlm_packet_set_member(member: &TlmPacketSetMember) -> String {
+    let text = match member {
+        TlmPacketSetMember::SpecInclude(spec) => spec_include(spec),
+        TlmPacketSetMember::SpecTlmPacket(packet) => body(
+            format!(
+                "packet {}{} group {}",
+                ident(&packet.name.data),
+                opt_expr("id", &packet.id),
+                expr(&packet.group)
+            ),
+            packet.members.iter().map(tlm_packet_member).collect(),
+            "\n",
+        ),
+    };
+
+    annotate(member, text)
+}
+
+/// Print a telemetry packet member
+pub fn tlm_packet_member(member: &TlmPacketMember) -> String {
+    match member {
+        TlmPacketMember::SpecInclude(spec) => spec_include(spec),
+        TlmPacketMember::TlmChannelIdentifier(channel) => tlm_channel_identifier(channel),
+    }
+}
+
+fn def_state_machine(def: &DefStateMachine) -> String {
+    let header = format!("state machine {}", ident(&def.name.data));
+    match &def.members {
+        None => header,
+        Some(members) => body(
+            header,
+            members.iter().map(state_machine_member).collect(),
+            "\n\n",
+        ),
+    }
+}
+
+fn state_machine_member(member: &StateMachineMember) -> String {
+    let text = match member {
+        StateMachineMember::DefAction(def) => typed_element("action", &def.name, &def.type_name),
+        StateMachineMember::DefChoice(def) => def_choice(def),
+        StateMachineMember::DefGuard(def) => typed_element("guard", &def.name, &def.type_name),
+        StateMachineMember::DefSignal(def) => typed_element("signal", &def.name, &def.type_name),
+        StateMachineMember::DefState(def) => def_state(def),
+        StateMachineMember::SpecInitialTransition(spec) => {
+            format!("initial {}", transition_expr(&spec.transition))
+        }
+    };
+
+    annotate(member, text)
+}
+
+/// An action, guard or signal definition with an optional type
+fn typed_element(keyword: &str, name: &Name, ty: &Option<TypeName>) -> String {
+    match ty {
+        None => format!("{} {}", keyword, ident(&name.data)),
+        Some(ty) => format!("{} {}: {}", keyword, ident(&name.data), type_name(ty)),
+    }
+}
+
+fn def_choice(def: &DefChoice) -> String {
+    format!(
+        "choice {} {{ if {} {} else {} }}",
+        ident(&def.name.data),
+        ident(&def.guard.data),
+        transition_expr(&def.if_transition),
+        transition_expr(&def.else_transition)
+    )
+}
+
+fn def_state(def: &DefState) -> String {
+    let members: Vec<String> = def
+        .members
+        .iter()
+        .map(|member| {
+            let text = match member {
+                StateMember::DefChoice(def) => def_choice(def),
+                StateMember::DefState(def) => def_state(def),
+                StateMember::SpecInitialTransition(spec) => {
+                    format!("initial {}", transition_expr(&spec.transition))
+                }
+                StateMember::SpecStateEntry(spec) => format!("entry {}", do_expr(&spec.actions)),
+                StateMember::SpecStateExit(spec) => format!("exit {}", do_expr(&spec.actions)),
+                StateMember::SpecStateTransition(spec) => {
+                    let guard = match &spec.guard {
+                        None => String::new(),
+                        Some(guard) => format!(" if {}", ident(&guard.data)),
+                    };
+                    let transition_or_do = match &spec.transition_or_do {
+                        TransitionOrDo::Transition(transition) => transition_expr(transition),
+                        TransitionOrDo::Do(actions) => do_expr(actions),
+                    };
+                    format!(
+                        "on {}{} {}",
+                        ident(&spec.signal.data),
+                        guard,
+                        transition_or_do
+                    )
+                }
+            };
+            annotate(member, text)
+        })
+        .collect();
+
+    let header = format!("state {}", ident(&def.name.data));
+    if def.members.is_empty() {
+        header
+    } else {
+        body(header, members, "\n\n")
+    }
+}
+
+fn transition_expr(transition: &TransitionExpr) -> String {
+    match &transition.actions {
+        None => format!("enter {}", qual_ident(&transition.target)),
+        Some(actions) => format!(
+            "{} enter {}",
+            do_expr(actions),
+            qual_ident(&transition.target)
+        ),
+    }
+}
+
+fn do_expr(actions: &DoExpr) -> String {
+    if actions.actions.is_empty() {
+        return "do {}".to_string();
+    }
+
+    format!(
+        "do {{ {} }}",
+        actions
+            .actions
+            .iter()
+            .map(|action| ident(&action.data))
+            .collect::<Vec<_>>()
+            .join(", ")
+    )
+}
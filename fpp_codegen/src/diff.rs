@@ -0,0 +1,674 @@
+//! Semantic comparison of two versions of an FPP model
+//!
+//! Definitions are matched by their qualified names. A change is breaking when
+//! flight software and ground tools built from different versions would
+//! disagree on identifiers or serialized data, and compatible otherwise.
+
+use crate::types::{struct_value_syntax, value_syntax};
+use fpp_analysis::semantics::{CommandKind, ComponentDictionary, Symbol, SymbolInterface, Type};
+use fpp_analysis::Analysis;
+use fpp_ast::{
+    AstNode, EventSeverity, Expr, FloatKind, FormalParam, FormalParamKind, TypeName, TypeNameKind,
+};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::ops::Deref;
+use std::sync::Arc;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Compatibility {
+    Breaking,
+    Compatible,
+}
+
+/// A difference between the old and new version of a definition
+#[derive(Debug, Clone, Serialize)]
+pub struct Change {
+    pub compatibility: Compatibility,
+    /// The qualified name of the definition
+    pub definition: String,
+    /// The part of the definition that changed, like `command CLEAR`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub member: Option<String>,
+    pub message: String,
+}
+
+impl Display for Change {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let compatibility = match self.compatibility {
+            Compatibility::Breaking => "breaking",
+            Compatibility::Compatible => "compatible",
+        };
+        write!(f, "{}: {}", compatibility, self.definition)?;
+        if let Some(member) = &self.member {
+            write!(f, ": {}", member)?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+/// The changes as a JSON report for CI
+pub fn to_json(changes: &[Change]) -> String {
+    #[derive(Serialize)]
+    struct Report<'a> {
+        breaking: usize,
+        compatible: usize,
+        changes: &'a [Change],
+    }
+
+    let breaking = changes
+        .iter()
+        .filter(|change| change.compatibility == Compatibility::Breaking)
+        .count();
+    let report = Report {
+        breaking,
+        compatible: changes.len() - breaking,
+        changes,
+    };
+    serde_json::to_string_pretty(&report).expect("failed to serialize changes")
+}
+
+/// Compare the definitions of two analyzed versions of a model
+pub fn diff(old: &Analysis, new: &Analysis) -> Vec<Change> {
+    let mut d = Differ {
+        old,
+        new,
+        changes: vec![],
+    };
+
+    let old_defs = definitions(old);
+    let new_defs = definitions(new);
+    for (name, old_symbol) in &old_defs {
+        match new_defs.get(name) {
+            None => d.push(
+                removal(old_symbol),
+                name,
+                None,
+                format!("{} removed", old_symbol.kind()),
+            ),
+            Some(new_symbol) => d.definition(name, old_symbol, new_symbol),
+        }
+    }
+    for (name, new_symbol) in &new_defs {
+        if !old_defs.contains_key(name) {
+            d.push(
+                Compatibility::Compatible,
+                name,
+                None,
+                format!("{} added", new_symbol.kind()),
+            );
+        }
+    }
+
+    d.changes
+}
+
+/// The definitions that are compared, by qualified name
+fn definitions(a: &Analysis) -> BTreeMap<String, &Symbol> {
+    a.symbol_map
+        .values()
+        .filter(|symbol| {
+            matches!(
+                symbol,
+                Symbol::AbsType(_)
+                    | Symbol::AliasType(_)
+                    | Symbol::Array(_)
+                    | Symbol::Component(_)
+                    | Symbol::ComponentInstance(_)
+                    | Symbol::Constant(_)
+                    | Symbol::Enum(_)
+                    | Symbol::Port(_)
+                    | Symbol::Struct(_)
+                    | Symbol::Topology(_)
+            )
+        })
+        .map(|symbol| (a.qualified_name(symbol), symbol))
+        .collect()
+}
+
+/// Removing a constant only breaks the models that use it, which fail to analyze
+fn removal(symbol: &Symbol) -> Compatibility {
+    match symbol {
+        Symbol::Constant(_) => Compatibility::Compatible,
+        _ => Compatibility::Breaking,
+    }
+}
+
+fn hex(id: i128) -> String {
+    format!("{:#x}", id)
+}
+
+/// A type name with qualified names for definitions, the same in both versions
+/// if they are resolved to definitions of the same name
+fn type_name(a: &Analysis, node: &TypeName) -> String {
+    match &node.kind {
+        TypeNameKind::Bool => "bool".to_string(),
+        TypeNameKind::Floating(FloatKind::F32) => "F32".to_string(),
+        TypeNameKind::Floating(FloatKind::F64) => "F64".to_string(),
+        TypeNameKind::Integer(kind) => format!("{:?}", kind),
+        TypeNameKind::QualIdent(node) => match a.use_def_map.get(&node.id()) {
+            Some(symbol) => a.qualified_name(symbol),
+            None => "<unresolved>".to_string(),
+        },
+        TypeNameKind::String(size) => match size.as_ref().and_then(|size| a.integer_value(size)) {
+            Some(size) => format!("string size {}", size),
+            None => "string".to_string(),
+        },
+    }
+}
+
+/// The types of formal parameters in order, which give their serialized layout
+fn param_types(a: &Analysis, params: &[FormalParam]) -> String {
+    let types: Vec<String> = params
+        .iter()
+        .map(|param| match param.kind {
+            FormalParamKind::Ref => format!("ref {}", type_name(a, &param.type_name)),
+            FormalParamKind::Value => type_name(a, &param.type_name),
+        })
+        .collect();
+    format!("({})", types.join(", "))
+}
+
+fn param_names(params: &[FormalParam]) -> String {
+    let names: Vec<&str> = params
+        .iter()
+        .map(|param| param.name.data.as_str())
+        .collect();
+    format!("({})", names.join(", "))
+}
+
+fn severity(severity: &EventSeverity) -> &'static str {
+    match severity {
+        EventSeverity::ActivityHigh => "activity high",
+        EventSeverity::ActivityLow => "activity low",
+        EventSeverity::Command => "command",
+        EventSeverity::Diagnostic => "diagnostic",
+        EventSeverity::Fatal => "fatal",
+        EventSeverity::WarningHigh => "warning high",
+        EventSeverity::WarningLow => "warning low",
+    }
+}
+
+/// The value of an expression in FPP syntax
+fn expr_value(a: &Analysis, e: Option<&Expr>) -> String {
+    match e.and_then(|e| a.value_map.get(&e.id())) {
+        Some(value) => value_syntax(a, value),
+        None => "none".to_string(),
+    }
+}
+
+/// The default value of a type in FPP syntax
+fn default_value(a: &Analysis, ty: Option<&Arc<Type>>) -> String {
+    let default = match ty.map(|ty| ty.deref()) {
+        Some(Type::Array(array)) => array.default.as_ref(),
+        Some(Type::Enum(enumeration)) => enumeration.default.as_ref(),
+        Some(Type::Struct(structure)) => {
+            if let Some(default) = &structure.default {
+                return struct_value_syntax(a, default, structure);
+            }
+            None
+        }
+        _ => None,
+    };
+
+    default.map_or("none".to_string(), |default| value_syntax(a, default))
+}
+
+/// The identifiers of one kind in a component dictionary: name, identifier
+/// and the properties compared between versions as (property, value, breaking)
+type Entries = Vec<(String, i128, Vec<(&'static str, String, Compatibility)>)>;
+
+fn commands(a: &Analysis, dictionary: &ComponentDictionary) -> Entries {
+    dictionary
+        .commands
+        .iter()
+        .map(|command| {
+            let args = match &command.kind {
+                CommandKind::Command(spec) => param_types(a, &spec.params),
+                CommandKind::ParamSet(spec) => format!("({})", type_name(a, &spec.type_name)),
+                CommandKind::ParamSave(_) => "()".to_string(),
+            };
+            let names = match &command.kind {
+                CommandKind::Command(spec) => param_names(&spec.params),
+                CommandKind::ParamSet(_) => "(val)".to_string(),
+                CommandKind::ParamSave(_) => "()".to_string(),
+            };
+            (
+                command.name.clone(),
+                command.opcode,
+                vec![
+                    ("argument types", args, Compatibility::Breaking),
+                    ("argument names", names, Compatibility::Compatible),
+                ],
+            )
+        })
+        .collect()
+}
+
+fn events(a: &Analysis, dictionary: &ComponentDictionary) -> Entries {
+    dictionary
+        .events
+        .iter()
+        .map(|event| {
+            (
+                event.name.clone(),
+                event.id,
+                vec![
+                    (
+                        "argument types",
+                        param_types(a, &event.node.params),
+                        Compatibility::Breaking,
+                    ),
+                    (
+                        "argument names",
+                        param_names(&event.node.params),
+                        Compatibility::Compatible,
+                    ),
+                    (
+                        "severity",
+                        severity(&event.node.severity).to_string(),
+                        Compatibility::Compatible,
+                    ),
+                    (
+                        "format",
+                        format!("{:?}", event.node.format.data),
+                        Compatibility::Compatible,
+                    ),
+                ],
+            )
+        })
+        .collect()
+}
+
+fn channels(a: &Analysis, dictionary: &ComponentDictionary) -> Entries {
+    dictionary
+        .channels
+        .iter()
+        .map(|channel| {
+            (
+                channel.name.clone(),
+                channel.id,
+                vec![(
+                    "type",
+                    type_name(a, &channel.node.type_name),
+                    Compatibility::Breaking,
+                )],
+            )
+        })
+        .collect()
+}
+
+fn params(a: &Analysis, dictionary: &ComponentDictionary) -> Entries {
+    dictionary
+        .params
+        .iter()
+        .map(|param| {
+            (
+                param.name.clone(),
+                param.id,
+                vec![
+                    (
+                        "type",
+                        type_name(a, &param.node.type_name),
+                        Compatibility::Breaking,
+                    ),
+                    (
+                        "default",
+                        expr_value(a, param.node.default.as_ref()),
+                        Compatibility::Compatible,
+                    ),
+                ],
+            )
+        })
+        .collect()
+}
+
+struct Differ<'a> {
+    old: &'a Analysis,
+    new: &'a Analysis,
+    changes: Vec<Change>,
+}
+
+impl Differ<'_> {
+    fn push(
+        &mut self,
+        compatibility: Compatibility,
+        definition: &str,
+        member: Option<&str>,
+        message: String,
+    ) {
+        self.changes.push(Change {
+            compatibility,
+            definition: definition.to_string(),
+            member: member.map(str::to_string),
+            message,
+        })
+    }
+
+    /// Record a change if a property differs between versions
+    fn compare(
+        &mut self,
+        compatibility: Compatibility,
+        definition: &str,
+        member: Option<&str>,
+        property: &str,
+        old: String,
+        new: String,
+    ) {
+        if old != new {
+            self.push(
+                compatibility,
+                definition,
+                member,
+                format!("{} changed from {} to {}", property, old, new),
+            );
+        }
+    }
+
+    fn definition(&mut self, name: &str, old_symbol: &Symbol, new_symbol: &Symbol) {
+        let (old, new) = (self.old, self.new);
+        let old_ty = old.type_map.get(&old_symbol.node());
+        let new_ty = new.type_map.get(&new_symbol.node());
+
+        match (old_symbol, new_symbol) {
+            (Symbol::AbsType(_), Symbol::AbsType(_)) => {}
+            (Symbol::AliasType(old_def), Symbol::AliasType(new_def)) => self.compare(
+                Compatibility::Breaking,
+                name,
+                None,
+                "type",
+                type_name(old, &old_def.type_name),
+                type_name(new, &new_def.type_name),
+            ),
+            (Symbol::Array(old_def), Symbol::Array(new_def)) => {
+                self.compare(
+                    Compatibility::Breaking,
+                    name,
+                    None,
+                    "size",
+                    expr_value(old, Some(&old_def.size)),
+                    expr_value(new, Some(&new_def.size)),
+                );
+                self.compare(
+                    Compatibility::Breaking,
+                    name,
+                    None,
+                    "element type",
+                    type_name(old, &old_def.elt_type),
+                    type_name(new, &new_def.elt_type),
+                );
+                self.defaults(name, old_ty, new_ty);
+            }
+            (Symbol::Component(old_def), Symbol::Component(new_def)) => self.dictionary(
+                name,
+                &old.component_dictionary(old_def),
+                &new.component_dictionary(new_def),
+            ),
+            (Symbol::ComponentInstance(old_def), Symbol::ComponentInstance(new_def)) => {
+                self.compare(
+                    Compatibility::Breaking,
+                    name,
+                    None,
+                    "component",
+                    old.use_def_map
+                        .get(&old_def.component.id())
+                        .map_or(String::new(), |symbol| old.qualified_name(symbol)),
+                    new.use_def_map
+                        .get(&new_def.component.id())
+                        .map_or(String::new(), |symbol| new.qualified_name(symbol)),
+                );
+                let old_id = old.integer_value(&old_def.base_id);
+                let new_id = new.integer_value(&new_def.base_id);
+                self.compare(
+                    Compatibility::Breaking,
+                    name,
+                    None,
+                    "base ID",
+                    old_id.map_or(String::new(), hex),
+                    new_id.map_or(String::new(), hex),
+                );
+            }
+            (Symbol::Constant(old_def), Symbol::Constant(new_def)) => self.compare(
+                Compatibility::Compatible,
+                name,
+                None,
+                "value",
+                expr_value(old, Some(&old_def.value)),
+                expr_value(new, Some(&new_def.value)),
+            ),
+            (Symbol::Enum(_), Symbol::Enum(_)) => self.enumeration(name, old_ty, new_ty),
+            (Symbol::Port(old_def), Symbol::Port(new_def)) => {
+                self.params(name, None, &old_def.params, &new_def.params);
+                let return_type = |a: &Analysis, ty: &Option<TypeName>| {
+                    ty.as_ref()
+                        .map_or("none".to_string(), |ty| type_name(a, ty))
+                };
+                self.compare(
+                    Compatibility::Breaking,
+                    name,
+                    None,
+                    "return type",
+                    return_type(old, &old_def.return_type),
+                    return_type(new, &new_def.return_type),
+                );
+            }
+            (Symbol::Struct(old_def), Symbol::Struct(new_def)) => {
+                let layout = |a: &Analysis, def: &fpp_ast::DefStruct| {
+                    let members: Vec<String> = def
+                        .members
+                        .iter()
+                        .map(|member| {
+                            let size = member
+                                .size
+                                .as_ref()
+                                .and_then(|size| a.integer_value(size))
+                                .map_or(String::new(), |size| format!("[{}] ", size));
+                            format!(
+                                "{}: {}{}",
+                                member.name.data,
+                                size,
+                                type_name(a, &member.type_name)
+                            )
+                        })
+                        .collect();
+                    format!("{{ {} }}", members.join(", "))
+                };
+                self.compare(
+                    Compatibility::Breaking,
+                    name,
+                    None,
+                    "layout",
+                    layout(old, old_def),
+                    layout(new, new_def),
+                );
+                self.defaults(name, old_ty, new_ty);
+            }
+            (Symbol::Topology(old_def), Symbol::Topology(new_def)) => {
+                let instances = |a: &Analysis, def: &fpp_ast::DefTopology| -> Vec<String> {
+                    a.topology_instances(def)
+                        .into_iter()
+                        .map(|instance| instance.name)
+                        .collect()
+                };
+                let old_instances = instances(old, old_def);
+                let new_instances = instances(new, new_def);
+                for instance in &old_instances {
+                    if !new_instances.contains(instance) {
+                        self.push(
+                            Compatibility::Breaking,
+                            name,
+                            Some(&format!("instance {}", instance)),
+                            "removed".to_string(),
+                        );
+                    }
+                }
+                for instance in &new_instances {
+                    if !old_instances.contains(instance) {
+                        self.push(
+                            Compatibility::Compatible,
+                            name,
+                            Some(&format!("instance {}", instance)),
+                            "added".to_string(),
+                        );
+                    }
+                }
+            }
+            _ => self.push(
+                Compatibility::Breaking,
+                name,
+                None,
+                format!("changed from {} to {}", old_symbol.kind(), new_symbol.kind()),
+            ),
+        }
+    }
+
+    /// Compare the default values of two types, which only change the initial
+    /// values of data and not its layout
+    fn defaults(&mut self, name: &str, old_ty: Option<&Arc<Type>>, new_ty: Option<&Arc<Type>>) {
+        self.compare(
+            Compatibility::Compatible,
+            name,
+            None,
+            "default",
+            default_value(self.old, old_ty),
+            default_value(self.new, new_ty),
+        );
+    }
+
+    fn enumeration(&mut self, name: &str, old_ty: Option<&Arc<Type>>, new_ty: Option<&Arc<Type>>) {
+        let (Some(Type::Enum(old_enum)), Some(Type::Enum(new_enum))) =
+            (old_ty.map(|ty| ty.deref()), new_ty.map(|ty| ty.deref()))
+        else {
+            return;
+        };
+
+        self.compare(
+            Compatibility::Breaking,
+            name,
+            None,
+            "representation type",
+            format!("{:?}", old_enum.rep_type),
+            format!("{:?}", new_enum.rep_type),
+        );
+
+        for (constant, old_value) in &old_enum.constants {
+            let member = format!("constant {}", constant);
+            match new_enum.constants.iter().find(|(c, _)| c == constant) {
+                None => self.push(
+                    Compatibility::Breaking,
+                    name,
+                    Some(&member),
+                    "removed".to_string(),
+                ),
+                Some((_, new_value)) => self.compare(
+                    Compatibility::Breaking,
+                    name,
+                    Some(&member),
+                    "value",
+                    old_value.to_string(),
+                    new_value.to_string(),
+                ),
+            }
+        }
+        for (constant, _) in &new_enum.constants {
+            if old_enum.constants.iter().all(|(c, _)| c != constant) {
+                self.push(
+                    Compatibility::Compatible,
+                    name,
+                    Some(&format!("constant {}", constant)),
+                    "added".to_string(),
+                );
+            }
+        }
+
+        self.defaults(name, old_ty, new_ty);
+    }
+
+    /// Compare formal parameters: their types in order give the serialized layout,
+    /// their names only matter to people reading the dictionary
+    fn params(
+        &mut self,
+        name: &str,
+        member: Option<&str>,
+        old_params: &[FormalParam],
+        new_params: &[FormalParam],
+    ) {
+        self.compare(
+            Compatibility::Breaking,
+            name,
+            member,
+            "argument types",
+            param_types(self.old, old_params),
+            param_types(self.new, new_params),
+        );
+        self.compare(
+            Compatibility::Compatible,
+            name,
+            member,
+            "argument names",
+            param_names(old_params),
+            param_names(new_params),
+        );
+    }
+
+    fn dictionary(&mut self, name: &str, old: &ComponentDictionary, new: &ComponentDictionary) {
+        let (old_commands, new_commands) = (commands(self.old, old), commands(self.new, new));
+        self.entries(name, "command", "opcode", old_commands, new_commands);
+        let (old_events, new_events) = (events(self.old, old), events(self.new, new));
+        self.entries(name, "event", "ID", old_events, new_events);
+        let (old_channels, new_channels) = (channels(self.old, old), channels(self.new, new));
+        self.entries(name, "channel", "ID", old_channels, new_channels);
+        let (old_params, new_params) = (params(self.old, old), params(self.new, new));
+        self.entries(name, "param", "ID", old_params, new_params);
+    }
+
+    /// Compare the entries of one kind in a component dictionary by name
+    fn entries(&mut self, name: &str, kind: &str, id: &str, old: Entries, new: Entries) {
+        for (entry, old_id, old_properties) in &old {
+            let member = format!("{} {}", kind, entry);
+            let Some((_, new_id, new_properties)) = new.iter().find(|(e, ..)| e == entry) else {
+                self.push(
+                    Compatibility::Breaking,
+                    name,
+                    Some(&member),
+                    "removed".to_string(),
+                );
+                continue;
+            };
+
+            self.compare(
+                Compatibility::Breaking,
+                name,
+                Some(&member),
+                id,
+                hex(*old_id),
+                hex(*new_id),
+            );
+            for ((property, old_value, compatibility), (_, new_value, _)) in
+                old_properties.iter().zip(new_properties)
+            {
+                self.compare(
+                    *compatibility,
+                    name,
+                    Some(&member),
+                    property,
+                    old_value.clone(),
+                    new_value.clone(),
+                );
+            }
+        }
+
+        for (entry, ..) in &new {
+            if old.iter().all(|(e, ..)| e != entry) {
+                self.push(
+                    Compatibility::Compatible,
+                    name,
+                    Some(&format!("{} {}", kind, entry)),
+                    "added".to_string(),
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,908 @@
+//! Generation of a documentation site from FPP annotations
+//!
+//! Every module, component and topology gets a page, definitions outside of
+//! any module are listed on the index page. Annotations are copied into the
+//! pages as Markdown and uses of definitions link to the page documenting them.
+
+use crate::types::{struct_value_syntax, value_syntax};
+use crate::Page;
+use fpp_analysis::passes::doc_annotation;
+use fpp_analysis::semantics::{CommandKind, StructType, Symbol, SymbolInterface, Type};
+use fpp_analysis::Analysis;
+use fpp_ast::{
+    AstNode, ComponentKind, ComponentMember, DefAbsType, DefAliasType, DefArray, DefComponent,
+    DefComponentInstance, DefConstant, DefEnum, DefInterface, DefPort, DefStateMachine, DefStruct,
+    DefTopology, EventSeverity, FloatKind, FormalParam, FormalParamKind, GeneralPortInstanceKind,
+    InputPortKind, ModuleMember, QualIdent, SpecPortInstance, SpecialPortInstanceKind,
+    TlmChannelUpdate, TopologyMember, TransUnit, TypeName, TypeNameKind,
+};
+use fpp_core::Annotated;
+use std::fmt::Write;
+use std::ops::Deref;
+
+/// The name of the page listing the definitions outside of any module
+const INDEX: &str = "index";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DocFormat {
+    Markdown,
+    Html,
+}
+
+impl DocFormat {
+    fn extension(&self) -> &'static str {
+        match self {
+            DocFormat::Markdown => "md",
+            DocFormat::Html => "html",
+        }
+    }
+}
+
+/// Generate the pages documenting the definitions in `ast`
+pub fn generate(a: &Analysis, ast: &[&TransUnit], format: DocFormat) -> Vec<Page> {
+    let mut modules = vec![ModulePage::default()];
+    for trans_unit in ast {
+        collect(&mut modules, 0, None, &trans_unit.0);
+    }
+
+    let g = DocGenerator { a, format };
+    let mut pages = vec![];
+    for module in &modules {
+        let name = module.name.clone().unwrap_or_else(|| INDEX.to_string());
+        pages.push(g.page(&name, g.module_page(module, &modules)));
+
+        for member in &module.members {
+            match member {
+                ModuleMember::DefComponent(def) => {
+                    let name = qualify(module.name.as_deref(), &def.name.data);
+                    pages.push(g.page(&name, g.component_page(&name, def)));
+                }
+                ModuleMember::DefTopology(def) => {
+                    let name = qualify(module.name.as_deref(), &def.name.data);
+                    pages.push(g.page(&name, g.topology_page(&name, def)));
+                }
+                _ => {}
+            }
+        }
+    }
+
+    pages
+}
+
+/// The members of a module, merged over every `module` block that opens it
+#[derive(Default)]
+struct ModulePage<'ast> {
+    /// The qualified name of the module, or `None` for the index page
+    name: Option<String>,
+    annotation: Vec<String>,
+    members: Vec<&'ast ModuleMember>,
+    /// The qualified names of the modules nested in this one
+    modules: Vec<String>,
+}
+
+fn qualify(scope: Option<&str>, name: &str) -> String {
+    match scope {
+        None => name.to_string(),
+        Some(scope) => format!("{}.{}", scope, name),
+    }
+}
+
+fn collect<'ast>(
+    modules: &mut Vec<ModulePage<'ast>>,
+    index: usize,
+    scope: Option<&str>,
+    members: &'ast [ModuleMember],
+) {
+    for member in members {
+        match member {
+            ModuleMember::DefModule(def) => {
+                let name = qualify(scope, &def.name.data);
+                let child = match modules
+                    .iter()
+                    .position(|module| module.name.as_ref() == Some(&name))
+                {
+                    Some(child) => child,
+                    None => {
+                        modules.push(ModulePage {
+                            name: Some(name.clone()),
+                            ..Default::default()
+                        });
+                        modules[index].modules.push(name.clone());
+                        modules.len() - 1
+                    }
+                };
+
+                modules[child].annotation.extend(doc_annotation(def));
+                collect(modules, child, Some(&name), &def.members);
+            }
+            _ => modules[index].members.push(member),
+        }
+    }
+}
+
+/// A definition documented on the page of the module or component enclosing it
+enum Def<'ast> {
+    AbsType(&'ast DefAbsType),
+    AliasType(&'ast DefAliasType),
+    Array(&'ast DefArray),
+    ComponentInstance(&'ast DefComponentInstance),
+    Constant(&'ast DefConstant),
+    Enum(&'ast DefEnum),
+    Interface(&'ast DefInterface),
+    Port(&'ast DefPort),
+    StateMachine(&'ast DefStateMachine),
+    Struct(&'ast DefStruct),
+}
+
+impl<'ast> Def<'ast> {
+    fn from_module_member(member: &'ast ModuleMember) -> Option<Def<'ast>> {
+        Some(match member {
+            ModuleMember::DefAbsType(def) => Def::AbsType(def),
+            ModuleMember::DefAliasType(def) => Def::AliasType(def),
+            ModuleMember::DefArray(def) => Def::Array(def),
+            ModuleMember::DefComponentInstance(def) => Def::ComponentInstance(def),
+            ModuleMember::DefConstant(def) => Def::Constant(def),
+            ModuleMember::DefEnum(def) => Def::Enum(def),
+            ModuleMember::DefInterface(def) => Def::Interface(def),
+            ModuleMember::DefPort(def) => Def::Port(def),
+            ModuleMember::DefStateMachine(def) => Def::StateMachine(def),
+            ModuleMember::DefStruct(def) => Def::Struct(def),
+            _ => return None,
+        })
+    }
+
+    fn from_component_member(member: &'ast ComponentMember) -> Option<Def<'ast>> {
+        Some(match member {
+            ComponentMember::DefAbsType(def) => Def::AbsType(def),
+            ComponentMember::DefAliasType(def) => Def::AliasType(def),
+            ComponentMember::DefArray(def) => Def::Array(def),
+            ComponentMember::DefConstant(def) => Def::Constant(def),
+            ComponentMember::DefEnum(def) => Def::Enum(def),
+            ComponentMember::DefStateMachine(def) => Def::StateMachine(def),
+            ComponentMember::DefStruct(def) => Def::Struct(def),
+            _ => return None,
+        })
+    }
+
+    /// The section of a page listing the definition
+    fn section(&self) -> &'static str {
+        match self {
+            Def::AbsType(_) | Def::AliasType(_) | Def::Array(_) | Def::Enum(_) | Def::Struct(_) => {
+                "Types"
+            }
+            Def::ComponentInstance(_) => "Component instances",
+            Def::Constant(_) => "Constants",
+            Def::Interface(_) => "Interfaces",
+            Def::Port(_) => "Ports",
+            Def::StateMachine(_) => "State machines",
+        }
+    }
+}
+
+/// The sections listing definitions, in page order
+const SECTIONS: &[&str] = &[
+    "Types",
+    "Constants",
+    "Ports",
+    "Interfaces",
+    "State machines",
+    "Component instances",
+];
+
+/// The annotation of a node as a single line for a table cell
+fn summary<N: Annotated>(node: &N) -> String {
+    cell(&doc_annotation(node).join(" "))
+}
+
+/// Escape text placed in a table cell
+fn cell(text: &str) -> String {
+    text.trim().replace('|', "\\|")
+}
+
+/// Text shown as inline code
+fn code(text: &str) -> String {
+    if text.contains('`') {
+        format!("`` {} ``", text)
+    } else {
+        format!("`{}`", text)
+    }
+}
+
+fn hex(id: i128) -> String {
+    if id < 0 {
+        id.to_string()
+    } else {
+        format!("0x{:X}", id)
+    }
+}
+
+fn html_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn qual_ident(node: &QualIdent) -> String {
+    match node {
+        QualIdent::Unqualified(name) => name.data.clone(),
+        QualIdent::Qualified(qualified) => {
+            format!(
+                "{}.{}",
+                qual_ident(&qualified.qualifier),
+                qualified.name.data
+            )
+        }
+    }
+}
+
+fn input_kind(kind: &InputPortKind) -> &'static str {
+    match kind {
+        InputPortKind::Async => "async",
+        InputPortKind::Guarded => "guarded",
+        InputPortKind::Sync => "sync",
+    }
+}
+
+fn severity(severity: &EventSeverity) -> &'static str {
+    match severity {
+        EventSeverity::ActivityHigh => "activity high",
+        EventSeverity::ActivityLow => "activity low",
+        EventSeverity::Command => "command",
+        EventSeverity::Diagnostic => "diagnostic",
+        EventSeverity::Fatal => "fatal",
+        EventSeverity::WarningHigh => "warning high",
+        EventSeverity::WarningLow => "warning low",
+    }
+}
+
+fn special_port_kind(kind: &SpecialPortInstanceKind) -> &'static str {
+    match kind {
+        SpecialPortInstanceKind::CommandRecv => "command recv",
+        SpecialPortInstanceKind::CommandReg => "command reg",
+        SpecialPortInstanceKind::CommandResp => "command resp",
+        SpecialPortInstanceKind::Event => "event",
+        SpecialPortInstanceKind::ParamGet => "param get",
+        SpecialPortInstanceKind::ParamSet => "param set",
+        SpecialPortInstanceKind::ProductGet => "product get",
+        SpecialPortInstanceKind::ProductRecv => "product recv",
+        SpecialPortInstanceKind::ProductRequest => "product request",
+        SpecialPortInstanceKind::ProductSend => "product send",
+        SpecialPortInstanceKind::Telemetry => "telemetry",
+        SpecialPortInstanceKind::TextEvent => "text event",
+        SpecialPortInstanceKind::TimeGet => "time get",
+    }
+}
+
+/// Write a Markdown table, leaving it out if there are no rows
+fn table(out: &mut String, title: &str, header: &[&str], rows: Vec<Vec<String>>) {
+    if rows.is_empty() {
+        return;
+    }
+
+    writeln!(out, "## {}\n", title).unwrap();
+    rows_table(out, header, rows);
+}
+
+/// Write the rows of a Markdown table
+fn rows_table(out: &mut String, header: &[&str], rows: Vec<Vec<String>>) {
+    writeln!(out, "| {} |", header.join(" | ")).unwrap();
+    writeln!(out, "|{}", " --- |".repeat(header.len())).unwrap();
+    for row in rows {
+        writeln!(out, "| {} |", row.join(" | ")).unwrap();
+    }
+    out.push('\n');
+}
+
+/// Write the annotation of a node as Markdown paragraphs
+fn paragraphs(out: &mut String, lines: &[String]) {
+    if lines.is_empty() {
+        return;
+    }
+
+    for line in lines {
+        writeln!(out, "{}", line.trim_end()).unwrap();
+    }
+    out.push('\n');
+}
+
+/// Renders the pages of one site
+struct DocGenerator<'a> {
+    a: &'a Analysis,
+    format: DocFormat,
+}
+
+impl<'a> DocGenerator<'a> {
+    fn file(&self, page: &str) -> String {
+        format!("{}.{}", page, self.format.extension())
+    }
+
+    /// Turn the Markdown of a page into the output format
+    fn page(&self, name: &str, markdown: String) -> Page {
+        let content = match self.format {
+            DocFormat::Markdown => markdown,
+            DocFormat::Html => {
+                let parser = pulldown_cmark::Parser::new_ext(
+                    &markdown,
+                    pulldown_cmark::Options::ENABLE_TABLES,
+                );
+                let mut body = String::new();
+                pulldown_cmark::html::push_html(&mut body, parser);
+                format!(
+                    "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n\
+                     <style>\n{}</style>\n</head>\n<body>\n{}</body>\n</html>\n",
+                    html_escape(name),
+                    STYLE,
+                    body
+                )
+            }
+        };
+
+        Page {
+            path: self.file(name),
+            content,
+        }
+    }
+
+    /// The link to the documentation of a symbol
+    fn href(&self, symbol: &Symbol) -> String {
+        if let Symbol::Module(_) | Symbol::Component(_) | Symbol::Topology(_) = symbol {
+            return self.file(&self.a.qualified_name(symbol));
+        }
+
+        // Definitions are documented on the page of the closest enclosing
+        // module or component, under the name of the outermost definition
+        let mut current = symbol;
+        loop {
+            match self.a.parent_symbol_map.get(current) {
+                None => {
+                    return format!("{}#{}", self.file(INDEX), current.name().data);
+                }
+                Some(parent @ (Symbol::Module(_) | Symbol::Component(_))) => {
+                    return format!(
+                        "{}#{}",
+                        self.file(&self.a.qualified_name(parent)),
+                        current.name().data
+                    );
+                }
+                Some(parent) => current = parent,
+            }
+        }
+    }
+
+    /// A use of a definition, linked to its documentation when it resolves
+    fn link(&self, node: &QualIdent) -> String {
+        let text = code(&qual_ident(node));
+        match self.a.use_def_map.get(&node.id()) {
+            None => text,
+            Some(symbol) => format!("[{}]({})", text, self.href(symbol)),
+        }
+    }
+
+    fn type_name(&self, node: &TypeName) -> String {
+        match &node.kind {
+            TypeNameKind::Bool => code("bool"),
+            TypeNameKind::Floating(kind) => code(match kind {
+                FloatKind::F32 => "F32",
+                FloatKind::F64 => "F64",
+            }),
+            TypeNameKind::Integer(kind) => code(&format!("{:?}", kind)),
+            TypeNameKind::QualIdent(node) => self.link(node),
+            TypeNameKind::String(None) => code("string"),
+            TypeNameKind::String(Some(size)) => match self.a.integer_value(size) {
+                Some(size) => code(&format!("string size {}", size)),
+                None => code("string"),
+            },
+        }
+    }
+
+    fn params(&self, params: &[FormalParam]) -> String {
+        let params: Vec<String> = params
+            .iter()
+            .map(|param| {
+                format!(
+                    "{}{}: {}",
+                    match param.kind {
+                        FormalParamKind::Ref => "ref ",
+                        FormalParamKind::Value => "",
+                    },
+                    param.name.data,
+                    self.type_name(&param.type_name)
+                )
+            })
+            .collect();
+        params.join(", ")
+    }
+
+    /// Write the sections documenting `defs`
+    fn defs(&self, out: &mut String, defs: &[Def]) {
+        for section in SECTIONS {
+            let defs: Vec<&Def> = defs
+                .iter()
+                .filter(|def| def.section() == *section)
+                .collect();
+            if defs.is_empty() {
+                continue;
+            }
+
+            writeln!(out, "## {}\n", section).unwrap();
+            for def in defs {
+                self.def(out, def);
+            }
+        }
+    }
+
+    fn def(&self, out: &mut String, def: &Def) {
+        let (kind, name, lines) = match def {
+            Def::AbsType(def) => ("Abstract type", &def.name, doc_annotation(*def)),
+            Def::AliasType(def) => ("Alias type", &def.name, doc_annotation(*def)),
+            Def::Array(def) => ("Array", &def.name, doc_annotation(*def)),
+            Def::ComponentInstance(def) => ("Component instance", &def.name, doc_annotation(*def)),
+            Def::Constant(def) => ("Constant", &def.name, doc_annotation(*def)),
+            Def::Enum(def) => ("Enum", &def.name, doc_annotation(*def)),
+            Def::Interface(def) => ("Interface", &def.name, doc_annotation(*def)),
+            Def::Port(def) => ("Port", &def.name, doc_annotation(*def)),
+            Def::StateMachine(def) => ("State machine", &def.name, doc_annotation(*def)),
+            Def::Struct(def) => ("Struct", &def.name, doc_annotation(*def)),
+        };
+
+        writeln!(out, "<a id=\"{}\"></a>\n", name.data).unwrap();
+        writeln!(out, "### {} {}\n", kind, code(&name.data)).unwrap();
+        paragraphs(out, &lines);
+
+        let mut facts: Vec<String> = vec![];
+        let mut rows: Vec<Vec<String>> = vec![];
+        let mut header: &[&str] = &[];
+
+        match def {
+            Def::AbsType(_) | Def::Interface(_) | Def::StateMachine(_) => {}
+            Def::AliasType(def) => {
+                facts.push(format!("Type: {}", self.type_name(&def.type_name)));
+            }
+            Def::Array(def) => {
+                let ty = self.a.type_map.get(&def.id());
+                if let Some(Type::Array(ty)) = ty.map(|ty| ty.deref()) {
+                    facts.push(format!(
+                        "Type: {} of {}",
+                        code(&format!("[{}]", ty.anon_array.size.unwrap_or_default())),
+                        self.type_name(&def.elt_type)
+                    ));
+                }
+                if let Some(default) = ty.and_then(|ty| ty.default_value()) {
+                    facts.push(format!(
+                        "Default: {}",
+                        code(&value_syntax(self.a, &default))
+                    ));
+                }
+                if let Some(format) = &def.format {
+                    facts.push(format!("Format: {}", code(&format.data)));
+                }
+            }
+            Def::ComponentInstance(def) => {
+                facts.push(format!("Component: {}", self.link(&def.component)));
+                if let Some(base_id) = self.a.integer_value(&def.base_id) {
+                    facts.push(format!("Base ID: {}", code(&hex(base_id))));
+                }
+                if let Some(size) = def
+                    .queue_size
+                    .as_ref()
+                    .and_then(|e| self.a.integer_value(e))
+                {
+                    facts.push(format!("Queue size: {}", code(&size.to_string())));
+                }
+            }
+            Def::Constant(def) => {
+                if let Some(value) = self.a.value_map.get(&def.id()) {
+                    facts.push(format!("Value: {}", code(&value_syntax(self.a, value))));
+                }
+                match self.a.type_map.get(&def.id()).map(|ty| ty.deref()) {
+                    None | Some(Type::AnonArray(_) | Type::AnonStruct(_)) => {}
+                    Some(ty) => facts.push(format!("Type: {}", code(&ty.to_string()))),
+                }
+            }
+            Def::Enum(def) => {
+                let ty = self.a.type_map.get(&def.id());
+                if let Some(Type::Enum(ty)) = ty.map(|ty| ty.deref()) {
+                    facts.push(format!(
+                        "Representation type: {}",
+                        code(&format!("{:?}", ty.rep_type))
+                    ));
+                    if let Some(default) = &ty.default {
+                        facts.push(format!("Default: {}", code(&value_syntax(self.a, default))));
+                    }
+
+                    header = &["Constant", "Value", "Description"];
+                    for constant in &def.constants {
+                        let value = ty
+                            .constants
+                            .iter()
+                            .find(|(name, _)| *name == constant.name.data)
+                            .map_or(String::new(), |(_, value)| value.to_string());
+                        rows.push(vec![code(&constant.name.data), value, summary(constant)]);
+                    }
+                }
+            }
+            Def::Port(def) => {
+                if let Some(return_type) = &def.return_type {
+                    facts.push(format!("Return type: {}", self.type_name(return_type)));
+                }
+
+                header = &["Argument", "Kind", "Type", "Description"];
+                for param in &def.params {
+                    rows.push(vec![
+                        code(&param.name.data),
+                        match param.kind {
+                            FormalParamKind::Ref => "ref",
+                            FormalParamKind::Value => "value",
+                        }
+                        .to_string(),
+                        self.type_name(&param.type_name),
+                        summary(param),
+                    ]);
+                }
+            }
+            Def::Struct(def) => {
+                let ty = self.a.type_map.get(&def.id()).map(|ty| ty.deref());
+                if let Some(Type::Struct(
+                    ty @ StructType {
+                        default: Some(default),
+                        ..
+                    },
+                )) = ty
+                {
+                    facts.push(format!(
+                        "Default: {}",
+                        code(&struct_value_syntax(self.a, default, ty))
+                    ));
+                }
+
+                header = &["Member", "Type", "Format", "Description"];
+                for member in &def.members {
+                    let size = member
+                        .size
+                        .as_ref()
+                        .and_then(|size| self.a.integer_value(size))
+                        .map_or(String::new(), |size| {
+                            format!("{} of ", code(&format!("[{}]", size)))
+                        });
+                    rows.push(vec![
+                        code(&member.name.data),
+                        format!("{}{}", size, self.type_name(&member.type_name)),
+                        member
+                            .format
+                            .as_ref()
+                            .map_or(String::new(), |f| code(&f.data)),
+                        summary(member),
+                    ]);
+                }
+            }
+        }
+
+        if !facts.is_empty() {
+            for fact in facts {
+                writeln!(out, "- {}", fact).unwrap();
+            }
+            out.push('\n');
+        }
+
+        if !rows.is_empty() {
+            rows_table(out, header, rows);
+        }
+    }
+
+    /// Write a list of links to pages
+    fn page_list(&self, out: &mut String, title: &str, pages: Vec<(String, Vec<String>)>) {
+        if pages.is_empty() {
+            return;
+        }
+
+        writeln!(out, "## {}\n", title).unwrap();
+        for (name, lines) in pages {
+            write!(out, "- [{}]({})", code(&name), self.file(&name)).unwrap();
+            match lines.first() {
+                None => out.push('\n'),
+                Some(line) => writeln!(out, ": {}", line.trim()).unwrap(),
+            }
+        }
+        out.push('\n');
+    }
+
+    fn module_page(&self, module: &ModulePage, modules: &[ModulePage]) -> String {
+        let mut out = String::new();
+        match &module.name {
+            None => writeln!(out, "# Index\n").unwrap(),
+            Some(name) => writeln!(out, "# Module {}\n", code(name)).unwrap(),
+        }
+        paragraphs(&mut out, &module.annotation);
+
+        let scope = module.name.as_deref();
+        let mut components = vec![];
+        let mut topologies = vec![];
+        let mut defs = vec![];
+        for member in &module.members {
+            match member {
+                ModuleMember::DefComponent(def) => {
+                    components.push((qualify(scope, &def.name.data), doc_annotation(def)))
+                }
+                ModuleMember::DefTopology(def) => {
+                    topologies.push((qualify(scope, &def.name.data), doc_annotation(def)))
+                }
+                _ => defs.extend(Def::from_module_member(member)),
+            }
+        }
+
+        let modules = module
+            .modules
+            .iter()
+            .map(|name| {
+                let annotation = modules
+                    .iter()
+                    .find(|module| module.name.as_ref() == Some(name))
+                    .map_or(vec![], |module| module.annotation.clone());
+                (name.clone(), annotation)
+            })
+            .collect();
+        self.page_list(&mut out, "Modules", modules);
+        self.page_list(&mut out, "Components", components);
+        self.page_list(&mut out, "Topologies", topologies);
+        self.defs(&mut out, &defs);
+
+        out
+    }
+
+    fn component_page(&self, name: &str, def: &DefComponent) -> String {
+        let mut out = String::new();
+        let kind = match def.kind {
+            ComponentKind::Active => "Active",
+            ComponentKind::Passive => "Passive",
+            ComponentKind::Queued => "Queued",
+        };
+        writeln!(out, "# {} component {}\n", kind, code(name)).unwrap();
+        paragraphs(&mut out, &doc_annotation(def));
+
+        let mut ports = vec![];
+        for member in &def.members {
+            match member {
+                ComponentMember::SpecPortInstance(SpecPortInstance::General(port)) => {
+                    let kind = match &port.kind {
+                        GeneralPortInstanceKind::Input(kind) => {
+                            format!("{} input", input_kind(kind))
+                        }
+                        GeneralPortInstanceKind::Output => "output".to_string(),
+                    };
+                    let size = port
+                        .size
+                        .as_ref()
+                        .and_then(|size| self.a.integer_value(size))
+                        .map_or(String::new(), |size| {
+                            format!("{} of ", code(&format!("[{}]", size)))
+                        });
+                    let ty = port
+                        .port
+                        .as_ref()
+                        .map_or(code("serial"), |port| self.link(port));
+                    ports.push(vec![
+                        code(&port.name.data),
+                        kind,
+                        format!("{}{}", size, ty),
+                        summary(port),
+                    ]);
+                }
+                ComponentMember::SpecPortInstance(SpecPortInstance::Special(port)) => {
+                    let kind = match &port.input_kind {
+                        None => special_port_kind(&port.kind).to_string(),
+                        Some(input) => {
+                            format!("{} {}", input_kind(input), special_port_kind(&port.kind))
+                        }
+                    };
+                    ports.push(vec![
+                        code(&port.name.data),
+                        kind,
+                        String::new(),
+                        summary(port),
+                    ]);
+                }
+                ComponentMember::SpecInternalPort(port) => {
+                    ports.push(vec![
+                        code(&port.name.data),
+                        "internal".to_string(),
+                        self.params(&port.params),
+                        summary(port),
+                    ]);
+                }
+                _ => {}
+            }
+        }
+        table(
+            &mut out,
+            "Ports",
+            &["Name", "Kind", "Type", "Description"],
+            ports,
+        );
+
+        let dictionary = self.a.component_dictionary(def);
+        let commands = dictionary
+            .commands
+            .iter()
+            .map(|command| {
+                let (kind, args, description) = match &command.kind {
+                    CommandKind::Command(spec) => (
+                        input_kind(&spec.kind).to_string(),
+                        self.params(&spec.params),
+                        summary(spec),
+                    ),
+                    CommandKind::ParamSet(param) => (
+                        "param set".to_string(),
+                        format!("val: {}", self.type_name(&param.type_name)),
+                        format!("Set parameter {}", code(&param.name.data)),
+                    ),
+                    CommandKind::ParamSave(param) => (
+                        "param save".to_string(),
+                        String::new(),
+                        format!("Save parameter {}", code(&param.name.data)),
+                    ),
+                };
+                vec![
+                    code(&command.name),
+                    kind,
+                    code(&hex(command.opcode)),
+                    args,
+                    description,
+                ]
+            })
+            .collect();
+        table(
+            &mut out,
+            "Commands",
+            &["Name", "Kind", "Opcode", "Arguments", "Description"],
+            commands,
+        );
+
+        let events = dictionary
+            .events
+            .iter()
+            .map(|event| {
+                vec![
+                    code(&event.name),
+                    code(&hex(event.id)),
+                    severity(&event.node.severity).to_string(),
+                    self.params(&event.node.params),
+                    code(&cell(&event.node.format.data)),
+                    summary(&event.node),
+                ]
+            })
+            .collect();
+        table(
+            &mut out,
+            "Events",
+            &[
+                "Name",
+                "ID",
+                "Severity",
+                "Arguments",
+                "Format",
+                "Description",
+            ],
+            events,
+        );
+
+        let channels = dictionary
+            .channels
+            .iter()
+            .map(|channel| {
+                vec![
+                    code(&channel.name),
+                    code(&hex(channel.id)),
+                    self.type_name(&channel.node.type_name),
+                    match channel.node.update {
+                        None | Some(TlmChannelUpdate::Always) => "always",
+                        Some(TlmChannelUpdate::OnChange) => "on change",
+                    }
+                    .to_string(),
+                    channel
+                        .node
+                        .format
+                        .as_ref()
+                        .map_or(String::new(), |format| code(&cell(&format.data))),
+                    summary(&channel.node),
+                ]
+            })
+            .collect();
+        table(
+            &mut out,
+            "Telemetry",
+            &["Name", "ID", "Type", "Update", "Format", "Description"],
+            channels,
+        );
+
+        let params = dictionary
+            .params
+            .iter()
+            .map(|param| {
+                let default = param
+                    .node
+                    .default
+                    .as_ref()
+                    .and_then(|default| self.a.value_map.get(&default.node_id))
+                    .map_or(String::new(), |default| {
+                        code(&value_syntax(self.a, default))
+                    });
+                vec![
+                    code(&param.name),
+                    code(&hex(param.id)),
+                    self.type_name(&param.node.type_name),
+                    default,
+                    summary(&param.node),
+                ]
+            })
+            .collect();
+        table(
+            &mut out,
+            "Parameters",
+            &["Name", "ID", "Type", "Default", "Description"],
+            params,
+        );
+
+        let defs: Vec<Def> = def
+            .members
+            .iter()
+            .filter_map(Def::from_component_member)
+            .collect();
+        self.defs(&mut out, &defs);
+
+        out
+    }
+
+    fn topology_page(&self, name: &str, def: &DefTopology) -> String {
+        let mut out = String::new();
+        writeln!(out, "# Topology {}\n", code(name)).unwrap();
+        paragraphs(&mut out, &doc_annotation(def));
+
+        let instances = self
+            .a
+            .topology_instances(def)
+            .into_iter()
+            .map(|instance| {
+                let symbol = Symbol::ComponentInstance(instance.node.clone());
+                vec![
+                    format!("[{}]({})", code(&instance.name), self.href(&symbol)),
+                    self.link(&instance.node.component),
+                    code(&hex(instance.base_id)),
+                    summary(instance.node.deref()),
+                ]
+            })
+            .collect();
+        table(
+            &mut out,
+            "Instances",
+            &["Instance", "Component", "Base ID", "Description"],
+            instances,
+        );
+
+        let imports = def
+            .members
+            .iter()
+            .filter_map(|member| match member {
+                TopologyMember::SpecInstance(spec) => {
+                    match self.a.use_def_map.get(&spec.instance.id()) {
+                        Some(Symbol::Topology(topology)) => {
+                            Some(vec![self.link(&spec.instance), summary(topology.deref())])
+                        }
+                        _ => None,
+                    }
+                }
+                _ => None,
+            })
+            .collect();
+        table(&mut out, "Imports", &["Topology", "Description"], imports);
+
+        out
+    }
+}
+
+/// Style sheet of HTML pages
+const STYLE: &str = "body { font-family: sans-serif; max-width: 60em; margin: auto; padding: 1em; }
+table { border-collapse: collapse; margin-bottom: 1em; }
+th, td { border: 1px solid #ccc; padding: 0.3em 0.6em; text-align: left; }
+code { background: #f4f4f4; padding: 0 0.2em; }
+";
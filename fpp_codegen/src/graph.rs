@@ -0,0 +1,735 @@
+//! Export of topologies and state machines as graphs
+//!
+//! Topologies become graphs of component instances connected by their port
+//! connections. State machines become graphs of states and choices connected by
+//! their transitions. Each graph is written as Graphviz DOT, Mermaid or JSON.
+
+use crate::Page;
+use fpp_analysis::semantics::{ConnectionEndpoint, Symbol, TopologyConnection};
+use fpp_analysis::Analysis;
+use fpp_ast::{
+    AstNode, ComponentMember, ConnectionPatternKind, DefChoice, DefState, DefStateMachine,
+    DefTopology, DoExpr, ModuleMember, QualIdent, StateMachineMember, StateMember, TransUnit,
+    TransitionExpr, TransitionOrDo,
+};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fmt::Write;
+use std::sync::Arc;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GraphFormat {
+    Dot,
+    Mermaid,
+    Json,
+}
+
+impl GraphFormat {
+    fn extension(&self) -> &'static str {
+        match self {
+            GraphFormat::Dot => "dot",
+            GraphFormat::Mermaid => "mmd",
+            GraphFormat::Json => "json",
+        }
+    }
+}
+
+/// Generate a graph for every topology and state machine in `ast`.
+/// Topology graphs only show the connection graphs named in `connections`,
+/// or every connection graph if it is empty.
+pub fn generate(
+    a: &Analysis,
+    ast: &[&TransUnit],
+    format: GraphFormat,
+    connections: &[String],
+) -> Vec<Page> {
+    let g = GraphGenerator { a, connections };
+    let mut graphs = vec![];
+    for trans_unit in ast {
+        g.module_members(&mut graphs, "", &trans_unit.0);
+    }
+
+    graphs
+        .into_iter()
+        .map(|graph| Page {
+            path: format!("{}.{}", graph.name, format.extension()),
+            content: graph.render(format),
+        })
+        .collect()
+}
+
+/// Generate the graph of the topology `def` with its fully qualified `name`
+pub fn generate_topology(
+    a: &Analysis,
+    name: &str,
+    def: &DefTopology,
+    format: GraphFormat,
+) -> String {
+    let g = GraphGenerator {
+        a,
+        connections: &[],
+    };
+    g.topology(name.to_string(), def).render(format)
+}
+
+#[derive(Serialize)]
+struct Graph {
+    name: String,
+    kind: &'static str,
+    nodes: Vec<GraphNode>,
+    edges: Vec<GraphEdge>,
+}
+
+#[derive(Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+enum NodeKind {
+    Instance,
+    State,
+    Choice,
+    Initial,
+}
+
+#[derive(Serialize)]
+struct GraphNode {
+    /// Qualified name of the instance, or path of the state relative to the state machine
+    id: String,
+    label: String,
+    kind: NodeKind,
+    /// The state enclosing this node
+    #[serde(skip_serializing_if = "Option::is_none")]
+    parent: Option<String>,
+    /// Further lines of text, like the entry and exit actions of a state
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    details: Vec<String>,
+}
+
+#[derive(Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+enum EdgeKind {
+    Connection,
+    /// Connections implied by a pattern graph, drawn between instances only
+    Pattern,
+    Transition,
+}
+
+#[derive(Serialize)]
+struct GraphEdge {
+    from: String,
+    to: String,
+    label: String,
+    kind: EdgeKind,
+    /// The connection graph the edge belongs to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    graph: Option<String>,
+}
+
+fn qualify(scope: &str, name: &str) -> String {
+    if scope.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}.{}", scope, name)
+    }
+}
+
+fn qual_ident(node: &QualIdent) -> Vec<String> {
+    match node {
+        QualIdent::Unqualified(name) => vec![name.data.clone()],
+        QualIdent::Qualified(qualified) => {
+            let mut out = qual_ident(&qualified.qualifier);
+            out.push(qualified.name.data.clone());
+            out
+        }
+    }
+}
+
+fn pattern_kind(kind: &ConnectionPatternKind) -> &'static str {
+    match kind {
+        ConnectionPatternKind::Command => "command",
+        ConnectionPatternKind::Event => "event",
+        ConnectionPatternKind::Health => "health",
+        ConnectionPatternKind::Param => "param",
+        ConnectionPatternKind::Telemetry => "telemetry",
+        ConnectionPatternKind::TextEvent => "text event",
+        ConnectionPatternKind::Time => "time",
+    }
+}
+
+/// A port of a connection and its number, if any: `port[n]`
+fn port(endpoint: &ConnectionEndpoint) -> String {
+    match endpoint.index {
+        Some(index) => format!("{}[{}]", endpoint.port, index),
+        None => endpoint.port.clone(),
+    }
+}
+
+/// The label of a transition: `signal [guard] / action, ...`
+fn transition_label(signal: Option<&str>, guard: Option<&str>, actions: Option<&DoExpr>) -> String {
+    let mut parts = vec![];
+    parts.extend(signal.map(str::to_string));
+    parts.extend(guard.map(|guard| format!("[{}]", guard)));
+    if let Some(actions) = actions.filter(|actions| !actions.actions.is_empty()) {
+        let actions: Vec<&str> = actions
+            .actions
+            .iter()
+            .map(|action| action.data.as_str())
+            .collect();
+        parts.push(format!("/ {}", actions.join(", ")));
+    }
+
+    parts.join(" ")
+}
+
+struct GraphGenerator<'a> {
+    a: &'a Analysis,
+    connections: &'a [String],
+}
+
+impl GraphGenerator<'_> {
+    fn module_members(&self, graphs: &mut Vec<Graph>, scope: &str, members: &[ModuleMember]) {
+        for member in members {
+            match member {
+                ModuleMember::DefModule(def) => {
+                    self.module_members(graphs, &qualify(scope, &def.name.data), &def.members)
+                }
+                ModuleMember::DefComponent(def) => {
+                    let scope = qualify(scope, &def.name.data);
+                    for member in &def.members {
+                        if let ComponentMember::DefStateMachine(def) = member {
+                            graphs.extend(self.state_machine(&scope, def));
+                        }
+                    }
+                }
+                ModuleMember::DefStateMachine(def) => graphs.extend(self.state_machine(scope, def)),
+                ModuleMember::DefTopology(def) => {
+                    graphs.push(self.topology(qualify(scope, &def.name.data), def))
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn is_shown(&self, graph: &str) -> bool {
+        self.connections.is_empty() || self.connections.iter().any(|name| name == graph)
+    }
+
+    fn topology(&self, name: String, def: &DefTopology) -> Graph {
+        let mut graph = Graph {
+            name,
+            kind: "topology",
+            nodes: vec![],
+            edges: vec![],
+        };
+
+        for instance in self.a.topology_component_instances(def) {
+            let component = self.use_name(&instance.component);
+            let id = self.a.qualified_name(&Symbol::ComponentInstance(instance));
+            self.instance(&mut graph, &id, vec![component]);
+        }
+
+        // The connections of the imported topologies are part of the topology
+        let imported = self.a.imported_topologies(def);
+        for topology in std::iter::once(def).chain(imported.iter().map(Arc::as_ref)) {
+            for connection in self.a.topology_connections(topology) {
+                self.connection(&mut graph, connection);
+            }
+        }
+
+        // Only keep the instances taking part in the selected connection graphs
+        if !self.connections.is_empty() {
+            let connected: HashSet<String> = graph
+                .edges
+                .iter()
+                .flat_map(|edge| [edge.from.clone(), edge.to.clone()])
+                .collect();
+            graph.nodes.retain(|node| connected.contains(&node.id));
+        }
+
+        graph
+    }
+
+    fn connection(&self, graph: &mut Graph, connection: TopologyConnection) {
+        if !self.is_shown(&connection.graph) {
+            return;
+        }
+
+        let from = self.endpoint_instance(graph, &connection.from);
+        let to = self.endpoint_instance(graph, &connection.to);
+        let edge = match &connection.pattern {
+            None => GraphEdge {
+                from,
+                to,
+                label: format!("{} -> {}", port(&connection.from), port(&connection.to)),
+                kind: EdgeKind::Connection,
+                graph: Some(connection.graph),
+            },
+            Some(kind) => GraphEdge {
+                from,
+                to,
+                label: pattern_kind(kind).to_string(),
+                kind: EdgeKind::Pattern,
+                graph: Some(connection.graph),
+            },
+        };
+
+        // A pattern implies several connections between the same instances,
+        // which are drawn as one edge
+        let drawn = graph.edges.iter().any(|other| {
+            other.kind == EdgeKind::Pattern
+                && other.from == edge.from
+                && other.to == edge.to
+                && other.graph == edge.graph
+        });
+        if edge.kind != EdgeKind::Pattern || !drawn {
+            graph.edges.push(edge);
+        }
+    }
+
+    /// The qualified name of the definition `node` refers to
+    fn use_name(&self, node: &QualIdent) -> String {
+        match self.a.use_def_map.get(&node.id()) {
+            Some(symbol) => self.a.qualified_name(symbol),
+            None => qual_ident(node).join("."),
+        }
+    }
+
+    fn instance(&self, graph: &mut Graph, id: &str, details: Vec<String>) {
+        if graph.nodes.iter().all(|node| node.id != id) {
+            graph.nodes.push(GraphNode {
+                id: id.to_string(),
+                label: id.to_string(),
+                kind: NodeKind::Instance,
+                parent: None,
+                details,
+            });
+        }
+    }
+
+    /// The instance of a connection endpoint, adding it to the graph if the
+    /// topology does not list it
+    fn endpoint_instance(&self, graph: &mut Graph, endpoint: &ConnectionEndpoint) -> String {
+        let id = self
+            .a
+            .qualified_name(&Symbol::ComponentInstance(endpoint.instance.clone()));
+        self.instance(graph, &id, vec![]);
+        id
+    }
+
+    fn state_machine(&self, scope: &str, def: &DefStateMachine) -> Option<Graph> {
+        // External state machines have no states to draw
+        let members = def.members.as_ref()?;
+        let mut builder = StateMachineBuilder::default();
+        builder.members(&[], members);
+
+        let edges = builder
+            .transitions
+            .iter()
+            .map(|transition| GraphEdge {
+                from: transition.from.clone(),
+                to: builder
+                    .resolve(&transition.scope, &transition.target)
+                    .unwrap_or_else(|| transition.target.join(".")),
+                label: transition.label.clone(),
+                kind: EdgeKind::Transition,
+                graph: None,
+            })
+            .collect();
+
+        Some(Graph {
+            name: qualify(scope, &def.name.data),
+            kind: "state_machine",
+            nodes: builder.nodes,
+            edges,
+        })
+    }
+}
+
+/// A transition whose target is resolved once every state is known
+struct Transition {
+    from: String,
+    /// Path of the state the transition is written in
+    scope: Vec<String>,
+    target: Vec<String>,
+    label: String,
+}
+
+#[derive(Default)]
+struct StateMachineBuilder {
+    nodes: Vec<GraphNode>,
+    transitions: Vec<Transition>,
+}
+
+/// A member of a state machine or state that is drawn as a node
+enum Vertex<'ast> {
+    Choice(&'ast DefChoice),
+    State(&'ast DefState),
+    Initial(&'ast TransitionExpr),
+}
+
+impl StateMachineBuilder {
+    fn members(&mut self, scope: &[String], members: &[StateMachineMember]) {
+        let vertices: Vec<Vertex> = members
+            .iter()
+            .filter_map(|member| match member {
+                StateMachineMember::DefChoice(def) => Some(Vertex::Choice(def)),
+                StateMachineMember::DefState(def) => Some(Vertex::State(def)),
+                StateMachineMember::SpecInitialTransition(spec) => {
+                    Some(Vertex::Initial(&spec.transition))
+                }
+                _ => None,
+            })
+            .collect();
+        self.vertices(scope, vertices);
+    }
+
+    fn vertices(&mut self, scope: &[String], vertices: Vec<Vertex>) {
+        let parent = (!scope.is_empty()).then(|| scope.join("."));
+        for vertex in vertices {
+            match vertex {
+                Vertex::Initial(transition) => {
+                    let id = qualify(&scope.join("."), "initial");
+                    self.nodes.push(GraphNode {
+                        id: id.clone(),
+                        label: String::new(),
+                        kind: NodeKind::Initial,
+                        parent: parent.clone(),
+                        details: vec![],
+                    });
+                    self.transition(scope, id, transition, None, None);
+                }
+                Vertex::Choice(def) => {
+                    let path = [scope, std::slice::from_ref(&def.name.data)].concat();
+                    let id = path.join(".");
+                    self.nodes.push(GraphNode {
+                        id: id.clone(),
+                        label: def.name.data.clone(),
+                        kind: NodeKind::Choice,
+                        parent: parent.clone(),
+                        details: vec![],
+                    });
+                    self.transition(
+                        scope,
+                        id.clone(),
+                        &def.if_transition,
+                        None,
+                        Some(&def.guard.data),
+                    );
+                    self.transition(scope, id, &def.else_transition, None, Some("else"));
+                }
+                Vertex::State(def) => self.state(scope, parent.clone(), def),
+            }
+        }
+    }
+
+    fn state(&mut self, scope: &[String], parent: Option<String>, def: &DefState) {
+        let path = [scope, std::slice::from_ref(&def.name.data)].concat();
+        let id = path.join(".");
+
+        let mut details = vec![];
+        let mut vertices = vec![];
+        for member in &def.members {
+            match member {
+                StateMember::DefChoice(def) => vertices.push(Vertex::Choice(def)),
+                StateMember::DefState(def) => vertices.push(Vertex::State(def)),
+                StateMember::SpecInitialTransition(spec) => {
+                    vertices.push(Vertex::Initial(&spec.transition))
+                }
+                StateMember::SpecStateEntry(spec) => {
+                    details.push(transition_label(Some("entry"), None, Some(&spec.actions)))
+                }
+                StateMember::SpecStateExit(spec) => {
+                    details.push(transition_label(Some("exit"), None, Some(&spec.actions)))
+                }
+                StateMember::SpecStateTransition(spec) => {
+                    let signal = Some(spec.signal.data.as_str());
+                    let guard = spec.guard.as_ref().map(|guard| guard.data.as_str());
+                    match &spec.transition_or_do {
+                        TransitionOrDo::Transition(transition) => {
+                            self.transition(scope, id.clone(), transition, signal, guard)
+                        }
+                        // Internal transitions do not leave the state
+                        TransitionOrDo::Do(actions) => {
+                            details.push(transition_label(signal, guard, Some(actions)))
+                        }
+                    }
+                }
+            }
+        }
+
+        self.nodes.push(GraphNode {
+            id,
+            label: def.name.data.clone(),
+            kind: NodeKind::State,
+            parent,
+            details,
+        });
+        self.vertices(&path, vertices);
+    }
+
+    fn transition(
+        &mut self,
+        scope: &[String],
+        from: String,
+        transition: &TransitionExpr,
+        signal: Option<&str>,
+        guard: Option<&str>,
+    ) {
+        self.transitions.push(Transition {
+            from,
+            scope: scope.to_vec(),
+            target: qual_ident(&transition.target),
+            label: transition_label(signal, guard, transition.actions.as_ref()),
+        });
+    }
+
+    /// Resolve a state or choice name written in `scope` the way FPP does,
+    /// searching the enclosing states from the innermost outwards
+    fn resolve(&self, scope: &[String], target: &[String]) -> Option<String> {
+        (0..=scope.len()).rev().find_map(|len| {
+            let id = [&scope[..len], target].concat().join(".");
+            self.nodes
+                .iter()
+                .any(|node| node.id == id && node.kind != NodeKind::Initial)
+                .then_some(id)
+        })
+    }
+}
+
+/// Quote a string for DOT
+fn dot_string(s: &str) -> String {
+    let s = s
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n");
+    format!("\"{}\"", s)
+}
+
+/// An identifier usable by Mermaid
+fn mermaid_id(id: &str) -> String {
+    id.replace('.', "_")
+}
+
+/// Quote a string for Mermaid, which escapes characters as HTML entities
+fn mermaid_string(s: &str) -> String {
+    format!("\"{}\"", s.replace('"', "#quot;"))
+}
+
+impl Graph {
+    fn render(&self, format: GraphFormat) -> String {
+        match format {
+            GraphFormat::Dot => self.dot(),
+            GraphFormat::Mermaid => self.mermaid(),
+            GraphFormat::Json => {
+                serde_json::to_string_pretty(self).expect("failed to serialize graph") + "\n"
+            }
+        }
+    }
+
+    fn has_children(&self, id: &str) -> bool {
+        self.nodes
+            .iter()
+            .any(|node| node.parent.as_deref() == Some(id))
+    }
+
+    fn children<'a>(&'a self, parent: Option<&'a str>) -> impl Iterator<Item = &'a GraphNode> {
+        self.nodes
+            .iter()
+            .filter(move |node| node.parent.as_deref() == parent)
+    }
+
+    fn dot(&self) -> String {
+        let mut out = String::new();
+        writeln!(out, "digraph {} {{", dot_string(&self.name)).unwrap();
+        if self.kind == "topology" {
+            writeln!(out, "  rankdir=LR;").unwrap();
+            writeln!(out, "  node [shape=box];").unwrap();
+        } else {
+            writeln!(out, "  compound=true;").unwrap();
+            writeln!(out, "  node [shape=box, style=rounded];").unwrap();
+        }
+
+        self.dot_nodes(&mut out, None, 1);
+        for edge in &self.edges {
+            let mut attributes = vec![];
+            if !edge.label.is_empty() {
+                attributes.push(format!("label={}", dot_string(&edge.label)));
+            }
+            if edge.kind == EdgeKind::Pattern {
+                attributes.push("style=dashed".to_string());
+            }
+
+            // Edges to and from composite states are drawn at the border of their cluster
+            let from = self.dot_anchor(&edge.from);
+            if from != edge.from {
+                attributes.push(format!(
+                    "ltail={}",
+                    dot_string(&format!("cluster_{}", edge.from))
+                ));
+            }
+            let to = self.dot_anchor(&edge.to);
+            if to != edge.to {
+                attributes.push(format!(
+                    "lhead={}",
+                    dot_string(&format!("cluster_{}", edge.to))
+                ));
+            }
+
+            write!(out, "  {} -> {}", dot_string(&from), dot_string(&to)).unwrap();
+            if !attributes.is_empty() {
+                write!(out, " [{}]", attributes.join(", ")).unwrap();
+            }
+            out.push_str(";\n");
+        }
+
+        out.push_str("}\n");
+        out
+    }
+
+    /// A node standing in for a composite state, which DOT draws as a cluster
+    fn dot_anchor(&self, id: &str) -> String {
+        if !self.has_children(id) {
+            return id.to_string();
+        }
+
+        let mut children = self.children(Some(id));
+        let first = children.next().map(|node| node.id.clone());
+        self.children(Some(id))
+            .find(|node| node.kind == NodeKind::Initial)
+            .map(|node| node.id.clone())
+            .or(first)
+            .unwrap_or_else(|| id.to_string())
+    }
+
+    fn dot_nodes(&self, out: &mut String, parent: Option<&str>, depth: usize) {
+        let indent = "  ".repeat(depth);
+        for node in self.children(parent) {
+            let label = [node.label.clone()]
+                .into_iter()
+                .chain(node.details.iter().cloned())
+                .collect::<Vec<_>>()
+                .join("\n");
+
+            if self.has_children(&node.id) {
+                writeln!(
+                    out,
+                    "{}subgraph {} {{",
+                    indent,
+                    dot_string(&format!("cluster_{}", node.id))
+                )
+                .unwrap();
+                writeln!(out, "{}  label={};", indent, dot_string(&label)).unwrap();
+                self.dot_nodes(out, Some(&node.id), depth + 1);
+                writeln!(out, "{}}}", indent).unwrap();
+                continue;
+            }
+
+            let attributes = match node.kind {
+                NodeKind::Initial => "shape=point".to_string(),
+                NodeKind::Choice => format!("shape=diamond, label={}", dot_string(&label)),
+                NodeKind::Instance | NodeKind::State => format!("label={}", dot_string(&label)),
+            };
+            writeln!(out, "{}{} [{}];", indent, dot_string(&node.id), attributes).unwrap();
+        }
+    }
+
+    fn mermaid(&self) -> String {
+        let mut out = String::new();
+        if self.kind == "topology" {
+            out.push_str("flowchart LR\n");
+            for node in &self.nodes {
+                let label = [node.label.clone()]
+                    .into_iter()
+                    .chain(node.details.iter().cloned())
+                    .collect::<Vec<_>>()
+                    .join("<br>");
+                writeln!(
+                    out,
+                    "  {}[{}]",
+                    mermaid_id(&node.id),
+                    mermaid_string(&label)
+                )
+                .unwrap();
+            }
+
+            for edge in &self.edges {
+                let arrow = match edge.kind {
+                    EdgeKind::Pattern => "-.->",
+                    _ => "-->",
+                };
+                writeln!(
+                    out,
+                    "  {} {}|{}| {}",
+                    mermaid_id(&edge.from),
+                    arrow,
+                    mermaid_string(&edge.label),
+                    mermaid_id(&edge.to)
+                )
+                .unwrap();
+            }
+        } else {
+            out.push_str("stateDiagram-v2\n");
+            self.mermaid_states(&mut out, None, 1);
+            for edge in &self.edges {
+                if self
+                    .nodes
+                    .iter()
+                    .any(|node| node.id == edge.from && node.kind == NodeKind::Initial)
+                {
+                    continue;
+                }
+
+                write!(
+                    out,
+                    "  {} --> {}",
+                    mermaid_id(&edge.from),
+                    mermaid_id(&edge.to)
+                )
+                .unwrap();
+                if !edge.label.is_empty() {
+                    write!(out, " : {}", edge.label).unwrap();
+                }
+                out.push('\n');
+            }
+        }
+
+        out
+    }
+
+    /// The states of a Mermaid state diagram, with the initial transitions of
+    /// each composite state written inside it
+    fn mermaid_states(&self, out: &mut String, parent: Option<&str>, depth: usize) {
+        let indent = "  ".repeat(depth);
+        for node in self.children(parent) {
+            let id = mermaid_id(&node.id);
+            match node.kind {
+                NodeKind::Initial => {
+                    for edge in self.edges.iter().filter(|edge| edge.from == node.id) {
+                        write!(out, "{}[*] --> {}", indent, mermaid_id(&edge.to)).unwrap();
+                        if !edge.label.is_empty() {
+                            write!(out, " : {}", edge.label).unwrap();
+                        }
+                        out.push('\n');
+                    }
+                }
+                NodeKind::Choice => writeln!(out, "{}state {} <<choice>>", indent, id).unwrap(),
+                NodeKind::Instance | NodeKind::State => {
+                    writeln!(
+                        out,
+                        "{}state {} as {}",
+                        indent,
+                        mermaid_string(&node.label),
+                        id
+                    )
+                    .unwrap();
+                    for detail in &node.details {
+                        writeln!(out, "{}{} : {}", indent, id, detail).unwrap();
+                    }
+                    if self.has_children(&node.id) {
+                        writeln!(out, "{}state {} {{", indent, id).unwrap();
+                        self.mermaid_states(out, Some(&node.id), depth + 1);
+                        writeln!(out, "{}}}", indent).unwrap();
+                    }
+                }
+            }
+        }
+    }
+}
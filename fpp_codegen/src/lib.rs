@@ -0,0 +1,23 @@
+//! Code generators driven by the results of semantic analysis
+
+mod tree;
+mod types;
+mod writer;
+
+pub mod diff;
+pub mod doc;
+pub mod graph;
+pub mod python;
+pub mod rust;
+pub mod xml;
+
+/// A generated file
+#[derive(Debug, Clone)]
+pub struct Page {
+    /// The file name relative to the output directory
+    pub path: String,
+    pub content: String,
+}
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,844 @@
+//! Generation of Python modules for scripting against F Prime deployments
+//!
+//! FPP modules, components and topologies become nested classes. Enums become
+//! `enum.IntEnum` classes, arrays and structs become dataclasses, and all of
+//! them pack to and unpack from F Prime serialized bytes. Each topology lists
+//! the command opcodes, event IDs, telemetry channel IDs and parameter IDs
+//! of its component instances.
+
+use crate::tree::{Def, ModuleTree};
+use crate::types::{integer_value, is_supported, serialized_size, string_size, struct_members};
+use crate::writer::CodeWriter;
+use fpp_analysis::passes::doc_annotation;
+use fpp_analysis::semantics::{
+    AnonArrayValue, ArrayValue, DictionaryInstance, EnumConstantValue, FloatValue, StructValue,
+    Type, Value,
+};
+use fpp_analysis::Analysis;
+use fpp_ast::{
+    AstNode, DefAliasType, DefArray, DefConstant, DefEnum, DefStruct, DefTopology, FloatKind,
+    IntegerKind, Name, TransUnit,
+};
+use fpp_core::Annotated;
+use std::ops::Deref;
+use std::sync::Arc;
+
+/// Source of the support definitions
+const RUNTIME: &str = include_str!("runtime.py");
+
+/// Generate a Python source file with the types, constants and topology
+/// dictionaries defined in `ast`
+pub fn generate(a: &Analysis, ast: &[&TransUnit]) -> String {
+    let mut g = PythonGenerator {
+        a,
+        w: CodeWriter::new("    "),
+        depth: 0,
+        deferred: vec![],
+    };
+
+    g.w.line("# Generated from FPP by `fpp to-python`, do not edit");
+    g.w.blank();
+    g.w.line("from __future__ import annotations");
+    g.w.blank();
+    for module in IMPORTS {
+        g.w.line(format!("import {}", module));
+    }
+    g.separate();
+    g.w.lines(RUNTIME);
+
+    let mut root = ModuleTree::default();
+    for trans_unit in ast {
+        root.module_members(&trans_unit.0);
+    }
+
+    g.module_contents(&root);
+
+    // Class bodies cannot refer to the classes enclosing them,
+    // so these are assigned once every class exists
+    if !g.deferred.is_empty() {
+        let deferred = std::mem::take(&mut g.deferred);
+        g.separate();
+        for line in deferred {
+            g.w.line(line);
+        }
+    }
+
+    g.w.finish()
+}
+
+const PYTHON_KEYWORDS: &[&str] = &[
+    "False", "None", "True", "and", "as", "assert", "async", "await", "break", "class", "continue",
+    "def", "del", "elif", "else", "except", "finally", "for", "from", "global", "if", "import",
+    "in", "is", "lambda", "nonlocal", "not", "or", "pass", "raise", "return", "try", "while",
+    "with", "yield",
+];
+
+/// Modules imported by the generated code
+const IMPORTS: &[&str] = &["dataclasses", "enum", "struct", "typing"];
+
+/// Attributes of generated classes that members must not replace
+const CLASS_ATTRIBUTES: &[&str] = &[
+    "self",
+    "SIZE",
+    "SERIALIZED_SIZE",
+    "pack",
+    "pack_into",
+    "unpack",
+    "unpack_from",
+];
+
+/// Attributes of enum classes that constants must not replace
+const ENUM_ATTRIBUTES: &[&str] = &["default", "name", "value"];
+
+/// A Python identifier for an FPP name
+fn ident(name: &str) -> String {
+    if PYTHON_KEYWORDS.contains(&name) || IMPORTS.contains(&name) {
+        format!("{}_", name)
+    } else {
+        name.to_string()
+    }
+}
+
+/// A Python identifier for a struct member or enum constant
+fn member_ident(name: &str, reserved: &[&str]) -> String {
+    if CLASS_ATTRIBUTES.contains(&name) || reserved.contains(&name) {
+        format!("{}_", name)
+    } else {
+        ident(name)
+    }
+}
+
+/// The `struct` module format of a primitive type
+fn struct_format(ty: &Type) -> Option<&'static str> {
+    Some(match ty {
+        Type::PrimitiveInt(kind) => match kind {
+            IntegerKind::U8 => ">B",
+            IntegerKind::I8 => ">b",
+            IntegerKind::U16 => ">H",
+            IntegerKind::I16 => ">h",
+            IntegerKind::U32 => ">I",
+            IntegerKind::I32 => ">i",
+            IntegerKind::U64 => ">Q",
+            IntegerKind::I64 => ">q",
+        },
+        Type::Float(FloatKind::F32) => ">f",
+        Type::Float(FloatKind::F64) => ">d",
+        _ => return None,
+    })
+}
+
+fn float_literal(v: f64) -> String {
+    if v.is_nan() {
+        "float(\"nan\")".to_string()
+    } else if v.is_infinite() {
+        format!("float(\"{}inf\")", if v < 0.0 { "-" } else { "" })
+    } else {
+        format!("{:?}", v)
+    }
+}
+
+fn string_literal(s: &str) -> String {
+    let mut out = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+fn id_literal(id: i128) -> String {
+    if id < 0 {
+        id.to_string()
+    } else {
+        format!("0x{:X}", id)
+    }
+}
+
+/// Check if values of `ty` are built-in Python values, which may be
+/// written anywhere without referring to generated classes
+fn is_builtin(ty: &Arc<Type>) -> bool {
+    match Type::underlying_type(ty).deref() {
+        Type::PrimitiveInt(_)
+        | Type::Integer
+        | Type::Float(_)
+        | Type::String(_)
+        | Type::Boolean => true,
+        Type::AnonArray(ty) => is_builtin(&ty.elt_type),
+        Type::AnonStruct(ty) => ty.members.values().all(is_builtin),
+        _ => false,
+    }
+}
+
+/// Check if values of `ty` are immutable, so one value may be shared as a default
+fn is_immutable(ty: &Arc<Type>) -> bool {
+    is_builtin(ty)
+        && !matches!(
+            Type::underlying_type(ty).deref(),
+            Type::AnonArray(_) | Type::AnonStruct(_)
+        )
+}
+
+/// Emits the definitions of one generated file
+struct PythonGenerator<'a> {
+    a: &'a Analysis,
+    w: CodeWriter,
+    /// The number of generated classes enclosing the current item
+    depth: usize,
+    /// Assignments written after all classes are defined
+    deferred: Vec<String>,
+}
+
+impl<'a> PythonGenerator<'a> {
+    /// Write the lines produced by `f` one level deeper
+    fn nested<F: FnOnce(&mut Self)>(&mut self, f: F) {
+        self.w.indent();
+        f(self);
+        self.w.dedent();
+    }
+
+    /// Write `header:` and the lines produced by `f`
+    fn block<S: AsRef<str>, F: FnOnce(&mut Self)>(&mut self, header: S, f: F) {
+        self.w.line(format!("{}:", header.as_ref()));
+        self.nested(f);
+    }
+
+    /// Separate items, top level items by two blank lines
+    fn separate(&mut self) {
+        self.w.blank();
+        if self.depth == 0 {
+            self.w.line("");
+        }
+    }
+
+    fn comment<N: Annotated>(&mut self, node: &N) {
+        for line in doc_annotation(node) {
+            self.w.line(format!("# {}", line).trim_end());
+        }
+    }
+
+    fn docstring(&mut self, lines: &[String]) {
+        let lines: Vec<String> = lines
+            .iter()
+            .map(|line| line.replace('\\', "\\\\").replace('"', "\\\""))
+            .collect();
+
+        match lines.as_slice() {
+            [] => {}
+            [line] => self.w.line(format!("\"\"\"{}\"\"\"", line)),
+            [first, rest @ ..] => {
+                self.w.line(format!("\"\"\"{}", first));
+                for line in rest {
+                    self.w.line(line);
+                }
+                self.w.line("\"\"\"");
+            }
+        }
+    }
+
+    /// Write a class holding the lines produced by `f`, `f` returns the number
+    /// of generated definitions inside the class. The class is left out entirely
+    /// if nothing inside it was generated.
+    fn class<F: FnOnce(&mut Self) -> usize>(
+        &mut self,
+        header: String,
+        doc: &[String],
+        f: F,
+    ) -> usize {
+        let start = self.w.len();
+        self.separate();
+        self.w.lines(&format!("{}:", header));
+
+        self.depth += 1;
+        self.w.indent();
+        self.docstring(doc);
+        let body = self.w.len();
+        let count = f(self);
+        if self.w.len() == body && doc.is_empty() {
+            self.w.line("pass");
+        }
+        self.w.dedent();
+        self.depth -= 1;
+
+        if count == 0 {
+            self.w.truncate(start);
+        }
+
+        count
+    }
+
+    fn module(&mut self, name: &str, module: &ModuleTree) -> usize {
+        self.class(format!("class {}", ident(name)), &module.annotation, |g| {
+            g.module_contents(module)
+        })
+    }
+
+    fn module_contents(&mut self, module: &ModuleTree) -> usize {
+        let mut count = 0;
+        for def in &module.defs {
+            count += match def {
+                Def::AliasType(def) => self.def_alias_type(def),
+                Def::Array(def) => self.def_array(def),
+                Def::Constant(def) => self.def_constant(def),
+                Def::Enum(def) => self.def_enum(def),
+                Def::Struct(def) => self.def_struct(def),
+                Def::Topology(def) => self.def_topology(def),
+                Def::Port(_) => 0,
+            }
+        }
+
+        for (name, child) in &module.modules {
+            count += self.module(name, child);
+        }
+
+        count
+    }
+
+    /// The finalized type of a definition, if it can be represented in Python
+    fn def_type<N: AstNode>(&mut self, node: &N, name: &Name) -> Option<Arc<Type>> {
+        let ty = self.a.type_map.get(&node.id())?.clone();
+        if is_supported(&ty) {
+            Some(ty)
+        } else {
+            self.w.blank();
+            self.w.line(format!(
+                "# `{}` is not generated since it depends on an abstract type",
+                name.data
+            ));
+            None
+        }
+    }
+
+    /// The path of a definition from the top level of the generated file
+    fn path(&self, def: fpp_core::Node) -> String {
+        let symbol = self
+            .a
+            .symbol_map
+            .get(&def)
+            .expect("definition has no symbol");
+        let path: Vec<String> = self
+            .a
+            .qualified_name(symbol)
+            .split('.')
+            .map(ident)
+            .collect();
+
+        path.join(".")
+    }
+
+    fn python_type(&self, ty: &Type) -> String {
+        match ty {
+            Type::PrimitiveInt(_) | Type::Integer => "int".to_string(),
+            Type::Float(_) => "float".to_string(),
+            Type::String(_) => "str".to_string(),
+            Type::Boolean => "bool".to_string(),
+            Type::AnonArray(ty) => format!("typing.List[{}]", self.python_type(&ty.elt_type)),
+            Type::AnonStruct(_) => "typing.Dict[str, typing.Any]".to_string(),
+            _ => match ty.def_node_id() {
+                Some(def) => self.path(def),
+                None => panic!("{} has no Python type", ty),
+            },
+        }
+    }
+
+    /// A Python expression constructing `value` as a value of `ty`
+    fn value_expr(&self, value: &Value, ty: &Arc<Type>) -> String {
+        let value = value.convert(ty).unwrap_or_else(|| value.clone());
+        let underlying = Type::underlying_type(ty);
+
+        match (underlying.deref(), &value) {
+            (Type::PrimitiveInt(_) | Type::Integer, _) => {
+                integer_value(&value).unwrap_or(0).to_string()
+            }
+            (Type::Float(_), Value::Float(FloatValue { value, .. })) => float_literal(*value),
+            (Type::Float(_), _) => float_literal(integer_value(&value).unwrap_or(0) as f64),
+            (Type::Boolean, Value::Boolean(v)) => if v.0 { "True" } else { "False" }.to_string(),
+            (Type::String(_), Value::String(v)) => string_literal(&v.0),
+            (Type::Enum(_), Value::EnumConstant(EnumConstantValue { value, .. })) => format!(
+                "{}.{}",
+                self.python_type(&underlying),
+                member_ident(&value.0, ENUM_ATTRIBUTES)
+            ),
+            (
+                Type::Array(_) | Type::AnonArray(_),
+                Value::Array(ArrayValue {
+                    anon_array: AnonArrayValue { elements },
+                    ..
+                })
+                | Value::AnonArray(AnonArrayValue { elements }),
+            ) => {
+                let (elt_type, named) = match underlying.deref() {
+                    Type::Array(ty) => (&ty.anon_array.elt_type, true),
+                    Type::AnonArray(ty) => (&ty.elt_type, false),
+                    _ => unreachable!(),
+                };
+
+                let elements: Vec<String> = elements
+                    .iter()
+                    .map(|element| self.value_expr(element, elt_type))
+                    .collect();
+
+                if named {
+                    format!(
+                        "{}([{}])",
+                        self.python_type(&underlying),
+                        elements.join(", ")
+                    )
+                } else {
+                    format!("[{}]", elements.join(", "))
+                }
+            }
+            (
+                Type::Struct(struct_ty),
+                Value::Struct(StructValue { anon_struct, .. }) | Value::AnonStruct(anon_struct),
+            ) => {
+                let members: Vec<String> = struct_members(struct_ty)
+                    .into_iter()
+                    .filter_map(|(name, member_ty)| {
+                        let member = anon_struct
+                            .members
+                            .get(&name)
+                            .cloned()
+                            .or_else(|| member_ty.default_value())?;
+                        Some(format!(
+                            "{}={}",
+                            member_ident(&name, &[]),
+                            self.value_expr(&member, &member_ty)
+                        ))
+                    })
+                    .collect();
+
+                format!("{}({})", self.python_type(&underlying), members.join(", "))
+            }
+            (Type::AnonStruct(struct_ty), Value::AnonStruct(anon_struct)) => {
+                let members: Vec<String> = struct_ty
+                    .members
+                    .iter()
+                    .filter_map(|(name, member_ty)| {
+                        let member = anon_struct.members.get(name)?;
+                        Some(format!(
+                            "{}: {}",
+                            string_literal(name),
+                            self.value_expr(member, member_ty)
+                        ))
+                    })
+                    .collect();
+
+                format!("{{{}}}", members.join(", "))
+            }
+            _ => "None".to_string(),
+        }
+    }
+
+    /// A dataclass field default of `value` as a value of `ty`
+    fn field_default(&self, value: &Value, ty: &Arc<Type>) -> String {
+        let expr = self.value_expr(value, ty);
+        if is_immutable(ty) {
+            expr
+        } else {
+            format!("dataclasses.field(default_factory=lambda: {})", expr)
+        }
+    }
+
+    /// Pack the value of `expr` into `out`, `level` is the number of enclosing array loops
+    fn pack(&mut self, expr: &str, ty: &Arc<Type>, level: usize) {
+        let underlying = Type::underlying_type(ty);
+        if let Some(format) = struct_format(&underlying) {
+            self.w
+                .line(format!("out += struct.pack({:?}, {})", format, expr));
+            return;
+        }
+
+        match underlying.deref() {
+            Type::Boolean => self.w.line(format!("_pack_bool(out, {})", expr)),
+            Type::String(size) => self.w.line(format!(
+                "_pack_string(out, {}, {})",
+                expr,
+                string_size(size)
+            )),
+            Type::AnonArray(ty) => {
+                let element = format!("e{}", level);
+                self.block(format!("for {} in {}", element, expr), |g| {
+                    g.pack(&element, &ty.elt_type, level + 1)
+                })
+            }
+            _ => self.w.line(format!("{}.pack_into(out)", expr)),
+        }
+    }
+
+    /// A Python expression unpacking a value of `ty` from `reader`
+    fn unpack_expr(&self, ty: &Arc<Type>) -> String {
+        let underlying = Type::underlying_type(ty);
+        if let Some(format) = struct_format(&underlying) {
+            return format!("reader.unpack({:?})", format);
+        }
+
+        match underlying.deref() {
+            Type::Boolean => "reader.unpack_bool()".to_string(),
+            Type::String(size) => format!("reader.unpack_string({})", string_size(size)),
+            Type::AnonArray(ty) => format!(
+                "[{} for _ in range({})]",
+                self.unpack_expr(&ty.elt_type),
+                ty.size.unwrap_or_default()
+            ),
+            _ => format!("{}.unpack_from(reader)", self.python_type(&underlying)),
+        }
+    }
+
+    /// Write `pack_into` and `unpack_from`, `unpack` is the expression returning the value
+    fn serialize_methods<F: FnOnce(&mut Self)>(&mut self, name: &str, pack: F, unpack: String) {
+        self.w.blank();
+        self.block("def pack_into(self, out: bytearray) -> None", pack);
+        self.w.blank();
+        self.w.line("@classmethod");
+        self.block(
+            format!("def unpack_from(cls, reader: _Reader) -> {}", name),
+            |g| g.w.lines(&unpack),
+        );
+    }
+
+    fn def_alias_type(&mut self, def: &DefAliasType) -> usize {
+        let ty = match self.def_type(def, &def.name) {
+            None => return 0,
+            Some(ty) => ty,
+        };
+
+        let alias_type = match ty.deref() {
+            Type::AliasType(ty) => ty.alias_type.clone(),
+            _ => panic!("expected alias type"),
+        };
+
+        if is_builtin(&alias_type) {
+            let underlying = Type::underlying_type(&alias_type);
+            self.w.blank();
+            self.comment(def);
+            self.w.line(format!(
+                "{} = {}",
+                ident(&def.name.data),
+                self.python_type(&underlying)
+            ));
+        } else {
+            let underlying = Type::underlying_type(&alias_type);
+            let mut lines: Vec<String> = doc_annotation(def)
+                .into_iter()
+                .map(|line| format!("# {}", line).trim_end().to_string())
+                .collect();
+            lines.push(format!(
+                "{} = {}",
+                self.path(def.id()),
+                self.python_type(&underlying)
+            ));
+            self.deferred.extend(lines);
+        }
+        1
+    }
+
+    fn def_constant(&mut self, def: &DefConstant) -> usize {
+        let (value, ty) = match (
+            self.a.value_map.get(&def.id()),
+            self.a.type_map.get(&def.id()),
+        ) {
+            (Some(value), Some(ty)) => (value.clone(), ty.clone()),
+            _ => return 0,
+        };
+
+        if matches!(ty.deref(), Type::AbsType(_)) {
+            return 0;
+        }
+
+        let expr = self.value_expr(&value, &ty);
+        if is_builtin(&ty) {
+            self.w.blank();
+            self.comment(def);
+            self.w.line(format!("{} = {}", ident(&def.name.data), expr));
+        } else {
+            let mut lines: Vec<String> = doc_annotation(def)
+                .into_iter()
+                .map(|line| format!("# {}", line).trim_end().to_string())
+                .collect();
+            lines.push(format!("{} = {}", self.path(def.id()), expr));
+            self.deferred.extend(lines);
+        }
+        1
+    }
+
+    fn def_array(&mut self, def: &DefArray) -> usize {
+        let ty = match self.def_type(def, &def.name) {
+            None => return 0,
+            Some(ty) => ty,
+        };
+
+        let array_ty = match ty.deref() {
+            Type::Array(array_ty) => array_ty,
+            _ => panic!("expected array type"),
+        };
+
+        let name = ident(&def.name.data);
+        let path = self.path(def.id());
+        let elements = Arc::new(Type::AnonArray(array_ty.anon_array.clone()));
+        let default = array_ty
+            .default
+            .clone()
+            .or_else(|| ty.default_value())
+            .map(|default| self.field_default(&default, &elements))
+            .unwrap_or_else(|| "dataclasses.field(default_factory=list)".to_string());
+
+        self.class(
+            format!("@dataclasses.dataclass\nclass {}(_Serializable)", name),
+            &doc_annotation(def),
+            |g| {
+                g.w.blank();
+                g.w.line(format!(
+                    "elements: {} = {}",
+                    g.python_type(&elements),
+                    default
+                ));
+                g.w.blank();
+                g.w.line(format!(
+                    "SIZE = {}",
+                    array_ty.anon_array.size.unwrap_or_default()
+                ));
+                g.w.line(format!("SERIALIZED_SIZE = {}", serialized_size(&ty)));
+
+                let unpack = format!("return cls({})", g.unpack_expr(&elements));
+                g.serialize_methods(&path, |g| g.pack("self.elements", &elements, 0), unpack);
+                1
+            },
+        )
+    }
+
+    fn def_enum(&mut self, def: &DefEnum) -> usize {
+        let ty = match self.def_type(def, &def.name) {
+            None => return 0,
+            Some(ty) => ty,
+        };
+
+        let enum_ty = match ty.deref() {
+            Type::Enum(enum_ty) => enum_ty,
+            _ => panic!("expected enum type"),
+        };
+
+        let name = ident(&def.name.data);
+        let path = self.path(def.id());
+        let format = struct_format(&Type::PrimitiveInt(enum_ty.rep_type)).unwrap();
+
+        self.class(
+            format!("class {}(_Serializable, enum.IntEnum)", name),
+            &doc_annotation(def),
+            |g| {
+                g.w.blank();
+                for constant in &def.constants {
+                    let value = enum_ty
+                        .constants
+                        .iter()
+                        .find(|(name, _)| *name == constant.name.data);
+
+                    if let Some((constant_name, value)) = value {
+                        g.comment(constant);
+                        g.w.line(format!(
+                            "{} = {}",
+                            member_ident(constant_name, ENUM_ATTRIBUTES),
+                            value
+                        ));
+                    }
+                }
+
+                if let Some(Value::EnumConstant(default)) = &enum_ty.default {
+                    g.w.blank();
+                    g.w.line("@classmethod");
+                    g.block(format!("def default(cls) -> {}", path), |g| {
+                        g.w.line(format!(
+                            "return cls.{}",
+                            member_ident(&default.value.0, ENUM_ATTRIBUTES)
+                        ))
+                    });
+                }
+
+                g.serialize_methods(
+                    &path,
+                    |g| g.w.line(format!("out += struct.pack({:?}, self)", format)),
+                    format!("return reader.unpack_enum(cls, {:?})", format),
+                );
+                1
+            },
+        )
+    }
+
+    fn def_struct(&mut self, def: &DefStruct) -> usize {
+        let ty = match self.def_type(def, &def.name) {
+            None => return 0,
+            Some(ty) => ty,
+        };
+
+        let struct_ty = match ty.deref() {
+            Type::Struct(struct_ty) => struct_ty,
+            _ => panic!("expected struct type"),
+        };
+
+        let path = self.path(def.id());
+        let members = struct_members(struct_ty);
+
+        self.class(
+            format!(
+                "@dataclasses.dataclass\nclass {}(_Serializable)",
+                ident(&def.name.data)
+            ),
+            &doc_annotation(def),
+            |g| {
+                g.w.blank();
+                for (member_name, member_ty) in &members {
+                    if let Some(member) = def
+                        .members
+                        .iter()
+                        .find(|member| member.name.data == *member_name)
+                    {
+                        g.comment(member);
+                    }
+
+                    let default = struct_ty
+                        .default
+                        .as_ref()
+                        .and_then(|default| default.anon_struct.members.get(member_name).cloned())
+                        .or_else(|| member_ty.default_value());
+
+                    let python_type = g.python_type(member_ty);
+                    match default {
+                        None => g.w.line(format!(
+                            "{}: {}",
+                            member_ident(member_name, &[]),
+                            python_type
+                        )),
+                        Some(default) => g.w.line(format!(
+                            "{}: {} = {}",
+                            member_ident(member_name, &[]),
+                            python_type,
+                            g.field_default(&default, member_ty)
+                        )),
+                    }
+                }
+
+                g.w.blank();
+                g.w.line(format!("SERIALIZED_SIZE = {}", serialized_size(&ty)));
+
+                let mut unpack = vec!["return cls(".to_string()];
+                for (member_name, member_ty) in &members {
+                    unpack.push(format!(
+                        "    {}={},",
+                        member_ident(member_name, &[]),
+                        g.unpack_expr(member_ty)
+                    ));
+                }
+                unpack.push(")".to_string());
+
+                g.serialize_methods(
+                    &path,
+                    |g| {
+                        for (member_name, member_ty) in &members {
+                            g.pack(
+                                &format!("self.{}", member_ident(member_name, &[])),
+                                member_ty,
+                                0,
+                            );
+                        }
+
+                        if members.is_empty() {
+                            g.w.line("pass");
+                        }
+                    },
+                    unpack.join("\n"),
+                );
+                1
+            },
+        )
+    }
+
+    fn def_topology(&mut self, def: &DefTopology) -> usize {
+        let instances = self.a.topology_instances(def);
+        self.class(
+            format!("class {}", ident(&def.name.data)),
+            &doc_annotation(def),
+            |g| {
+                for instance in &instances {
+                    g.instance(instance);
+                }
+                1
+            },
+        )
+    }
+
+    /// Write the identifiers of a component instance, offset by its base identifier
+    fn instance(&mut self, instance: &DictionaryInstance) {
+        let component = self
+            .a
+            .symbol_map
+            .get(&instance.component.id())
+            .map(|symbol| self.a.qualified_name(symbol))
+            .unwrap_or_else(|| instance.component.name.data.clone());
+        let doc = vec![format!("`{}` of component `{}`", instance.name, component)];
+        let name = instance.node.name.data.clone();
+        let base_id = instance.base_id;
+        let dictionary = &instance.dictionary;
+
+        let kinds: [(&str, Vec<(&String, i128)>); 4] = [
+            (
+                "Commands",
+                dictionary
+                    .commands
+                    .iter()
+                    .map(|command| (&command.name, command.opcode))
+                    .collect(),
+            ),
+            (
+                "Events",
+                dictionary
+                    .events
+                    .iter()
+                    .map(|event| (&event.name, event.id))
+                    .collect(),
+            ),
+            (
+                "Channels",
+                dictionary
+                    .channels
+                    .iter()
+                    .map(|channel| (&channel.name, channel.id))
+                    .collect(),
+            ),
+            (
+                "Params",
+                dictionary
+                    .params
+                    .iter()
+                    .map(|param| (&param.name, param.id))
+                    .collect(),
+            ),
+        ];
+
+        self.class(format!("class {}", ident(&name)), &doc, |g| {
+            g.w.blank();
+            g.w.line(format!("NAME = {}", string_literal(&instance.name)));
+            g.w.line(format!("BASE_ID = {}", id_literal(base_id)));
+
+            for (kind, ids) in kinds {
+                if ids.is_empty() {
+                    continue;
+                }
+
+                g.class(format!("class {}", kind), &[], |g| {
+                    for (name, id) in ids {
+                        g.w.line(format!("{} = {}", ident(name), id_literal(base_id + id)));
+                    }
+                    1
+                });
+            }
+            1
+        });
+    }
+}
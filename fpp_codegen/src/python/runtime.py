@@ -0,0 +1,79 @@
+class DeserializeError(Exception):
+    """Raised when bytes do not hold a serialized value"""
+
+
+class _Reader:
+    """Reads serialized values from the front of a buffer"""
+
+    def __init__(self, data: bytes):
+        self.data = bytes(data)
+        self.offset = 0
+
+    def take(self, size: int) -> bytes:
+        if self.offset + size > len(self.data):
+            raise DeserializeError("unexpected end of input")
+        chunk = self.data[self.offset : self.offset + size]
+        self.offset += size
+        return chunk
+
+    def unpack(self, fmt: str) -> typing.Any:
+        return struct.unpack(fmt, self.take(struct.calcsize(fmt)))[0]
+
+    def unpack_bool(self) -> bool:
+        value = self.unpack(">B")
+        if value == 0xFF:
+            return True
+        if value == 0x00:
+            return False
+        raise DeserializeError(f"invalid boolean {value:#04x}")
+
+    def unpack_string(self, max_size: int) -> str:
+        size = self.unpack(">H")
+        if size > max_size:
+            raise DeserializeError(f"string of length {size} exceeds {max_size}")
+        try:
+            return self.take(size).decode("utf-8")
+        except UnicodeDecodeError as err:
+            raise DeserializeError("invalid UTF-8 in string") from err
+
+    def unpack_enum(self, cls: typing.Any, fmt: str) -> typing.Any:
+        value = self.unpack(fmt)
+        try:
+            return cls(value)
+        except ValueError as err:
+            raise DeserializeError(f"invalid {cls.__name__} value {value}") from err
+
+
+def _pack_bool(out: bytearray, value: bool) -> None:
+    out += b"\xff" if value else b"\x00"
+
+
+def _pack_string(out: bytearray, value: str, max_size: int) -> None:
+    """Pack a string, truncating it to `max_size` bytes at a character boundary"""
+    data = value.encode("utf-8")[:max_size].decode("utf-8", "ignore").encode("utf-8")
+    out += struct.pack(">H", len(data))
+    out += data
+
+
+class _Serializable:
+    """Packing to and unpacking from F Prime serialized bytes"""
+
+    def pack_into(self, out: bytearray) -> None:
+        raise NotImplementedError
+
+    @classmethod
+    def unpack_from(cls, reader: _Reader) -> typing.Any:
+        raise NotImplementedError
+
+    def pack(self) -> bytes:
+        out = bytearray()
+        self.pack_into(out)
+        return bytes(out)
+
+    @classmethod
+    def unpack(cls, data: bytes) -> typing.Any:
+        reader = _Reader(data)
+        value = cls.unpack_from(reader)
+        if reader.offset != len(reader.data):
+            raise DeserializeError(f"{len(reader.data) - reader.offset} trailing bytes")
+        return value
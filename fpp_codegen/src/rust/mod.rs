@@ -0,0 +1,820 @@
+//! Generation of Rust modules from FPP type and port definitions
+//!
+//! FPP modules and components become nested `pub mod` items. Enums, arrays
+//! and structs implement `Default`, `Display` and F Prime compatible
+//! serialization through the `fpp_runtime` module emitted at the root of
+//! the generated file. All paths are relative so the file may be included
+//! anywhere in a crate.
+
+use crate::tree::{Def, ModuleTree};
+use crate::types::{integer_value, is_supported, serialized_size, string_size, struct_members};
+use crate::writer::CodeWriter;
+use fpp_analysis::passes::doc_annotation;
+use fpp_analysis::semantics::{
+    AnonArrayValue, ArrayValue, EnumConstantValue, FloatValue, Format, FormatPart,
+    FormatReplacementKind, IntegerFormatKind, RationalFormatKind, StructValue, Type, Value,
+};
+use fpp_analysis::Analysis;
+use fpp_ast::{
+    AstNode, DefAliasType, DefArray, DefEnum, DefPort, DefStruct, FloatKind, IntegerKind, Name,
+    TransUnit,
+};
+use fpp_core::Annotated;
+use std::ops::Deref;
+use std::sync::Arc;
+
+/// Name of the support module at the root of the generated file
+const RUNTIME_MODULE: &str = "fpp_runtime";
+
+/// Source of the support module
+const RUNTIME: &str = include_str!("runtime.rs");
+
+/// Precision used by `%e`, `%f` and `%g` when none is given
+const DEFAULT_PRECISION: usize = 6;
+
+/// Generate a Rust source file with the types and ports defined in `ast`
+pub fn generate(a: &Analysis, ast: &[&TransUnit]) -> String {
+    let mut g = RustGenerator {
+        a,
+        w: CodeWriter::new("    "),
+        depth: 0,
+    };
+
+    g.w.line("// Generated from FPP by `fpp to-rust`, do not edit");
+    g.w.blank();
+    g.w.line("#[allow(dead_code)]");
+    g.w.line(format!("pub mod {} {{", RUNTIME_MODULE));
+    g.nested(|g| g.w.lines(RUNTIME));
+    g.w.line("}");
+
+    let mut root = ModuleTree::default();
+    for trans_unit in ast {
+        root.module_members(&trans_unit.0);
+    }
+
+    g.module_contents(&root);
+
+    g.w.finish()
+}
+
+const RUST_KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "do", "dyn",
+    "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if", "impl", "in", "let",
+    "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref", "return",
+    "static", "struct", "trait", "true", "try", "type", "typeof", "unsafe", "unsized", "use",
+    "virtual", "where", "while", "yield",
+];
+
+/// A Rust identifier for an FPP name
+fn ident(name: &str) -> String {
+    match name {
+        // These cannot be raw identifiers
+        "self" | "Self" | "super" | "crate" => format!("{}_", name),
+        _ if RUST_KEYWORDS.contains(&name) => format!("r#{}", name),
+        _ => name.to_string(),
+    }
+}
+
+fn int_type(kind: IntegerKind) -> &'static str {
+    match kind {
+        IntegerKind::U8 => "u8",
+        IntegerKind::I8 => "i8",
+        IntegerKind::U16 => "u16",
+        IntegerKind::I16 => "i16",
+        IntegerKind::U32 => "u32",
+        IntegerKind::I32 => "i32",
+        IntegerKind::U64 => "u64",
+        IntegerKind::I64 => "i64",
+    }
+}
+
+fn float_type(kind: &FloatKind) -> &'static str {
+    match kind {
+        FloatKind::F32 => "f32",
+        FloatKind::F64 => "f64",
+    }
+}
+
+fn float_literal(v: f64, kind: &FloatKind) -> String {
+    let ty = float_type(kind);
+    if v.is_nan() {
+        format!("{}::NAN", ty)
+    } else if v.is_infinite() {
+        format!("{}::{}INFINITY", ty, if v < 0.0 { "NEG_" } else { "" })
+    } else {
+        match kind {
+            FloatKind::F32 => format!("{:?}{}", v as f32, ty),
+            FloatKind::F64 => format!("{:?}{}", v, ty),
+        }
+    }
+}
+
+/// Emits the definitions of one generated file
+struct RustGenerator<'a> {
+    a: &'a Analysis,
+    w: CodeWriter,
+    /// The number of generated modules enclosing the current item
+    depth: usize,
+}
+
+impl<'a> RustGenerator<'a> {
+    /// Write the lines produced by `f` one level deeper
+    fn nested<F: FnOnce(&mut Self)>(&mut self, f: F) {
+        self.w.indent();
+        f(self);
+        self.w.dedent();
+    }
+
+    /// Write `header {`, the lines produced by `f` and the closing brace
+    fn block<S: AsRef<str>, F: FnOnce(&mut Self)>(&mut self, header: S, f: F) {
+        self.w.line(format!("{} {{", header.as_ref()));
+        self.nested(f);
+        self.w.line("}");
+    }
+
+    /// Prefix making a path from the root of the generated file relative to the current module
+    fn root(&self) -> String {
+        "super::".repeat(self.depth)
+    }
+
+    fn runtime(&self) -> String {
+        format!("{}{}", self.root(), RUNTIME_MODULE)
+    }
+
+    fn doc<N: Annotated>(&mut self, node: &N) {
+        for line in doc_annotation(node) {
+            self.w.line(format!("/// {}", line).trim_end());
+        }
+    }
+
+    /// Generate a module, leaving it out entirely if nothing inside it was generated
+    fn module(&mut self, name: &str, module: &ModuleTree) -> usize {
+        let start = self.w.len();
+        self.w.blank();
+        for line in &module.annotation {
+            self.w.line(format!("/// {}", line).trim_end());
+        }
+        self.w
+            .line("#[allow(non_snake_case, non_camel_case_types)]");
+        self.w.line(format!("pub mod {} {{", ident(name)));
+
+        self.depth += 1;
+        self.w.indent();
+        let count = self.module_contents(module);
+        self.w.dedent();
+        self.depth -= 1;
+
+        if count == 0 {
+            self.w.truncate(start);
+        } else {
+            self.w.line("}");
+        }
+
+        count
+    }
+
+    fn module_contents(&mut self, module: &ModuleTree) -> usize {
+        let mut count = 0;
+        for def in &module.defs {
+            count += match def {
+                Def::AliasType(def) => self.def_alias_type(def),
+                Def::Array(def) => self.def_array(def),
+                Def::Enum(def) => self.def_enum(def),
+                Def::Port(def) => self.def_port(def),
+                Def::Struct(def) => self.def_struct(def),
+                Def::Constant(_) | Def::Topology(_) => 0,
+            }
+        }
+
+        for (name, child) in &module.modules {
+            count += self.module(name, child);
+        }
+
+        count
+    }
+
+    /// The finalized type of a definition, if it can be represented in Rust
+    fn def_type<N: AstNode>(&mut self, node: &N, name: &Name) -> Option<Arc<Type>> {
+        let ty = self.a.type_map.get(&node.id())?.clone();
+        if is_supported(&ty) {
+            Some(ty)
+        } else {
+            self.w.blank();
+            self.w.line(format!(
+                "// `{}` is not generated since it depends on an abstract type",
+                name.data
+            ));
+            None
+        }
+    }
+
+    /// The path of a definition relative to the current module
+    fn path(&self, def: fpp_core::Node) -> String {
+        let symbol = self
+            .a
+            .symbol_map
+            .get(&def)
+            .expect("definition has no symbol");
+        let path: Vec<String> = self
+            .a
+            .qualified_name(symbol)
+            .split('.')
+            .map(ident)
+            .collect();
+
+        format!("{}{}", self.root(), path.join("::"))
+    }
+
+    fn rust_type(&self, ty: &Type) -> String {
+        match ty {
+            Type::PrimitiveInt(kind) => int_type(*kind).to_string(),
+            Type::Float(kind) => float_type(kind).to_string(),
+            Type::String(_) => "String".to_string(),
+            Type::Boolean => "bool".to_string(),
+            Type::AnonArray(ty) => format!(
+                "[{}; {}]",
+                self.rust_type(&ty.elt_type),
+                ty.size.unwrap_or_default()
+            ),
+            _ => match ty.def_node_id() {
+                Some(def) => self.path(def),
+                None => panic!("{} has no Rust type", ty),
+            },
+        }
+    }
+
+    /// A Rust expression constructing `value` as a value of `ty`
+    fn value_expr(&self, value: &Value, ty: &Arc<Type>) -> String {
+        let value = value.convert(ty).unwrap_or_else(|| value.clone());
+        let underlying = Type::underlying_type(ty);
+
+        match (underlying.deref(), &value) {
+            (Type::PrimitiveInt(kind), _) => {
+                format!("{}{}", integer_value(&value).unwrap_or(0), int_type(*kind))
+            }
+            (Type::Float(kind), Value::Float(FloatValue { value, .. })) => {
+                float_literal(*value, kind)
+            }
+            (Type::Float(kind), _) => {
+                float_literal(integer_value(&value).unwrap_or(0) as f64, kind)
+            }
+            (Type::Boolean, Value::Boolean(v)) => v.0.to_string(),
+            (Type::String(_), Value::String(v)) => format!("String::from({:?})", v.0),
+            (Type::Enum(_), Value::EnumConstant(EnumConstantValue { value, .. })) => {
+                format!("{}::{}", self.rust_type(&underlying), ident(&value.0))
+            }
+            (
+                Type::Array(_) | Type::AnonArray(_),
+                Value::Array(ArrayValue {
+                    anon_array: AnonArrayValue { elements },
+                    ..
+                })
+                | Value::AnonArray(AnonArrayValue { elements }),
+            ) => {
+                let (elt_type, named) = match underlying.deref() {
+                    Type::Array(ty) => (&ty.anon_array.elt_type, true),
+                    Type::AnonArray(ty) => (&ty.elt_type, false),
+                    _ => unreachable!(),
+                };
+
+                let elements: Vec<String> = elements
+                    .iter()
+                    .map(|element| self.value_expr(element, elt_type))
+                    .collect();
+
+                if named {
+                    format!("{}([{}])", self.rust_type(&underlying), elements.join(", "))
+                } else {
+                    format!("[{}]", elements.join(", "))
+                }
+            }
+            (
+                Type::Struct(struct_ty),
+                Value::Struct(StructValue { anon_struct, .. }) | Value::AnonStruct(anon_struct),
+            ) => {
+                let members: Vec<String> = struct_members(struct_ty)
+                    .into_iter()
+                    .map(|(name, member_ty)| {
+                        let expr = match anon_struct
+                            .members
+                            .get(&name)
+                            .cloned()
+                            .or_else(|| member_ty.default_value())
+                        {
+                            None => "Default::default()".to_string(),
+                            Some(member) => self.value_expr(&member, &member_ty),
+                        };
+
+                        format!("{}: {}", ident(&name), expr)
+                    })
+                    .collect();
+
+                format!(
+                    "{} {{ {} }}",
+                    self.rust_type(&underlying),
+                    members.join(", ")
+                )
+            }
+            _ => "Default::default()".to_string(),
+        }
+    }
+
+    /// Serialize the value referenced by `expr` into `out`
+    fn serialize(&mut self, expr: &str, ty: &Arc<Type>) {
+        let runtime = self.runtime();
+        match Type::underlying_type(ty).deref() {
+            Type::String(size) => self.w.line(format!(
+                "{}::serialize_string({}, {}, out);",
+                runtime,
+                expr,
+                string_size(size)
+            )),
+            Type::AnonArray(ty) => self.block(format!("for e in {}", expr), |g| {
+                g.serialize("e", &ty.elt_type)
+            }),
+            _ => self
+                .w
+                .line(format!("{}::Serialize::serialize({}, out);", runtime, expr)),
+        }
+    }
+
+    /// A Rust expression deserializing a value of `ty` from `bytes`
+    fn deserialize_expr(&self, ty: &Arc<Type>) -> String {
+        let runtime = self.runtime();
+        match Type::underlying_type(ty).deref() {
+            Type::String(size) => format!(
+                "{}::deserialize_string(bytes, {})?",
+                runtime,
+                string_size(size)
+            ),
+            Type::AnonArray(ty) => format!(
+                "{}::deserialize_array(bytes, {})?",
+                runtime,
+                self.deserialize_fn(&ty.elt_type)
+            ),
+            _ => format!(
+                "<{} as {}::Serialize>::deserialize(bytes)?",
+                self.rust_type(ty),
+                runtime
+            ),
+        }
+    }
+
+    /// A Rust function deserializing a value of `ty`, used for array elements
+    fn deserialize_fn(&self, ty: &Arc<Type>) -> String {
+        let runtime = self.runtime();
+        match Type::underlying_type(ty).deref() {
+            Type::String(size) => format!(
+                "|bytes| {}::deserialize_string(bytes, {})",
+                runtime,
+                string_size(size)
+            ),
+            Type::AnonArray(ty) => format!(
+                "|bytes| {}::deserialize_array(bytes, {})",
+                runtime,
+                self.deserialize_fn(&ty.elt_type)
+            ),
+            _ => format!(
+                "<{} as {}::Serialize>::deserialize",
+                self.rust_type(ty),
+                runtime
+            ),
+        }
+    }
+
+    /// Write the value referenced by `expr` to the formatter `f`.
+    /// Arrays apply `format` to each element.
+    fn display(&mut self, expr: &str, ty: &Arc<Type>, format: Option<&Format>) {
+        let ty = Type::underlying_type(ty);
+        if let Type::AnonArray(array_ty) = ty.deref() {
+            self.w.line("f.write_str(\"[ \")?;");
+            self.w.line("let mut separator = \"\";");
+            self.block(format!("for e in {}", expr), |g| {
+                g.w.line("f.write_str(separator)?;");
+                g.w.line("separator = \", \";");
+                g.display("e", &array_ty.elt_type, format);
+            });
+            self.w.line("f.write_str(\" ]\")?;");
+            return;
+        }
+
+        match format {
+            None => self.display_field(expr, &ty, &FormatReplacementKind::Default),
+            Some(format) => {
+                for part in &format.0 {
+                    match part {
+                        FormatPart::Literal(s) => self.w.line(format!("f.write_str({:?})?;", s)),
+                        FormatPart::FormatReplacement(field) => {
+                            self.display_field(expr, &ty, &field.kind)
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn display_field(&mut self, expr: &str, ty: &Type, kind: &FormatReplacementKind) {
+        let runtime = self.runtime();
+        let line = match (kind, ty) {
+            (FormatReplacementKind::Default, Type::Float(_)) => format!(
+                "f.write_str(&{}::format_fixed(*{} as f64, {}))?;",
+                runtime, expr, DEFAULT_PRECISION
+            ),
+            (FormatReplacementKind::Integer(kind), Type::PrimitiveInt(_)) => match kind {
+                IntegerFormatKind::Character => {
+                    format!("write!(f, \"{{}}\", *{} as u8 as char)?;", expr)
+                }
+                IntegerFormatKind::Decimal => format!("write!(f, \"{{}}\", {})?;", expr),
+                IntegerFormatKind::Hexadecimal => format!("write!(f, \"{{:x}}\", {})?;", expr),
+                IntegerFormatKind::Octal => format!("write!(f, \"{{:o}}\", {})?;", expr),
+            },
+            (
+                FormatReplacementKind::Rational { precision, kind },
+                Type::PrimitiveInt(_) | Type::Float(_),
+            ) => format!(
+                "f.write_str(&{}::format_{}(*{} as f64, {}))?;",
+                runtime,
+                match kind {
+                    RationalFormatKind::Exponent => "exponent",
+                    RationalFormatKind::Fixed => "fixed",
+                    RationalFormatKind::General => "general",
+                },
+                expr,
+                precision.map_or(DEFAULT_PRECISION, |p| p as usize)
+            ),
+            _ => format!("write!(f, \"{{}}\", {})?;", expr),
+        };
+
+        self.w.line(line);
+    }
+
+    fn impl_default(&mut self, name: &str, expr: String) {
+        self.w.blank();
+        self.block(format!("impl Default for {}", name), |g| {
+            g.block("fn default() -> Self", |g| g.w.line(expr))
+        });
+    }
+
+    fn impl_constants(&mut self, name: &str, constants: Vec<String>) {
+        self.w.blank();
+        self.block(format!("impl {}", name), |g| {
+            for constant in constants {
+                g.w.line(constant);
+            }
+        });
+    }
+
+    /// Implement `Serialize`, `deserialize` is the expression returning the value
+    fn impl_serialize<F: FnOnce(&mut Self)>(
+        &mut self,
+        name: &str,
+        serialize: F,
+        deserialize: String,
+    ) {
+        let runtime = self.runtime();
+        self.w.blank();
+        self.block(format!("impl {}::Serialize for {}", runtime, name), |g| {
+            g.block("fn serialize(&self, out: &mut Vec<u8>)", serialize);
+            g.w.blank();
+            g.block(
+                format!(
+                    "fn deserialize(bytes: &mut &[u8]) -> Result<Self, {}::DeserializeError>",
+                    runtime
+                ),
+                |g| g.w.lines(&deserialize),
+            );
+        });
+    }
+
+    fn impl_display<F: FnOnce(&mut Self)>(&mut self, name: &str, f: F) {
+        self.w.blank();
+        self.block(format!("impl std::fmt::Display for {}", name), |g| {
+            g.block(
+                "fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result",
+                f,
+            )
+        });
+    }
+
+    fn def_alias_type(&mut self, def: &DefAliasType) -> usize {
+        let ty = match self.def_type(def, &def.name) {
+            None => return 0,
+            Some(ty) => ty,
+        };
+
+        let alias_type = match ty.deref() {
+            Type::AliasType(ty) => ty.alias_type.clone(),
+            _ => panic!("expected alias type"),
+        };
+
+        self.w.blank();
+        self.doc(def);
+        self.w.line("#[allow(non_camel_case_types)]");
+        self.w.line(format!(
+            "pub type {} = {};",
+            ident(&def.name.data),
+            self.rust_type(&alias_type)
+        ));
+        1
+    }
+
+    fn def_array(&mut self, def: &DefArray) -> usize {
+        let ty = match self.def_type(def, &def.name) {
+            None => return 0,
+            Some(ty) => ty,
+        };
+
+        let array_ty = match ty.deref() {
+            Type::Array(array_ty) => array_ty,
+            _ => panic!("expected array type"),
+        };
+
+        let name = ident(&def.name.data);
+        let elements = Arc::new(Type::AnonArray(array_ty.anon_array.clone()));
+        let elements_type = self.rust_type(&elements);
+
+        self.w.blank();
+        self.doc(def);
+        self.w.line("#[allow(non_camel_case_types)]");
+        self.w.line("#[derive(Debug, Clone, PartialEq)]");
+        self.w
+            .line(format!("pub struct {}(pub {});", name, elements_type));
+
+        self.impl_constants(
+            &name,
+            vec![
+                format!(
+                    "pub const SIZE: usize = {};",
+                    array_ty.anon_array.size.unwrap_or_default()
+                ),
+                serialized_size_constant(&ty),
+            ],
+        );
+
+        let default = match &array_ty.default {
+            None => "Self(Default::default())".to_string(),
+            Some(default) => self.value_expr(default, &ty),
+        };
+        self.impl_default(&name, default);
+
+        self.w.blank();
+        self.block(format!("impl std::ops::Deref for {}", name), |g| {
+            g.w.line(format!("type Target = {};", elements_type));
+            g.w.blank();
+            g.block("fn deref(&self) -> &Self::Target", |g| g.w.line("&self.0"));
+        });
+
+        self.w.blank();
+        self.block(format!("impl std::ops::DerefMut for {}", name), |g| {
+            g.block("fn deref_mut(&mut self) -> &mut Self::Target", |g| {
+                g.w.line("&mut self.0")
+            });
+        });
+
+        let deserialize = format!("Ok(Self({}))", self.deserialize_expr(&elements));
+        self.impl_serialize(&name, |g| g.serialize("&self.0", &elements), deserialize);
+
+        self.impl_display(&name, |g| {
+            g.display("&self.0", &elements, array_ty.format.as_ref());
+            g.w.line("Ok(())");
+        });
+        1
+    }
+
+    fn def_enum(&mut self, def: &DefEnum) -> usize {
+        let ty = match self.def_type(def, &def.name) {
+            None => return 0,
+            Some(ty) => ty,
+        };
+
+        let enum_ty = match ty.deref() {
+            Type::Enum(enum_ty) => enum_ty,
+            _ => panic!("expected enum type"),
+        };
+
+        let name = ident(&def.name.data);
+        let rep_type = int_type(enum_ty.rep_type);
+        let runtime = self.runtime();
+
+        self.w.blank();
+        self.doc(def);
+        self.w.line("#[allow(non_camel_case_types)]");
+        self.w
+            .line("#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]");
+        self.w.line(format!("#[repr({})]", rep_type));
+        self.block(format!("pub enum {}", name), |g| {
+            for constant in &def.constants {
+                let value = enum_ty
+                    .constants
+                    .iter()
+                    .find(|(name, _)| *name == constant.name.data);
+
+                if let Some((constant_name, value)) = value {
+                    g.doc(constant);
+                    g.w.line(format!("{} = {},", ident(constant_name), value));
+                }
+            }
+        });
+
+        self.impl_constants(&name, vec![serialized_size_constant(&ty)]);
+
+        if let Some(Value::EnumConstant(default)) = &enum_ty.default {
+            self.impl_default(&name, format!("Self::{}", ident(&default.value.0)));
+        }
+
+        let mut deserialize = vec![format!(
+            "match <{} as {}::Serialize>::deserialize(bytes)? {{",
+            rep_type, runtime
+        )];
+        for (constant, value) in &enum_ty.constants {
+            deserialize.push(format!("    {} => Ok(Self::{}),", value, ident(constant)));
+        }
+        deserialize.push(format!(
+            "    value => Err({}::DeserializeError::InvalidEnumValue(value as i128)),",
+            runtime
+        ));
+        deserialize.push("}".to_string());
+
+        self.impl_serialize(
+            &name,
+            |g| {
+                g.w.line(format!(
+                    "{}::Serialize::serialize(&(*self as {}), out);",
+                    runtime, rep_type
+                ))
+            },
+            deserialize.join("\n"),
+        );
+
+        self.impl_display(&name, |g| {
+            g.w.line("f.write_str(match self {");
+            g.nested(|g| {
+                for (constant, _) in &enum_ty.constants {
+                    g.w.line(format!("Self::{} => {:?},", ident(constant), constant));
+                }
+            });
+            g.w.line("})");
+        });
+        1
+    }
+
+    fn def_struct(&mut self, def: &DefStruct) -> usize {
+        let ty = match self.def_type(def, &def.name) {
+            None => return 0,
+            Some(ty) => ty,
+        };
+
+        let struct_ty = match ty.deref() {
+            Type::Struct(struct_ty) => struct_ty,
+            _ => panic!("expected struct type"),
+        };
+
+        let name = ident(&def.name.data);
+        let members = struct_members(struct_ty);
+
+        self.w.blank();
+        self.doc(def);
+        self.w
+            .line("#[allow(non_camel_case_types, non_snake_case)]");
+        self.w.line("#[derive(Debug, Clone, PartialEq)]");
+        self.block(format!("pub struct {}", name), |g| {
+            for (member_name, member_ty) in &members {
+                if let Some(member) = def
+                    .members
+                    .iter()
+                    .find(|member| member.name.data == *member_name)
+                {
+                    g.doc(member);
+                }
+
+                let rust_type = g.rust_type(member_ty);
+                g.w.line(format!("pub {}: {},", ident(member_name), rust_type));
+            }
+        });
+
+        self.impl_constants(&name, vec![serialized_size_constant(&ty)]);
+
+        if let Some(default) = &struct_ty.default {
+            let default = self.value_expr(&Value::Struct(default.clone()), &ty);
+            self.impl_default(&name, default);
+        }
+
+        let mut deserialize = vec!["Ok(Self {".to_string()];
+        for (member_name, member_ty) in &members {
+            deserialize.push(format!(
+                "    {}: {},",
+                ident(member_name),
+                self.deserialize_expr(member_ty)
+            ));
+        }
+        deserialize.push("})".to_string());
+
+        self.impl_serialize(
+            &name,
+            |g| {
+                for (member_name, member_ty) in &members {
+                    g.serialize(&format!("&self.{}", ident(member_name)), member_ty);
+                }
+            },
+            deserialize.join("\n"),
+        );
+
+        self.impl_display(&name, |g| {
+            g.w.line("f.write_str(\"( \")?;");
+            for (i, (member_name, member_ty)) in members.iter().enumerate() {
+                let label = format!("{}{} = ", if i == 0 { "" } else { ", " }, member_name);
+                g.w.line(format!("f.write_str({:?})?;", label));
+                g.display(
+                    &format!("&self.{}", ident(member_name)),
+                    member_ty,
+                    struct_ty.formats.get(member_name),
+                );
+            }
+            g.w.line("f.write_str(\" )\")");
+        });
+        1
+    }
+
+    fn def_port(&mut self, def: &DefPort) -> usize {
+        let params: Option<Vec<(String, Arc<Type>)>> = def
+            .params
+            .iter()
+            .map(|param| {
+                let ty = self.a.type_map.get(&param.type_name.node_id)?;
+                is_supported(ty).then(|| (param.name.data.clone(), ty.clone()))
+            })
+            .collect();
+
+        let params = match params {
+            None => {
+                self.w.blank();
+                self.w.line(format!(
+                    "// Port `{}` is not generated since its arguments cannot be serialized",
+                    def.name.data
+                ));
+                return 0;
+            }
+            Some(params) => params,
+        };
+
+        let name = ident(&def.name.data);
+        let runtime = self.runtime();
+        let names: Vec<&str> = params.iter().map(|(name, _)| name.as_str()).collect();
+        let types: Vec<String> = params.iter().map(|(_, ty)| self.rust_type(ty)).collect();
+        let args: Vec<String> = params
+            .iter()
+            .map(|(_, ty)| self.deserialize_expr(ty))
+            .collect();
+
+        self.w.blank();
+        self.doc(def);
+        self.w
+            .line(format!("/// Arguments: `({})`", names.join(", ")));
+        self.w.line("#[allow(non_camel_case_types)]");
+        self.w.line(format!("pub struct {};", name));
+
+        self.w.blank();
+        self.block(format!("impl {}::Port for {}", runtime, name), |g| {
+            g.w.line(format!("type Args = {};", tuple(&types)));
+            g.w.blank();
+            g.block("fn serialize_args(args: &Self::Args, out: &mut Vec<u8>)", |g| {
+                if params.is_empty() {
+                    g.w.line("let _ = (args, out);");
+                }
+
+                for (i, (_, ty)) in params.iter().enumerate() {
+                    g.serialize(&format!("&args.{}", i), ty);
+                }
+            });
+            g.w.blank();
+            g.block(
+                format!(
+                    "fn deserialize_args(bytes: &mut &[u8]) -> Result<Self::Args, {}::DeserializeError>",
+                    runtime
+                ),
+                |g| {
+                    if params.is_empty() {
+                        g.w.line("let _ = bytes;");
+                    }
+
+                    g.w.line(format!("Ok({})", tuple(&args)));
+                },
+            );
+        });
+        1
+    }
+}
+
+fn serialized_size_constant(ty: &Type) -> String {
+    format!(
+        "pub const SERIALIZED_SIZE: usize = {};",
+        serialized_size(ty)
+    )
+}
+
+fn tuple(elements: &[String]) -> String {
+    match elements.len() {
+        1 => format!("({},)", elements[0]),
+        _ => format!("({})", elements.join(", ")),
+    }
+}
@@ -0,0 +1,206 @@
+//! Support code emitted once at the root of every generated file
+
+/// An error while deserializing a value
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DeserializeError {
+    /// The buffer ended before the value was complete
+    UnexpectedEnd { needed: usize, remaining: usize },
+    /// A byte other than 0x00 or 0xFF was found for a boolean
+    InvalidBoolean(u8),
+    /// The representation does not match any enum constant
+    InvalidEnumValue(i128),
+    /// The length prefix of a string exceeds its maximum size
+    StringTooLong { len: usize, max: usize },
+    /// The bytes of a string are not valid UTF-8
+    InvalidUtf8,
+}
+
+impl std::fmt::Display for DeserializeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DeserializeError::UnexpectedEnd { needed, remaining } => write!(
+                f,
+                "expected {} more bytes but only {} remain",
+                needed, remaining
+            ),
+            DeserializeError::InvalidBoolean(byte) => {
+                write!(f, "0x{:02X} is not a serialized boolean", byte)
+            }
+            DeserializeError::InvalidEnumValue(value) => {
+                write!(f, "{} is not an enum constant", value)
+            }
+            DeserializeError::StringTooLong { len, max } => write!(
+                f,
+                "string of {} bytes exceeds the maximum size of {} bytes",
+                len, max
+            ),
+            DeserializeError::InvalidUtf8 => f.write_str("string is not valid UTF-8"),
+        }
+    }
+}
+
+impl std::error::Error for DeserializeError {}
+
+/// Values with an F Prime serialized form
+pub trait Serialize: Sized {
+    /// Append the serialized form of this value to `out`
+    fn serialize(&self, out: &mut Vec<u8>);
+
+    /// Read a value from the front of `bytes`, advancing it past the value
+    fn deserialize(bytes: &mut &[u8]) -> Result<Self, DeserializeError>;
+}
+
+/// Ports with serializable arguments
+pub trait Port {
+    /// The port arguments in declaration order
+    type Args;
+
+    fn serialize_args(args: &Self::Args, out: &mut Vec<u8>);
+
+    fn deserialize_args(bytes: &mut &[u8]) -> Result<Self::Args, DeserializeError>;
+}
+
+fn take<'a>(bytes: &mut &'a [u8], n: usize) -> Result<&'a [u8], DeserializeError> {
+    if bytes.len() < n {
+        return Err(DeserializeError::UnexpectedEnd {
+            needed: n,
+            remaining: bytes.len(),
+        });
+    }
+
+    let (head, tail) = bytes.split_at(n);
+    *bytes = tail;
+    Ok(head)
+}
+
+macro_rules! serialize_primitive {
+    ($($t:ty),*) => {
+        $(
+            impl Serialize for $t {
+                fn serialize(&self, out: &mut Vec<u8>) {
+                    out.extend_from_slice(&self.to_be_bytes());
+                }
+
+                fn deserialize(bytes: &mut &[u8]) -> Result<Self, DeserializeError> {
+                    let head = take(bytes, std::mem::size_of::<$t>())?;
+                    Ok(<$t>::from_be_bytes(head.try_into().unwrap()))
+                }
+            }
+        )*
+    };
+}
+
+serialize_primitive!(u8, u16, u32, u64, i8, i16, i32, i64, f32, f64);
+
+impl Serialize for bool {
+    fn serialize(&self, out: &mut Vec<u8>) {
+        out.push(if *self { 0xFF } else { 0x00 });
+    }
+
+    fn deserialize(bytes: &mut &[u8]) -> Result<Self, DeserializeError> {
+        match take(bytes, 1)?[0] {
+            0xFF => Ok(true),
+            0x00 => Ok(false),
+            byte => Err(DeserializeError::InvalidBoolean(byte)),
+        }
+    }
+}
+
+/// Serialize a string with a 16-bit length prefix.
+/// Strings longer than `max` bytes are truncated at a character boundary.
+pub fn serialize_string(s: &str, max: usize, out: &mut Vec<u8>) {
+    let mut len = s.len().min(max);
+    while !s.is_char_boundary(len) {
+        len -= 1;
+    }
+
+    (len as u16).serialize(out);
+    out.extend_from_slice(&s.as_bytes()[..len]);
+}
+
+pub fn deserialize_string(bytes: &mut &[u8], max: usize) -> Result<String, DeserializeError> {
+    let len = u16::deserialize(bytes)? as usize;
+    if len > max {
+        return Err(DeserializeError::StringTooLong { len, max });
+    }
+
+    String::from_utf8(take(bytes, len)?.to_vec()).map_err(|_| DeserializeError::InvalidUtf8)
+}
+
+pub fn deserialize_array<T, const N: usize>(
+    bytes: &mut &[u8],
+    element: impl Fn(&mut &[u8]) -> Result<T, DeserializeError>,
+) -> Result<[T; N], DeserializeError> {
+    let mut elements = Vec::with_capacity(N);
+    for _ in 0..N {
+        elements.push(element(bytes)?);
+    }
+
+    Ok(elements.try_into().unwrap_or_else(|_| unreachable!()))
+}
+
+fn non_finite(v: f64) -> Option<String> {
+    if v.is_nan() {
+        Some("nan".to_string())
+    } else if v.is_infinite() {
+        Some(if v < 0.0 { "-inf" } else { "inf" }.to_string())
+    } else {
+        None
+    }
+}
+
+/// `%.{precision}f`
+pub fn format_fixed(v: f64, precision: usize) -> String {
+    non_finite(v).unwrap_or_else(|| format!("{:.*}", precision, v))
+}
+
+/// `%.{precision}e`
+pub fn format_exponent(v: f64, precision: usize) -> String {
+    if let Some(s) = non_finite(v) {
+        return s;
+    }
+
+    let s = format!("{:.*e}", precision, v);
+    let (mantissa, exponent) = s.split_once('e').unwrap();
+    let exponent: i32 = exponent.parse().unwrap();
+    format!(
+        "{}e{}{:02}",
+        mantissa,
+        if exponent < 0 { '-' } else { '+' },
+        exponent.abs()
+    )
+}
+
+/// `%.{precision}g`
+pub fn format_general(v: f64, precision: usize) -> String {
+    if let Some(s) = non_finite(v) {
+        return s;
+    }
+
+    let precision = precision.max(1);
+    let exponent: i32 = format!("{:.*e}", precision - 1, v)
+        .split_once('e')
+        .unwrap()
+        .1
+        .parse()
+        .unwrap();
+
+    let s = if exponent < -4 || exponent >= precision as i32 {
+        format_exponent(v, precision - 1)
+    } else {
+        format_fixed(v, (precision as i32 - 1 - exponent) as usize)
+    };
+
+    match s.split_once('e') {
+        None => trim_fraction(&s).to_string(),
+        Some((mantissa, exponent)) => format!("{}e{}", trim_fraction(mantissa), exponent),
+    }
+}
+
+fn trim_fraction(s: &str) -> &str {
+    if s.contains('.') {
+        s.trim_end_matches('0').trim_end_matches('.')
+    } else {
+        s
+    }
+}
@@ -0,0 +1,38 @@
+use crate::diff::{diff, to_json, Change};
+use crate::test::{check_ref, with_analyses};
+use pretty_assertions::assert_eq;
+
+fn changes(old: &str, new: &str) -> Vec<Change> {
+    with_analyses(&[old, new], |analyses| diff(&analyses[0], &analyses[1]))
+}
+
+#[test]
+fn changes_between_versions() {
+    let report: String = changes("diff/old.fpp", "diff/new.fpp")
+        .iter()
+        .map(|change| format!("{}\n", change))
+        .collect();
+    check_ref("diff/changes.ref.txt", &report);
+}
+
+#[test]
+fn json_report() {
+    let changes = changes("diff/old.fpp", "diff/new.fpp");
+    let report: serde_json::Value = serde_json::from_str(&to_json(&changes)).unwrap();
+
+    assert_eq!(report["breaking"], 15);
+    assert_eq!(report["compatible"], 11);
+    assert_eq!(
+        report["changes"][0],
+        serde_json::json!({
+            "compatibility": "breaking",
+            "definition": "Fw.Count",
+            "message": "type changed from U16 to U32",
+        })
+    );
+}
+
+#[test]
+fn unchanged() {
+    assert!(changes("diff/old.fpp", "diff/old.fpp").is_empty());
+}
@@ -0,0 +1,31 @@
+use crate::doc::DocFormat;
+use crate::test::{check_ref, with_analysis};
+use crate::Page;
+
+fn generate(format: DocFormat) -> Vec<Page> {
+    with_analysis("doc/model.fpp", |a, ast| {
+        crate::doc::generate(a, &[ast], format)
+    })
+}
+
+#[test]
+fn markdown() {
+    let generated: String = generate(DocFormat::Markdown)
+        .iter()
+        .map(|page| format!("==> {} <==\n{}", page.path, page.content))
+        .collect();
+    check_ref("doc/model.ref.txt", &generated);
+}
+
+#[test]
+fn html() {
+    let pages = generate(DocFormat::Html);
+    assert!(pages.iter().all(|page| page.path.ends_with(".html")));
+
+    let logger = pages
+        .iter()
+        .find(|page| page.path == "Svc.Logger.html")
+        .expect("no page for Svc.Logger");
+    assert!(logger.content.contains("<table>"));
+    assert!(logger.content.contains("href=\"Fw.html#Send\""));
+}
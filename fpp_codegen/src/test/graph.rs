@@ -0,0 +1,54 @@
+use crate::graph::GraphFormat;
+use crate::test::{check_ref, with_analysis};
+use pretty_assertions::assert_eq;
+
+fn generate(format: GraphFormat, connections: &[String]) -> String {
+    with_analysis("graph/model.fpp", |a, ast| {
+        crate::graph::generate(a, &[ast], format, connections)
+    })
+    .iter()
+    .map(|page| format!("==> {} <==\n{}", page.path, page.content))
+    .collect()
+}
+
+#[test]
+fn dot() {
+    check_ref("graph/model.dot.ref.txt", &generate(GraphFormat::Dot, &[]));
+}
+
+#[test]
+fn mermaid() {
+    check_ref(
+        "graph/model.mmd.ref.txt",
+        &generate(GraphFormat::Mermaid, &[]),
+    );
+}
+
+/// Only the instances taking part in the selected connection graph are kept
+#[test]
+fn connections() {
+    let pages = with_analysis("graph/model.fpp", |a, ast| {
+        crate::graph::generate(a, &[ast], GraphFormat::Json, &["Ping".to_string()])
+    });
+    let deployment = pages
+        .iter()
+        .find(|page| page.path == "Ref.Deployment.json")
+        .expect("no graph for Ref.Deployment");
+    let graph: serde_json::Value = serde_json::from_str(&deployment.content).unwrap();
+
+    let nodes: Vec<&str> = graph["nodes"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|node| node["id"].as_str().unwrap())
+        .collect();
+    assert_eq!(nodes, ["Ref.right", "Ref.left"]);
+
+    let edges: Vec<&str> = graph["edges"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|edge| edge["graph"].as_str().unwrap())
+        .collect();
+    assert_eq!(edges, ["Ping", "Ping"]);
+}
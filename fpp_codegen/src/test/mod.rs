@@ -0,0 +1,127 @@
+use fpp_analysis::semantics::{SymbolInterface, Type, Value};
+use fpp_analysis::Analysis;
+use fpp_ast::TransUnit;
+use fpp_core::SourceFile;
+use pretty_assertions::assert_eq;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::{env, fs};
+
+mod diff;
+mod doc;
+mod graph;
+mod python;
+mod rust;
+mod xml;
+
+/// The path of a file in the test directory
+pub(crate) fn test_path(file_path: &str) -> PathBuf {
+    let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    path.push("src/test");
+    path.push(file_path);
+    path
+}
+
+/// Analyze a test input, which must not report any diagnostics, and pass
+/// the analysis and AST to `f`
+pub(crate) fn with_analysis<R, F: FnOnce(&Analysis, &TransUnit) -> R>(file_path: &str, f: F) -> R {
+    let path = test_path(file_path);
+    let src = fs::read_to_string(&path)
+        .unwrap_or_else(|err| panic!("failed to open {}: {}", path.display(), err));
+
+    let mut diagnostics = vec![];
+    let mut ctx = fpp_core::CompilerContext::new(fpp_errors::WriteEmitter::new(&mut diagnostics));
+
+    let out = fpp_core::run(&mut ctx, || {
+        let src = SourceFile::new(file_path, src);
+        let ast = fpp_parser::parse(src, |p| p.trans_unit(), None);
+        let mut a = Analysis::new();
+        let _ = fpp_analysis::check_semantics(&mut a, vec![&ast]);
+        f(&a, &ast)
+    });
+
+    assert!(
+        diagnostics.is_empty(),
+        "{}",
+        String::from_utf8_lossy(&diagnostics)
+    );
+
+    out
+}
+
+/// Analyze each test input on its own, none of which may report diagnostics,
+/// and pass the analyses to `f`
+pub(crate) fn with_analyses<R, F: FnOnce(&[Analysis]) -> R>(file_paths: &[&str], f: F) -> R {
+    let sources: Vec<(&str, String)> = file_paths
+        .iter()
+        .map(|file_path| {
+            let path = test_path(file_path);
+            let src = fs::read_to_string(&path)
+                .unwrap_or_else(|err| panic!("failed to open {}: {}", path.display(), err));
+            (*file_path, src)
+        })
+        .collect();
+
+    let mut diagnostics = vec![];
+    let mut ctx = fpp_core::CompilerContext::new(fpp_errors::WriteEmitter::new(&mut diagnostics));
+
+    let out = fpp_core::run(&mut ctx, || {
+        let analyses: Vec<Analysis> = sources
+            .into_iter()
+            .map(|(file_path, src)| {
+                let src = SourceFile::new(file_path, src);
+                let ast = fpp_parser::parse(src, |p| p.trans_unit(), None);
+                let mut a = Analysis::new();
+                let _ = fpp_analysis::check_semantics(&mut a, vec![&ast]);
+                a
+            })
+            .collect();
+        f(&analyses)
+    });
+
+    assert!(
+        diagnostics.is_empty(),
+        "{}",
+        String::from_utf8_lossy(&diagnostics)
+    );
+
+    out
+}
+
+/// Compare generated output against the reference file,
+/// or update the reference file when `FPP_UPDATE_REF` is set
+pub(crate) fn check_ref(file_path: &str, output: &str) {
+    let ref_file = test_path(file_path);
+    match env::var("FPP_UPDATE_REF") {
+        Ok(_) => fs::write(ref_file, output).expect("failed to write ref.txt"),
+        Err(_) => {
+            let ref_txt = fs::read_to_string(ref_file).expect("failed to read ref.txt");
+            assert_eq!(ref_txt, output)
+        }
+    }
+}
+
+/// The type of the definition with the qualified name `name`
+pub(crate) fn lookup(a: &Analysis, name: &str) -> Arc<Type> {
+    let symbol = a
+        .symbol_map
+        .values()
+        .find(|symbol| a.qualified_name(symbol) == name)
+        .unwrap_or_else(|| panic!("no definition named {}", name));
+    a.type_map.get(&symbol.node()).unwrap().clone()
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// The hex encoding of `values` serialized one after another
+pub(crate) fn encode(values: &[(Value, Arc<Type>)]) -> String {
+    let mut out = vec![];
+    for (value, ty) in values {
+        value
+            .encode(ty, &mut out)
+            .unwrap_or_else(|err| panic!("failed to encode {}: {}", ty, err));
+    }
+    hex(&out)
+}
@@ -0,0 +1,113 @@
+use crate::test::{check_ref, encode, lookup, with_analysis};
+use pretty_assertions::assert_eq;
+use std::fmt::Write;
+use std::process::Command;
+use std::{env, fs};
+
+/// Types whose defaults are checked against the analysis
+const TYPES: &[&str] = &[
+    "Fw.Mode",
+    "Fw.Volts",
+    "Fw.Names",
+    "Fw.Point",
+    "Fw.Points",
+    "Fw.Flag",
+    "Svc.Logger.Level",
+    "Svc.Logger.Entry",
+];
+
+/// Identifiers of the reference deployment, checked by hand
+const IDS: &str = "\
+Ref.logger 0x100
+CLEAR 0x110
+DUMP 0x111
+THRESHOLD_PRM_SET 0x112
+THRESHOLD_PRM_SAVE 0x113
+MODE_PRM_SET 0x140
+MODE_PRM_SAVE 0x142
+Cleared 0x120
+Dumped 0x121
+Count 0x100
+LastLevel 0x107
+Errors 0x108
+Threshold 0x105
+Mode 0x106
+Ref.clock 0x200
+Time 0x200
+";
+
+#[test]
+fn types() {
+    let generated = with_analysis("python/types.fpp", |a, ast| {
+        crate::python::generate(a, &[ast])
+    });
+    check_ref("python/types.ref.txt", &generated);
+}
+
+/// Run the generated module and check that it packs default values the same
+/// way as the analysis and lists the expected dictionary identifiers
+#[test]
+fn run() {
+    let (generated, mut expected) = with_analysis("python/types.fpp", |a, ast| {
+        let mut expected = String::new();
+        for name in TYPES {
+            let ty = lookup(a, name);
+            let default = ty.default_value().unwrap();
+            writeln!(expected, "{} {}", name, encode(&[(default, ty)])).unwrap();
+        }
+
+        (crate::python::generate(a, &[ast]), expected)
+    });
+    expected.push_str(IDS);
+
+    let mut script = String::from(
+        "import generated
+
+def check(name):
+    cls = generated
+    for part in name.split(\".\"):
+        cls = getattr(cls, part)
+    value = cls.default() if hasattr(cls, \"default\") else cls()
+    data = value.pack()
+    assert cls.unpack(data) == value
+    print(name, data.hex())
+
+",
+    );
+
+    for name in TYPES {
+        writeln!(script, "check({:?})", name).unwrap();
+    }
+
+    script.push_str(
+        "
+for instance in [generated.Ref.Deployment.logger, generated.Ref.Deployment.clock]:
+    print(instance.NAME, hex(instance.BASE_ID))
+    for kind in [\"Commands\", \"Events\", \"Channels\", \"Params\"]:
+        ids = vars(getattr(instance, kind, object))
+        for name, id in ids.items():
+            if not name.startswith(\"_\"):
+                print(name, hex(id))
+",
+    );
+
+    let dir = env::temp_dir().join(format!("fpp_codegen_python_{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("generated.py"), &generated).unwrap();
+    fs::write(dir.join("main.py"), &script).unwrap();
+
+    let python = env::var("PYTHON").unwrap_or_else(|_| "python3".to_string());
+    let run = Command::new(python)
+        .arg("-B")
+        .arg(dir.join("main.py"))
+        .output()
+        .expect("failed to run python");
+
+    fs::remove_dir_all(&dir).unwrap();
+    assert!(
+        run.status.success(),
+        "{}",
+        String::from_utf8_lossy(&run.stderr)
+    );
+    assert_eq!(String::from_utf8_lossy(&run.stdout), expected);
+}
@@ -0,0 +1,142 @@
+use crate::test::{check_ref, encode, lookup, with_analysis};
+use fpp_analysis::semantics::{IntegerValue, StringValue, Type, Value};
+use pretty_assertions::assert_eq;
+use std::fmt::Write;
+use std::process::Command;
+use std::sync::Arc;
+use std::{env, fs};
+
+/// Types whose defaults are checked against the analysis
+const TYPES: &[&str] = &[
+    "Fw.Mode",
+    "Fw.Volts",
+    "Fw.Names",
+    "Fw.Point",
+    "Fw.Points",
+    "Svc.Logger.Level",
+    "Svc.Logger.Entry",
+    "Svc.Empty",
+];
+
+#[test]
+fn types() {
+    let generated = with_analysis("rust/types.fpp", |a, ast| crate::rust::generate(a, &[ast]));
+    check_ref("rust/types.ref.txt", &generated);
+}
+
+/// Compile the generated code and check that it serializes and displays
+/// default values the same way as the analysis
+#[test]
+fn compiled() {
+    let (generated, expected) = with_analysis("rust/types.fpp", |a, ast| {
+        let mut expected = String::new();
+        for name in TYPES {
+            let ty = lookup(a, name);
+            let default = ty.default_value().unwrap();
+            writeln!(
+                expected,
+                "{} {} {}",
+                name,
+                encode(&[(default.clone(), ty.clone())]),
+                default.render(&ty)
+            )
+            .unwrap();
+        }
+
+        let point = lookup(a, "Fw.Point");
+        writeln!(
+            expected,
+            "Fw.Send {}",
+            encode(&[
+                (
+                    Value::Integer(IntegerValue(5)),
+                    Arc::new(Type::PrimitiveInt(fpp_ast::IntegerKind::U32))
+                ),
+                (point.default_value().unwrap(), point),
+                (
+                    Value::String(StringValue("hi".to_string())),
+                    Arc::new(Type::String(None))
+                ),
+            ])
+        )
+        .unwrap();
+
+        (crate::rust::generate(a, &[ast]), expected)
+    });
+
+    let mut main = String::from(
+        "#![allow(dead_code)]
+mod generated {
+    include!(\"generated.rs\");
+}
+
+use generated::fpp_runtime::{Port, Serialize};
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!(\"{:02x}\", b)).collect()
+}
+
+fn check<T: Serialize + Default + PartialEq + std::fmt::Debug + std::fmt::Display>(name: &str) {
+    let value = T::default();
+    let mut out = vec![];
+    value.serialize(&mut out);
+    let mut bytes = out.as_slice();
+    assert_eq!(T::deserialize(&mut bytes).unwrap(), value);
+    assert!(bytes.is_empty());
+    println!(\"{} {} {}\", name, hex(&out), value);
+}
+
+fn main() {
+",
+    );
+
+    for name in TYPES {
+        writeln!(
+            main,
+            "    check::<generated::{}>({:?});",
+            name.replace('.', "::"),
+            name
+        )
+        .unwrap();
+    }
+
+    main.push_str(
+        "    let args = (5, generated::Fw::Point::default(), \"hi\".to_string());
+    let mut out = vec![];
+    generated::Fw::Send::serialize_args(&args, &mut out);
+    assert_eq!(generated::Fw::Send::deserialize_args(&mut out.as_slice()).unwrap(), args);
+    println!(\"Fw.Send {}\", hex(&out));
+}
+",
+    );
+
+    let dir = env::temp_dir().join(format!("fpp_codegen_rust_{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("generated.rs"), &generated).unwrap();
+    fs::write(dir.join("main.rs"), &main).unwrap();
+
+    let rustc = env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string());
+    let build = Command::new(rustc)
+        .args(["--edition", "2021", "-D", "warnings", "-o"])
+        .arg(dir.join("main"))
+        .arg(dir.join("main.rs"))
+        .output()
+        .expect("failed to run rustc");
+    assert!(
+        build.status.success(),
+        "generated code does not compile:\n{}",
+        String::from_utf8_lossy(&build.stderr)
+    );
+
+    let run = Command::new(dir.join("main"))
+        .output()
+        .expect("failed to run the generated code");
+    assert!(
+        run.status.success(),
+        "{}",
+        String::from_utf8_lossy(&run.stderr)
+    );
+
+    fs::remove_dir_all(&dir).unwrap();
+    assert_eq!(String::from_utf8_lossy(&run.stdout), expected);
+}
@@ -0,0 +1,139 @@
+use crate::test::{check_ref, test_path};
+use crate::xml::{format_string, fpp_file_name, from_xml};
+use fpp_core::SourceFile;
+use pretty_assertions::assert_eq;
+use std::fs;
+use std::path::Path;
+
+const FILES: &[&str] = &[
+    "SignalGenComponentAi.xml",
+    "SignalInfoPortAi.xml",
+    "SignalPairSerializableAi.xml",
+    "SignalSetArrayAi.xml",
+    "SignalTypeEnumAi.xml",
+];
+
+/// Translate XML test inputs in a fresh compiler context, returning the FPP
+/// for each and the diagnostics reported along the way
+fn translate(files: &[(&str, String)]) -> (Vec<Option<String>>, String) {
+    let mut diagnostics = vec![];
+    let mut ctx = fpp_core::CompilerContext::new(fpp_errors::WriteEmitter::new(&mut diagnostics));
+    let out = fpp_core::run(&mut ctx, || {
+        files
+            .iter()
+            .map(|(path, xml)| from_xml(SourceFile::new(path, xml.clone())))
+            .collect()
+    });
+
+    (out, String::from_utf8_lossy(&diagnostics).to_string())
+}
+
+fn read(file: &str) -> (&str, String) {
+    (
+        file,
+        fs::read_to_string(test_path(&format!("xml/{}", file))).unwrap(),
+    )
+}
+
+#[test]
+fn translate_definitions() {
+    let files: Vec<_> = FILES.iter().map(|file| read(file)).collect();
+    let (out, diagnostics) = translate(&files);
+    assert_eq!(diagnostics, "");
+
+    for (file, fpp) in FILES.iter().zip(out) {
+        let name = fpp_file_name(Path::new(file));
+        check_ref(&format!("xml/{}.ref.txt", name), &fpp.unwrap());
+    }
+}
+
+#[test]
+fn translation_checks() {
+    let files: Vec<_> = FILES.iter().map(|file| read(file)).collect();
+    let (out, _) = translate(&files);
+
+    let mut diagnostics = vec![];
+    let mut ctx = fpp_core::CompilerContext::new(fpp_errors::WriteEmitter::new(&mut diagnostics));
+    fpp_core::run(&mut ctx, || {
+        let mut sources = vec![SourceFile::new("Fw.fpp", read("Fw.fpp").1)];
+        for (file, fpp) in FILES.iter().zip(out) {
+            let name = fpp_file_name(Path::new(file));
+            sources.push(SourceFile::new(&name, fpp.unwrap()));
+        }
+
+        let asts: Vec<_> = sources
+            .into_iter()
+            .map(|src| fpp_parser::parse(src, |p| p.trans_unit(), None))
+            .collect();
+        let mut a = fpp_analysis::Analysis::new();
+        let _ = fpp_analysis::check_semantics(&mut a, asts.iter().collect());
+    });
+
+    assert_eq!(String::from_utf8_lossy(&diagnostics), "");
+}
+
+#[test]
+fn unsupported_constructs() {
+    let xml = r#"<component name="Legacy" kind="passive">
+    <import_dictionary>Legacy/Commands.xml</import_dictionary>
+    <ports>
+        <port name="ptrIn" data_type="void*" kind="sync_input"/>
+    </ports>
+    <events>
+        <event id="0" name="Pointer" severity="DIAGNOSTIC" format_string="At %p"/>
+    </events>
+</component>"#;
+
+    let (out, diagnostics) = translate(&[("LegacyComponentAi.xml", xml.to_string())]);
+    assert!(diagnostics.contains("dictionary `Legacy/Commands.xml` was not translated"));
+    assert!(diagnostics.contains("unsupported port type `void*`"));
+    assert!(diagnostics.contains("format string `At %p` has no FPP equivalent"));
+    assert_eq!(
+        out[0].as_deref(),
+        Some(
+            "passive component Legacy {\n  \
+               event Pointer severity diagnostic id 0 format \"At %p\"\n\
+             }\n"
+        )
+    );
+}
+
+#[test]
+fn not_fprime_xml() {
+    let (out, diagnostics) = translate(&[
+        ("Commands.xml", "<commands></commands>".to_string()),
+        ("Broken.xml", "<enum name=\"E\">".to_string()),
+    ]);
+
+    assert_eq!(out, vec![None, None]);
+    assert!(diagnostics.contains("`commands` is not an F Prime XML definition"));
+    assert!(diagnostics.contains("invalid XML"));
+}
+
+#[test]
+fn format_strings() {
+    assert_eq!(format_string("%u of %d").as_deref(), Some("{} of {}"));
+    assert_eq!(format_string("%08X %o").as_deref(), Some("{x} {o}"));
+    assert_eq!(
+        format_string("%.2f %e %5.1g").as_deref(),
+        Some("{.2f} {e} {.1g}")
+    );
+    assert_eq!(format_string("%lu %hhd %llx").as_deref(), Some("{} {} {x}"));
+    assert_eq!(format_string("100%% {x}").as_deref(), Some("100% {{x}}"));
+    assert_eq!(format_string("%p"), None);
+    assert_eq!(format_string("trailing %"), None);
+}
+
+#[test]
+fn file_names() {
+    assert_eq!(
+        fpp_file_name(Path::new("Ref/SignalGenComponentAi.xml")),
+        "SignalGen.fpp"
+    );
+    assert_eq!(
+        fpp_file_name(Path::new("ChoicePairSerializableAi.xml")),
+        "ChoicePair.fpp"
+    );
+    assert_eq!(fpp_file_name(Path::new("PortAi.xml")), "Port.fpp");
+    assert_eq!(fpp_file_name(Path::new("Other.xml")), "Other.fpp");
+}
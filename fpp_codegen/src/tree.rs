@@ -0,0 +1,78 @@
+use fpp_analysis::passes::doc_annotation;
+use fpp_ast::{
+    ComponentMember, DefAliasType, DefArray, DefConstant, DefEnum, DefPort, DefStruct, DefTopology,
+    ModuleMember, Name,
+};
+use fpp_core::Annotated;
+
+/// A definition that generators may emit an item for
+pub(crate) enum Def<'ast> {
+    AliasType(&'ast DefAliasType),
+    Array(&'ast DefArray),
+    Constant(&'ast DefConstant),
+    Enum(&'ast DefEnum),
+    Port(&'ast DefPort),
+    Struct(&'ast DefStruct),
+    Topology(&'ast DefTopology),
+}
+
+/// The definitions in an FPP module or component.
+/// Definitions of the same module spread over several `module` blocks are merged
+/// since generated namespaces cannot be reopened.
+#[derive(Default)]
+pub(crate) struct ModuleTree<'ast> {
+    pub annotation: Vec<String>,
+    pub defs: Vec<Def<'ast>>,
+    pub modules: Vec<(String, ModuleTree<'ast>)>,
+}
+
+impl<'ast> ModuleTree<'ast> {
+    fn child<N: Annotated>(&mut self, node: &N, name: &Name) -> &mut ModuleTree<'ast> {
+        let index = match self.modules.iter().position(|(n, _)| *n == name.data) {
+            Some(index) => index,
+            None => {
+                self.modules
+                    .push((name.data.clone(), ModuleTree::default()));
+                self.modules.len() - 1
+            }
+        };
+
+        let child = &mut self.modules[index].1;
+        child.annotation.extend(doc_annotation(node));
+        child
+    }
+
+    pub fn module_members(&mut self, members: &'ast [ModuleMember]) {
+        for member in members {
+            match member {
+                ModuleMember::DefAliasType(def) => self.defs.push(Def::AliasType(def)),
+                ModuleMember::DefArray(def) => self.defs.push(Def::Array(def)),
+                ModuleMember::DefComponent(def) => {
+                    self.child(def, &def.name).component_members(&def.members)
+                }
+                ModuleMember::DefConstant(def) => self.defs.push(Def::Constant(def)),
+                ModuleMember::DefEnum(def) => self.defs.push(Def::Enum(def)),
+                ModuleMember::DefModule(def) => {
+                    self.child(def, &def.name).module_members(&def.members)
+                }
+                ModuleMember::DefPort(def) => self.defs.push(Def::Port(def)),
+                ModuleMember::DefStruct(def) => self.defs.push(Def::Struct(def)),
+                ModuleMember::DefTopology(def) => self.defs.push(Def::Topology(def)),
+                _ => {}
+            }
+        }
+    }
+
+    fn component_members(&mut self, members: &'ast [ComponentMember]) {
+        for member in members {
+            match member {
+                ComponentMember::DefAliasType(def) => self.defs.push(Def::AliasType(def)),
+                ComponentMember::DefArray(def) => self.defs.push(Def::Array(def)),
+                ComponentMember::DefConstant(def) => self.defs.push(Def::Constant(def)),
+                ComponentMember::DefEnum(def) => self.defs.push(Def::Enum(def)),
+                ComponentMember::DefStruct(def) => self.defs.push(Def::Struct(def)),
+                _ => {}
+            }
+        }
+    }
+}
@@ -0,0 +1,124 @@
+use fpp_analysis::semantics::{
+    AnonArrayType, AnonArrayValue, ArrayValue, IntegerValue, PrimitiveIntegerValue, StructType,
+    StructValue, Type, Value,
+};
+use fpp_analysis::Analysis;
+use std::sync::Arc;
+
+/// Check if values of `ty` can be represented and serialized by generated code
+pub(crate) fn is_supported(ty: &Type) -> bool {
+    match ty {
+        Type::PrimitiveInt(_) | Type::Float(_) | Type::String(_) | Type::Boolean => true,
+        Type::Integer | Type::AbsType(_) | Type::AnonStruct(_) => false,
+        Type::AliasType(ty) => is_supported(&ty.alias_type),
+        Type::Array(ty) => is_supported(&ty.anon_array.elt_type),
+        Type::AnonArray(ty) => ty.size.is_some() && is_supported(&ty.elt_type),
+        Type::Enum(ty) => !ty.constants.is_empty(),
+        Type::Struct(ty) => ty
+            .anon_struct
+            .members
+            .values()
+            .all(|member| is_supported(member)),
+    }
+}
+
+/// The members of a struct in order, members with a size are arrays
+pub(crate) fn struct_members(struct_ty: &StructType) -> Vec<(String, Arc<Type>)> {
+    struct_ty
+        .anon_struct
+        .members
+        .iter()
+        .map(|(name, member_ty)| {
+            let member_ty = match struct_ty.sizes.get(name) {
+                None => member_ty.clone(),
+                Some(size) => Arc::new(Type::AnonArray(AnonArrayType {
+                    size: Some(*size as usize),
+                    elt_type: member_ty.clone(),
+                })),
+            };
+
+            (name.clone(), member_ty)
+        })
+        .collect()
+}
+
+pub(crate) fn string_size(size: &Option<i128>) -> usize {
+    size.map_or(fpp_analysis::semantics::DEFAULT_STRING_SIZE, |size| {
+        size as usize
+    })
+}
+
+pub(crate) fn integer_value(value: &Value) -> Option<i128> {
+    match value {
+        Value::PrimitiveInteger(PrimitiveIntegerValue { value, .. })
+        | Value::Integer(IntegerValue(value)) => Some(*value),
+        _ => None,
+    }
+}
+
+pub(crate) fn serialized_size(ty: &Type) -> usize {
+    ty.serialized_size().map_or(0, |size| size.max)
+}
+
+/// A value written in FPP syntax
+pub(crate) fn value_syntax(a: &Analysis, value: &Value) -> String {
+    match value {
+        Value::PrimitiveInteger(_) | Value::Integer(_) => {
+            integer_value(value).unwrap_or_default().to_string()
+        }
+        Value::Float(value) => value.value.to_string(),
+        Value::Boolean(value) => value.0.to_string(),
+        Value::String(value) => {
+            format!("\"{}\"", value.0.replace('\\', "\\\\").replace('"', "\\\""))
+        }
+        Value::EnumConstant(value) => match a.symbol_map.get(&value.ty().node.node_id) {
+            Some(symbol) => format!("{}.{}", a.qualified_name(symbol), value.value.0),
+            None => value.value.0.clone(),
+        },
+        Value::Array(ArrayValue {
+            anon_array: AnonArrayValue { elements },
+            ..
+        })
+        | Value::AnonArray(AnonArrayValue { elements }) => {
+            let elements: Vec<String> = elements.iter().map(|e| value_syntax(a, e)).collect();
+            format!("[ {} ]", elements.join(", "))
+        }
+        Value::Struct(StructValue { anon_struct, .. }) | Value::AnonStruct(anon_struct) => {
+            let members: Vec<String> = anon_struct
+                .members
+                .iter()
+                .map(|(name, member)| format!("{} = {}", name, value_syntax(a, member)))
+                .collect();
+            format!("{{ {} }}", members.join(", "))
+        }
+        Value::AbsType(_) => "default".to_string(),
+    }
+}
+
+/// The default value of a struct, members with a size hold one value per element
+pub(crate) fn struct_value_syntax(a: &Analysis, value: &StructValue, ty: &StructType) -> String {
+    let members: Vec<String> = value
+        .anon_struct
+        .members
+        .iter()
+        .map(|(name, member)| {
+            let member = match (ty.sizes.get(name), member) {
+                (
+                    Some(size),
+                    Value::PrimitiveInteger(_)
+                    | Value::Integer(_)
+                    | Value::Float(_)
+                    | Value::Boolean(_)
+                    | Value::String(_)
+                    | Value::EnumConstant(_),
+                ) => {
+                    let elements = vec![value_syntax(a, member); *size as usize];
+                    format!("[ {} ]", elements.join(", "))
+                }
+                _ => value_syntax(a, member),
+            };
+            format!("{} = {}", name, member)
+        })
+        .collect();
+    format!("{{ {} }}", members.join(", "))
+}
@@ -0,0 +1,70 @@
+/// Accumulates lines of generated source at the current indentation
+pub(crate) struct CodeWriter {
+    out: String,
+    indent: usize,
+    indent_unit: &'static str,
+}
+
+impl CodeWriter {
+    pub fn new(indent_unit: &'static str) -> CodeWriter {
+        CodeWriter {
+            out: String::new(),
+            indent: 0,
+            indent_unit,
+        }
+    }
+
+    /// Write a single line, blank lines are not indented
+    pub fn line<S: AsRef<str>>(&mut self, line: S) {
+        let line = line.as_ref();
+        if !line.is_empty() {
+            for _ in 0..self.indent {
+                self.out.push_str(self.indent_unit);
+            }
+
+            self.out.push_str(line);
+        }
+
+        self.out.push('\n');
+    }
+
+    /// Write a blank line separating items, unless it would follow
+    /// another blank line or open a block
+    pub fn blank(&mut self) {
+        if !(self.out.is_empty()
+            || self.out.ends_with("\n\n")
+            || self.out.ends_with("{\n")
+            || self.out.ends_with(":\n"))
+        {
+            self.out.push('\n');
+        }
+    }
+
+    /// Write each line of `text` at the current indentation
+    pub fn lines(&mut self, text: &str) {
+        for line in text.lines() {
+            self.line(line);
+        }
+    }
+
+    pub fn indent(&mut self) {
+        self.indent += 1;
+    }
+
+    pub fn dedent(&mut self) {
+        self.indent -= 1;
+    }
+
+    /// The length of the output so far, used to roll back with [CodeWriter::truncate]
+    pub fn len(&self) -> usize {
+        self.out.len()
+    }
+
+    pub fn truncate(&mut self, len: usize) {
+        self.out.truncate(len)
+    }
+
+    pub fn finish(self) -> String {
+        self.out
+    }
+}
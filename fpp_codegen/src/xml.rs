@@ -0,0 +1,1002 @@
+//! Translation of the F Prime XML that predates FPP (`*Ai.xml` files) into FPP
+//!
+//! Each XML file is translated into `fpp_ast` definitions which are printed as
+//! FPP source. Comments in the XML become annotations. Constructs without an
+//! FPP equivalent are reported as warnings and left out of the translation.
+
+use fpp_ast::*;
+use fpp_core::{BytePos, SourceFile, Span};
+use roxmltree::Node as XmlNode;
+use std::path::Path;
+
+/// Translate an XML component, port, serializable, enum or array definition
+/// into FPP definitions. Returns `None` if the file is not F Prime XML.
+pub fn import(src: SourceFile) -> Option<TransUnit> {
+    let text = src.read().as_ref().to_string();
+    let doc = match roxmltree::Document::parse(&text) {
+        Ok(doc) => doc,
+        Err(err) => {
+            let pos = err.pos();
+            let offset = text
+                .split_inclusive('\n')
+                .take(pos.row as usize - 1)
+                .map(|line| line.len())
+                .sum::<usize>()
+                + pos.col as usize
+                - 1;
+            Span::new(src, offset.min(text.len()) as BytePos, 0, None)
+                .error(format!("invalid XML: {}", err))
+                .emit();
+            return None;
+        }
+    };
+
+    Importer { file: src }.root(doc.root_element())
+}
+
+/// Translate an XML definition into FPP source, see [import]
+pub fn from_xml(src: SourceFile) -> Option<String> {
+    import(src).map(|ast| unparse::trans_unit(&ast))
+}
+
+/// The name of the FPP file translated from an XML file,
+/// `SignalGenComponentAi.xml` becomes `SignalGen.fpp`
+pub fn fpp_file_name(xml_file: &Path) -> String {
+    let stem = xml_file
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let mut stem = stem.strip_suffix("Ai").unwrap_or(&stem);
+    for kind in ["Component", "Port", "Serializable", "Enum", "Array"] {
+        match stem.strip_suffix(kind) {
+            Some(name) if !name.is_empty() => {
+                stem = name;
+                break;
+            }
+            _ => {}
+        }
+    }
+
+    format!("{}.fpp", stem)
+}
+
+/// Translate a printf style format string into an FPP format string,
+/// `None` if it uses a conversion FPP has no replacement field for
+pub(crate) fn format_string(format: &str) -> Option<String> {
+    let mut out = String::new();
+    let mut chars = format.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' => out.push_str("{{"),
+            '}' => out.push_str("}}"),
+            '%' if chars.peek() == Some(&'%') => {
+                chars.next();
+                out.push('%');
+            }
+            '%' => {
+                // Flags and field width have no FPP equivalent
+                while chars
+                    .peek()
+                    .is_some_and(|c| matches!(c, '-' | '+' | ' ' | '#') || c.is_ascii_digit())
+                {
+                    chars.next();
+                }
+
+                let mut precision = String::new();
+                if chars.peek() == Some(&'.') {
+                    chars.next();
+                    while let Some(digit) = chars.next_if(char::is_ascii_digit) {
+                        precision.push(digit);
+                    }
+                }
+
+                while chars
+                    .next_if(|c| matches!(c, 'h' | 'l' | 'L' | 'q' | 'j' | 'z' | 't'))
+                    .is_some()
+                {}
+
+                let rational = |kind: char| {
+                    if precision.is_empty() {
+                        format!("{{{}}}", kind)
+                    } else {
+                        format!("{{.{}{}}}", precision, kind)
+                    }
+                };
+
+                match chars.next()? {
+                    'd' | 'i' | 'u' | 'c' | 's' => out.push_str("{}"),
+                    'x' | 'X' => out.push_str("{x}"),
+                    'o' => out.push_str("{o}"),
+                    'e' | 'E' => out.push_str(&rational('e')),
+                    'f' | 'F' => out.push_str(&rational('f')),
+                    'g' | 'G' => out.push_str(&rational('g')),
+                    _ => return None,
+                }
+            }
+            c => out.push(c),
+        }
+    }
+
+    Some(out)
+}
+
+/// Escape the double quotes of XML text used in an FPP string literal
+fn escape(text: &str) -> String {
+    let mut out = String::new();
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                out.push(c);
+                out.extend(chars.next());
+            }
+            '"' => out.push_str("\\\""),
+            c => out.push(c),
+        }
+    }
+
+    out
+}
+
+/// The lines of an XML comment as annotation lines
+fn annotation_lines(text: &str) -> Vec<String> {
+    let lines: Vec<&str> = text.lines().map(str::trim).collect();
+    let start = lines.iter().position(|line| !line.is_empty());
+    let end = lines.iter().rposition(|line| !line.is_empty());
+    match (start, end) {
+        (Some(start), Some(end)) => lines[start..=end].iter().map(|l| l.to_string()).collect(),
+        _ => vec![],
+    }
+}
+
+fn is_identifier(text: &str) -> bool {
+    let mut chars = text.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Child elements of `xml` with the tag `name`
+fn elements<'a, 'input>(
+    xml: XmlNode<'a, 'input>,
+    name: &'static str,
+) -> impl Iterator<Item = XmlNode<'a, 'input>> {
+    xml.children()
+        .filter(move |child| child.is_element() && child.tag_name().name() == name)
+}
+
+fn element<'a, 'input>(
+    xml: XmlNode<'a, 'input>,
+    name: &'static str,
+) -> Option<XmlNode<'a, 'input>> {
+    elements(xml, name).next()
+}
+
+/// The text inside an element
+fn text(xml: XmlNode) -> String {
+    xml.descendants()
+        .filter(|node| node.is_text())
+        .filter_map(|node| node.text())
+        .collect()
+}
+
+struct Importer {
+    file: SourceFile,
+}
+
+impl Importer {
+    fn span(&self, start: usize, len: usize) -> Span {
+        Span::new(self.file, start as BytePos, len as BytePos, None)
+    }
+
+    /// The span of the opening `<tag` of an element
+    fn tag_span(&self, xml: XmlNode) -> Span {
+        self.span(xml.range().start, xml.tag_name().name().len() + 1)
+    }
+
+    /// The span of the value of an attribute, or the element when it is missing
+    fn attr_span(&self, xml: XmlNode, attr: &str) -> Span {
+        match xml.attribute_node(attr) {
+            Some(attr) => {
+                let range = attr.range_value();
+                self.span(range.start, range.len())
+            }
+            None => self.tag_span(xml),
+        }
+    }
+
+    /// The span of the text inside an element
+    fn text_span(&self, xml: XmlNode) -> Span {
+        match xml.first_child().filter(|child| child.is_text()) {
+            Some(text) => self.span(text.range().start, text.range().len()),
+            None => self.tag_span(xml),
+        }
+    }
+
+    /// A new AST node for an element, annotated with its comment
+    fn node(&self, xml: XmlNode) -> fpp_core::Node {
+        let node = fpp_core::Node::new(self.tag_span(xml));
+        let comment = match (element(xml, "comment"), xml.attribute("comment")) {
+            (Some(comment), _) => text(comment),
+            (None, Some(comment)) => comment.to_string(),
+            (None, None) => String::new(),
+        };
+        fpp_core::Node::annotate(&node, annotation_lines(&comment), vec![]);
+        node
+    }
+
+    fn required<'a>(&self, xml: XmlNode<'a, '_>, attr: &str) -> Option<&'a str> {
+        let value = xml.attribute(attr);
+        if value.is_none() {
+            self.tag_span(xml)
+                .error(format!(
+                    "`{}` is missing the `{}` attribute",
+                    xml.tag_name().name(),
+                    attr
+                ))
+                .emit();
+        }
+
+        value
+    }
+
+    fn unsupported(&self, span: Span, what: &str, value: &str) {
+        span.error(format!("unsupported {} `{}`", what, value))
+            .emit();
+    }
+
+    fn name(&self, xml: XmlNode, attr: &str) -> Option<Name> {
+        let name = self.required(xml, attr)?;
+        let span = self.attr_span(xml, attr);
+        if !is_identifier(name) {
+            span.error(format!("`{}` is not a valid FPP name", name))
+                .emit();
+            return None;
+        }
+
+        Some(Name {
+            node_id: fpp_core::Node::new(span),
+            data: name.to_string(),
+        })
+    }
+
+    fn qual_ident(&self, text: &str, span: Span) -> Option<QualIdent> {
+        let mut segments = text.split("::");
+        let ident = |data: &str| Ident {
+            node_id: fpp_core::Node::new(span),
+            data: data.to_string(),
+        };
+
+        let first = segments.next().filter(|first| is_identifier(first))?;
+        let mut out = QualIdent::Unqualified(ident(first));
+        for segment in segments {
+            if !is_identifier(segment) {
+                return None;
+            }
+
+            out = QualIdent::Qualified(Qualified {
+                node_id: fpp_core::Node::new(span),
+                qualifier: Box::new(out),
+                name: ident(segment),
+            });
+        }
+
+        Some(out)
+    }
+
+    /// Translate a C++ value from the XML into an FPP expression
+    fn expr(&self, text: &str, span: Span) -> Option<Expr> {
+        let text = text.trim();
+        let kind = if let Some(e) = text.strip_prefix('-') {
+            ExprKind::Unop {
+                op: Unop::Minus,
+                e: Box::new(self.expr(e, span)?),
+            }
+        } else if text == "true" || text == "false" {
+            ExprKind::LiteralBool(text == "true")
+        } else if let Some(s) = text.strip_prefix('"').and_then(|s| s.strip_suffix('"')) {
+            ExprKind::LiteralString(escape(s))
+        } else if text.starts_with(|c: char| c.is_ascii_digit() || c == '.') {
+            // Integer and float suffixes have no meaning in FPP
+            let number = text.trim_end_matches(['u', 'U', 'l', 'L']);
+            let is_hex = number.len() > 2
+                && (number.starts_with("0x") || number.starts_with("0X"))
+                && number[2..].chars().all(|c| c.is_ascii_hexdigit());
+            if is_hex || number.chars().all(|c| c.is_ascii_digit()) {
+                ExprKind::LiteralInt(number.to_string())
+            } else {
+                let number = text.trim_end_matches(['f', 'F']);
+                number.parse::<f64>().ok()?;
+                let number = match (number.starts_with('.'), number.ends_with('.')) {
+                    (true, _) => format!("0{}", number),
+                    (_, true) => format!("{}0", number),
+                    _ => number.to_string(),
+                };
+                ExprKind::LiteralFloat(number)
+            }
+        } else {
+            return self
+                .qual_ident(text, span)
+                .map(|q| qual_ident_expr(q, span));
+        };
+
+        Some(Expr {
+            node_id: fpp_core::Node::new(span),
+            kind,
+        })
+    }
+
+    /// Translate an optional attribute holding an expression
+    fn opt_expr(&self, xml: XmlNode, attr: &str) -> Option<Expr> {
+        let value = xml
+            .attribute(attr)
+            .filter(|value| !value.trim().is_empty())?;
+        let span = self.attr_span(xml, attr);
+        let e = self.expr(value, span);
+        if e.is_none() {
+            span.warning(format!(
+                "`{}` has no FPP equivalent and was left out",
+                value
+            ))
+            .emit();
+        }
+
+        e
+    }
+
+    /// Translate a value of type `type_name`, where strings may be unquoted
+    fn value(&self, text: &str, span: Span, type_name: &TypeName) -> Option<Expr> {
+        let text = text.trim();
+        let e = match type_name.kind {
+            TypeNameKind::String(_) if !text.starts_with('"') => Some(Expr {
+                node_id: fpp_core::Node::new(span),
+                kind: ExprKind::LiteralString(escape(text)),
+            }),
+            _ => self.expr(text, span),
+        };
+
+        if e.is_none() {
+            span.warning(format!(
+                "default value `{}` has no FPP equivalent and was left out",
+                text
+            ))
+            .emit();
+        }
+
+        e
+    }
+
+    fn queue_full(&self, xml: XmlNode) -> Option<QueueFull> {
+        let value = xml.attribute("full")?;
+        match value {
+            "assert" => Some(QueueFull::Assert),
+            "block" => Some(QueueFull::Block),
+            "drop" => Some(QueueFull::Drop),
+            "hook" => Some(QueueFull::Hook),
+            "" => None,
+            _ => {
+                self.unsupported(self.attr_span(xml, "full"), "queue full behavior", value);
+                None
+            }
+        }
+    }
+
+    fn lit_string(&self, data: String, span: Span) -> LitString {
+        LitString {
+            node_id: fpp_core::Node::new(span),
+            data,
+            inner_span: span,
+        }
+    }
+
+    /// Translate a printf format string, keeping it as is if it cannot be
+    fn format(&self, format: &str, span: Span) -> LitString {
+        let data = match format_string(format) {
+            Some(format) => escape(&format),
+            None => {
+                span.warning(format!(
+                    "format string `{}` has no FPP equivalent and was copied as is",
+                    format
+                ))
+                .emit();
+                escape(format)
+            }
+        };
+
+        self.lit_string(data, span)
+    }
+
+    fn opt_format(&self, xml: XmlNode, attr: &str) -> Option<LitString> {
+        let format = xml.attribute(attr).filter(|format| !format.is_empty())?;
+        Some(self.format(format, self.attr_span(xml, attr)))
+    }
+
+    /// Translate a type named in the XML, `size` is the length of a string type
+    fn type_name(&self, ty: &str, span: Span, size: Option<Expr>) -> Option<TypeName> {
+        let kind = match ty.trim() {
+            "bool" => TypeNameKind::Bool,
+            "F32" => TypeNameKind::Floating(FloatKind::F32),
+            "F64" => TypeNameKind::Floating(FloatKind::F64),
+            "U8" => TypeNameKind::Integer(IntegerKind::U8),
+            "I8" => TypeNameKind::Integer(IntegerKind::I8),
+            "U16" => TypeNameKind::Integer(IntegerKind::U16),
+            "I16" => TypeNameKind::Integer(IntegerKind::I16),
+            "U32" => TypeNameKind::Integer(IntegerKind::U32),
+            "I32" => TypeNameKind::Integer(IntegerKind::I32),
+            "U64" => TypeNameKind::Integer(IntegerKind::U64),
+            "I64" => TypeNameKind::Integer(IntegerKind::I64),
+            "string" => TypeNameKind::String(size),
+            ty => match self.qual_ident(ty, span) {
+                Some(q) => TypeNameKind::QualIdent(q),
+                None => {
+                    self.unsupported(span, "type", ty);
+                    return None;
+                }
+            },
+        };
+
+        Some(TypeName {
+            node_id: fpp_core::Node::new(span),
+            kind,
+        })
+    }
+
+    /// Translate the type in the attribute `attr`. A type `ENUM` is defined
+    /// by a nested `enum` element, which is added to `enums`.
+    fn attr_type(&self, xml: XmlNode, attr: &str, enums: &mut Vec<DefEnum>) -> Option<TypeName> {
+        let ty = self.required(xml, attr)?;
+        let span = self.attr_span(xml, attr);
+        if ty == "ENUM" {
+            let Some(def) = element(xml, "enum").and_then(|def| self.def_enum(def)) else {
+                span.error("`ENUM` type without a nested `enum` definition")
+                    .emit();
+                return None;
+            };
+
+            let name = def.name.data.clone();
+            enums.push(def);
+            return self.type_name(&name, span, None);
+        }
+
+        self.type_name(ty, span, self.opt_expr(xml, "size"))
+    }
+
+    /// Wrap definitions in the modules of a namespace like `Ref::Sub`
+    fn in_namespace(&self, xml: XmlNode, members: Vec<ModuleMember>) -> Vec<ModuleMember> {
+        let Some(namespace) = xml.attribute("namespace").filter(|ns| !ns.is_empty()) else {
+            return members;
+        };
+
+        let span = self.attr_span(xml, "namespace");
+        namespace.rsplit("::").fold(members, |members, module| {
+            vec![ModuleMember::DefModule(DefModule {
+                node_id: fpp_core::Node::new(span),
+                name: Name {
+                    node_id: fpp_core::Node::new(span),
+                    data: module.to_string(),
+                },
+                members,
+            })]
+        })
+    }
+
+    fn root(&self, xml: XmlNode) -> Option<TransUnit> {
+        let members = match xml.tag_name().name() {
+            "component" => self.def_component(xml)?,
+            "interface" => self.def_port(xml)?,
+            "serializable" => self.def_struct(xml)?,
+            "enum" => vec![ModuleMember::DefEnum(self.def_enum(xml)?)],
+            "array" => vec![ModuleMember::DefArray(self.def_array(xml)?)],
+            other => {
+                self.tag_span(xml)
+                    .error(format!("`{}` is not an F Prime XML definition", other))
+                    .note("expected a component, interface, serializable, enum or array")
+                    .emit();
+                return None;
+            }
+        };
+
+        Some(TransUnit(self.in_namespace(xml, members)))
+    }
+
+    fn formal_params(&self, xml: XmlNode, enums: &mut Vec<DefEnum>) -> FormalParamList {
+        let Some(args) = element(xml, "args") else {
+            return vec![];
+        };
+
+        elements(args, "arg")
+            .filter_map(|arg| {
+                let kind = match arg.attribute("pass_by") {
+                    Some("reference") => FormalParamKind::Ref,
+                    _ => FormalParamKind::Value,
+                };
+
+                Some(FormalParam {
+                    node_id: self.node(arg),
+                    kind,
+                    name: self.name(arg, "name")?,
+                    type_name: self.attr_type(arg, "type", enums)?,
+                })
+            })
+            .collect()
+    }
+
+    fn def_enum(&self, xml: XmlNode) -> Option<DefEnum> {
+        let type_name = match xml.attribute("serialize_type") {
+            Some(ty) => Some(self.type_name(ty, self.attr_span(xml, "serialize_type"), None)?),
+            None => None,
+        };
+
+        let constants = elements(xml, "item")
+            .filter_map(|item| {
+                Some(DefEnumConstant {
+                    node_id: self.node(item),
+                    name: self.name(item, "name")?,
+                    value: self.opt_expr(item, "value"),
+                })
+            })
+            .collect();
+
+        // The default names a constant of the enum, which is in scope unqualified
+        let default = xml.attribute("default").and_then(|default| {
+            let constant = default.rsplit("::").next().unwrap_or(default);
+            self.expr(constant, self.attr_span(xml, "default"))
+        });
+
+        Some(DefEnum {
+            node_id: self.node(xml),
+            name: self.name(xml, "name")?,
+            type_name,
+            constants,
+            default,
+            is_dictionary_def: false,
+        })
+    }
+
+    fn def_array(&self, xml: XmlNode) -> Option<DefArray> {
+        let Some(ty) = element(xml, "type") else {
+            self.tag_span(xml).error("array without a `type`").emit();
+            return None;
+        };
+        let Some(size) = element(xml, "size") else {
+            self.tag_span(xml).error("array without a `size`").emit();
+            return None;
+        };
+
+        let elt_type = self.type_name(&text(ty), self.text_span(ty), self.opt_expr(ty, "size"))?;
+        let size_span = self.text_span(size);
+        let Some(size) = self.expr(&text(size), size_span) else {
+            self.unsupported(size_span, "array size", &text(size));
+            return None;
+        };
+
+        let default = element(xml, "default").and_then(|default| {
+            let values: Option<Vec<Expr>> = elements(default, "value")
+                .map(|value| self.value(&text(value), self.text_span(value), &elt_type))
+                .collect();
+            values.map(|values| Expr {
+                node_id: fpp_core::Node::new(self.tag_span(default)),
+                kind: ExprKind::Array(values),
+            })
+        });
+
+        let format =
+            element(xml, "format").map(|format| self.format(&text(format), self.text_span(format)));
+
+        Some(DefArray {
+            node_id: self.node(xml),
+            name: self.name(xml, "name")?,
+            size,
+            elt_type,
+            default,
+            format,
+            is_dictionary_def: false,
+        })
+    }
+
+    fn def_struct(&self, xml: XmlNode) -> Option<Vec<ModuleMember>> {
+        let mut enums = vec![];
+        let mut members = vec![];
+        let mut defaults = vec![];
+        for member in element(xml, "members")
+            .into_iter()
+            .flat_map(|members| elements(members, "member"))
+        {
+            let Some(name) = self.name(member, "name") else {
+                continue;
+            };
+
+            // The size of a string member is its length, the array size is separate
+            let is_string = member.attribute("type") == Some("string");
+            let (string_size, array_size) = if is_string {
+                (
+                    self.opt_expr(member, "size"),
+                    self.opt_expr(member, "array_size"),
+                )
+            } else {
+                let attr = if member.has_attribute("array_size") {
+                    "array_size"
+                } else {
+                    "size"
+                };
+                (None, self.opt_expr(member, attr))
+            };
+
+            let type_name = if is_string {
+                self.type_name("string", self.attr_span(member, "type"), string_size)
+            } else {
+                self.attr_type(member, "type", &mut enums)
+            };
+            let Some(type_name) = type_name else {
+                continue;
+            };
+
+            if let Some(default) = member.attribute("default") {
+                let span = self.attr_span(member, "default");
+                if let Some(value) = self.value(default, span, &type_name) {
+                    defaults.push(StructExprMember {
+                        node_id: fpp_core::Node::new(span),
+                        name: name.clone(),
+                        value,
+                    });
+                }
+            }
+
+            members.push(StructTypeMember {
+                node_id: self.node(member),
+                name,
+                size: array_size,
+                type_name,
+                format: self.opt_format(member, "format"),
+            });
+        }
+
+        let default = (!defaults.is_empty()).then(|| Expr {
+            node_id: fpp_core::Node::new(self.tag_span(xml)),
+            kind: ExprKind::Struct(defaults),
+        });
+
+        let def = DefStruct {
+            node_id: self.node(xml),
+            name: self.name(xml, "name")?,
+            members,
+            default,
+            is_dictionary_def: false,
+        };
+
+        Some(
+            enums
+                .into_iter()
+                .map(ModuleMember::DefEnum)
+                .chain([ModuleMember::DefStruct(def)])
+                .collect(),
+        )
+    }
+
+    fn def_port(&self, xml: XmlNode) -> Option<Vec<ModuleMember>> {
+        let mut enums = vec![];
+        let params = self.formal_params(xml, &mut enums);
+        let return_type = match element(xml, "return") {
+            Some(ret) => Some(self.attr_type(ret, "type", &mut enums)?),
+            None => None,
+        };
+
+        let def = DefPort {
+            node_id: self.node(xml),
+            name: self.name(xml, "name")?,
+            params,
+            return_type,
+        };
+
+        Some(
+            enums
+                .into_iter()
+                .map(ModuleMember::DefEnum)
+                .chain([ModuleMember::DefPort(def)])
+                .collect(),
+        )
+    }
+
+    fn def_component(&self, xml: XmlNode) -> Option<Vec<ModuleMember>> {
+        let kind = match self.required(xml, "kind")? {
+            "active" => ComponentKind::Active,
+            "passive" => ComponentKind::Passive,
+            "queued" => ComponentKind::Queued,
+            kind => {
+                self.unsupported(self.attr_span(xml, "kind"), "component kind", kind);
+                return None;
+            }
+        };
+
+        let mut enums = vec![];
+        let mut members = vec![];
+        for child in xml.children().filter(|child| child.is_element()) {
+            match child.tag_name().name() {
+                "ports" => members.extend(
+                    elements(child, "port").filter_map(|port| self.spec_port_instance(port)),
+                ),
+                "internal_interfaces" => members.extend(
+                    elements(child, "internal_interface")
+                        .filter_map(|port| self.spec_internal_port(port, &mut enums)),
+                ),
+                "commands" => members.extend(
+                    elements(child, "command")
+                        .filter_map(|command| self.spec_command(command, &mut enums)),
+                ),
+                "events" => members.extend(
+                    elements(child, "event").filter_map(|event| self.spec_event(event, &mut enums)),
+                ),
+                "telemetry" => members.extend(
+                    elements(child, "channel")
+                        .filter_map(|channel| self.spec_tlm_channel(channel, &mut enums)),
+                ),
+                "parameters" => members.extend(
+                    elements(child, "parameter")
+                        .filter_map(|param| self.spec_param(param, &mut enums)),
+                ),
+                // FPP resolves names across the whole model, nothing has to be imported
+                "comment"
+                | "import_port_type"
+                | "import_serializable_type"
+                | "import_enum_type"
+                | "import_array_type"
+                | "include_header" => {}
+                "import_dictionary" => {
+                    self.text_span(child)
+                        .warning(format!(
+                            "dictionary `{}` was not translated, move its definitions into the component",
+                            text(child).trim()
+                        ))
+                        .emit();
+                }
+                other => {
+                    self.tag_span(child)
+                        .warning(format!(
+                            "`{}` has no FPP equivalent and was left out",
+                            other
+                        ))
+                        .emit();
+                }
+            }
+        }
+
+        let def = DefComponent {
+            node_id: self.node(xml),
+            kind,
+            name: self.name(xml, "name")?,
+            members: enums
+                .into_iter()
+                .map(ComponentMember::DefEnum)
+                .chain(members)
+                .collect(),
+        };
+
+        Some(vec![ModuleMember::DefComponent(def)])
+    }
+
+    fn spec_port_instance(&self, xml: XmlNode) -> Option<ComponentMember> {
+        let special = match xml.attribute("role").unwrap_or("") {
+            "Cmd" => Some(SpecialPortInstanceKind::CommandRecv),
+            "CmdRegistration" => Some(SpecialPortInstanceKind::CommandReg),
+            "CmdResponse" => Some(SpecialPortInstanceKind::CommandResp),
+            "LogEvent" => Some(SpecialPortInstanceKind::Event),
+            "LogTextEvent" => Some(SpecialPortInstanceKind::TextEvent),
+            "ParamGet" => Some(SpecialPortInstanceKind::ParamGet),
+            "ParamSet" => Some(SpecialPortInstanceKind::ParamSet),
+            "Telemetry" => Some(SpecialPortInstanceKind::Telemetry),
+            "TimeGet" => Some(SpecialPortInstanceKind::TimeGet),
+            "" => None,
+            role => {
+                self.unsupported(self.attr_span(xml, "role"), "port role", role);
+                return None;
+            }
+        };
+
+        if let Some(kind) = special {
+            return Some(ComponentMember::SpecPortInstance(
+                SpecPortInstance::Special(SpecSpecialPortInstance {
+                    node_id: self.node(xml),
+                    input_kind: None,
+                    kind,
+                    name: self.name(xml, "name")?,
+                    priority: None,
+                    queue_full: None,
+                }),
+            ));
+        }
+
+        let kind = match self.required(xml, "kind")? {
+            "async_input" => GeneralPortInstanceKind::Input(InputPortKind::Async),
+            "guarded_input" => GeneralPortInstanceKind::Input(InputPortKind::Guarded),
+            "sync_input" => GeneralPortInstanceKind::Input(InputPortKind::Sync),
+            "output" => GeneralPortInstanceKind::Output,
+            kind => {
+                self.unsupported(self.attr_span(xml, "kind"), "port kind", kind);
+                return None;
+            }
+        };
+
+        let data_type = self.required(xml, "data_type")?;
+        let port = match data_type {
+            "Serial" | "serial" => None,
+            _ => {
+                let span = self.attr_span(xml, "data_type");
+                match self.qual_ident(data_type, span) {
+                    Some(port) => Some(port),
+                    None => {
+                        self.unsupported(span, "port type", data_type);
+                        return None;
+                    }
+                }
+            }
+        };
+
+        let size = match xml.attribute("max_number") {
+            Some("1") | None => None,
+            Some(_) => self.opt_expr(xml, "max_number"),
+        };
+
+        Some(ComponentMember::SpecPortInstance(
+            SpecPortInstance::General(SpecGeneralPortInstance {
+                node_id: self.node(xml),
+                kind,
+                name: self.name(xml, "name")?,
+                size,
+                port,
+                priority: self.opt_expr(xml, "priority"),
+                queue_full: self.queue_full(xml),
+            }),
+        ))
+    }
+
+    fn input_kind(&self, xml: XmlNode) -> Option<InputPortKind> {
+        match self.required(xml, "kind")? {
+            "async" => Some(InputPortKind::Async),
+            "guarded" => Some(InputPortKind::Guarded),
+            "sync" => Some(InputPortKind::Sync),
+            kind => {
+                self.unsupported(self.attr_span(xml, "kind"), "command kind", kind);
+                None
+            }
+        }
+    }
+
+    fn spec_internal_port(
+        &self,
+        xml: XmlNode,
+        enums: &mut Vec<DefEnum>,
+    ) -> Option<ComponentMember> {
+        Some(ComponentMember::SpecInternalPort(SpecInternalPort {
+            node_id: self.node(xml),
+            name: self.name(xml, "name")?,
+            params: self.formal_params(xml, enums),
+            priority: self.opt_expr(xml, "priority"),
+            queue_full: self.queue_full(xml),
+        }))
+    }
+
+    fn spec_command(&self, xml: XmlNode, enums: &mut Vec<DefEnum>) -> Option<ComponentMember> {
+        Some(ComponentMember::SpecCommand(SpecCommand {
+            node_id: self.node(xml),
+            kind: self.input_kind(xml)?,
+            name: self.name(xml, "mnemonic")?,
+            params: self.formal_params(xml, enums),
+            opcode: self.opt_expr(xml, "opcode"),
+            priority: self.opt_expr(xml, "priority"),
+            queue_full: self.queue_full(xml),
+        }))
+    }
+
+    fn spec_event(&self, xml: XmlNode, enums: &mut Vec<DefEnum>) -> Option<ComponentMember> {
+        let severity = match self.required(xml, "severity")? {
+            "ACTIVITY_HI" => EventSeverity::ActivityHigh,
+            "ACTIVITY_LO" => EventSeverity::ActivityLow,
+            "COMMAND" => EventSeverity::Command,
+            "DIAGNOSTIC" => EventSeverity::Diagnostic,
+            "FATAL" => EventSeverity::Fatal,
+            "WARNING_HI" => EventSeverity::WarningHigh,
+            "WARNING_LO" => EventSeverity::WarningLow,
+            severity => {
+                self.unsupported(self.attr_span(xml, "severity"), "event severity", severity);
+                return None;
+            }
+        };
+
+        let format = self.format(
+            self.required(xml, "format_string")?,
+            self.attr_span(xml, "format_string"),
+        );
+
+        let throttle = self.opt_expr(xml, "throttle").map(|count| EventThrottle {
+            node_id: fpp_core::Node::new(self.attr_span(xml, "throttle")),
+            count,
+            every: None,
+        });
+
+        Some(ComponentMember::SpecEvent(SpecEvent {
+            node_id: self.node(xml),
+            name: self.name(xml, "name")?,
+            params: self.formal_params(xml, enums),
+            severity,
+            id: self.opt_expr(xml, "id"),
+            format,
+            throttle,
+        }))
+    }
+
+    fn spec_tlm_channel(&self, xml: XmlNode, enums: &mut Vec<DefEnum>) -> Option<ComponentMember> {
+        let update = match xml.attribute("update") {
+            Some("always") => Some(TlmChannelUpdate::Always),
+            Some("on_change") => Some(TlmChannelUpdate::OnChange),
+            Some(update) => {
+                self.unsupported(self.attr_span(xml, "update"), "update kind", update);
+                None
+            }
+            None => None,
+        };
+
+        let limits = |side: &str| -> Vec<TlmChannelLimit> {
+            [
+                ("yellow", TlmChannelLimitKind::Yellow),
+                ("orange", TlmChannelLimitKind::Orange),
+                ("red", TlmChannelLimitKind::Red),
+            ]
+            .into_iter()
+            .filter_map(|(color, kind)| {
+                let attr = format!("{}_{}", side, color);
+                Some(TlmChannelLimit {
+                    node_id: fpp_core::Node::new(self.attr_span(xml, &attr)),
+                    kind,
+                    value: self.opt_expr(xml, &attr)?,
+                })
+            })
+            .collect()
+        };
+
+        Some(ComponentMember::SpecTlmChannel(SpecTlmChannel {
+            node_id: self.node(xml),
+            name: self.name(xml, "name")?,
+            type_name: self.attr_type(xml, "data_type", enums)?,
+            id: self.opt_expr(xml, "id"),
+            update,
+            format: self.opt_format(xml, "format_string"),
+            low: limits("low"),
+            high: limits("high"),
+        }))
+    }
+
+    fn spec_param(&self, xml: XmlNode, enums: &mut Vec<DefEnum>) -> Option<ComponentMember> {
+        let type_name = self.attr_type(xml, "data_type", enums)?;
+        let default = xml
+            .attribute("default")
+            .and_then(|default| self.value(default, self.attr_span(xml, "default"), &type_name));
+
+        Some(ComponentMember::SpecParam(SpecParam {
+            node_id: self.node(xml),
+            name: self.name(xml, "name")?,
+            type_name,
+            default,
+            id: self.opt_expr(xml, "id"),
+            set_opcode: self.opt_expr(xml, "set_opcode"),
+            save_opcode: self.opt_expr(xml, "save_opcode"),
+            is_external: false,
+        }))
+    }
+}
+
+/// An expression naming a definition, like `Ref.Choice.ONE`
+fn qual_ident_expr(q: QualIdent, span: Span) -> Expr {
+    let kind = match q {
+        QualIdent::Unqualified(ident) => ExprKind::Ident(ident.data),
+        QualIdent::Qualified(q) => ExprKind::Dot {
+            e: Box::new(qual_ident_expr(*q.qualifier, span)),
+            id: q.name,
+        },
+    };
+
+    Expr {
+        node_id: fpp_core::Node::new(span),
+        kind,
+    }
+}
@@ -6,6 +6,7 @@ use crate::span::Span;
 use crate::{BytePos, Diagnostic, DiagnosticMessageKind, Level, Node, Position};
 use line_index::{LineCol, LineIndex};
 use rustc_hash::FxHashSet;
+use std::borrow::Cow;
 use std::ops::Deref;
 use std::sync::{Arc, Weak};
 
@@ -162,6 +163,8 @@ pub struct NodeData {
     pub span_handle: usize,
     pub pre_annotation: Vec<String>,
     pub post_annotation: Vec<String>,
+    /// The parser recovered from a syntax error while parsing this node
+    pub recovered: bool,
 }
 
 #[derive(Debug)]
@@ -191,6 +194,7 @@ pub struct DiagnosticMessageData {
 #[derive(Debug)]
 pub struct DiagnosticData {
     pub level: Level,
+    pub code: Option<Cow<'static, str>>,
     pub message: String,
     pub span: SpanData,
     pub children: Vec<DiagnosticMessageData>,
@@ -268,6 +272,7 @@ impl<E: DiagnosticEmitter> CompilerContext<E> {
             span_handle: span.handle,
             pre_annotation: vec![],
             post_annotation: vec![],
+            recovered: false,
         });
 
         self.gc.as_mut().map(|g| g.nodes.insert(handle));
@@ -330,6 +335,7 @@ impl<E: DiagnosticEmitter> CompilerContext<E> {
     fn diagnostic_get(&self, diagnostic: Diagnostic) -> DiagnosticData {
         DiagnosticData {
             level: diagnostic.level,
+            code: diagnostic.code,
             message: diagnostic.msg,
             span: self.span_get(&diagnostic.span).clone(),
             children: diagnostic
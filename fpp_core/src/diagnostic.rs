@@ -1,5 +1,6 @@
 use crate::interface::with;
 use crate::{Span, Spanned};
+use std::borrow::Cow;
 
 /// An enum representing a diagnostic level.
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
@@ -33,6 +34,7 @@ pub(crate) struct DiagnosticMessage {
 #[derive(Clone, Debug)]
 pub struct Diagnostic {
     pub(crate) level: Level,
+    pub(crate) code: Option<Cow<'static, str>>,
     pub(crate) msg: String,
     pub(crate) span: Span,
     pub(crate) children: Vec<DiagnosticMessage>,
@@ -77,12 +79,19 @@ impl Diagnostic {
     {
         Diagnostic {
             level,
+            code: None,
             msg: message.into(),
             span: span.span(),
             children: vec![],
         }
     }
 
+    /// Sets the stable code identifying the kind of diagnostic, for example `E0102`
+    pub fn code<C: Into<Cow<'static, str>>>(mut self, code: C) -> Diagnostic {
+        self.code = Some(code.into());
+        self
+    }
+
     diagnostic_child_methods!(span_annotation, annotation, DiagnosticMessageKind::Primary);
     diagnostic_child_methods!(span_note, note, DiagnosticMessageKind::Note);
 
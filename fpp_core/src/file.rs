@@ -1,9 +1,10 @@
 use line_index::LineIndex;
 
 use crate::interface::with;
-use crate::{Error, Span};
+use crate::{Diagnostic, Error, Level, Span, Spanned};
 use std::cell::Ref;
 use std::fmt::{Debug, Display, Formatter};
+use std::path::{Path, PathBuf};
 
 pub trait FileReader {
     /// Resolve an include path relative to the parent source file
@@ -33,10 +34,138 @@ pub trait FileReader {
         }
     }
 
+    /// Where paths are searched besides the directory of the including file
+    fn search_paths(&self) -> Option<&SearchPaths> {
+        None
+    }
+
+    /// The path of `include` inside the search directory `dir`, in the form
+    /// expected by [FileReader::read]
+    fn search_path_in(&self, dir: &Path, include: &str) -> String {
+        dir.join(include).display().to_string()
+    }
+
+    /// Every path an include or locate path may refer to, in the order they
+    /// are tried: relative to the including file, then in each search directory
+    fn candidates(&self, current: SourceFile, include: &str) -> Result<Vec<String>, Error> {
+        let search_paths = match self.search_paths() {
+            None => return Ok(vec![self.resolve(current, include)?]),
+            Some(search_paths) => search_paths,
+        };
+
+        let include = search_paths.remap(include);
+        let mut candidates = vec![self.resolve(current, &include)?];
+        if !Path::new(&include).is_absolute() {
+            for dir in &search_paths.include {
+                candidates.push(self.search_path_in(dir, &include));
+            }
+        }
+
+        Ok(candidates)
+    }
+
+    /// Read the first candidate of `include` that exists, returning its path
+    /// and content
+    fn find(&self, current: SourceFile, include: &str) -> Result<(String, String), FindError> {
+        let candidates = self
+            .candidates(current, include)
+            .map_err(FindError::Resolve)?;
+
+        let mut tried = vec![];
+        for path in candidates {
+            match self.read(&path) {
+                Ok(content) => return Ok((path, content)),
+                Err(err) => tried.push((path, err)),
+            }
+        }
+
+        Err(FindError::NotFound(tried))
+    }
+
     /// Read a file given its path
     fn read(&self, path: &str) -> Result<String, Error>;
 }
 
+/// Directories searched for include and locate paths, and path prefixes
+/// replaced before searching
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SearchPaths {
+    /// Directories searched in order when a path is not found relative to
+    /// the including file
+    pub include: Vec<PathBuf>,
+    /// Prefixes replaced by a directory, so `fprime/Fw/Cmd.fpp` can refer to
+    /// a copy of F Prime vendored under another name
+    pub remap: Vec<(String, PathBuf)>,
+}
+
+impl SearchPaths {
+    /// Replace the longest prefix of `path` that is remapped. Prefixes only
+    /// match whole path components.
+    pub fn remap(&self, path: &str) -> String {
+        let remapped = self
+            .remap
+            .iter()
+            .filter_map(|(prefix, dir)| {
+                let prefix = prefix.trim_end_matches('/');
+                let rest = path.strip_prefix(prefix)?;
+                if rest.is_empty() || rest.starts_with('/') {
+                    Some((prefix.len(), dir, rest.trim_start_matches('/')))
+                } else {
+                    None
+                }
+            })
+            .max_by_key(|(len, _, _)| *len);
+
+        match remapped {
+            None => path.to_string(),
+            Some((_, dir, "")) => dir.display().to_string(),
+            Some((_, dir, rest)) => dir.join(rest).display().to_string(),
+        }
+    }
+}
+
+/// Whether the file or directory `dir` holds `uri`. Only whole path
+/// components match.
+pub fn uri_in_dir(uri: &str, dir: &str) -> bool {
+    match uri.strip_prefix(dir) {
+        None => false,
+        Some(rest) => rest.is_empty() || rest.starts_with('/') || dir.ends_with('/'),
+    }
+}
+
+/// Why an include or locate path could not be found
+#[derive(Debug)]
+pub enum FindError {
+    /// The path could not be resolved relative to the including file
+    Resolve(Error),
+    /// None of the candidates could be read
+    NotFound(Vec<(String, Error)>),
+}
+
+impl FindError {
+    /// A diagnostic at the path in the including file, listing every
+    /// candidate tried when there was more than one
+    pub fn diagnostic<S: Spanned>(&self, span: S, include: &str) -> Diagnostic {
+        match self {
+            FindError::Resolve(err) => Diagnostic::new(span, Level::Error, err.to_string()),
+            FindError::NotFound(tried) if tried.len() == 1 => {
+                Diagnostic::new(span, Level::Error, tried[0].1.to_string())
+            }
+            FindError::NotFound(tried) => {
+                let mut diagnostic = Diagnostic::new(
+                    span,
+                    Level::Error,
+                    format!("cannot find `{}` in any of the search paths", include),
+                );
+                for (path, _) in tried {
+                    diagnostic = diagnostic.note(format!("tried {}", path));
+                }
+                diagnostic
+            }
+        }
+    }
+}
+
 pub type BytePos = u32;
 
 #[derive(Clone, Copy, Hash, Eq, PartialEq)]
@@ -95,6 +224,16 @@ impl SourceFile {
         with(|w| w.file_parent(self))
     }
 
+    /// The outermost file of the includes bringing this file in, or this file
+    /// if it is not included
+    pub fn top_file(&self) -> SourceFile {
+        let mut file = *self;
+        while let Some(parent) = file.parent() {
+            file = parent;
+        }
+        file
+    }
+
     pub fn read(&self) -> SourceFileContent<'_> {
         with(|w| SourceFileContent {
             data: w.file_content(self),
@@ -42,6 +42,14 @@ impl<'ctx, E: DiagnosticEmitter> CompilerInterface for Container<'ctx, E> {
         node.post_annotation = post;
     }
 
+    fn node_mark_recovered(&self, node: &Node) {
+        self.ctx.borrow_mut().node_get_mut(node).recovered = true;
+    }
+
+    fn node_is_recovered(&self, node: &Node) -> bool {
+        self.ctx.borrow().node_get(node).recovered
+    }
+
     fn file_new(&self, uri: &str, content: String, parent: Option<SourceFile>) -> SourceFile {
         self.ctx.borrow_mut().file_new(uri, content, parent)
     }
@@ -140,6 +148,8 @@ pub(crate) trait CompilerInterface {
     fn node_pre_annotation(&self, node: &Node) -> Vec<String>;
     fn node_post_annotation(&self, node: &Node) -> Vec<String>;
     fn node_add_annotation(&self, node: &Node, pre: Vec<String>, post: Vec<String>);
+    fn node_mark_recovered(&self, node: &Node);
+    fn node_is_recovered(&self, node: &Node) -> bool;
 
     /** Source file related functions */
     fn file_new(&self, uri: &str, content: String, parent: Option<SourceFile>) -> SourceFile;
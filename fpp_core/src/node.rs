@@ -24,6 +24,17 @@ impl Node {
     pub fn annotate(node: &Node, pre: Vec<String>, post: Vec<String>) {
         with(|w| w.node_add_annotation(node, pre, post))
     }
+
+    /// Mark this node as having been recovered from a syntax error.
+    /// The node may be missing some of its children.
+    pub fn mark_recovered(&self) {
+        with(|w| w.node_mark_recovered(self))
+    }
+
+    /// Check if this node was recovered from a syntax error
+    pub fn is_recovered(&self) -> bool {
+        with(|w| w.node_is_recovered(self))
+    }
 }
 
 impl Spanned for Node {
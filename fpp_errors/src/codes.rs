@@ -0,0 +1,31 @@
+macro_rules! register_codes {
+    ($($code:ident),* $(,)?) => {
+        /// Every diagnostic code with its long form explanation
+        pub const DIAGNOSTIC_CODES: &[(&str, &str)] = &[
+            $((stringify!($code), include_str!(concat!("codes/", stringify!($code), ".md"))),)*
+        ];
+    };
+}
+
+register_codes! {
+    // Syntax errors
+    E0001, E0002, E0003, E0004, E0005, E0006, E0007,
+    // Symbols
+    E0101, E0102, E0103, E0104,
+    // Types
+    E0201, E0202, E0203, E0204, E0205,
+    // Enums
+    E0301, E0302, E0303,
+    // Values
+    E0401, E0402, E0403,
+    // Format strings
+    E0501, E0502, E0503, E0504,
+}
+
+/// Look up the long form explanation of a diagnostic code such as `E0102`
+pub fn explain(code: &str) -> Option<&'static str> {
+    DIAGNOSTIC_CODES
+        .iter()
+        .find(|(c, _)| c.eq_ignore_ascii_case(code))
+        .map(|(_, explanation)| *explanation)
+}
@@ -0,0 +1,88 @@
+use crate::location::SpanLocation;
+use fpp_core::{DiagnosticData, DiagnosticEmitter, DiagnosticMessageKind, Level};
+use serde::Serialize;
+use std::borrow::Cow;
+use std::io::Write;
+
+fn level_name(level: Level) -> &'static str {
+    match level {
+        Level::Error => "error",
+        Level::Warning => "warning",
+        Level::Note => "note",
+        Level::Help => "help",
+        _ => "note",
+    }
+}
+
+#[derive(Serialize)]
+struct JsonChild {
+    kind: &'static str,
+    message: String,
+    span: Option<SpanLocation>,
+}
+
+#[derive(Serialize)]
+struct JsonDiagnostic {
+    level: &'static str,
+    code: Option<Cow<'static, str>>,
+    message: String,
+    span: SpanLocation,
+    children: Vec<JsonChild>,
+}
+
+impl From<&DiagnosticData> for JsonDiagnostic {
+    fn from(diagnostic: &DiagnosticData) -> Self {
+        JsonDiagnostic {
+            level: level_name(diagnostic.level),
+            code: diagnostic.code.clone(),
+            message: diagnostic.message.clone(),
+            span: SpanLocation::new(&diagnostic.span),
+            children: diagnostic
+                .children
+                .iter()
+                .map(|child| JsonChild {
+                    kind: match child.kind {
+                        DiagnosticMessageKind::Primary => "primary",
+                        DiagnosticMessageKind::Note => "note",
+                    },
+                    message: child.message.clone(),
+                    span: child.span.as_ref().map(SpanLocation::new),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Writes every diagnostic as a single line JSON object
+pub struct JsonEmitter<W: Write> {
+    write: W,
+    seen_errors: bool,
+}
+
+impl<W: Write> JsonEmitter<W> {
+    pub fn new(w: W) -> JsonEmitter<W> {
+        JsonEmitter {
+            write: w,
+            seen_errors: false,
+        }
+    }
+
+    pub fn has_errors(&self) -> bool {
+        self.seen_errors
+    }
+}
+
+impl<W: Write> DiagnosticEmitter for &mut JsonEmitter<W> {
+    fn emit(&mut self, diagnostic: DiagnosticData) {
+        if diagnostic.level == Level::Error {
+            self.seen_errors = true;
+        }
+
+        let mut out = serde_json::to_string(&JsonDiagnostic::from(&diagnostic))
+            .expect("failed to serialize diagnostic");
+        out.push('\n');
+        self.write
+            .write_all(out.as_bytes())
+            .expect("failed to write diagnostic");
+    }
+}
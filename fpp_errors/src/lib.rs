@@ -1,6 +1,13 @@
+mod codes;
 mod console;
+mod json;
+mod location;
+mod sarif;
 mod snippet;
 mod write;
 
+pub use codes::{explain, DIAGNOSTIC_CODES};
 pub use console::ConsoleEmitter;
+pub use json::JsonEmitter;
+pub use sarif::SarifEmitter;
 pub use write::WriteEmitter;
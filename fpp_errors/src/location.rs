@@ -0,0 +1,71 @@
+use fpp_core::{BytePos, SourceFileData, SpanData};
+use serde::Serialize;
+
+/// A 1-based line and column where the column is counted in unicode code points
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct LineColumn {
+    pub line: u32,
+    pub column: u32,
+}
+
+/// A region of a source file
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct Region {
+    pub uri: String,
+    pub start: LineColumn,
+    pub end: LineColumn,
+    /// Byte offset of the start of the region in the file
+    pub offset: BytePos,
+    /// Length of the region in bytes
+    pub length: BytePos,
+}
+
+/// The resolved source location of a span
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct SpanLocation {
+    #[serde(flatten)]
+    pub region: Region,
+    /// Include specifiers that pulled in the file of this span,
+    /// from the innermost to the outermost file
+    pub includes: Vec<Region>,
+}
+
+fn line_column(file: &SourceFileData, offset: BytePos) -> LineColumn {
+    let pos = file.lines.line_col(offset.into());
+    let line_start: usize = file.lines.line(pos.line).unwrap().start().into();
+
+    LineColumn {
+        line: pos.line + 1,
+        column: file.content[line_start..offset as usize].chars().count() as u32 + 1,
+    }
+}
+
+impl Region {
+    fn new(span: &SpanData) -> Region {
+        let file = span.file.upgrade().unwrap();
+
+        Region {
+            uri: file.uri.clone(),
+            start: line_column(&file, span.start),
+            end: line_column(&file, span.start + span.length),
+            offset: span.start,
+            length: span.length,
+        }
+    }
+}
+
+impl SpanLocation {
+    pub fn new(span: &SpanData) -> SpanLocation {
+        let mut includes = vec![];
+        let mut include_span = &span.include_span;
+        while let Some(loc) = include_span {
+            includes.push(Region::new(loc));
+            include_span = &loc.include_span;
+        }
+
+        SpanLocation {
+            region: Region::new(span),
+            includes,
+        }
+    }
+}
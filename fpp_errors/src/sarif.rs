@@ -0,0 +1,215 @@
+use crate::location::{self, SpanLocation};
+use fpp_core::{DiagnosticData, DiagnosticEmitter, Level};
+use serde::Serialize;
+use std::borrow::Cow;
+use std::io::Write;
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+const SARIF_VERSION: &str = "2.1.0";
+
+#[derive(Serialize)]
+struct Message {
+    text: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ArtifactLocation {
+    uri: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Region {
+    start_line: u32,
+    start_column: u32,
+    end_line: u32,
+    end_column: u32,
+    byte_offset: u32,
+    byte_length: u32,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PhysicalLocation {
+    artifact_location: ArtifactLocation,
+    region: Region,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Location {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    id: Option<usize>,
+    physical_location: PhysicalLocation,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    message: Option<Message>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifResult {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rule_id: Option<Cow<'static, str>>,
+    level: &'static str,
+    message: Message,
+    locations: Vec<Location>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    related_locations: Vec<Location>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Driver {
+    name: &'static str,
+    version: &'static str,
+}
+
+#[derive(Serialize)]
+struct Tool {
+    driver: Driver,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Run {
+    tool: Tool,
+    column_kind: &'static str,
+    results: Vec<SarifResult>,
+}
+
+#[derive(Serialize)]
+struct Log {
+    #[serde(rename = "$schema")]
+    schema: &'static str,
+    version: &'static str,
+    runs: Vec<Run>,
+}
+
+fn sarif_level(level: Level) -> &'static str {
+    match level {
+        Level::Error => "error",
+        Level::Warning => "warning",
+        _ => "note",
+    }
+}
+
+impl From<&location::Region> for PhysicalLocation {
+    fn from(region: &location::Region) -> Self {
+        PhysicalLocation {
+            artifact_location: ArtifactLocation {
+                uri: region.uri.clone(),
+            },
+            region: Region {
+                start_line: region.start.line,
+                start_column: region.start.column,
+                end_line: region.end.line,
+                end_column: region.end.column,
+                byte_offset: region.offset,
+                byte_length: region.length,
+            },
+        }
+    }
+}
+
+impl From<&DiagnosticData> for SarifResult {
+    fn from(diagnostic: &DiagnosticData) -> Self {
+        let mut message = diagnostic.message.clone();
+        let mut related_locations = vec![];
+        let mut push_related = |region: &location::Region, text: String| {
+            related_locations.push(Location {
+                id: Some(related_locations.len()),
+                physical_location: PhysicalLocation::from(region),
+                message: Some(Message { text }),
+            })
+        };
+
+        // SARIF has no notion of an include stack,
+        // include specifiers are reported as related locations
+        let span = SpanLocation::new(&diagnostic.span);
+        for include in &span.includes {
+            push_related(include, "included from here".to_string());
+        }
+
+        for child in &diagnostic.children {
+            match &child.span {
+                Some(child_span) => {
+                    let child_span = SpanLocation::new(child_span);
+                    push_related(&child_span.region, child.message.clone());
+                    for include in &child_span.includes {
+                        push_related(include, "included from here".to_string());
+                    }
+                }
+                // Notes without a location can only be reported in the message text
+                None => message.push_str(&format!("\nnote: {}", child.message)),
+            }
+        }
+
+        SarifResult {
+            rule_id: diagnostic.code.clone(),
+            level: sarif_level(diagnostic.level),
+            message: Message { text: message },
+            locations: vec![Location {
+                id: None,
+                physical_location: PhysicalLocation::from(&span.region),
+                message: None,
+            }],
+            related_locations,
+        }
+    }
+}
+
+/// Collects all diagnostics into a single SARIF 2.1.0 log
+///
+/// The log is only written once [`SarifEmitter::finish`] is called.
+pub struct SarifEmitter<W: Write> {
+    write: W,
+    results: Vec<SarifResult>,
+    seen_errors: bool,
+}
+
+impl<W: Write> SarifEmitter<W> {
+    pub fn new(w: W) -> SarifEmitter<W> {
+        SarifEmitter {
+            write: w,
+            results: vec![],
+            seen_errors: false,
+        }
+    }
+
+    pub fn has_errors(&self) -> bool {
+        self.seen_errors
+    }
+
+    /// Write the SARIF log with all the diagnostics emitted so far
+    pub fn finish(mut self) -> std::io::Result<W> {
+        let log = Log {
+            schema: SARIF_SCHEMA,
+            version: SARIF_VERSION,
+            runs: vec![Run {
+                tool: Tool {
+                    driver: Driver {
+                        name: "fpp",
+                        version: env!("CARGO_PKG_VERSION"),
+                    },
+                },
+                column_kind: "unicodeCodePoints",
+                results: self.results,
+            }],
+        };
+
+        serde_json::to_writer_pretty(&mut self.write, &log)?;
+        self.write.write_all(b"\n")?;
+        Ok(self.write)
+    }
+}
+
+impl<W: Write> DiagnosticEmitter for &mut SarifEmitter<W> {
+    fn emit(&mut self, diagnostic: DiagnosticData) {
+        if diagnostic.level == Level::Error {
+            self.seen_errors = true;
+        }
+
+        self.results.push(SarifResult::from(&diagnostic));
+    }
+}
@@ -16,20 +16,41 @@ fn diagnostic_snippet_to_annotation<'a>(
     kind: AnnotationKind,
     snippet: &DiagnosticDataSnippet,
 ) -> Annotation<'a> {
-    let mut annotation = kind
-        .span((snippet.start as usize)..(snippet.end as usize))
-        .label(message);
+    kind.span((snippet.start as usize)..(snippet.end as usize))
+        .label(message)
+}
+
+/// The message of the diagnostic along with its code
+/// Errors are the common case so only other levels are spelled out
+fn primary_label(diagnostic: &DiagnosticData) -> String {
+    let message = match diagnostic.level {
+        Level::Error => diagnostic.message.clone(),
+        Level::Warning => format!("warning: {}", diagnostic.message),
+        Level::Note => format!("note: {}", diagnostic.message),
+        Level::Help => format!("help: {}", diagnostic.message),
+        _ => diagnostic.message.clone(),
+    };
 
-    for include_loc in &snippet.include_spans {
-        annotation = annotation.label(format!(
-            "included from {}:{}:{}",
-            include_loc.uri,
-            include_loc.line + 1,
-            include_loc.column + 1
-        ))
+    match &diagnostic.code {
+        None => message,
+        Some(code) => format!("{} [{}]", message, code),
     }
+}
 
-    annotation
+/// Notes pointing to the include specifiers that pulled in the snippet's file
+fn include_notes<'a>(snippet: &DiagnosticDataSnippet) -> Vec<Element<'a>> {
+    snippet
+        .include_spans
+        .iter()
+        .map(|include_loc| {
+            Element::Message(diagnostic_level(Level::Note).message(format!(
+                "included from {}:{}:{}",
+                include_loc.uri,
+                include_loc.line + 1,
+                include_loc.column + 1
+            )))
+        })
+        .collect()
 }
 
 pub(crate) fn diagnostic_to_snippet_group(diagnostic: &'_ DiagnosticData) -> Group<'_> {
@@ -40,35 +61,41 @@ pub(crate) fn diagnostic_to_snippet_group(diagnostic: &'_ DiagnosticData) -> Gro
                 .line_start(snippet.line_offset + 1)
                 .path(snippet.uri.clone())
                 .annotation(diagnostic_snippet_to_annotation(
-                    diagnostic.message.clone(),
+                    primary_label(diagnostic),
                     AnnotationKind::Primary,
                     &snippet,
                 )),
         )
-        .elements(diagnostic.children.iter().map(|child| {
-            match &child.span {
-                None => Element::Message(
-                    (match child.kind {
-                        DiagnosticMessageKind::Primary => diagnostic_level(diagnostic.level),
-                        DiagnosticMessageKind::Note => diagnostic_level(Level::Note),
-                    })
-                    .message(child.message.clone()),
-                ),
-                Some(span) => {
-                    let snippet = span.snippet();
-                    Snippet::source(snippet.file_content.clone())
-                        .line_start(snippet.line_offset + 1)
-                        .path(snippet.uri.clone())
-                        .annotation(diagnostic_snippet_to_annotation(
-                            child.message.clone(),
-                            match child.kind {
-                                DiagnosticMessageKind::Primary => AnnotationKind::Primary,
-                                DiagnosticMessageKind::Note => AnnotationKind::Context,
-                            },
-                            &snippet,
-                        ))
-                        .into()
-                }
-            }
-        }))
+        .elements(include_notes(&snippet))
+        .elements(
+            diagnostic
+                .children
+                .iter()
+                .flat_map(|child| match &child.span {
+                    None => vec![Element::Message(
+                        (match child.kind {
+                            DiagnosticMessageKind::Primary => diagnostic_level(diagnostic.level),
+                            DiagnosticMessageKind::Note => diagnostic_level(Level::Note),
+                        })
+                        .message(child.message.clone()),
+                    )],
+                    Some(span) => {
+                        let snippet = span.snippet();
+                        let mut elements = vec![Snippet::source(snippet.file_content.clone())
+                            .line_start(snippet.line_offset + 1)
+                            .path(snippet.uri.clone())
+                            .annotation(diagnostic_snippet_to_annotation(
+                                child.message.clone(),
+                                match child.kind {
+                                    DiagnosticMessageKind::Primary => AnnotationKind::Primary,
+                                    DiagnosticMessageKind::Note => AnnotationKind::Context,
+                                },
+                                &snippet,
+                            ))
+                            .into()];
+                        elements.extend(include_notes(&snippet));
+                        elements
+                    }
+                }),
+        )
 }
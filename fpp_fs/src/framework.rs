@@ -0,0 +1,130 @@
+use crate::{normalize_path, FsReader};
+use fpp_core::{Error, FileReader, SearchPaths, SourceFile};
+use std::path::Path;
+
+/// Version of F Prime the bundled framework definitions are taken from
+pub const FRAMEWORK_VERSION: &str = "4.0.0";
+
+/// Scheme of the URIs the bundled framework files are served at, like
+/// `fpp-framework:///Fw/Cmd/Cmd.fpp`
+pub const FRAMEWORK_SCHEME: &str = "fpp-framework";
+
+/// Prefix of the URIs of every bundled framework file
+pub const FRAMEWORK_PREFIX: &str = "fpp-framework:///";
+
+macro_rules! framework_files {
+    ($($path:literal),* $(,)?) => {
+        &[$(($path, include_str!(concat!("../framework/", $path)))),*]
+    };
+}
+
+/// Path of each bundled file relative to the root of F Prime, and its content
+const FILES: &[(&str, &str)] = framework_files![
+    "config/DpCfg.fpp",
+    "config/FpConfig.fpp",
+    "Fw/Buffer/Buffer.fpp",
+    "Fw/Cmd/Cmd.fpp",
+    "Fw/Dp/Dp.fpp",
+    "Fw/Log/Log.fpp",
+    "Fw/Ports/Ports.fpp",
+    "Fw/Prm/Prm.fpp",
+    "Fw/Time/Time.fpp",
+    "Fw/Tlm/Tlm.fpp",
+    "Fw/Types/Types.fpp",
+    "Svc/Fatal/Fatal.fpp",
+    "Svc/Ping/Ping.fpp",
+    "Svc/Sched/Sched.fpp",
+    "Svc/WatchDog/WatchDog.fpp",
+];
+
+/// URIs of the bundled framework files, in the order they should be read
+pub fn framework_files() -> Vec<String> {
+    FILES
+        .iter()
+        .map(|(path, _)| format!("{}{}", FRAMEWORK_PREFIX, path))
+        .collect()
+}
+
+/// Content of the bundled framework file at `uri`
+pub fn framework_file(uri: &str) -> Option<&'static str> {
+    let path = uri.strip_prefix(FRAMEWORK_PREFIX)?;
+    FILES
+        .iter()
+        .find(|(file, _)| *file == path)
+        .map(|(_, content)| *content)
+}
+
+/// Whether `uri` refers to a bundled framework file, even one that does not exist
+pub fn is_framework_uri(uri: &str) -> bool {
+    uri.starts_with(FRAMEWORK_PREFIX)
+}
+
+/// Resolve `include` relative to the bundled framework file `current`
+///
+/// Bundled files may only include each other, the path is resolved without
+/// the file system.
+pub fn resolve_framework_uri(current: &str, include: &str) -> Option<String> {
+    let path = current.strip_prefix(FRAMEWORK_PREFIX)?;
+    let dir = Path::new(path).parent().unwrap_or(Path::new(""));
+    let path = normalize_path(&dir.join(include));
+    Some(format!("{}{}", FRAMEWORK_PREFIX, path.display()))
+}
+
+/// Whether the bundled framework definitions replace the ones on disk
+///
+/// With `prefer_disk`, the bundled definitions are only a fallback for when
+/// none of the `on_disk` framework locations exist, like in a checkout
+/// without the F Prime submodule.
+pub fn use_builtin_framework(
+    builtin: bool,
+    prefer_disk: bool,
+    on_disk: &[impl AsRef<Path>],
+) -> bool {
+    builtin && !(prefer_disk && on_disk.iter().any(|path| path.as_ref().exists()))
+}
+
+/// Serves the bundled framework files before falling back to another reader
+pub struct FrameworkReader<R: FileReader = FsReader> {
+    inner: R,
+}
+
+impl<R: FileReader> FrameworkReader<R> {
+    pub fn new(inner: R) -> FrameworkReader<R> {
+        FrameworkReader { inner }
+    }
+}
+
+impl<R: FileReader> FileReader for FrameworkReader<R> {
+    fn resolve(&self, current: SourceFile, include: &str) -> Result<String, Error> {
+        match resolve_framework_uri(&current.uri(), include) {
+            Some(uri) => Ok(uri),
+            None => self.inner.resolve(current, include),
+        }
+    }
+
+    fn candidates(&self, current: SourceFile, include: &str) -> Result<Vec<String>, Error> {
+        match resolve_framework_uri(&current.uri(), include) {
+            Some(uri) => Ok(vec![uri]),
+            None => self.inner.candidates(current, include),
+        }
+    }
+
+    fn search_paths(&self) -> Option<&SearchPaths> {
+        self.inner.search_paths()
+    }
+
+    fn search_path_in(&self, dir: &Path, include: &str) -> String {
+        self.inner.search_path_in(dir, include)
+    }
+
+    fn read(&self, path: &str) -> Result<String, Error> {
+        if !is_framework_uri(path) {
+            return self.inner.read(path);
+        }
+
+        match framework_file(path) {
+            Some(content) => Ok(content.to_string()),
+            None => Err(format!("{} is not a bundled framework file", path).into()),
+        }
+    }
+}
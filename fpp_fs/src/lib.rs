@@ -1,9 +1,32 @@
-use fpp_core::{Error, FileReader};
+mod framework;
+pub use framework::*;
+
+mod manifest;
+pub use manifest::*;
+
+mod overlay;
+pub use overlay::*;
+
+use fpp_core::{Error, FileReader, SearchPaths};
 use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+#[derive(Default)]
+pub struct FsReader {
+    pub search_paths: SearchPaths,
+}
 
-pub struct FsReader {}
+impl FsReader {
+    pub fn new(search_paths: SearchPaths) -> FsReader {
+        FsReader { search_paths }
+    }
+}
 
 impl FileReader for FsReader {
+    fn search_paths(&self) -> Option<&SearchPaths> {
+        Some(&self.search_paths)
+    }
+
     fn read(&self, path: &str) -> Result<String, Error> {
         let fs_path = std::path::Path::new(&path).canonicalize()?;
 
@@ -15,3 +38,29 @@ impl FileReader for FsReader {
         Ok(content)
     }
 }
+
+/// Remove `.` and `..` components without touching the file system, so
+/// paths that do not exist yet can still be compared
+pub fn normalize_path(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if out.file_name().is_some() {
+                    out.pop();
+                } else if !out.has_root() {
+                    out.push("..");
+                }
+            }
+            component => out.push(component),
+        }
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    mod manifest;
+}
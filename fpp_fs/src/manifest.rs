@@ -0,0 +1,402 @@
+use crate::{normalize_path, use_builtin_framework};
+use fpp_core::{Error, SearchPaths};
+use ignore::WalkBuilder;
+use std::fs;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use toml_edit::{Document, Item, Table};
+
+/// Lint levels and checks enabled for a project
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ManifestLints {
+    pub allow: Vec<String>,
+    pub warn: Vec<String>,
+    pub deny: Vec<String>,
+    /// Style rules written as `<name>=<kind>:<check>`
+    pub style: Vec<String>,
+    pub max_port_buffer_size: Option<usize>,
+    pub check_unused: bool,
+}
+
+/// Directories generated files are written to
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ManifestOutput {
+    pub doc: Option<PathBuf>,
+    pub graph: Option<PathBuf>,
+    pub from_xml: Option<PathBuf>,
+}
+
+/// A project manifest, the `fpp.toml` file at the root of a project
+///
+/// ```toml
+/// [project]
+/// sources = ["Ref", "Components"]
+/// locs = ["build/locs.fpp"]
+/// include = ["lib/fprime"]
+/// remap = { fprime = "lib/fprime" }
+/// exclude = ["build"]
+/// framework = ["lib/fprime/Fw", "lib/fprime/Svc"]
+/// builtin-framework = true
+/// prefer-disk-framework = true
+///
+/// [lint]
+/// deny = ["unknown_lints"]
+/// check-unused = true
+///
+/// [output]
+/// doc = "build/doc"
+/// ```
+///
+/// Every path is relative to the directory of the manifest. Without
+/// `sources`, the whole directory is a source root.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Manifest {
+    /// Path of the manifest file itself
+    pub path: PathBuf,
+    /// Directories and files searched for `.fpp` files
+    pub sources: Vec<PathBuf>,
+    /// Location files whose `locate` specifiers add files to the model
+    pub locs: Vec<PathBuf>,
+    /// Directories searched for included and located files
+    pub include: Vec<PathBuf>,
+    /// Path prefixes of included and located files replaced by a directory
+    pub remap: Vec<(String, PathBuf)>,
+    /// Directories skipped when searching the source roots
+    pub exclude: Vec<PathBuf>,
+    /// Directories and files holding the F Prime framework definitions
+    pub framework: Vec<PathBuf>,
+    /// Read the framework definitions bundled with the tool instead of the
+    /// `framework` locations
+    pub builtin_framework: bool,
+    /// Only use the bundled framework definitions when none of the
+    /// `framework` locations exist
+    pub prefer_disk_framework: bool,
+    pub lints: ManifestLints,
+    pub output: ManifestOutput,
+}
+
+impl Manifest {
+    pub const FILE_NAME: &'static str = "fpp.toml";
+
+    /// Read and parse the manifest at `path`
+    pub fn load(path: &Path) -> Result<Manifest, Error> {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(err) => return Err(format!("failed to read {}: {}", path.display(), err).into()),
+        };
+
+        Manifest::parse(path, &text)
+    }
+
+    /// Parse the `text` of the manifest at `path`
+    pub fn parse(path: &Path, text: &str) -> Result<Manifest, Error> {
+        let document = match Document::parse(text) {
+            Ok(document) => document,
+            Err(err) => {
+                let line = err.span().map_or(1, |span| line_of(text, span.start));
+                return Err(format!("{}:{}: {}", path.display(), line, err.message()).into());
+            }
+        };
+
+        let root = normalize_path(path.parent().unwrap_or(Path::new(".")));
+        let parser = ManifestParser {
+            path,
+            text,
+            root: &root,
+        };
+        let top = document.as_table();
+        parser.only_keys(top, "", &["project", "lint", "output"])?;
+
+        let project = parser.table(top, "project")?;
+        let lint = parser.table(top, "lint")?;
+        let output = parser.table(top, "output")?;
+
+        let mut manifest = Manifest {
+            path: path.to_path_buf(),
+            sources: vec![],
+            locs: vec![],
+            include: vec![],
+            remap: vec![],
+            exclude: vec![],
+            framework: vec![],
+            builtin_framework: false,
+            prefer_disk_framework: false,
+            lints: Default::default(),
+            output: Default::default(),
+        };
+
+        if let Some(project) = project {
+            parser.only_keys(
+                project,
+                "project.",
+                &[
+                    "sources",
+                    "locs",
+                    "include",
+                    "remap",
+                    "exclude",
+                    "framework",
+                    "builtin-framework",
+                    "prefer-disk-framework",
+                ],
+            )?;
+
+            manifest.sources = parser.paths(project, "project.", "sources")?;
+            manifest.locs = parser.paths(project, "project.", "locs")?;
+            manifest.include = parser.paths(project, "project.", "include")?;
+            manifest.remap = parser.remap(project)?;
+            manifest.exclude = parser.paths(project, "project.", "exclude")?;
+            manifest.framework = parser.paths(project, "project.", "framework")?;
+            manifest.builtin_framework = parser.bool(project, "project.", "builtin-framework")?;
+            manifest.prefer_disk_framework =
+                parser.bool(project, "project.", "prefer-disk-framework")?;
+        }
+
+        if manifest.sources.is_empty() {
+            manifest.sources.push(root.clone());
+        }
+
+        if let Some(lint) = lint {
+            parser.only_keys(
+                lint,
+                "lint.",
+                &[
+                    "allow",
+                    "warn",
+                    "deny",
+                    "style",
+                    "max-port-buffer-size",
+                    "check-unused",
+                ],
+            )?;
+
+            manifest.lints = ManifestLints {
+                allow: parser.strings(lint, "lint.", "allow")?,
+                warn: parser.strings(lint, "lint.", "warn")?,
+                deny: parser.strings(lint, "lint.", "deny")?,
+                style: parser.strings(lint, "lint.", "style")?,
+                max_port_buffer_size: match lint.get("max-port-buffer-size") {
+                    None => None,
+                    Some(item) => match item.as_integer().map(usize::try_from) {
+                        Some(Ok(size)) => Some(size),
+                        _ => {
+                            return Err(parser.error(
+                                item.span(),
+                                "`lint.max-port-buffer-size` must be a positive integer",
+                            ));
+                        }
+                    },
+                },
+                check_unused: parser.bool(lint, "lint.", "check-unused")?,
+            };
+        }
+
+        if let Some(output) = output {
+            parser.only_keys(output, "output.", &["doc", "graph", "from-xml"])?;
+
+            manifest.output = ManifestOutput {
+                doc: parser.path(output, "output.", "doc")?,
+                graph: parser.path(output, "output.", "graph")?,
+                from_xml: parser.path(output, "output.", "from-xml")?,
+            };
+        }
+
+        Ok(manifest)
+    }
+
+    /// Directory holding the manifest, which relative paths are resolved against
+    pub fn root(&self) -> &Path {
+        self.path.parent().unwrap_or(Path::new("."))
+    }
+
+    /// Where included and located files are searched
+    pub fn search_paths(&self) -> SearchPaths {
+        SearchPaths {
+            include: self.include.clone(),
+            remap: self.remap.clone(),
+        }
+    }
+
+    /// Whether the bundled framework definitions are read instead of the
+    /// `framework` locations
+    pub fn uses_builtin_framework(&self) -> bool {
+        use_builtin_framework(
+            self.builtin_framework,
+            self.prefer_disk_framework,
+            &self.framework,
+        )
+    }
+
+    /// Whether `path` is inside one of the excluded directories
+    pub fn is_excluded(&self, path: &Path) -> bool {
+        let path = normalize_path(path);
+        self.exclude.iter().any(|exclude| path.starts_with(exclude))
+    }
+
+    /// The `.fpp` files under the source roots and framework locations
+    ///
+    /// Excluded directories and files ignored by git are skipped, and so are
+    /// the framework locations when the bundled framework is used. Files are
+    /// sorted so the model is always read in the same order.
+    pub fn source_files(&self) -> Vec<PathBuf> {
+        let framework: &[PathBuf] = match self.uses_builtin_framework() {
+            true => &[],
+            false => &self.framework,
+        };
+
+        let mut files = vec![];
+        for root in self.sources.iter().chain(framework.iter()) {
+            let exclude = self.exclude.clone();
+            let walk = WalkBuilder::new(root)
+                .filter_entry(move |entry| {
+                    let path = normalize_path(entry.path());
+                    !exclude.iter().any(|exclude| path.starts_with(exclude))
+                })
+                .build();
+
+            for entry in walk.flatten() {
+                let path = entry.path();
+                if path.is_file() && path.extension().is_some_and(|ext| ext == "fpp") {
+                    files.push(normalize_path(path));
+                }
+            }
+        }
+
+        files.sort();
+        files.dedup();
+        files
+    }
+}
+
+struct ManifestParser<'a> {
+    path: &'a Path,
+    text: &'a str,
+    root: &'a Path,
+}
+
+impl ManifestParser<'_> {
+    fn error(&self, span: Option<Range<usize>>, message: impl Into<String>) -> Error {
+        let line = span.map_or(1, |span| line_of(self.text, span.start));
+        format!("{}:{}: {}", self.path.display(), line, message.into()).into()
+    }
+
+    /// Reject keys of `table` not in `keys`, usually a misspelled setting
+    fn only_keys(&self, table: &Table, prefix: &str, keys: &[&str]) -> Result<(), Error> {
+        for (key, _) in table.iter() {
+            if !keys.contains(&key) {
+                return Err(self.error(
+                    table.key(key).and_then(|key| key.span()),
+                    format!(
+                        "unknown setting `{}{}`, expected one of {}",
+                        prefix,
+                        key,
+                        keys.iter()
+                            .map(|key| format!("`{}{}`", prefix, key))
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    ),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn table<'t>(&self, table: &'t Table, key: &str) -> Result<Option<&'t Table>, Error> {
+        match table.get(key) {
+            None => Ok(None),
+            Some(item) => match item.as_table() {
+                Some(table) => Ok(Some(table)),
+                None => Err(self.error(item.span(), format!("`{}` must be a table", key))),
+            },
+        }
+    }
+
+    fn strings(&self, table: &Table, prefix: &str, key: &str) -> Result<Vec<String>, Error> {
+        let item = match table.get(key) {
+            None => return Ok(vec![]),
+            Some(item) => item,
+        };
+
+        let strings = item.as_array().and_then(|array| {
+            array
+                .iter()
+                .map(|value| value.as_str().map(String::from))
+                .collect::<Option<Vec<_>>>()
+        });
+
+        match strings {
+            Some(strings) => Ok(strings),
+            None => Err(self.error(
+                item.span(),
+                format!("`{}{}` must be an array of strings", prefix, key),
+            )),
+        }
+    }
+
+    fn paths(&self, table: &Table, prefix: &str, key: &str) -> Result<Vec<PathBuf>, Error> {
+        Ok(self
+            .strings(table, prefix, key)?
+            .into_iter()
+            .map(|path| normalize_path(&self.root.join(path)))
+            .collect())
+    }
+
+    fn path(&self, table: &Table, prefix: &str, key: &str) -> Result<Option<PathBuf>, Error> {
+        match table.get(key) {
+            None => Ok(None),
+            Some(Item::Value(value)) if value.is_str() => Ok(Some(normalize_path(
+                &self.root.join(value.as_str().unwrap()),
+            ))),
+            Some(item) => {
+                Err(self.error(item.span(), format!("`{}{}` must be a string", prefix, key)))
+            }
+        }
+    }
+
+    fn bool(&self, table: &Table, prefix: &str, key: &str) -> Result<bool, Error> {
+        match table.get(key) {
+            None => Ok(false),
+            Some(item) => match item.as_bool() {
+                Some(value) => Ok(value),
+                None => Err(self.error(
+                    item.span(),
+                    format!("`{}{}` must be a boolean", prefix, key),
+                )),
+            },
+        }
+    }
+
+    fn remap(&self, table: &Table) -> Result<Vec<(String, PathBuf)>, Error> {
+        let item = match table.get("remap") {
+            None => return Ok(vec![]),
+            Some(item) => item,
+        };
+
+        let remap = match item.as_table_like() {
+            Some(remap) => remap,
+            None => {
+                return Err(self.error(item.span(), "`project.remap` must be a table"));
+            }
+        };
+
+        let mut out = vec![];
+        for (prefix, dir) in remap.iter() {
+            match dir.as_str() {
+                Some(dir) => out.push((prefix.to_string(), normalize_path(&self.root.join(dir)))),
+                None => {
+                    return Err(self.error(
+                        dir.span(),
+                        format!("`project.remap.{}` must be a string", prefix),
+                    ));
+                }
+            }
+        }
+
+        Ok(out)
+    }
+}
+
+fn line_of(text: &str, offset: usize) -> usize {
+    text[..offset.min(text.len())].matches('\n').count() + 1
+}
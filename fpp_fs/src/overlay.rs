@@ -0,0 +1,57 @@
+use crate::{normalize_path, FsReader};
+use fpp_core::{Error, FileReader, SearchPaths, SourceFile};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Serves files from memory before falling back to another reader, so tests
+/// and tools can check models that are not on disk or that differ from it
+pub struct OverlayReader<R: FileReader = FsReader> {
+    files: HashMap<String, String>,
+    inner: R,
+}
+
+impl<R: FileReader> OverlayReader<R> {
+    pub fn new(inner: R) -> OverlayReader<R> {
+        OverlayReader {
+            files: Default::default(),
+            inner,
+        }
+    }
+
+    /// Serve `content` for `path`, hiding the file of the underlying reader
+    pub fn insert(&mut self, path: &str, content: impl Into<String>) {
+        self.files.insert(key(path), content.into());
+    }
+}
+
+fn key(path: &str) -> String {
+    normalize_path(Path::new(path)).display().to_string()
+}
+
+impl<R: FileReader> FileReader for OverlayReader<R> {
+    fn resolve(&self, current: SourceFile, include: &str) -> Result<String, Error> {
+        // Files in memory do not exist on disk, resolve their paths without it
+        let path = key(&current.uri());
+        if !self.files.contains_key(&path) {
+            return self.inner.resolve(current, include);
+        }
+
+        let dir = Path::new(&path).parent().unwrap_or(Path::new(""));
+        Ok(key(&dir.join(include).display().to_string()))
+    }
+
+    fn search_paths(&self) -> Option<&SearchPaths> {
+        self.inner.search_paths()
+    }
+
+    fn search_path_in(&self, dir: &Path, include: &str) -> String {
+        key(&self.inner.search_path_in(dir, include))
+    }
+
+    fn read(&self, path: &str) -> Result<String, Error> {
+        match self.files.get(&key(path)) {
+            Some(content) => Ok(content.clone()),
+            None => self.inner.read(path),
+        }
+    }
+}
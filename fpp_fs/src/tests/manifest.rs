@@ -0,0 +1,190 @@
+use crate::{Manifest, ManifestLints, ManifestOutput};
+use std::path::{Path, PathBuf};
+
+fn parse(text: &str) -> Result<Manifest, String> {
+    Manifest::parse(Path::new("/project/fpp.toml"), text).map_err(|err| err.to_string())
+}
+
+#[test]
+fn empty() {
+    let manifest = parse("").unwrap();
+    assert_eq!(manifest.sources, vec![PathBuf::from("/project")]);
+    assert_eq!(manifest.locs, Vec::<PathBuf>::new());
+    assert_eq!(manifest.lints, ManifestLints::default());
+    assert_eq!(manifest.output, ManifestOutput::default());
+}
+
+#[test]
+fn full() {
+    let manifest = parse(
+        r#"
+[project]
+sources = ["Ref", "./Components"]
+locs = ["build/locs.fpp"]
+include = ["../fprime"]
+remap = { fprime = "lib/fprime", "Svc/" = "/opt/Svc" }
+exclude = ["Ref/test"]
+framework = ["../fprime/Fw"]
+builtin-framework = true
+prefer-disk-framework = true
+
+[lint]
+allow = ["unused_definitions"]
+warn = ["style"]
+deny = ["unknown_lints"]
+style = ["instance-case=instance:lowerCamelCase"]
+max-port-buffer-size = 256
+check-unused = true
+
+[output]
+doc = "build/doc"
+graph = "build/graph"
+from-xml = "xml"
+"#,
+    )
+    .unwrap();
+
+    assert_eq!(
+        manifest.sources,
+        vec![
+            PathBuf::from("/project/Ref"),
+            PathBuf::from("/project/Components")
+        ]
+    );
+    assert_eq!(
+        manifest.locs,
+        vec![PathBuf::from("/project/build/locs.fpp")]
+    );
+    assert_eq!(manifest.include, vec![PathBuf::from("/fprime")]);
+    assert_eq!(
+        manifest.search_paths().remap,
+        vec![
+            ("fprime".to_string(), PathBuf::from("/project/lib/fprime")),
+            ("Svc/".to_string(), PathBuf::from("/opt/Svc")),
+        ]
+    );
+    assert_eq!(manifest.exclude, vec![PathBuf::from("/project/Ref/test")]);
+    assert_eq!(manifest.framework, vec![PathBuf::from("/fprime/Fw")]);
+    assert!(manifest.builtin_framework);
+    assert!(manifest.prefer_disk_framework);
+    assert_eq!(
+        manifest.lints,
+        ManifestLints {
+            allow: vec!["unused_definitions".to_string()],
+            warn: vec!["style".to_string()],
+            deny: vec!["unknown_lints".to_string()],
+            style: vec!["instance-case=instance:lowerCamelCase".to_string()],
+            max_port_buffer_size: Some(256),
+            check_unused: true,
+        }
+    );
+    assert_eq!(
+        manifest.output,
+        ManifestOutput {
+            doc: Some(PathBuf::from("/project/build/doc")),
+            graph: Some(PathBuf::from("/project/build/graph")),
+            from_xml: Some(PathBuf::from("/project/xml")),
+        }
+    );
+
+    assert!(manifest.is_excluded(Path::new("/project/Ref/test/Test.fpp")));
+    assert!(!manifest.is_excluded(Path::new("/project/Ref/Top.fpp")));
+}
+
+#[test]
+fn errors() {
+    for (text, error) in [
+        (
+            "[project]\nsource = [\"Ref\"]",
+            "/project/fpp.toml:2: unknown setting `project.source`, expected one of \
+             `project.sources`, `project.locs`, `project.include`, `project.remap`, \
+             `project.exclude`, `project.framework`, `project.builtin-framework`, \
+             `project.prefer-disk-framework`",
+        ),
+        (
+            "[project]\nsources = \"Ref\"",
+            "/project/fpp.toml:2: `project.sources` must be an array of strings",
+        ),
+        (
+            "[lint]\n\nmax-port-buffer-size = -1",
+            "/project/fpp.toml:3: `lint.max-port-buffer-size` must be a positive integer",
+        ),
+        (
+            "[lint]\ncheck-unused = \"yes\"",
+            "/project/fpp.toml:2: `lint.check-unused` must be a boolean",
+        ),
+        (
+            "[project]\nbuiltin-framework = 1",
+            "/project/fpp.toml:2: `project.builtin-framework` must be a boolean",
+        ),
+        (
+            "[output]\ndoc = [\"doc\"]",
+            "/project/fpp.toml:2: `output.doc` must be a string",
+        ),
+        ("lint = 1", "/project/fpp.toml:1: `lint` must be a table"),
+        (
+            "[project]\nremap = { fprime = 1 }",
+            "/project/fpp.toml:2: `project.remap.fprime` must be a string",
+        ),
+    ] {
+        assert_eq!(parse(text).unwrap_err(), error);
+    }
+
+    assert!(
+        parse("[project\n")
+            .unwrap_err()
+            .starts_with("/project/fpp.toml:1: ")
+    );
+}
+
+#[test]
+fn source_files() {
+    let root = std::env::temp_dir().join(format!("fpp_manifest_{}", std::process::id()));
+    for dir in ["Ref/test", "Fw"] {
+        std::fs::create_dir_all(root.join(dir)).unwrap();
+    }
+    for file in [
+        "Ref/Top.fpp",
+        "Ref/Top.fppi",
+        "Ref/test/Test.fpp",
+        "Fw/Cmd.fpp",
+    ] {
+        std::fs::write(root.join(file), "").unwrap();
+    }
+
+    let manifest = Manifest::parse(
+        &root.join(Manifest::FILE_NAME),
+        "[project]\nsources = [\"Ref\"]\nexclude = [\"Ref/test\"]\nframework = [\"Fw\"]",
+    )
+    .unwrap();
+    let files = manifest.source_files();
+
+    // The bundled framework replaces the one on disk unless it is preferred
+    let builtin = Manifest::parse(
+        &root.join(Manifest::FILE_NAME),
+        "[project]\nsources = [\"Ref\"]\nexclude = [\"Ref/test\"]\nframework = [\"Fw\"]\n\
+         builtin-framework = true",
+    )
+    .unwrap();
+    let mut prefer_disk = builtin.clone();
+    prefer_disk.prefer_disk_framework = true;
+    let mut missing = prefer_disk.clone();
+    missing.framework = vec![root.join("lib/fprime/Fw")];
+
+    let builtin_files = builtin.source_files();
+    let prefer_disk_files = prefer_disk.source_files();
+    let uses_builtin = (
+        builtin.uses_builtin_framework(),
+        prefer_disk.uses_builtin_framework(),
+        missing.uses_builtin_framework(),
+    );
+    std::fs::remove_dir_all(&root).unwrap();
+
+    assert_eq!(
+        files,
+        vec![root.join("Fw/Cmd.fpp"), root.join("Ref/Top.fpp")]
+    );
+    assert_eq!(builtin_files, vec![root.join("Ref/Top.fpp")]);
+    assert_eq!(prefer_disk_files, files);
+    assert_eq!(uses_builtin, (true, false, true));
+}
@@ -1,6 +1,6 @@
 use crate::token::KeywordKind::*;
 use crate::token::TokenKind::*;
-use crate::token::{Token, TokenKind};
+use crate::token::{KeywordKind, Token, TokenKind};
 use std::str::Chars;
 
 pub struct LexerError {
@@ -68,6 +68,127 @@ pub fn is_id_continue(c: char) -> bool {
     unicode_ident::is_xid_continue(c)
 }
 
+/// The keyword spelled `text`, if any. Identifiers spelled like a keyword
+/// must be escaped with `$`.
+pub fn keyword(text: &str) -> Option<KeywordKind> {
+    Some(match text {
+        "action" => Action,
+        "active" => Active,
+        "activity" => Activity,
+        "always" => Always,
+        "array" => Array,
+        "assert" => Assert,
+        "async" => Async,
+        "at" => At,
+        "base" => Base,
+        "block" => Block,
+        "bool" => Bool,
+        "change" => Change,
+        "command" => Command,
+        "component" => Component,
+        "connections" => Connections,
+        "constant" => Constant,
+        "container" => Container,
+        "cpu" => Cpu,
+        "default" => Default,
+        "diagnostic" => Diagnostic,
+        "dictionary" => Dictionary,
+        "do" => Do,
+        "drop" => Drop,
+        "else" => Else,
+        "enter" => Enter,
+        "entry" => Entry,
+        "enum" => Enum,
+        "event" => Event,
+        "every" => Every,
+        "exit" => Exit,
+        "external" => External,
+        "F32" => F32,
+        "F64" => F64,
+        "false" => False,
+        "fatal" => Fatal,
+        "format" => Format,
+        "get" => Get,
+        "group" => Group,
+        "guard" => Guard,
+        "guarded" => Guarded,
+        "health" => Health,
+        "high" => High,
+        "hook" => Hook,
+        "I16" => I16,
+        "I32" => I32,
+        "I64" => I64,
+        "I8" => I8,
+        "id" => Id,
+        "if" => If,
+        "implements" => Implements,
+        "import" => Import,
+        "include" => Include,
+        "initial" => Initial,
+        "input" => Input,
+        "instance" => Instance,
+        "interface" => Interface,
+        "internal" => Internal,
+        "choice" => Choice,
+        "locate" => Locate,
+        "low" => Low,
+        "machine" => Machine,
+        "match" => Match,
+        "module" => Module,
+        "omit" => Omit,
+        "on" => On,
+        "opcode" => Opcode,
+        "orange" => Orange,
+        "output" => Output,
+        "packet" => Packet,
+        "packets" => Packets,
+        "param" => Param,
+        "passive" => Passive,
+        "phase" => Phase,
+        "port" => Port,
+        "priority" => Priority,
+        "product" => Product,
+        "queue" => Queue,
+        "queued" => Queued,
+        "record" => Record,
+        "recv" => Recv,
+        "red" => Red,
+        "ref" => Ref,
+        "reg" => Reg,
+        "request" => Request,
+        "resp" => Resp,
+        "save" => Save,
+        "send" => Send,
+        "serial" => Serial,
+        "set" => Set,
+        "severity" => Severity,
+        "signal" => Signal,
+        "size" => Size,
+        "stack" => Stack,
+        "state" => State,
+        "string" => String_,
+        "struct" => Struct,
+        "sync" => Sync,
+        "telemetry" => Telemetry,
+        "text" => Text,
+        "throttle" => Throttle,
+        "time" => Time,
+        "topology" => Topology,
+        "true" => True,
+        "type" => Type,
+        "U16" => U16,
+        "U32" => U32,
+        "U64" => U64,
+        "U8" => U8,
+        "unmatched" => Unmatched,
+        "update" => Update,
+        "warning" => Warning,
+        "with" => With,
+        "yellow" => Yellow,
+        _ => return None,
+    })
+}
+
 impl<'a> Iterator for Lexer<'a> {
     type Item = Token;
 
@@ -82,122 +203,7 @@ impl<'a> Iterator for Lexer<'a> {
                 let kind = match (raw_kind, self.escaped_identifier) {
                     (Identifier, false) => {
                         // Check if this is a keyword
-                        match &self.content[start..end] {
-                            "action" => Keyword(Action),
-                            "active" => Keyword(Active),
-                            "activity" => Keyword(Activity),
-                            "always" => Keyword(Always),
-                            "array" => Keyword(Array),
-                            "assert" => Keyword(Assert),
-                            "async" => Keyword(Async),
-                            "at" => Keyword(At),
-                            "base" => Keyword(Base),
-                            "block" => Keyword(Block),
-                            "bool" => Keyword(Bool),
-                            "change" => Keyword(Change),
-                            "command" => Keyword(Command),
-                            "component" => Keyword(Component),
-                            "connections" => Keyword(Connections),
-                            "constant" => Keyword(Constant),
-                            "container" => Keyword(Container),
-                            "cpu" => Keyword(Cpu),
-                            "default" => Keyword(Default),
-                            "diagnostic" => Keyword(Diagnostic),
-                            "dictionary" => Keyword(Dictionary),
-                            "do" => Keyword(Do),
-                            "drop" => Keyword(Drop),
-                            "else" => Keyword(Else),
-                            "enter" => Keyword(Enter),
-                            "entry" => Keyword(Entry),
-                            "enum" => Keyword(Enum),
-                            "event" => Keyword(Event),
-                            "every" => Keyword(Every),
-                            "exit" => Keyword(Exit),
-                            "external" => Keyword(External),
-                            "F32" => Keyword(F32),
-                            "F64" => Keyword(F64),
-                            "false" => Keyword(False),
-                            "fatal" => Keyword(Fatal),
-                            "format" => Keyword(Format),
-                            "get" => Keyword(Get),
-                            "group" => Keyword(Group),
-                            "guard" => Keyword(Guard),
-                            "guarded" => Keyword(Guarded),
-                            "health" => Keyword(Health),
-                            "high" => Keyword(High),
-                            "hook" => Keyword(Hook),
-                            "I16" => Keyword(I16),
-                            "I32" => Keyword(I32),
-                            "I64" => Keyword(I64),
-                            "I8" => Keyword(I8),
-                            "id" => Keyword(Id),
-                            "if" => Keyword(If),
-                            "implements" => Keyword(Implements),
-                            "import" => Keyword(Import),
-                            "include" => Keyword(Include),
-                            "initial" => Keyword(Initial),
-                            "input" => Keyword(Input),
-                            "instance" => Keyword(Instance),
-                            "interface" => Keyword(Interface),
-                            "internal" => Keyword(Internal),
-                            "choice" => Keyword(Choice),
-                            "locate" => Keyword(Locate),
-                            "low" => Keyword(Low),
-                            "machine" => Keyword(Machine),
-                            "match" => Keyword(Match),
-                            "module" => Keyword(Module),
-                            "omit" => Keyword(Omit),
-                            "on" => Keyword(On),
-                            "opcode" => Keyword(Opcode),
-                            "orange" => Keyword(Orange),
-                            "output" => Keyword(Output),
-                            "packet" => Keyword(Packet),
-                            "packets" => Keyword(Packets),
-                            "param" => Keyword(Param),
-                            "passive" => Keyword(Passive),
-                            "phase" => Keyword(Phase),
-                            "port" => Keyword(Port),
-                            "priority" => Keyword(Priority),
-                            "product" => Keyword(Product),
-                            "queue" => Keyword(Queue),
-                            "queued" => Keyword(Queued),
-                            "record" => Keyword(Record),
-                            "recv" => Keyword(Recv),
-                            "red" => Keyword(Red),
-                            "ref" => Keyword(Ref),
-                            "reg" => Keyword(Reg),
-                            "request" => Keyword(Request),
-                            "resp" => Keyword(Resp),
-                            "save" => Keyword(Save),
-                            "send" => Keyword(Send),
-                            "serial" => Keyword(Serial),
-                            "set" => Keyword(Set),
-                            "severity" => Keyword(Severity),
-                            "signal" => Keyword(Signal),
-                            "size" => Keyword(Size),
-                            "stack" => Keyword(Stack),
-                            "state" => Keyword(State),
-                            "string" => Keyword(String_),
-                            "struct" => Keyword(Struct),
-                            "sync" => Keyword(Sync),
-                            "telemetry" => Keyword(Telemetry),
-                            "text" => Keyword(Text),
-                            "throttle" => Keyword(Throttle),
-                            "time" => Keyword(Time),
-                            "topology" => Keyword(Topology),
-                            "true" => Keyword(True),
-                            "type" => Keyword(Type),
-                            "U16" => Keyword(U16),
-                            "U32" => Keyword(U32),
-                            "U64" => Keyword(U64),
-                            "U8" => Keyword(U8),
-                            "unmatched" => Keyword(Unmatched),
-                            "update" => Keyword(Update),
-                            "warning" => Keyword(Warning),
-                            "with" => Keyword(With),
-                            "yellow" => Keyword(Yellow),
-                            _ => Identifier,
-                        }
+                        keyword(&self.content[start..end]).map_or(Identifier, Keyword)
                     }
                     (Identifier, true) => Identifier,
                     _ => raw_kind,
@@ -106,7 +106,8 @@ fn member_list(
     let m = p.start();
 
     while !p.at(ket) && !p.at(EOF) {
-        if p.at(bra) {
+        // Nested arrays are valid expressions, only stray blocks are skipped
+        if bra == LEFT_CURLY && p.at(LEFT_CURLY) {
             error_block(p, expected_error_msg);
             continue;
         }
@@ -30,8 +30,6 @@ pub(super) fn def_component(p: &mut Parser) {
 }
 
 pub(super) fn component_member(p: &mut Parser) {
-    p.eat(DICTIONARY_KW);
-
     match p.current() {
         DICTIONARY_KW if p.nth_at(1, TYPE_KW) => types::type_alias_or_abstract(p),
         TYPE_KW => types::type_alias_or_abstract(p),
@@ -90,7 +88,7 @@ fn spec_state_machine_instance(p: &mut Parser) {
 fn spec_command(p: &mut Parser) {
     let m = p.start();
     match p.current() {
-        ASYNC_KW | GUARD_KW | SYNC_KW => p.bump_any(),
+        ASYNC_KW | GUARDED_KW | SYNC_KW => p.bump_any(),
         _ => unreachable!(),
     }
 
@@ -154,7 +152,7 @@ fn spec_port_instance_special(p: &mut Parser) {
     let m = p.start();
 
     match p.current() {
-        ASYNC_KW | SYNC_KW | GUARD_KW => {
+        ASYNC_KW | SYNC_KW | GUARDED_KW => {
             p.bump_any();
         }
         _ => {}
@@ -316,7 +314,7 @@ fn spec_port_matching(p: &mut Parser) {
     p.expect(WITH_KW);
     name_ref(p);
 
-    m.complete(p, MATCH_KW);
+    m.complete(p, SPEC_PORT_MATCHING);
 }
 
 fn spec_param(p: &mut Parser) {
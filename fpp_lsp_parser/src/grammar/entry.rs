@@ -86,12 +86,56 @@ pub(crate) fn topology_entry(p: &mut Parser) {
 
 pub(crate) fn tlm_packet_entry(p: &mut Parser) {
     let m = p.start();
+    while !p.at(EOF) {
+        if p.at(LEFT_CURLY) {
+            error_block(p, "expected telemetry packet member");
+            continue;
+        }
+
+        while p.at(EOL) || p.at(COMMA) {
+            p.bump_any();
+        }
+
+        if p.at(EOF) {
+            break;
+        }
+
+        topology::tlm_packet_member(p);
+        match p.current() {
+            COMMA | EOL | EOF => {}
+            _ => {
+                p.err_recover("expected `,`", MEMBER_RECOVERY_SET);
+            }
+        }
+    }
 
     m.complete(p, ROOT);
 }
 
 pub(crate) fn tlm_packet_set_entry(p: &mut Parser) {
     let m = p.start();
+    while !p.at(EOF) {
+        if p.at(LEFT_CURLY) {
+            error_block(p, "expected telemetry packet set member");
+            continue;
+        }
+
+        while p.at(EOL) || p.at(COMMA) {
+            p.bump_any();
+        }
+
+        if p.at(EOF) {
+            break;
+        }
+
+        topology::tlm_packet_set_member(p);
+        match p.current() {
+            COMMA | EOL | EOF => {}
+            _ => {
+                p.err_recover("expected `,`", MEMBER_RECOVERY_SET);
+            }
+        }
+    }
 
     m.complete(p, ROOT);
 }
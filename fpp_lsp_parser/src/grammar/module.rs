@@ -55,6 +55,7 @@ fn spec_loc(p: &mut Parser) {
     assert!(p.at(LOCATE_KW));
     let m = p.start();
     p.bump(LOCATE_KW);
+    p.eat(DICTIONARY_KW);
     match p.current() {
         COMPONENT_KW | CONSTANT_KW | INSTANCE_KW | PORT_KW | TYPE_KW | INTERFACE_KW => {
             p.bump_any();
@@ -23,10 +23,20 @@ impl<'a> LexedStr<'a> {
     pub fn new(text: &'a str) -> LexedStr<'a> {
         let mut conv = Converter::new(text);
 
-        let lexer = fpp_lexer::Lexer::new(text);
-        for token in lexer {
+        let mut lexer = fpp_lexer::Lexer::new(text);
+        let mut n_errors = 0;
+        while let Some(token) = lexer.next() {
             let token_text = &text[conv.offset..][..token.len];
-            conv.push(token.kind.into(), token_text.len(), vec![]);
+
+            // Errors reported while lexing this token belong to it
+            let errors: Vec<String> = lexer
+                .errors()
+                .skip(n_errors)
+                .map(|e| e.msg.clone())
+                .collect();
+            n_errors += errors.len();
+
+            conv.push(token.kind.into(), token_text.len(), errors);
         }
 
         conv.finalize_with_eof()
@@ -2,6 +2,7 @@ mod event;
 mod grammar;
 mod input;
 mod lexed_str;
+mod lower;
 mod output;
 mod parser;
 mod ptr;
@@ -23,7 +24,7 @@ pub use visitor::*;
 
 pub use crate::{
     ptr::{AstPtr, SyntaxNodePtr},
-    syntax_error::SyntaxError,
+    syntax_error::{SyntaxError, SyntaxErrorKind},
     syntax_node::{
         FppLanguage, PreorderWithTokens, SyntaxElement, SyntaxElementChildren, SyntaxNode,
         SyntaxNodeChildren, SyntaxToken, SyntaxTreeBuilder,
@@ -107,7 +108,7 @@ pub(crate) fn build_tree(
             text_range.start.try_into().unwrap(),
             text_range.end.try_into().unwrap(),
         );
-        errors.push(SyntaxError::new(err, text_range))
+        errors.push(SyntaxError::new_invalid_token(err, text_range))
     }
 
     (node, errors, is_eof)
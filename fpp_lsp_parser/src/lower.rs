@@ -0,0 +1,1292 @@
+//! Lowering of the lossless syntax tree into the `fpp_ast` tree.
+//!
+//! The language server parses every file once with the error tolerant grammar
+//! in this crate. Semantic analysis runs on `fpp_ast`, so the AST is derived
+//! from the syntax tree here. Spans and annotations are computed the same way
+//! `fpp_parser` computes them so both trees are interchangeable.
+
+use crate::{Parse, SyntaxError, SyntaxKind, SyntaxKind::*, SyntaxNode, SyntaxToken, TextRange};
+use fpp_ast::*;
+use fpp_core::{BytePos, Diagnostic, Level, SourceFile, Span};
+
+impl Parse {
+    /// Lower the syntax tree of a `.fpp` file into a translation unit
+    pub fn lower_trans_unit(&self, file: SourceFile) -> TransUnit {
+        TransUnit(self.lower_module_members(file, None))
+    }
+
+    pub fn lower_module_members(
+        &self,
+        file: SourceFile,
+        include_span: Option<Span>,
+    ) -> Vec<ModuleMember> {
+        let lower = Lower::new(self, file, include_span);
+        lower.annotated(Some(self.syntax_node()), Lower::module_member)
+    }
+
+    pub fn lower_component_members(
+        &self,
+        file: SourceFile,
+        include_span: Option<Span>,
+    ) -> Vec<ComponentMember> {
+        let lower = Lower::new(self, file, include_span);
+        lower.annotated(Some(self.syntax_node()), Lower::component_member)
+    }
+
+    pub fn lower_topology_members(
+        &self,
+        file: SourceFile,
+        include_span: Option<Span>,
+    ) -> Vec<TopologyMember> {
+        let lower = Lower::new(self, file, include_span);
+        lower.annotated(Some(self.syntax_node()), Lower::topology_member)
+    }
+
+    pub fn lower_tlm_packet_set_members(
+        &self,
+        file: SourceFile,
+        include_span: Option<Span>,
+    ) -> Vec<TlmPacketSetMember> {
+        let lower = Lower::new(self, file, include_span);
+        lower.annotated(Some(self.syntax_node()), Lower::tlm_packet_set_member)
+    }
+
+    pub fn lower_tlm_packet_members(
+        &self,
+        file: SourceFile,
+        include_span: Option<Span>,
+    ) -> Vec<TlmPacketMember> {
+        let lower = Lower::new(self, file, include_span);
+        lower.sequence(Some(self.syntax_node()), Lower::tlm_packet_member)
+    }
+}
+
+/// Tokens that never start or end an AST node
+fn is_trivia(kind: SyntaxKind) -> bool {
+    matches!(
+        kind,
+        WHITESPACE | COMMENT | EOL | PRE_ANNOTATION | POST_ANNOTATION
+    )
+}
+
+fn first_token(node: &SyntaxNode) -> Option<SyntaxToken> {
+    node.descendants_with_tokens()
+        .filter_map(|element| element.into_token())
+        .find(|token| !is_trivia(token.kind()))
+}
+
+fn last_token(node: &SyntaxNode) -> Option<SyntaxToken> {
+    let start = node.text_range().start();
+    let mut token = node.last_token();
+    while let Some(current) = token {
+        if current.text_range().start() < start {
+            return None;
+        }
+
+        if !is_trivia(current.kind()) {
+            return Some(current);
+        }
+
+        token = current.prev_token();
+    }
+
+    None
+}
+
+/// Range of a node without the leading and trailing trivia
+fn node_range(node: &SyntaxNode) -> TextRange {
+    match (first_token(node), last_token(node)) {
+        (Some(first), Some(last)) => {
+            TextRange::new(first.text_range().start(), last.text_range().end())
+        }
+        _ => node.text_range(),
+    }
+}
+
+fn child(node: &SyntaxNode, kind: SyntaxKind) -> Option<SyntaxNode> {
+    node.children().find(|c| c.kind() == kind)
+}
+
+fn token(node: &SyntaxNode, kind: SyntaxKind) -> Option<SyntaxToken> {
+    node.children_with_tokens()
+        .filter_map(|element| element.into_token())
+        .find(|t| t.kind() == kind)
+}
+
+fn tokens(node: &SyntaxNode) -> impl Iterator<Item = SyntaxToken> {
+    node.children_with_tokens()
+        .filter_map(|element| element.into_token())
+        .filter(|t| !is_trivia(t.kind()))
+}
+
+fn first_token_kind(node: &SyntaxNode) -> Option<SyntaxKind> {
+    tokens(node).next().map(|t| t.kind())
+}
+
+/// Identifiers may be escaped keywords, the `$` is not part of the name
+fn ident_text(token: &SyntaxToken) -> String {
+    let text = token.text();
+    text.strip_prefix('$').unwrap_or(text).to_string()
+}
+
+/// Contents of a string literal between the quotes
+/// Multiline strings have the indentation of their first line removed from every line
+/// Unterminated literals, which are typed all the time in an editor, keep the text after
+/// the opening quotes
+fn string_text(text: &str) -> String {
+    if let Some(body) = text.strip_prefix("\"\"\"") {
+        let body = body.strip_suffix("\"\"\"").unwrap_or(body);
+
+        // The indent is counted on the first line, skipping a newline right after the quotes
+        let first_line = body.strip_prefix('\n').unwrap_or(body);
+        let indent = first_line.bytes().take_while(|c| *c == b' ').count();
+        body.split('\n')
+            .map(|l| {
+                if l.len() > indent {
+                    l.get(indent..).unwrap_or("")
+                } else {
+                    ""
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    } else {
+        let body = text.strip_prefix('"').unwrap_or(text);
+        body.strip_suffix('"').unwrap_or(body).to_string()
+    }
+}
+
+struct Lower {
+    file: SourceFile,
+    include_span: Option<Span>,
+    errors: Vec<SyntaxError>,
+}
+
+impl Lower {
+    fn new(parse: &Parse, file: SourceFile, include_span: Option<Span>) -> Lower {
+        let lower = Lower {
+            file,
+            include_span,
+            errors: parse.errors(),
+        };
+
+        let eof = parse.syntax_node().text_range().end();
+        for err in &lower.errors {
+            Diagnostic::new(
+                lower.span(err.range()),
+                Level::Error,
+                format!("syntax error: {err}"),
+            )
+            .code(err.code(eof))
+            .emit();
+        }
+
+        lower
+    }
+
+    fn span(&self, range: TextRange) -> Span {
+        Span::new(
+            self.file,
+            range.start().into(),
+            BytePos::from(range.len()),
+            self.include_span,
+        )
+    }
+
+    fn node(&self, node: &SyntaxNode) -> fpp_core::Node {
+        fpp_core::Node::new(self.span(node_range(node)))
+    }
+
+    /// Create a node for a definition with a body
+    /// The node is marked as recovered if a syntax error was reported inside it
+    fn body_node(&self, node: &SyntaxNode) -> fpp_core::Node {
+        let range = node.text_range();
+        let id = self.node(node);
+        if self
+            .errors
+            .iter()
+            .any(|err| range.contains_inclusive(err.range().start()))
+        {
+            id.mark_recovered();
+        }
+
+        id
+    }
+
+    fn token_node(&self, token: &SyntaxToken) -> fpp_core::Node {
+        fpp_core::Node::new(self.span(token.text_range()))
+    }
+
+    /// Lower every member of a sequence and attach the surrounding annotations
+    fn annotated<T: AstNode>(
+        &self,
+        list: Option<SyntaxNode>,
+        lower: fn(&Lower, &SyntaxNode) -> Option<T>,
+    ) -> Vec<T> {
+        match list {
+            None => vec![],
+            Some(list) => list
+                .children()
+                .filter_map(|member| {
+                    let out = lower(self, &member)?;
+                    fpp_core::Node::annotate(
+                        &out.id(),
+                        Self::pre_annotation(&member),
+                        Self::post_annotation(&member),
+                    );
+                    Some(out)
+                })
+                .collect(),
+        }
+    }
+
+    fn sequence<T>(
+        &self,
+        list: Option<SyntaxNode>,
+        lower: fn(&Lower, &SyntaxNode) -> Option<T>,
+    ) -> Vec<T> {
+        match list {
+            None => vec![],
+            Some(list) => list
+                .children()
+                .filter_map(|member| lower(self, &member))
+                .collect(),
+        }
+    }
+
+    /// Pre-annotations are the annotations directly above a member
+    fn pre_annotation(member: &SyntaxNode) -> Vec<String> {
+        let mut out = vec![];
+        let mut token = first_token(member).and_then(|t| t.prev_token());
+        while let Some(current) = token {
+            match current.kind() {
+                WHITESPACE | EOL | COMMENT => {}
+                PRE_ANNOTATION => out.push(current.text()[1..].trim().to_string()),
+                _ => break,
+            }
+
+            token = current.prev_token();
+        }
+
+        out.reverse();
+        out
+    }
+
+    /// Post-annotations follow a member on the same line, after its terminator
+    fn post_annotation(member: &SyntaxNode) -> Vec<String> {
+        let mut out = vec![];
+        let mut token = last_token(member).and_then(|t| t.next_token());
+        while let Some(current) = token.clone() {
+            match current.kind() {
+                WHITESPACE => token = current.next_token(),
+                SEMI | COMMA | EOL | COMMENT => {
+                    token = current.next_token();
+                    break;
+                }
+                _ => break,
+            }
+        }
+
+        while let Some(current) = token {
+            match current.kind() {
+                WHITESPACE | EOL | COMMENT => {}
+                POST_ANNOTATION => out.push(current.text()[2..].trim().to_string()),
+                _ => break,
+            }
+
+            token = current.next_token();
+        }
+
+        out
+    }
+
+    fn name(&self, node: Option<SyntaxNode>) -> Option<Name> {
+        let ident = token(&node?, IDENT)?;
+        Some(Name {
+            node_id: self.token_node(&ident),
+            data: ident_text(&ident),
+        })
+    }
+
+    fn ident(&self, token: &SyntaxToken) -> Ident {
+        Ident {
+            node_id: self.token_node(token),
+            data: ident_text(token),
+        }
+    }
+
+    fn name_ref(&self, node: &SyntaxNode) -> Option<Ident> {
+        match node.kind() {
+            NAME_REF => Some(self.ident(&token(node, IDENT)?)),
+            _ => None,
+        }
+    }
+
+    fn lit_string(&self, token: Option<SyntaxToken>) -> Option<LitString> {
+        let token = token?;
+        let data = string_text(token.text());
+        let offset = if token.text().starts_with("\"\"\"") {
+            3
+        } else {
+            1
+        };
+
+        let start: BytePos = token.text_range().start().into();
+        Some(LitString {
+            node_id: self.token_node(&token),
+            inner_span: Span::new(
+                self.file,
+                start + offset,
+                data.len() as BytePos,
+                self.include_span,
+            ),
+            data,
+        })
+    }
+
+    fn is_dictionary_def(node: &SyntaxNode) -> bool {
+        token(node, DICTIONARY_KW).is_some()
+    }
+
+    fn module_member(&self, node: &SyntaxNode) -> Option<ModuleMember> {
+        Some(match node.kind() {
+            DEF_ABSTRACT_TYPE => ModuleMember::DefAbsType(self.def_abs_type(node)?),
+            DEF_ALIAS_TYPE => ModuleMember::DefAliasType(self.def_alias_type(node)?),
+            DEF_ARRAY => ModuleMember::DefArray(self.def_array(node)?),
+            DEF_COMPONENT => ModuleMember::DefComponent(self.def_component(node)?),
+            DEF_COMPONENT_INSTANCE => {
+                ModuleMember::DefComponentInstance(self.def_component_instance(node)?)
+            }
+            DEF_CONSTANT => ModuleMember::DefConstant(self.def_constant(node)?),
+            DEF_ENUM => ModuleMember::DefEnum(self.def_enum(node)?),
+            DEF_INTERFACE => ModuleMember::DefInterface(self.def_interface(node)?),
+            DEF_MODULE => ModuleMember::DefModule(self.def_module(node)?),
+            DEF_PORT => ModuleMember::DefPort(self.def_port(node)?),
+            DEF_STATE_MACHINE => ModuleMember::DefStateMachine(self.def_state_machine(node)?),
+            DEF_STRUCT => ModuleMember::DefStruct(self.def_struct(node)?),
+            DEF_TOPOLOGY => ModuleMember::DefTopology(self.def_topology(node)?),
+            SPEC_INCLUDE => ModuleMember::SpecInclude(self.spec_include(node)?),
+            SPEC_LOC => ModuleMember::SpecLoc(self.spec_loc(node)?),
+            _ => return None,
+        })
+    }
+
+    fn def_abs_type(&self, node: &SyntaxNode) -> Option<DefAbsType> {
+        Some(DefAbsType {
+            node_id: self.node(node),
+            name: self.name(child(node, NAME))?,
+        })
+    }
+
+    fn def_alias_type(&self, node: &SyntaxNode) -> Option<DefAliasType> {
+        Some(DefAliasType {
+            node_id: self.node(node),
+            name: self.name(child(node, NAME))?,
+            type_name: self.type_name(&child(node, TYPE_NAME)?)?,
+            is_dictionary_def: Self::is_dictionary_def(node),
+        })
+    }
+
+    fn def_array(&self, node: &SyntaxNode) -> Option<DefArray> {
+        Some(DefArray {
+            node_id: self.node(node),
+            name: self.name(child(node, NAME))?,
+            size: self.child_expr(node, INDEX_OR_SIZE)?,
+            elt_type: self.type_name(&child(node, TYPE_NAME)?)?,
+            default: self.child_expr(node, DEFAULT),
+            format: self.format(node),
+            is_dictionary_def: Self::is_dictionary_def(node),
+        })
+    }
+
+    fn format(&self, node: &SyntaxNode) -> Option<LitString> {
+        self.lit_string(token(&child(node, FORMAT)?, LITERAL_STRING))
+    }
+
+    fn def_component(&self, node: &SyntaxNode) -> Option<DefComponent> {
+        let kind = match first_token_kind(node)? {
+            ACTIVE_KW => ComponentKind::Active,
+            PASSIVE_KW => ComponentKind::Passive,
+            QUEUED_KW => ComponentKind::Queued,
+            _ => return None,
+        };
+
+        Some(DefComponent {
+            node_id: self.body_node(node),
+            kind,
+            name: self.name(child(node, NAME))?,
+            members: self.annotated(child(node, COMPONENT_MEMBER_LIST), Lower::component_member),
+        })
+    }
+
+    fn def_component_instance(&self, node: &SyntaxNode) -> Option<DefComponentInstance> {
+        Some(DefComponentInstance {
+            node_id: self.node(node),
+            name: self.name(child(node, NAME))?,
+            component: self.qual_ident(&child(node, QUAL_IDENT)?)?,
+            base_id: self.child_expr(node, BASE_ID)?,
+            impl_type: child(node, COMPONENT_INSTANCE_TYPE)
+                .and_then(|n| self.lit_string(token(&n, LITERAL_STRING))),
+            file: child(node, COMPONENT_INSTANCE_FILE)
+                .and_then(|n| self.lit_string(token(&n, LITERAL_STRING))),
+            queue_size: self.child_expr(node, QUEUE_SIZE),
+            stack_size: self.child_expr(node, STACK_SIZE),
+            priority: self.child_expr(node, PRIORITY),
+            cpu: self.child_expr(node, CPU),
+            init_specs: self.annotated(child(node, INIT_SPEC_LIST), Lower::spec_init),
+        })
+    }
+
+    fn spec_init(&self, node: &SyntaxNode) -> Option<SpecInit> {
+        match node.kind() {
+            SPEC_INIT => Some(SpecInit {
+                node_id: self.node(node),
+                phase: self.expr(&child(node, EXPR)?)?,
+                code: self.lit_string(token(node, LITERAL_STRING))?,
+            }),
+            _ => None,
+        }
+    }
+
+    fn def_constant(&self, node: &SyntaxNode) -> Option<DefConstant> {
+        Some(DefConstant {
+            node_id: self.node(node),
+            name: self.name(child(node, NAME))?,
+            value: self.expr(&child(node, EXPR)?)?,
+            is_dictionary_def: Self::is_dictionary_def(node),
+        })
+    }
+
+    fn def_enum(&self, node: &SyntaxNode) -> Option<DefEnum> {
+        Some(DefEnum {
+            node_id: self.body_node(node),
+            name: self.name(child(node, NAME))?,
+            type_name: child(node, TYPE_NAME).and_then(|n| self.type_name(&n)),
+            constants: self.annotated(child(node, ENUM_MEMBER_LIST), Lower::def_enum_constant),
+            default: self.child_expr(node, DEFAULT),
+            is_dictionary_def: Self::is_dictionary_def(node),
+        })
+    }
+
+    fn def_enum_constant(&self, node: &SyntaxNode) -> Option<DefEnumConstant> {
+        match node.kind() {
+            DEF_ENUM_CONSTANT => Some(DefEnumConstant {
+                node_id: self.node(node),
+                name: self.name(child(node, NAME))?,
+                value: child(node, EXPR).and_then(|n| self.expr(&n)),
+            }),
+            _ => None,
+        }
+    }
+
+    fn def_interface(&self, node: &SyntaxNode) -> Option<DefInterface> {
+        Some(DefInterface {
+            node_id: self.body_node(node),
+            name: self.name(child(node, NAME))?,
+            members: self.annotated(child(node, INTERFACE_MEMBER_LIST), Lower::interface_member),
+        })
+    }
+
+    fn interface_member(&self, node: &SyntaxNode) -> Option<InterfaceMember> {
+        Some(match node.kind() {
+            SPEC_INTERFACE_IMPORT => {
+                InterfaceMember::SpecInterfaceImport(self.spec_import_interface(node)?)
+            }
+            _ => InterfaceMember::SpecPortInstance(self.spec_port_instance(node)?),
+        })
+    }
+
+    fn def_module(&self, node: &SyntaxNode) -> Option<DefModule> {
+        Some(DefModule {
+            node_id: self.body_node(node),
+            name: self.name(child(node, NAME))?,
+            members: self.annotated(child(node, MODULE_MEMBER_LIST), Lower::module_member),
+        })
+    }
+
+    fn def_port(&self, node: &SyntaxNode) -> Option<DefPort> {
+        Some(DefPort {
+            node_id: self.node(node),
+            name: self.name(child(node, NAME))?,
+            params: self.formal_param_list(node),
+            return_type: child(node, TYPE_NAME).and_then(|n| self.type_name(&n)),
+        })
+    }
+
+    fn spec_loc(&self, node: &SyntaxNode) -> Option<SpecLoc> {
+        let kind = tokens(node).find_map(|t| match t.kind() {
+            COMPONENT_KW => Some(SpecLocKind::Component),
+            CONSTANT_KW => Some(SpecLocKind::Constant),
+            INSTANCE_KW => Some(SpecLocKind::Instance),
+            PORT_KW => Some(SpecLocKind::Port),
+            STATE_KW => Some(SpecLocKind::StateMachine),
+            TYPE_KW => Some(SpecLocKind::Type),
+            INTERFACE_KW => Some(SpecLocKind::Interface),
+            _ => None,
+        })?;
+
+        Some(SpecLoc {
+            node_id: self.node(node),
+            kind,
+            symbol: self.qual_ident(&child(node, QUAL_IDENT)?)?,
+            file: self.lit_string(token(node, LITERAL_STRING))?,
+            is_dictionary_def: Self::is_dictionary_def(node),
+        })
+    }
+
+    fn def_struct(&self, node: &SyntaxNode) -> Option<DefStruct> {
+        Some(DefStruct {
+            node_id: self.body_node(node),
+            name: self.name(child(node, NAME))?,
+            members: self.annotated(child(node, STRUCT_MEMBER_LIST), Lower::struct_type_member),
+            default: self.child_expr(node, DEFAULT),
+            is_dictionary_def: Self::is_dictionary_def(node),
+        })
+    }
+
+    fn struct_type_member(&self, node: &SyntaxNode) -> Option<StructTypeMember> {
+        match node.kind() {
+            STRUCT_MEMBER => Some(StructTypeMember {
+                node_id: self.node(node),
+                name: self.name(child(node, NAME))?,
+                size: self.child_expr(node, INDEX_OR_SIZE),
+                type_name: self.type_name(&child(node, TYPE_NAME)?)?,
+                format: self.format(node),
+            }),
+            _ => None,
+        }
+    }
+
+    fn def_topology(&self, node: &SyntaxNode) -> Option<DefTopology> {
+        let implements = match child(node, IMPLEMENTS_CLAUSE) {
+            None => vec![],
+            Some(clause) => clause
+                .children()
+                .filter_map(|n| self.qual_ident(&n))
+                .collect(),
+        };
+
+        Some(DefTopology {
+            node_id: self.body_node(node),
+            name: self.name(child(node, NAME))?,
+            members: self.annotated(child(node, TOPOLOGY_MEMBER_LIST), Lower::topology_member),
+            implements,
+        })
+    }
+
+    fn spec_include(&self, node: &SyntaxNode) -> Option<SpecInclude> {
+        Some(SpecInclude {
+            node_id: self.node(node),
+            file: self.lit_string(token(node, LITERAL_STRING))?,
+        })
+    }
+
+    fn spec_import_interface(&self, node: &SyntaxNode) -> Option<SpecInterfaceImport> {
+        Some(SpecInterfaceImport {
+            node_id: self.node(node),
+            interface: self.qual_ident(&child(node, QUAL_IDENT)?)?,
+        })
+    }
+
+    fn formal_param_list(&self, node: &SyntaxNode) -> FormalParamList {
+        self.annotated(child(node, FORMAL_PARAM_LIST), Lower::formal_param)
+    }
+
+    fn formal_param(&self, node: &SyntaxNode) -> Option<FormalParam> {
+        match node.kind() {
+            FORMAL_PARAM => Some(FormalParam {
+                node_id: self.node(node),
+                kind: match token(node, REF_KW) {
+                    Some(_) => FormalParamKind::Ref,
+                    None => FormalParamKind::Value,
+                },
+                name: self.name(child(node, NAME))?,
+                type_name: self.type_name(&child(node, TYPE_NAME)?)?,
+            }),
+            _ => None,
+        }
+    }
+
+    fn component_member(&self, node: &SyntaxNode) -> Option<ComponentMember> {
+        Some(match node.kind() {
+            DEF_ABSTRACT_TYPE => ComponentMember::DefAbsType(self.def_abs_type(node)?),
+            DEF_ALIAS_TYPE => ComponentMember::DefAliasType(self.def_alias_type(node)?),
+            DEF_ARRAY => ComponentMember::DefArray(self.def_array(node)?),
+            DEF_CONSTANT => ComponentMember::DefConstant(self.def_constant(node)?),
+            DEF_ENUM => ComponentMember::DefEnum(self.def_enum(node)?),
+            DEF_STATE_MACHINE => ComponentMember::DefStateMachine(self.def_state_machine(node)?),
+            DEF_STRUCT => ComponentMember::DefStruct(self.def_struct(node)?),
+            SPEC_COMMAND => ComponentMember::SpecCommand(self.spec_command(node)?),
+            SPEC_CONTAINER => ComponentMember::SpecContainer(self.spec_container(node)?),
+            SPEC_EVENT => ComponentMember::SpecEvent(self.spec_event(node)?),
+            SPEC_INCLUDE => ComponentMember::SpecInclude(self.spec_include(node)?),
+            SPEC_PORT_INSTANCE_INTERNAL => {
+                ComponentMember::SpecInternalPort(self.spec_internal_port(node)?)
+            }
+            SPEC_PARAM => ComponentMember::SpecParam(self.spec_param(node)?),
+            SPEC_PORT_INSTANCE_GENERAL | SPEC_PORT_INSTANCE_SPECIAL => {
+                ComponentMember::SpecPortInstance(self.spec_port_instance(node)?)
+            }
+            SPEC_PORT_MATCHING => ComponentMember::SpecPortMatching(self.spec_port_matching(node)?),
+            SPEC_RECORD => ComponentMember::SpecRecord(self.spec_record(node)?),
+            SPEC_STATE_MACHINE_INSTANCE => {
+                ComponentMember::SpecStateMachineInstance(self.spec_state_machine_instance(node)?)
+            }
+            SPEC_TELEMETRY => ComponentMember::SpecTlmChannel(self.spec_tlm_channel(node)?),
+            SPEC_INTERFACE_IMPORT => {
+                ComponentMember::SpecInterfaceImport(self.spec_import_interface(node)?)
+            }
+            _ => return None,
+        })
+    }
+
+    fn input_port_kind(kind: SyntaxKind) -> Option<InputPortKind> {
+        match kind {
+            ASYNC_KW => Some(InputPortKind::Async),
+            GUARDED_KW => Some(InputPortKind::Guarded),
+            SYNC_KW => Some(InputPortKind::Sync),
+            _ => None,
+        }
+    }
+
+    fn queue_full(node: &SyntaxNode) -> Option<QueueFull> {
+        match first_token_kind(&child(node, QUEUE_FULL)?)? {
+            ASSERT_KW => Some(QueueFull::Assert),
+            BLOCK_KW => Some(QueueFull::Block),
+            DROP_KW => Some(QueueFull::Drop),
+            HOOK_KW => Some(QueueFull::Hook),
+            _ => None,
+        }
+    }
+
+    fn spec_command(&self, node: &SyntaxNode) -> Option<SpecCommand> {
+        Some(SpecCommand {
+            node_id: self.node(node),
+            kind: Self::input_port_kind(first_token_kind(node)?)?,
+            name: self.name(child(node, NAME))?,
+            params: self.formal_param_list(node),
+            opcode: self.child_expr(node, OPCODE),
+            priority: self.child_expr(node, PRIORITY),
+            queue_full: Self::queue_full(node),
+        })
+    }
+
+    fn spec_container(&self, node: &SyntaxNode) -> Option<SpecContainer> {
+        Some(SpecContainer {
+            node_id: self.node(node),
+            name: self.name(child(node, NAME))?,
+            id: self.child_expr(node, ID),
+            default_priority: self.child_expr(node, DEFAULT_PRIORITY),
+        })
+    }
+
+    fn spec_record(&self, node: &SyntaxNode) -> Option<SpecRecord> {
+        Some(SpecRecord {
+            node_id: self.node(node),
+            name: self.name(child(node, NAME))?,
+            record_type: self.type_name(&child(node, TYPE_NAME)?)?,
+            is_array: token(node, ARRAY_KW).is_some(),
+            id: self.child_expr(node, ID),
+        })
+    }
+
+    fn spec_event(&self, node: &SyntaxNode) -> Option<SpecEvent> {
+        let mut severity_tokens = tokens(node)
+            .skip_while(|t| t.kind() != SEVERITY_KW)
+            .skip(1)
+            .map(|t| t.kind());
+
+        let severity = match (severity_tokens.next()?, severity_tokens.next()) {
+            (ACTIVITY_KW, Some(HIGH_KW)) => EventSeverity::ActivityHigh,
+            (ACTIVITY_KW, Some(LOW_KW)) => EventSeverity::ActivityLow,
+            (WARNING_KW, Some(HIGH_KW)) => EventSeverity::WarningHigh,
+            (WARNING_KW, Some(LOW_KW)) => EventSeverity::WarningLow,
+            (COMMAND_KW, _) => EventSeverity::Command,
+            (DIAGNOSTIC_KW, _) => EventSeverity::Diagnostic,
+            (FATAL_KW, _) => EventSeverity::Fatal,
+            _ => return None,
+        };
+
+        let throttle = match child(node, EVENT_THROTTLE) {
+            None => None,
+            Some(throttle) => Some(EventThrottle {
+                node_id: self.node(&throttle),
+                count: self.expr(&child(&throttle, EXPR)?)?,
+                every: self.child_expr(&throttle, EVERY),
+            }),
+        };
+
+        Some(SpecEvent {
+            node_id: self.node(node),
+            name: self.name(child(node, NAME))?,
+            params: self.formal_param_list(node),
+            severity,
+            id: self.child_expr(node, ID),
+            format: self.format(node)?,
+            throttle,
+        })
+    }
+
+    fn spec_internal_port(&self, node: &SyntaxNode) -> Option<SpecInternalPort> {
+        Some(SpecInternalPort {
+            node_id: self.node(node),
+            name: self.name(child(node, NAME))?,
+            params: self.formal_param_list(node),
+            priority: self.child_expr(node, PRIORITY),
+            queue_full: Self::queue_full(node),
+        })
+    }
+
+    fn spec_port_matching(&self, node: &SyntaxNode) -> Option<SpecPortMatching> {
+        let mut ports = node.children().filter_map(|n| self.name_ref(&n));
+        Some(SpecPortMatching {
+            node_id: self.node(node),
+            port1: ports.next()?,
+            port2: ports.next()?,
+        })
+    }
+
+    fn spec_param(&self, node: &SyntaxNode) -> Option<SpecParam> {
+        Some(SpecParam {
+            node_id: self.node(node),
+            name: self.name(child(node, NAME))?,
+            type_name: self.type_name(&child(node, TYPE_NAME)?)?,
+            default: self.child_expr(node, DEFAULT),
+            id: self.child_expr(node, ID),
+            set_opcode: self.child_expr(node, SET_OPCODE),
+            save_opcode: self.child_expr(node, SAVE_OPCODE),
+            is_external: token(node, EXTERNAL_KW).is_some(),
+        })
+    }
+
+    fn spec_port_instance(&self, node: &SyntaxNode) -> Option<SpecPortInstance> {
+        match node.kind() {
+            SPEC_PORT_INSTANCE_GENERAL => Some(SpecPortInstance::General(
+                self.spec_port_instance_general(node)?,
+            )),
+            SPEC_PORT_INSTANCE_SPECIAL => Some(SpecPortInstance::Special(
+                self.spec_port_instance_special(node)?,
+            )),
+            _ => None,
+        }
+    }
+
+    fn spec_port_instance_general(&self, node: &SyntaxNode) -> Option<SpecGeneralPortInstance> {
+        let kind = match first_token_kind(node)? {
+            OUTPUT_KW => GeneralPortInstanceKind::Output,
+            kind => GeneralPortInstanceKind::Input(Self::input_port_kind(kind)?),
+        };
+
+        Some(SpecGeneralPortInstance {
+            node_id: self.node(node),
+            kind,
+            name: self.name(child(node, NAME))?,
+            size: self.child_expr(node, INDEX_OR_SIZE),
+            port: child(node, QUAL_IDENT).and_then(|n| self.qual_ident(&n)),
+            priority: self.child_expr(node, PRIORITY),
+            queue_full: Self::queue_full(node),
+        })
+    }
+
+    fn spec_port_instance_special(&self, node: &SyntaxNode) -> Option<SpecSpecialPortInstance> {
+        let mut kind_tokens = tokens(node).map(|t| t.kind()).peekable();
+        let input_kind = kind_tokens
+            .next_if(|kind| Self::input_port_kind(*kind).is_some())
+            .and_then(Self::input_port_kind);
+
+        let kind = match (kind_tokens.next()?, kind_tokens.next()) {
+            (COMMAND_KW, Some(RECV_KW)) => SpecialPortInstanceKind::CommandRecv,
+            (COMMAND_KW, Some(REG_KW)) => SpecialPortInstanceKind::CommandReg,
+            (COMMAND_KW, Some(RESP_KW)) => SpecialPortInstanceKind::CommandResp,
+            (EVENT_KW, _) => SpecialPortInstanceKind::Event,
+            (PARAM_KW, Some(GET_KW)) => SpecialPortInstanceKind::ParamGet,
+            (PARAM_KW, Some(SET_KW)) => SpecialPortInstanceKind::ParamSet,
+            (PRODUCT_KW, Some(GET_KW)) => SpecialPortInstanceKind::ProductGet,
+            (PRODUCT_KW, Some(RECV_KW)) => SpecialPortInstanceKind::ProductRecv,
+            (PRODUCT_KW, Some(REQUEST_KW)) => SpecialPortInstanceKind::ProductRequest,
+            (PRODUCT_KW, Some(SEND_KW)) => SpecialPortInstanceKind::ProductSend,
+            (TELEMETRY_KW, _) => SpecialPortInstanceKind::Telemetry,
+            (TEXT_KW, _) => SpecialPortInstanceKind::TextEvent,
+            (TIME_KW, _) => SpecialPortInstanceKind::TimeGet,
+            _ => return None,
+        };
+
+        Some(SpecSpecialPortInstance {
+            node_id: self.node(node),
+            input_kind,
+            kind,
+            name: self.name(child(node, NAME))?,
+            priority: self.child_expr(node, PRIORITY),
+            queue_full: Self::queue_full(node),
+        })
+    }
+
+    fn spec_state_machine_instance(&self, node: &SyntaxNode) -> Option<SpecStateMachineInstance> {
+        Some(SpecStateMachineInstance {
+            node_id: self.node(node),
+            name: self.name(child(node, NAME))?,
+            state_machine: self.qual_ident(&child(node, QUAL_IDENT)?)?,
+            priority: self.child_expr(node, PRIORITY),
+            queue_full: Self::queue_full(node),
+        })
+    }
+
+    fn spec_tlm_channel(&self, node: &SyntaxNode) -> Option<SpecTlmChannel> {
+        let update = match token(node, UPDATE_KW) {
+            None => None,
+            Some(_) => match token(node, ALWAYS_KW) {
+                Some(_) => Some(TlmChannelUpdate::Always),
+                None => Some(TlmChannelUpdate::OnChange),
+            },
+        };
+
+        // Limit sequences are only distinguished by the keyword before them
+        let mut low = vec![];
+        let mut high = vec![];
+        let mut is_low = true;
+        for element in node.children_with_tokens() {
+            match element.kind() {
+                LOW_KW => is_low = true,
+                HIGH_KW => is_low = false,
+                LIMIT_SEQUENCE => {
+                    let limits = self.sequence(element.into_node(), Lower::limit);
+                    if is_low {
+                        low = limits;
+                    } else {
+                        high = limits;
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Some(SpecTlmChannel {
+            node_id: self.node(node),
+            name: self.name(child(node, NAME))?,
+            type_name: self.type_name(&child(node, TYPE_NAME)?)?,
+            id: self.child_expr(node, ID),
+            update,
+            format: self.format(node),
+            low,
+            high,
+        })
+    }
+
+    fn limit(&self, node: &SyntaxNode) -> Option<TlmChannelLimit> {
+        let kind = match first_token_kind(node)? {
+            ORANGE_KW => TlmChannelLimitKind::Orange,
+            RED_KW => TlmChannelLimitKind::Red,
+            YELLOW_KW => TlmChannelLimitKind::Yellow,
+            _ => return None,
+        };
+
+        Some(TlmChannelLimit {
+            node_id: self.node(node),
+            kind,
+            value: self.expr(&child(node, EXPR)?)?,
+        })
+    }
+
+    fn topology_member(&self, node: &SyntaxNode) -> Option<TopologyMember> {
+        Some(match node.kind() {
+            SPEC_INSTANCE => TopologyMember::SpecInstance(SpecInstance {
+                node_id: self.node(node),
+                instance: self.qual_ident(&child(node, QUAL_IDENT)?)?,
+            }),
+            SPEC_CONNECTION_GRAPH_DIRECT => {
+                TopologyMember::SpecDirectConnectionGraph(self.spec_connection_graph_direct(node)?)
+            }
+            SPEC_CONNECTION_GRAPH_PATTERN => TopologyMember::SpecPatternConnectionGraph(
+                self.spec_connection_graph_pattern(node)?,
+            ),
+            SPEC_INCLUDE => TopologyMember::SpecInclude(self.spec_include(node)?),
+            SPEC_TOP_PORT => TopologyMember::SpecTopPort(SpecTopPort {
+                node_id: self.node(node),
+                name: self.name(child(node, NAME))?,
+                underlying_port: self
+                    .port_instance_identifier(&child(node, PORT_INSTANCE_IDENTIFIER)?)?,
+            }),
+            TLM_PACKET_SET => TopologyMember::SpecTlmPacketSet(self.spec_tlm_packet_set(node)?),
+            _ => return None,
+        })
+    }
+
+    fn spec_connection_graph_direct(&self, node: &SyntaxNode) -> Option<SpecDirectConnectionGraph> {
+        Some(SpecDirectConnectionGraph {
+            node_id: self.node(node),
+            name: self.name(child(node, NAME))?,
+            connections: self.sequence(child(node, CONNECTION_MEMBER_LIST), Lower::connection),
+        })
+    }
+
+    fn connection(&self, node: &SyntaxNode) -> Option<Connection> {
+        if node.kind() != CONNECTION {
+            return None;
+        }
+
+        let from = child(node, CONNECTION_FROM)?;
+        let to = child(node, CONNECTION_TO)?;
+        Some(Connection {
+            node_id: self.node(node),
+            is_unmatched: token(node, UNMATCHED_KW).is_some(),
+            from_port: self.port_instance_identifier(&child(&from, PORT_INSTANCE_IDENTIFIER)?)?,
+            from_index: self.child_expr(&from, INDEX_OR_SIZE),
+            to_port: self.port_instance_identifier(&child(&to, PORT_INSTANCE_IDENTIFIER)?)?,
+            to_index: self.child_expr(&to, INDEX_OR_SIZE),
+        })
+    }
+
+    fn spec_connection_graph_pattern(
+        &self,
+        node: &SyntaxNode,
+    ) -> Option<SpecPatternConnectionGraph> {
+        let kind = match first_token_kind(node)? {
+            COMMAND_KW => ConnectionPatternKind::Command,
+            EVENT_KW => ConnectionPatternKind::Event,
+            HEALTH_KW => ConnectionPatternKind::Health,
+            PARAM_KW => ConnectionPatternKind::Param,
+            TELEMETRY_KW => ConnectionPatternKind::Telemetry,
+            TEXT_KW => ConnectionPatternKind::TextEvent,
+            TIME_KW => ConnectionPatternKind::Time,
+            _ => return None,
+        };
+
+        Some(SpecPatternConnectionGraph {
+            node_id: self.node(node),
+            kind,
+            source: self.qual_ident(&child(node, QUAL_IDENT)?)?,
+            targets: self.sequence(child(node, PATTERN_TARGET_MEMBER_LIST), Lower::qual_ident),
+        })
+    }
+
+    fn spec_tlm_packet_set(&self, node: &SyntaxNode) -> Option<SpecTlmPacketSet> {
+        let omitted =
+            child(node, TLM_PACKET_OMIT).and_then(|omit| child(&omit, TLM_PACKET_OMIT_MEMBER_LIST));
+
+        Some(SpecTlmPacketSet {
+            node_id: self.node(node),
+            name: self.name(child(node, NAME))?,
+            members: self.annotated(
+                child(node, TLM_PACKET_SET_MEMBER_LIST),
+                Lower::tlm_packet_set_member,
+            ),
+            omitted: self.sequence(omitted, Lower::tlm_channel_identifier),
+        })
+    }
+
+    fn tlm_packet_set_member(&self, node: &SyntaxNode) -> Option<TlmPacketSetMember> {
+        Some(match node.kind() {
+            SPEC_INCLUDE => TlmPacketSetMember::SpecInclude(self.spec_include(node)?),
+            SPEC_TLM_PACKET => TlmPacketSetMember::SpecTlmPacket(SpecTlmPacket {
+                node_id: self.node(node),
+                name: self.name(child(node, NAME))?,
+                id: self.child_expr(node, ID),
+                group: self.child_expr(node, GROUP)?,
+                members: self.sequence(
+                    child(node, TLM_PACKET_MEMBER_LIST),
+                    Lower::tlm_packet_member,
+                ),
+            }),
+            _ => return None,
+        })
+    }
+
+    fn tlm_packet_member(&self, node: &SyntaxNode) -> Option<TlmPacketMember> {
+        Some(match node.kind() {
+            SPEC_INCLUDE => TlmPacketMember::SpecInclude(self.spec_include(node)?),
+            TLM_CHANNEL_IDENTIFIER => {
+                TlmPacketMember::TlmChannelIdentifier(self.tlm_channel_identifier(node)?)
+            }
+            _ => return None,
+        })
+    }
+
+    /// Split an interface instance member into the qualified instance and the member name
+    /// Every qualified identifier in the instance spans the entire member
+    fn interface_instance_member(&self, node: &SyntaxNode) -> Option<(QualIdent, Ident)> {
+        let mut idents: Vec<_> = node
+            .children_with_tokens()
+            .filter_map(|element| element.into_token())
+            .filter(|t| t.kind() == IDENT)
+            .map(|t| self.ident(&t))
+            .collect();
+
+        if idents.len() < 2 {
+            return None;
+        }
+
+        let member = idents.pop()?;
+        Some((self.fold_qualified(node, idents)?, member))
+    }
+
+    fn port_instance_identifier(&self, node: &SyntaxNode) -> Option<PortInstanceIdentifier> {
+        let (interface_instance, port_name) = self.interface_instance_member(node)?;
+        Some(PortInstanceIdentifier {
+            node_id: self.node(node),
+            interface_instance,
+            port_name,
+        })
+    }
+
+    fn tlm_channel_identifier(&self, node: &SyntaxNode) -> Option<TlmChannelIdentifier> {
+        if node.kind() != TLM_CHANNEL_IDENTIFIER {
+            return None;
+        }
+
+        let (component_instance, channel_name) = self.interface_instance_member(node)?;
+        Some(TlmChannelIdentifier {
+            node_id: self.node(node),
+            component_instance,
+            channel_name,
+        })
+    }
+
+    fn fold_qualified(&self, node: &SyntaxNode, idents: Vec<Ident>) -> Option<QualIdent> {
+        let mut idents = idents.into_iter();
+        let first = idents.next()?;
+        Some(idents.fold(QualIdent::Unqualified(first), |q, name| {
+            QualIdent::Qualified(Qualified {
+                node_id: self.node(node),
+                qualifier: Box::new(q),
+                name,
+            })
+        }))
+    }
+
+    fn qual_ident(&self, node: &SyntaxNode) -> Option<QualIdent> {
+        if node.kind() != QUAL_IDENT {
+            return None;
+        }
+
+        let idents = node
+            .children_with_tokens()
+            .filter_map(|element| element.into_token())
+            .filter(|t| t.kind() == IDENT)
+            .map(|t| self.ident(&t))
+            .collect();
+
+        self.fold_qualified(node, idents)
+    }
+
+    fn def_state_machine(&self, node: &SyntaxNode) -> Option<DefStateMachine> {
+        Some(DefStateMachine {
+            node_id: self.body_node(node),
+            name: self.name(child(node, NAME))?,
+            members: child(node, STATE_MACHINE_MEMBER_LIST)
+                .map(|list| self.annotated(Some(list), Lower::state_machine_member)),
+        })
+    }
+
+    fn state_machine_member(&self, node: &SyntaxNode) -> Option<StateMachineMember> {
+        Some(match node.kind() {
+            SPEC_INITIAL_TRANSITION => {
+                StateMachineMember::SpecInitialTransition(self.spec_initial_transition(node)?)
+            }
+            DEF_STATE => StateMachineMember::DefState(self.def_state(node)?),
+            DEF_SIGNAL => StateMachineMember::DefSignal(DefSignal {
+                node_id: self.node(node),
+                name: self.name(child(node, NAME))?,
+                type_name: child(node, TYPE_NAME).and_then(|n| self.type_name(&n)),
+            }),
+            DEF_ACTION => StateMachineMember::DefAction(DefAction {
+                node_id: self.node(node),
+                name: self.name(child(node, NAME))?,
+                type_name: child(node, TYPE_NAME).and_then(|n| self.type_name(&n)),
+            }),
+            DEF_GUARD => StateMachineMember::DefGuard(DefGuard {
+                node_id: self.node(node),
+                name: self.name(child(node, NAME))?,
+                type_name: child(node, TYPE_NAME).and_then(|n| self.type_name(&n)),
+            }),
+            DEF_CHOICE => StateMachineMember::DefChoice(self.def_choice(node)?),
+            _ => return None,
+        })
+    }
+
+    fn state_member(&self, node: &SyntaxNode) -> Option<StateMember> {
+        Some(match node.kind() {
+            DEF_CHOICE => StateMember::DefChoice(self.def_choice(node)?),
+            DEF_STATE => StateMember::DefState(self.def_state(node)?),
+            SPEC_INITIAL_TRANSITION => {
+                StateMember::SpecInitialTransition(self.spec_initial_transition(node)?)
+            }
+            SPEC_STATE_ENTRY => StateMember::SpecStateEntry(SpecStateEntry {
+                node_id: self.node(node),
+                actions: self.do_expr(&child(node, DO_EXPR)?),
+            }),
+            SPEC_STATE_EXIT => StateMember::SpecStateExit(SpecStateExit {
+                node_id: self.node(node),
+                actions: self.do_expr(&child(node, DO_EXPR)?),
+            }),
+            SPEC_STATE_TRANSITION => {
+                StateMember::SpecStateTransition(self.spec_state_transition(node)?)
+            }
+            _ => return None,
+        })
+    }
+
+    fn def_state(&self, node: &SyntaxNode) -> Option<DefState> {
+        Some(DefState {
+            node_id: self.body_node(node),
+            name: self.name(child(node, NAME))?,
+            members: self.annotated(child(node, STATE_MEMBER_LIST), Lower::state_member),
+        })
+    }
+
+    fn def_choice(&self, node: &SyntaxNode) -> Option<DefChoice> {
+        let if_transition = child(node, THEN_CLAUSE)?;
+        let else_transition = child(node, ELSE_CLAUSE)?;
+
+        Some(DefChoice {
+            node_id: self.node(node),
+            name: self.name(child(node, NAME))?,
+            guard: self.name_ref(&child(node, NAME_REF)?)?,
+            if_transition: self.transition_expr(&child(&if_transition, TRANSITION_EXPR)?)?,
+            else_transition: self.transition_expr(&child(&else_transition, TRANSITION_EXPR)?)?,
+        })
+    }
+
+    fn spec_initial_transition(&self, node: &SyntaxNode) -> Option<SpecInitialTransition> {
+        Some(SpecInitialTransition {
+            node_id: self.node(node),
+            transition: self.transition_expr(&child(node, TRANSITION_EXPR)?)?,
+        })
+    }
+
+    fn spec_state_transition(&self, node: &SyntaxNode) -> Option<SpecStateTransition> {
+        let transition_or_do = match child(node, TRANSITION_EXPR) {
+            Some(transition) => TransitionOrDo::Transition(self.transition_expr(&transition)?),
+            None => TransitionOrDo::Do(self.do_expr(&child(node, DO_EXPR)?)),
+        };
+
+        Some(SpecStateTransition {
+            node_id: self.node(node),
+            signal: self.ident(&token(node, IDENT)?),
+            guard: child(node, NAME_REF).and_then(|n| self.name_ref(&n)),
+            transition_or_do,
+        })
+    }
+
+    fn transition_expr(&self, node: &SyntaxNode) -> Option<TransitionExpr> {
+        Some(TransitionExpr {
+            node_id: self.node(node),
+            actions: child(node, DO_EXPR).map(|n| self.do_expr(&n)),
+            target: self.qual_ident(&child(node, QUAL_IDENT)?)?,
+        })
+    }
+
+    fn do_expr(&self, node: &SyntaxNode) -> DoExpr {
+        DoExpr {
+            node_id: self.node(node),
+            actions: self.sequence(child(node, DO_EXPR_MEMBER_LIST), Lower::name_ref),
+        }
+    }
+
+    fn type_name(&self, node: &SyntaxNode) -> Option<TypeName> {
+        let kind = match first_token_kind(node) {
+            Some(BOOL_KW) => TypeNameKind::Bool,
+            Some(I8_KW) => TypeNameKind::Integer(IntegerKind::I8),
+            Some(U8_KW) => TypeNameKind::Integer(IntegerKind::U8),
+            Some(I16_KW) => TypeNameKind::Integer(IntegerKind::I16),
+            Some(U16_KW) => TypeNameKind::Integer(IntegerKind::U16),
+            Some(I32_KW) => TypeNameKind::Integer(IntegerKind::I32),
+            Some(U32_KW) => TypeNameKind::Integer(IntegerKind::U32),
+            Some(I64_KW) => TypeNameKind::Integer(IntegerKind::I64),
+            Some(U64_KW) => TypeNameKind::Integer(IntegerKind::U64),
+            Some(F32_KW) => TypeNameKind::Floating(FloatKind::F32),
+            Some(F64_KW) => TypeNameKind::Floating(FloatKind::F64),
+            Some(STRING_KW) => TypeNameKind::String(child(node, EXPR).and_then(|n| self.expr(&n))),
+            _ => TypeNameKind::QualIdent(self.qual_ident(&child(node, QUAL_IDENT)?)?),
+        };
+
+        Some(TypeName {
+            node_id: self.node(node),
+            kind,
+        })
+    }
+
+    /// Lower the expression wrapped by a helper node such as `DEFAULT` or `ID`
+    fn child_expr(&self, node: &SyntaxNode, kind: SyntaxKind) -> Option<Expr> {
+        self.expr(&child(&child(node, kind)?, EXPR)?)
+    }
+
+    fn expr(&self, node: &SyntaxNode) -> Option<Expr> {
+        let kind = match node.kind() {
+            EXPR => match token(node, LEFT_PAREN) {
+                Some(_) => ExprKind::Paren(Box::new(self.expr(&child(node, EXPR)?)?)),
+                // Expression wrappers do not create a node of their own
+                None => return self.expr(&node.first_child()?),
+            },
+            EXPR_POSTFIX => return self.expr(&node.first_child()?),
+            EXPR_BINARY => {
+                let op = match first_token_kind(&child(node, BINARY_OP)?)? {
+                    PLUS => Binop::Add,
+                    MINUS => Binop::Sub,
+                    STAR => Binop::Mul,
+                    SLASH => Binop::Div,
+                    _ => return None,
+                };
+
+                let mut operands = node.children().filter(|n| n.kind() != BINARY_OP);
+                ExprKind::Binop {
+                    left: Box::new(self.expr(&operands.next()?)?),
+                    op,
+                    right: Box::new(self.expr(&operands.next()?)?),
+                }
+            }
+            EXPR_UNARY => ExprKind::Unop {
+                op: Unop::Minus,
+                e: Box::new(self.expr(&node.first_child()?)?),
+            },
+            EXPR_MEMBER => ExprKind::Dot {
+                e: Box::new(self.expr(&node.first_child()?)?),
+                id: self.ident(&token(node, IDENT)?),
+            },
+            EXPR_SUBSCRIPT => ExprKind::ArraySubscript {
+                e1: Box::new(self.expr(&node.first_child()?)?),
+                e2: Box::new(self.child_expr(node, INDEX_OR_SIZE)?),
+            },
+            EXPR_ARRAY => {
+                ExprKind::Array(self.sequence(child(node, EXPR_ARRAY_MEMBER_LIST), Lower::expr))
+            }
+            EXPR_STRUCT => ExprKind::Struct(self.sequence(
+                child(node, EXPR_STRUCT_MEMBER_LIST),
+                Lower::struct_expr_member,
+            )),
+            EXPR_LITERAL => {
+                let literal = tokens(node).next()?;
+                match literal.kind() {
+                    TRUE_KW => ExprKind::LiteralBool(true),
+                    FALSE_KW => ExprKind::LiteralBool(false),
+                    LITERAL_INT => ExprKind::LiteralInt(literal.text().to_string()),
+                    LITERAL_FLOAT => ExprKind::LiteralFloat(literal.text().to_string()),
+                    LITERAL_STRING => ExprKind::LiteralString(string_text(literal.text())),
+                    _ => return None,
+                }
+            }
+            EXPR_IDENT => ExprKind::Ident(ident_text(&token(node, IDENT)?)),
+            _ => return None,
+        };
+
+        Some(Expr {
+            node_id: self.node(node),
+            kind,
+        })
+    }
+
+    fn struct_expr_member(&self, node: &SyntaxNode) -> Option<StructExprMember> {
+        if node.kind() != EXPR_STRUCT_MEMBER {
+            return None;
+        }
+
+        let name = token(node, IDENT)?;
+        Some(StructExprMember {
+            node_id: self.node(node),
+            name: Name {
+                node_id: self.token_node(&name),
+                data: ident_text(&name),
+            },
+            value: self.expr(&child(node, EXPR)?)?,
+        })
+    }
+}
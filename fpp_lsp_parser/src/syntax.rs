@@ -195,6 +195,7 @@ pub enum SyntaxKind {
     SPEC_PORT_INSTANCE_GENERAL,
     SPEC_PORT_INSTANCE_SPECIAL,
     SPEC_PORT_INSTANCE_INTERNAL,
+    SPEC_PORT_MATCHING,
     SPEC_RECORD,
     SPEC_STATE_ENTRY,
     SPEC_STATE_EXIT,
@@ -347,6 +348,7 @@ impl SyntaxKind {
                 | SPEC_LOC
                 | SPEC_PORT_INSTANCE_GENERAL
                 | SPEC_PORT_INSTANCE_SPECIAL
+                | SPEC_PORT_MATCHING
                 | SPEC_RECORD
                 | SPEC_STATE_ENTRY
                 | SPEC_STATE_EXIT
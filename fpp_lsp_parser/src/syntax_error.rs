@@ -4,12 +4,46 @@ use std::fmt;
 
 use crate::{SyntaxKind, TextRange, TextSize};
 
+/// What kind of error a `SyntaxError` is, mirroring the errors of `fpp_parser`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyntaxErrorKind {
+    /// The parser could not continue with the current token
+    Syntax,
+    /// A specific token was expected
+    ExpectedToken,
+    /// One of several tokens or syntax elements was expected
+    ExpectedOneOf,
+    /// The lexer could not tokenize the text
+    InvalidToken,
+}
+
+impl SyntaxErrorKind {
+    /// The grammar reports a single missing token as "expected `;`" or
+    /// "expected a name", and a missing choice as "expected module member" or
+    /// "severity level expected"
+    fn of_message(msg: &str) -> SyntaxErrorKind {
+        let expected = match msg.strip_prefix("expected ") {
+            Some(expected) => expected,
+            None if msg.ends_with(" expected") => return SyntaxErrorKind::ExpectedOneOf,
+            None => return SyntaxErrorKind::Syntax,
+        };
+
+        let quotes = expected.matches(['`', '\'']).count();
+        if expected == "a name" || (quotes == 2 && !expected.contains(" or ")) {
+            SyntaxErrorKind::ExpectedToken
+        } else {
+            SyntaxErrorKind::ExpectedOneOf
+        }
+    }
+}
+
 /// Represents the result of unsuccessful tokenization, parsing
 /// or tree validation.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct SyntaxError {
     msg: String,
     range: TextRange,
+    kind: SyntaxErrorKind,
     expected: Option<SyntaxKind>,
 }
 
@@ -18,6 +52,16 @@ impl SyntaxError {
         Self {
             msg: message.into(),
             range,
+            kind: SyntaxErrorKind::Syntax,
+            expected: None,
+        }
+    }
+
+    pub fn new_invalid_token(message: impl Into<String>, range: TextRange) -> Self {
+        Self {
+            msg: message.into(),
+            range,
+            kind: SyntaxErrorKind::InvalidToken,
             expected: None,
         }
     }
@@ -30,13 +74,17 @@ impl SyntaxError {
         Self {
             msg: message.into(),
             range,
+            kind: SyntaxErrorKind::ExpectedToken,
             expected: Some(expected),
         }
     }
 
+    /// An error reported by the grammar, classified by its message
     pub fn new_at_offset(message: impl Into<String>, offset: TextSize) -> Self {
+        let msg = message.into();
         Self {
-            msg: message.into(),
+            kind: SyntaxErrorKind::of_message(&msg),
+            msg,
             range: TextRange::empty(offset),
             expected: None,
         }
@@ -50,6 +98,24 @@ impl SyntaxError {
         self.expected
     }
 
+    pub fn kind(&self) -> SyntaxErrorKind {
+        self.kind
+    }
+
+    /// The code `fpp_parser` reports for the same error, see `fpp explain`
+    ///
+    /// `eof` is the end of the parsed text, errors there are reported as an
+    /// unexpected end of input.
+    pub fn code(&self, eof: TextSize) -> &'static str {
+        match self.kind {
+            SyntaxErrorKind::InvalidToken => "E0006",
+            _ if self.range.start() >= eof => "E0004",
+            SyntaxErrorKind::Syntax => "E0001",
+            SyntaxErrorKind::ExpectedOneOf => "E0002",
+            SyntaxErrorKind::ExpectedToken => "E0003",
+        }
+    }
+
     pub fn with_range(mut self, range: TextRange) -> Self {
         self.range = range;
         self
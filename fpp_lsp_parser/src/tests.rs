@@ -166,3 +166,97 @@ fn topology_ports() {
 fn topology() {
     run_test_from_fpp_parser("topology")
 }
+
+fn fpp_files(dir: PathBuf, out: &mut Vec<PathBuf>) {
+    for entry in fs::read_dir(dir).expect("failed to read test directory") {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            fpp_files(path, out);
+        } else if path.extension().is_some_and(|ext| ext == "fpp") {
+            out.push(path);
+        }
+    }
+}
+
+/// The lowered syntax tree must match the AST from `fpp_parser` on every valid test file
+#[test]
+fn lower_matches_fpp_parser() {
+    let cargo_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    let mut files = vec![];
+    for dir in ["../fpp_parser/src/tests", "../fpp_analysis/src/test", "src/tests"] {
+        fpp_files(cargo_dir.join(dir), &mut files);
+    }
+
+    files.sort();
+    assert!(!files.is_empty());
+
+    for fpp_file in files {
+        let src = fs::read_to_string(&fpp_file).expect("failed to read fpp file");
+
+        let mut diagnostics_str = vec![];
+        let mut ctx =
+            fpp_core::CompilerContext::new(fpp_errors::WriteEmitter::new(&mut diagnostics_str));
+
+        let (expected, actual) = fpp_core::run(&mut ctx, || {
+            let file = fpp_core::SourceFile::new(fpp_file.to_str().unwrap(), src.clone());
+            let expected = fpp_parser::parse(file, |p| p.trans_unit(), None);
+            let actual = parse(&src, TopEntryPoint::Module).lower_trans_unit(file);
+            (format!("{:#?}", expected), format!("{:#?}", actual))
+        });
+
+        // Recovery differs between the parsers, only compare trees of valid files
+        if diagnostics_str.is_empty() {
+            assert_eq!(expected, actual, "{}", fpp_file.display());
+        }
+    }
+}
+
+/// Half typed string literals are lowered from their text after the opening quotes
+#[test]
+fn lower_unterminated_string() {
+    for (src, data) in [
+        ("constant a = \"é\n", "é"),
+        ("constant a = \"\"\"\n  é\n", "\né\n"),
+    ] {
+        let mut diagnostics_str = vec![];
+        let mut ctx =
+            fpp_core::CompilerContext::new(fpp_errors::WriteEmitter::new(&mut diagnostics_str));
+
+        let actual = fpp_core::run(&mut ctx, || {
+            let file = fpp_core::SourceFile::new("unterminated.fpp", src.to_string());
+            format!(
+                "{:?}",
+                parse(src, TopEntryPoint::Module).lower_trans_unit(file)
+            )
+        });
+
+        assert!(
+            actual.contains(&format!("LiteralString({:?})", data)),
+            "{}: {}",
+            src,
+            actual
+        );
+    }
+}
+
+/// Syntax errors have the codes `fpp_parser` reports for them
+#[test]
+fn syntax_error_codes() {
+    for (src, code) in [
+        ("constant = 1\n", "E0003"),
+        ("constant a = 1 +\n", "E0004"),
+        ("module M { 1 }\n", "E0002"),
+        ("constant a = \"abc\n", "E0006"),
+    ] {
+        let parse = parse(src, TopEntryPoint::Module);
+        let eof = parse.syntax_node().text_range().end();
+        let codes: Vec<&str> = parse.errors().iter().map(|err| err.code(eof)).collect();
+        assert!(
+            codes.contains(&code),
+            "{:?}: {:?} {:?}",
+            src,
+            codes,
+            parse.errors()
+        );
+    }
+}
@@ -1,12 +1,23 @@
 use crate::global_state::{GlobalState, TranslationUnitCache, Workspace};
+use crate::progress::Progress;
+use crate::vfs::{ProjectSearchPaths, Vfs};
+use fpp_analysis::semantics::{LintConfig, LintLevel, ProjectLintConfig};
 use fpp_analysis::Analysis;
-use fpp_ast::MutVisitor;
-use fpp_core::{CompilerContext, Diagnostic, GarbageCollectionSet, Level, SourceFile, Spanned};
+use fpp_ast::{
+    ComponentMember, ModuleMember, MutVisitor, SpecLoc, TlmPacketMember, TlmPacketSetMember,
+    TopologyMember,
+};
+use fpp_core::{
+    CompilerContext, Diagnostic, FileReader, GarbageCollectionSet, Level, SourceFile, Span, Spanned,
+};
+use fpp_fs::{framework_files, Manifest, FRAMEWORK_PREFIX};
+use fpp_lsp_parser::TopEntryPoint;
 use lsp_types::Uri;
 use rustc_hash::{FxHashMap, FxHashSet};
+use std::cell::RefCell;
 use std::sync::Arc;
 
-use fpp_parser::ResolveIncludes;
+use fpp_parser::{IncludeParser, ResolveIncludes};
 use ignore::WalkBuilder;
 use std::ffi::OsStr;
 use std::fmt::{Display, Formatter};
@@ -22,6 +33,9 @@ pub enum Task {
     ReloadWorkspace,
     LoadLocsFile(Uri),
     LoadFullWorkspace,
+    /// Find the `fpp.toml` manifest of each workspace folder and load the
+    /// projects they describe
+    LoadManifests,
     /// The VFS indicated a file changed, we need to reprocess it in the analysis
     /// This may trigger 0+ 'Reprocess' tasks
     Update(Uri),
@@ -48,6 +62,7 @@ impl Display for Task {
                 f.write_fmt(format_args!("LoadLocsFile {{ uri = {} }}", uri.as_str()))
             }
             Task::LoadFullWorkspace => f.write_str("LoadFullWorkspace"),
+            Task::LoadManifests => f.write_str("LoadManifests"),
             Task::Update(uri) => f.write_fmt(format_args!("Update {{ uri = {} }}", uri.as_str())),
             Task::Reprocess(_) => f.write_str("Reprocess"),
             Task::Analysis => f.write_str("Analysis"),
@@ -55,6 +70,103 @@ impl Display for Task {
     }
 }
 
+/// The file content is borrowed from the compiler context, it must be released
+/// before the syntax tree is lowered into new nodes
+fn parse_file(file: SourceFile, entry: TopEntryPoint) -> fpp_lsp_parser::Parse {
+    fpp_lsp_parser::parse(file.read().as_ref(), entry)
+}
+
+/// Parses included files with the same grammar as the files open in the editor,
+/// keeping the syntax tree of each file so requests don't parse it again
+struct LspIncludeParser<'a> {
+    parses: &'a RefCell<FxHashMap<SourceFile, fpp_lsp_parser::Parse>>,
+}
+
+impl LspIncludeParser<'_> {
+    fn parse(&self, file: SourceFile, entry: TopEntryPoint) -> fpp_lsp_parser::Parse {
+        let parse = parse_file(file, entry);
+        self.parses.borrow_mut().insert(file, parse.clone());
+        parse
+    }
+}
+
+impl IncludeParser for LspIncludeParser<'_> {
+    fn component_members(&self, file: SourceFile, include_span: Span) -> Vec<ComponentMember> {
+        self.parse(file, TopEntryPoint::Component)
+            .lower_component_members(file, Some(include_span))
+    }
+
+    fn module_members(&self, file: SourceFile, include_span: Span) -> Vec<ModuleMember> {
+        self.parse(file, TopEntryPoint::Module)
+            .lower_module_members(file, Some(include_span))
+    }
+
+    fn topology_members(&self, file: SourceFile, include_span: Span) -> Vec<TopologyMember> {
+        self.parse(file, TopEntryPoint::Topology)
+            .lower_topology_members(file, Some(include_span))
+    }
+
+    fn tlm_packet_members(&self, file: SourceFile, include_span: Span) -> Vec<TlmPacketMember> {
+        self.parse(file, TopEntryPoint::TlmPacket)
+            .lower_tlm_packet_members(file, Some(include_span))
+    }
+
+    fn tlm_packet_set_members(
+        &self,
+        file: SourceFile,
+        include_span: Span,
+    ) -> Vec<TlmPacketSetMember> {
+        self.parse(file, TopEntryPoint::TlmPacketSet)
+            .lower_tlm_packet_set_members(file, Some(include_span))
+    }
+}
+
+/// Parse a locs file, adding each file it locates to `file_locs` along with
+/// the specifier locating it. The specifiers belong to the returned set and
+/// must not be used after it is cleaned up.
+fn locate_files(
+    vfs: &Vfs,
+    locs_uri: &str,
+    locs_content: String,
+    file_locs: &mut FxHashMap<String, SpecLoc>,
+) -> GarbageCollectionSet {
+    GarbageCollectionSet::start();
+    let locs_file = SourceFile::new(locs_uri, locs_content);
+    let locs_tu =
+        parse_file(locs_file, TopEntryPoint::Module).lower_module_members(locs_file, None);
+    let locs_gc = GarbageCollectionSet::finish();
+
+    for loc in locs_tu {
+        let loc = match loc {
+            ModuleMember::SpecLoc(loc) => loc,
+            _ => continue,
+        };
+
+        match vfs.find(locs_file, &loc.file.data) {
+            Ok((file_uri, _)) => {
+                file_locs.insert(file_uri, loc);
+            }
+            Err(err) => {
+                err.diagnostic(loc.file.span(), &loc.file.data)
+                    .note("failed to resolve location specifier")
+                    .emit();
+            }
+        }
+    }
+
+    locs_gc
+}
+
+fn file_uri(path: &std::path::Path) -> Option<String> {
+    match Url::from_file_path(path) {
+        Ok(url) => Some(url.as_str().to_string()),
+        Err(_) => {
+            tracing::warn!(path = %path.display(), "failed to convert file path into url");
+            None
+        }
+    }
+}
+
 impl GlobalState {
     fn new_translation_unit_cache(&self, uri: &str) -> anyhow::Result<TranslationUnitCache> {
         GarbageCollectionSet::start();
@@ -64,15 +176,15 @@ impl GlobalState {
         let content = self.vfs.read(uri)?;
         let file = SourceFile::new(uri, content);
 
-        let mut ast = fpp_ast::TransUnit(fpp_parser::parse(
-            file,
-            |p: &mut fpp_parser::Parser| p.module_members(),
-            None,
-        ));
+        let parses = RefCell::new(FxHashMap::default());
+        let parser = LspIncludeParser { parses: &parses };
+        let mut ast = parser
+            .parse(file, TopEntryPoint::Module)
+            .lower_trans_unit(file);
 
         let mut include_context_map = Default::default();
-        let _ =
-            ResolveIncludes::new(&self.vfs).visit_trans_unit(&mut include_context_map, &mut ast);
+        let _ = ResolveIncludes::with_parser(&self.vfs, parser)
+            .visit_trans_unit(&mut include_context_map, &mut ast);
 
         tracing::debug!(file = %file, file_dbg = ?file, "computed translation unit cache");
 
@@ -80,11 +192,119 @@ impl GlobalState {
             file,
             ast,
             include_context_map,
+            parses: parses.into_inner(),
             gc: GarbageCollectionSet::finish(),
             diagnostics: self.diagnostics.finish_garbage_collection(),
         })
     }
 
+    /// Build the translation unit of each file, reporting files that fail to
+    /// load at the location specifier that located them
+    fn new_translation_unit_caches(
+        &self,
+        files: Vec<String>,
+        file_locs: &FxHashMap<String, SpecLoc>,
+        progress: &mut Progress,
+    ) -> FxHashMap<SourceFile, Arc<TranslationUnitCache>> {
+        progress.set_total(files.len());
+
+        files
+            .into_iter()
+            .filter_map(|file_uri| {
+                let filename =
+                    &file_uri[(file_uri.rfind("/").unwrap_or(0) + 1).min(file_uri.len())..];
+                progress.report(&filename);
+
+                tracing::debug!(uri = %file_uri, "processing file");
+                match self.new_translation_unit_cache(&file_uri) {
+                    Ok(tu_cache) => Some((tu_cache.file, Arc::new(tu_cache))),
+                    Err(err) => {
+                        match file_locs.get(&file_uri) {
+                            Some(loc) => {
+                                Diagnostic::new(
+                                    loc.span(),
+                                    Level::Error,
+                                    "failed to process location specifier",
+                                )
+                                .annotation(err.to_string())
+                                .emit();
+                            }
+                            None => {
+                                tracing::error!(file_uri = %file_uri, err = ?err, "failed to process file in project");
+                            }
+                        }
+                        None
+                    }
+                }
+            })
+            .collect()
+    }
+
+    /// The `fpp.toml` manifests at the root of the workspace folders
+    fn find_manifests(&self) -> Vec<Uri> {
+        let mut manifests = vec![];
+        for folder in self.workspace_folders.iter().flatten() {
+            let dir = match Url::parse(folder.uri.as_str()).map(|url| url.to_file_path()) {
+                Ok(Ok(dir)) => dir,
+                _ => {
+                    tracing::warn!(uri = %folder.uri.as_str(), "workspace folder is not a local directory");
+                    continue;
+                }
+            };
+
+            let path = dir.join(Manifest::FILE_NAME);
+            if !path.is_file() {
+                continue;
+            }
+
+            match file_uri(&path).map(|uri| Uri::from_str(&uri)) {
+                Some(Ok(uri)) => manifests.push(uri),
+                Some(Err(err)) => {
+                    tracing::warn!(err = ?err, "failed to convert Url to Uri");
+                }
+                None => {}
+            }
+        }
+
+        manifests
+    }
+
+    /// Read a manifest through the VFS so unsaved edits are picked up
+    fn load_manifest(&self, uri: &Uri) -> anyhow::Result<Manifest> {
+        let path = match Url::parse(uri.as_str())?.to_file_path() {
+            Ok(path) => path,
+            Err(_) => anyhow::bail!("{} is not a local file", uri.as_str()),
+        };
+
+        let text = self.vfs.read(uri.as_str())?;
+        Manifest::parse(&path, &text).map_err(|err| anyhow::anyhow!("{}", err))
+    }
+
+    fn show_error(&self, message: String) {
+        tracing::error!("{}", message);
+        self.send_notification::<lsp_types::notification::ShowMessage>(
+            lsp_types::ShowMessageParams {
+                typ: lsp_types::MessageType::ERROR,
+                message,
+            },
+        );
+    }
+
+    /// The syntax tree the analysis parsed for `file`, as long as `text`, the
+    /// current content of the document, is still what was parsed
+    pub(crate) fn cached_parse(
+        &self,
+        file: SourceFile,
+        text: &str,
+    ) -> Option<fpp_lsp_parser::Parse> {
+        let cache = self.cache.get(&self.parent_file(file))?;
+        if self.context.file_get(&file).content != text {
+            return None;
+        }
+
+        cache.parses.get(&file).cloned()
+    }
+
     pub fn parent_file(&self, file: SourceFile) -> SourceFile {
         let mut parent = file;
         loop {
@@ -109,6 +329,7 @@ impl GlobalState {
                     Workspace::None => {}
                     Workspace::LocsFile(uri) => self.task(Task::LoadLocsFile(uri)),
                     Workspace::FullWorkspace => self.task(Task::LoadFullWorkspace),
+                    Workspace::Manifests(_) => self.task(Task::LoadManifests),
                 }
             }
             Task::LoadLocsFile(locs_uri) => {
@@ -131,6 +352,9 @@ impl GlobalState {
                 self.files = Default::default();
                 self.analysis = Arc::new(Analysis::new());
                 self.workspace = Workspace::LocsFile(locs_uri.clone());
+                self.project_lint_configs = vec![];
+                self.framework_dirs = vec![];
+                self.vfs.search_paths = vec![];
 
                 let vfs = self.vfs.clone();
 
@@ -139,66 +363,11 @@ impl GlobalState {
                 let mut ctx = CompilerContext::new(self.diagnostics.clone());
                 self.cache = fpp_core::run(&mut ctx, || {
                     let mut file_locs = FxHashMap::default();
+                    let locs_gc =
+                        locate_files(&vfs, locs_uri.as_str(), locs_content, &mut file_locs);
 
-                    GarbageCollectionSet::start();
-                    let locs_tu = fpp_parser::parse(
-                        SourceFile::new(&locs_uri.as_str(), locs_content),
-                        |p| p.module_members(),
-                        None,
-                    );
-                    let locs_gc = GarbageCollectionSet::finish();
-
-                    let files: FxHashSet<String> = locs_tu
-                        .into_iter()
-                        .filter_map(|loc| match loc {
-                            fpp_ast::ModuleMember::SpecLoc(loc) => Some(loc),
-                            _ => None,
-                        })
-                        .filter_map(|loc| {
-                            match vfs.resolve_uri_relative_path(locs_uri.as_str(), &loc.file.data) {
-                                Ok(file_uri) => {
-                                    file_locs.insert(file_uri.clone(), loc);
-                                    Some(file_uri)
-                                }
-                                Err(err) => {
-                                    Diagnostic::new(
-                                        loc,
-                                        Level::Error,
-                                        "failed to resolve location specifier",
-                                    )
-                                    .annotation(err.to_string())
-                                    .emit();
-                                    None
-                                }
-                            }
-                        })
-                        .collect();
-
-                    progress.set_total(files.len());
-
-                    let out = files
-                        .into_iter()
-                        .filter_map(|file_uri| {
-                            let filename = &file_uri
-                                [(file_uri.rfind("/").unwrap_or(0) + 1).min(file_uri.len())..];
-                            progress.report(&filename);
-
-                            tracing::debug!(uri = %file_uri, "processing file from locs");
-                            match self.new_translation_unit_cache(&file_uri) {
-                                Ok(tu_cache) => Some((tu_cache.file, Arc::new(tu_cache))),
-                                Err(err) => {
-                                    Diagnostic::new(
-                                        file_locs.get(&file_uri).unwrap().span(),
-                                        Level::Error,
-                                        "failed to process location specifier",
-                                    )
-                                    .annotation(err.to_string())
-                                    .emit();
-                                    None
-                                }
-                            }
-                        })
-                        .collect();
+                    let files = file_locs.keys().cloned().collect();
+                    let out = self.new_translation_unit_caches(files, &file_locs, &mut progress);
 
                     locs_gc.cleanup();
                     out
@@ -277,6 +446,9 @@ impl GlobalState {
                 self.files = Default::default();
                 self.analysis = Arc::new(Analysis::new());
                 self.workspace = Workspace::FullWorkspace;
+                self.project_lint_configs = vec![];
+                self.framework_dirs = vec![];
+                self.vfs.search_paths = vec![];
 
                 let mut ctx = CompilerContext::new(self.diagnostics.clone());
                 let cache = fpp_core::run(&mut ctx, || {
@@ -312,6 +484,149 @@ impl GlobalState {
                 self.task(Task::Analysis);
                 self.send_request::<lsp_types::request::SemanticTokensRefresh>((), |_, _| {});
             }
+            Task::LoadManifests => {
+                let manifests = self.find_manifests();
+                if manifests.is_empty() {
+                    tracing::info!("no project manifest in the workspace folders");
+                    return;
+                }
+
+                tracing::info!("loading {} project manifests", manifests.len());
+                let now = Instant::now();
+
+                let mut projects = vec![];
+                for uri in &manifests {
+                    match self.load_manifest(uri) {
+                        Ok(project) => projects.push(project),
+                        Err(err) => {
+                            self.show_error(format!("failed to load project manifest: {}", err))
+                        }
+                    }
+                }
+
+                let mut project_lint_configs = vec![];
+                let mut search_paths = vec![];
+                for project in &projects {
+                    // Files located by the project may live outside of its
+                    // sources but still under its root
+                    let dirs: Vec<String> = std::iter::once(project.root())
+                        .chain(project.sources.iter().map(|source| source.as_path()))
+                        .filter_map(file_uri)
+                        .collect();
+
+                    search_paths.push(ProjectSearchPaths {
+                        dirs: dirs.clone(),
+                        search_paths: project.search_paths(),
+                    });
+
+                    let mut lint_config = LintConfig::default();
+                    for (names, level) in [
+                        (&project.lints.allow, LintLevel::Allow),
+                        (&project.lints.warn, LintLevel::Warn),
+                        (&project.lints.deny, LintLevel::Deny),
+                    ] {
+                        for name in names {
+                            if let Err(err) = lint_config.set(name, level) {
+                                self.show_error(format!("{}: {}", project.path.display(), err));
+                            }
+                        }
+                    }
+
+                    for rule in &project.lints.style {
+                        match rule.parse() {
+                            Ok(rule) => lint_config.style_rules.push(rule),
+                            Err(err) => {
+                                self.show_error(format!("{}: {}", project.path.display(), err))
+                            }
+                        }
+                    }
+
+                    project_lint_configs.push(ProjectLintConfig {
+                        sources: dirs,
+                        lint_config,
+                        max_port_buffer_size: project.lints.max_port_buffer_size,
+                        check_unused: project.lints.check_unused,
+                    });
+                }
+
+                let mut progress = self.new_progress("Indexing project", 1);
+
+                // Refresh the context and all caches
+                self.diagnostics.clear();
+                self.cache = Default::default();
+                self.files = Default::default();
+                self.analysis = Arc::new(Analysis::new());
+                self.workspace = Workspace::Manifests(manifests);
+                self.project_lint_configs = project_lint_configs;
+                self.framework_dirs = projects
+                    .iter()
+                    .flat_map(|project| &project.framework)
+                    .filter_map(|path| file_uri(path))
+                    .collect();
+                self.vfs.search_paths = search_paths;
+
+                let vfs = self.vfs.clone();
+
+                let mut ctx = CompilerContext::new(self.diagnostics.clone());
+                self.cache = fpp_core::run(&mut ctx, || {
+                    let mut files: Vec<String> = projects
+                        .iter()
+                        .flat_map(|project| project.source_files())
+                        .filter_map(|path| file_uri(&path))
+                        .collect();
+
+                    let mut file_locs = FxHashMap::default();
+                    let mut locs_gcs = vec![];
+                    for locs in projects.iter().flat_map(|project| &project.locs) {
+                        let locs_uri = match file_uri(locs) {
+                            None => continue,
+                            Some(locs_uri) => locs_uri,
+                        };
+
+                        match vfs.read(&locs_uri) {
+                            Ok(locs_content) => {
+                                locs_gcs.push(locate_files(
+                                    &vfs,
+                                    &locs_uri,
+                                    locs_content,
+                                    &mut file_locs,
+                                ));
+                            }
+                            Err(err) => {
+                                tracing::warn!(uri = %locs_uri, err = ?err, "failed to read locs file of project");
+                            }
+                        }
+                    }
+
+                    files.extend(file_locs.keys().cloned());
+                    if projects
+                        .iter()
+                        .any(|project| project.uses_builtin_framework())
+                    {
+                        files.extend(framework_files());
+                    }
+                    files.sort();
+                    files.dedup();
+
+                    let out = self.new_translation_unit_caches(files, &file_locs, &mut progress);
+
+                    for locs_gc in locs_gcs {
+                        locs_gc.cleanup();
+                    }
+                    out
+                });
+
+                // Replace the context and drop the old one
+                let _ = mem::replace(&mut self.context, ctx);
+
+                tracing::info!(
+                    "finished loading projects in {:.1}s",
+                    now.elapsed().as_secs_f64()
+                );
+                self.task(Task::Analysis);
+                self.send_request::<lsp_types::request::SemanticTokensRefresh>((), |_, _| {});
+                progress.finish(None);
+            }
             Task::Response(response) => self.respond(response),
             Task::Update(uri) => {
                 tracing::info!("updating file");
@@ -323,6 +638,15 @@ impl GlobalState {
                     return;
                 }
 
+                // Check if this file is one of the project manifests
+                if let Workspace::Manifests(manifests) = &self.workspace
+                    && manifests.contains(&uri)
+                {
+                    tracing::info!("project manifest has updated, refreshing workspace");
+                    self.task(Task::ReloadWorkspace);
+                    return;
+                }
+
                 // Check if this file is currently part of the compiler context
                 match self.files.get(uri.as_str()) {
                     None => {
@@ -384,6 +708,10 @@ impl GlobalState {
                 let (analysis, files) = fpp_core::run(&mut self.context, || {
                     let mut files = FxHashMap::default();
                     let mut analysis = Analysis::new();
+                    analysis.project_lint_configs = self.project_lint_configs.clone();
+                    analysis.framework_dirs = std::iter::once(FRAMEWORK_PREFIX.to_string())
+                        .chain(self.framework_dirs.iter().cloned())
+                        .collect();
 
                     for (file, cache) in &self.cache {
                         for (included, include_context) in &cache.include_context_map {
@@ -0,0 +1,259 @@
+use crate::global_state::GlobalState;
+use crate::util::{node_to_location, node_to_range};
+use fpp_analysis::semantics::{ConnectionEndpoint, Symbol, TopologyConnection};
+use fpp_ast::{AstNode, Node};
+use lsp_types::{CallHierarchyItem, Location, Range, SymbolKind};
+use serde::{Deserialize, Serialize};
+
+/// The port instances a call hierarchy item stands for
+///
+/// Connections are calls from an output port to an input port. An item names
+/// a port of a component instance, every port of a component instance, or the
+/// ports of every instance of a component. Names are fully qualified so the
+/// item still resolves after the workspace is analyzed again.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub(crate) struct CallHierarchyTarget {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    instance: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    component: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    port: Option<String>,
+}
+
+impl CallHierarchyTarget {
+    /// The target under the cursor, `nodes` being the innermost node first
+    pub(crate) fn at(
+        state: &GlobalState,
+        nodes: &[Node],
+    ) -> Option<(CallHierarchyTarget, Location)> {
+        let analysis = &state.analysis;
+        let on_name = matches!(nodes.first(), Some(Node::Name(_)));
+
+        for (i, node) in nodes.iter().enumerate() {
+            match node {
+                Node::PortInstanceIdentifier(pid) => {
+                    let instance = match analysis.use_def_map.get(&pid.interface_instance.id()) {
+                        Some(symbol @ Symbol::ComponentInstance(_)) => symbol,
+                        _ => return None,
+                    };
+
+                    return Some((
+                        CallHierarchyTarget {
+                            instance: Some(analysis.qualified_name(instance)),
+                            port: Some(pid.port_name.data.clone()),
+                            ..Default::default()
+                        },
+                        node_to_location(state, pid.node_id),
+                    ));
+                }
+                Node::SpecGeneralPortInstance(_) | Node::SpecSpecialPortInstance(_) => {
+                    let port = match node {
+                        Node::SpecGeneralPortInstance(spec) => &spec.name,
+                        Node::SpecSpecialPortInstance(spec) => &spec.name,
+                        _ => unreachable!(),
+                    };
+
+                    // Ports of interfaces are only connected through the components importing them
+                    let component = nodes[i + 1..].iter().find_map(|node| match node {
+                        Node::DefComponent(def) => analysis.symbol_map.get(&def.node_id),
+                        _ => None,
+                    })?;
+
+                    return Some((
+                        CallHierarchyTarget {
+                            component: Some(analysis.qualified_name(component)),
+                            port: Some(port.data.clone()),
+                            ..Default::default()
+                        },
+                        node_to_location(state, node.id()),
+                    ));
+                }
+                Node::DefComponentInstance(def) if on_name => {
+                    let instance = analysis.symbol_map.get(&def.node_id)?;
+                    return Some((
+                        CallHierarchyTarget {
+                            instance: Some(analysis.qualified_name(instance)),
+                            ..Default::default()
+                        },
+                        node_to_location(state, def.name.node_id),
+                    ));
+                }
+                Node::DefComponent(def) if on_name => {
+                    let component = analysis.symbol_map.get(&def.node_id)?;
+                    return Some((
+                        CallHierarchyTarget {
+                            component: Some(analysis.qualified_name(component)),
+                            ..Default::default()
+                        },
+                        node_to_location(state, def.name.node_id),
+                    ));
+                }
+                _ => match analysis.use_def_map.get(&node.id()) {
+                    None => {}
+                    Some(symbol @ Symbol::ComponentInstance(_)) => {
+                        return Some((
+                            CallHierarchyTarget {
+                                instance: Some(analysis.qualified_name(symbol)),
+                                ..Default::default()
+                            },
+                            node_to_location(state, node.id()),
+                        ));
+                    }
+                    Some(symbol @ Symbol::Component(_)) => {
+                        return Some((
+                            CallHierarchyTarget {
+                                component: Some(analysis.qualified_name(symbol)),
+                                ..Default::default()
+                            },
+                            node_to_location(state, node.id()),
+                        ));
+                    }
+                    Some(_) => return None,
+                },
+            }
+        }
+
+        None
+    }
+
+    pub(crate) fn to_item(&self, location: Location) -> CallHierarchyItem {
+        let name = match (&self.instance, &self.component, &self.port) {
+            (Some(instance), _, Some(port)) => format!("{}.{}", instance, port),
+            (Some(instance), _, None) => instance.clone(),
+            (None, Some(component), Some(port)) => format!("{}.{}", component, port),
+            (None, Some(component), None) => component.clone(),
+            (None, None, _) => String::new(),
+        };
+
+        CallHierarchyItem {
+            name,
+            kind: if self.port.is_some() {
+                SymbolKind::INTERFACE
+            } else {
+                SymbolKind::OBJECT
+            },
+            tags: None,
+            detail: match (&self.instance, &self.component) {
+                (None, Some(_)) => Some("All instances".to_string()),
+                _ => None,
+            },
+            uri: location.uri,
+            range: location.range,
+            selection_range: location.range,
+            data: serde_json::to_value(self).ok(),
+        }
+    }
+
+    pub(crate) fn from_item(item: &CallHierarchyItem) -> Option<CallHierarchyTarget> {
+        serde_json::from_value(item.data.clone()?).ok()
+    }
+
+    fn matches(&self, state: &GlobalState, endpoint: &ConnectionEndpoint) -> bool {
+        let analysis = &state.analysis;
+        let instance = Symbol::ComponentInstance(endpoint.instance.clone());
+
+        if let Some(name) = &self.instance
+            && analysis.qualified_name(&instance) != *name
+        {
+            return false;
+        }
+
+        if let Some(name) = &self.component {
+            match analysis.use_def_map.get(&endpoint.instance.component.id()) {
+                Some(component @ Symbol::Component(_))
+                    if analysis.qualified_name(component) == *name => {}
+                _ => return false,
+            }
+        }
+
+        match &self.port {
+            Some(port) => endpoint.port == *port,
+            None => true,
+        }
+    }
+
+    /// Every connection in every topology with this target as its `to` end
+    /// when `incoming`, or as its `from` end otherwise, as the items for their
+    /// other ends with the ranges of the connections.
+    pub(crate) fn calls(
+        &self,
+        state: &GlobalState,
+        incoming: bool,
+    ) -> Vec<(CallHierarchyItem, Vec<Range>)> {
+        let analysis = &state.analysis;
+
+        let mut topologies: Vec<(String, &Symbol)> = analysis
+            .symbol_map
+            .values()
+            .filter(|symbol| matches!(symbol, Symbol::Topology(_)))
+            .map(|symbol| (analysis.qualified_name(symbol), symbol))
+            .collect();
+        topologies.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        let mut calls: Vec<(CallHierarchyItem, Vec<Range>)> = vec![];
+        for (topology_name, topology) in topologies {
+            let def = match topology {
+                Symbol::Topology(def) => def,
+                _ => continue,
+            };
+
+            for connection in analysis.topology_connections(def) {
+                let (this, other) = match incoming {
+                    true => (&connection.to, &connection.from),
+                    false => (&connection.from, &connection.to),
+                };
+
+                if !self.matches(state, this) {
+                    continue;
+                }
+
+                // A pattern connects the same port to many instances, list it once
+                let item = connection_item(state, &topology_name, &connection, other);
+                let range = node_to_range(state, connection.node);
+                match calls.iter_mut().find(|(other, _)| {
+                    other.name == item.name && other.uri == item.uri && other.range == item.range
+                }) {
+                    Some((_, ranges)) => {
+                        if !ranges.contains(&range) {
+                            ranges.push(range)
+                        }
+                    }
+                    None => calls.push((item, vec![range])),
+                }
+            }
+        }
+
+        calls
+    }
+}
+
+/// The item for the `endpoint` of a connection, at the connection itself
+fn connection_item(
+    state: &GlobalState,
+    topology: &str,
+    connection: &TopologyConnection,
+    endpoint: &ConnectionEndpoint,
+) -> CallHierarchyItem {
+    let target = CallHierarchyTarget {
+        instance: Some(
+            state
+                .analysis
+                .qualified_name(&Symbol::ComponentInstance(endpoint.instance.clone())),
+        ),
+        port: Some(endpoint.port.clone()),
+        ..Default::default()
+    };
+
+    let mut item = target.to_item(node_to_location(state, connection.node));
+    if let Some(index) = endpoint.index {
+        item.name = format!("{}[{}]", item.name, index);
+    }
+
+    item.detail = Some(match connection.pattern {
+        Some(_) => format!("{}: {} (pattern)", topology, connection.graph),
+        None => format!("{}: {}", topology, connection.graph),
+    });
+
+    item
+}
@@ -0,0 +1,340 @@
+use crate::diagnostics::LspDiagnosticsEmitter;
+use crate::global_state::GlobalState;
+use crate::util::{node_to_location, node_to_range};
+use fpp_analysis::semantics::{CommandKind, ComponentDictionary, Symbol, SymbolInterface};
+use fpp_ast::{
+    AstNode, DefComponent, DefComponentInstance, MoveWalkable, Node, TopologyMember, Visitor,
+};
+use fpp_core::{CompilerContext, SourceFile};
+use lsp_types::{CodeLens, Command, Location, Range, Uri};
+use rustc_hash::FxHashMap;
+use std::ops::ControlFlow;
+use std::sync::Arc;
+
+/// Client command listing locations, with the document, position and
+/// locations as arguments like `editor.action.showReferences`
+pub const SHOW_REFERENCES: &str = "fpp.showReferences";
+
+/// Client command opening the connection graph of the topology named by its argument
+pub const SHOW_TOPOLOGY_GRAPH: &str = "fpp.showTopologyGraph";
+
+/// Collects the definitions of a file with the component enclosing them
+struct DefinitionVisitor<'a> {
+    source_file: SourceFile,
+    context: &'a CompilerContext<LspDiagnosticsEmitter>,
+    component: Option<&'a DefComponent>,
+}
+
+impl<'ast> Visitor<'ast> for DefinitionVisitor<'ast> {
+    type Break = ();
+    type State = Vec<(Node<'ast>, Option<&'ast DefComponent>)>;
+
+    fn super_visit(&self, a: &mut Self::State, node: Node<'ast>) -> ControlFlow<Self::Break> {
+        let span = self
+            .context
+            .span_get(&self.context.node_get_span(&node.id()));
+        let src_file: SourceFile = span.file.upgrade().unwrap().as_ref().into();
+
+        if src_file == self.source_file {
+            a.push((node, self.component));
+        }
+
+        // Members of included files may be in this file while their parent is not
+        match node {
+            Node::DefComponent(def) => node.walk(
+                a,
+                &DefinitionVisitor {
+                    component: Some(def),
+                    ..*self
+                },
+            ),
+            Node::DefModule(_) | Node::SpecInclude(_) => node.walk(a, self),
+            _ => ControlFlow::Continue(()),
+        }
+    }
+}
+
+/// The instances of a component and the topologies using them directly
+struct ComponentUsage {
+    /// Each instance, whether a topology uses it
+    instances: Vec<(String, Arc<DefComponentInstance>, bool)>,
+    topologies: Vec<String>,
+}
+
+fn component_usage(state: &GlobalState, def: &DefComponent) -> ComponentUsage {
+    let analysis = &state.analysis;
+
+    let mut instances: Vec<(String, Arc<DefComponentInstance>, bool)> = analysis
+        .symbol_map
+        .values()
+        .filter_map(|symbol| match symbol {
+            Symbol::ComponentInstance(instance) => {
+                match analysis.use_def_map.get(&instance.component.id()) {
+                    Some(component) if component.node() == def.node_id => {
+                        Some((analysis.qualified_name(symbol), instance.clone(), false))
+                    }
+                    _ => None,
+                }
+            }
+            _ => None,
+        })
+        .collect();
+    instances.sort_by(|(a, _, _), (b, _, _)| a.cmp(b));
+
+    let mut topologies = vec![];
+    for symbol in analysis.symbol_map.values() {
+        let topology = match symbol {
+            Symbol::Topology(topology) => topology,
+            _ => continue,
+        };
+
+        let mut uses = false;
+        for member in &topology.members {
+            let used = match member {
+                TopologyMember::SpecInstance(spec) => {
+                    match analysis.use_def_map.get(&spec.instance.id()) {
+                        Some(Symbol::ComponentInstance(used)) => used,
+                        _ => continue,
+                    }
+                }
+                _ => continue,
+            };
+
+            for (_, instance, in_topology) in instances.iter_mut() {
+                if instance.node_id == used.node_id {
+                    *in_topology = true;
+                    uses = true;
+                }
+            }
+        }
+
+        if uses {
+            topologies.push(analysis.qualified_name(symbol));
+        }
+    }
+    topologies.sort();
+
+    ComponentUsage {
+        instances,
+        topologies,
+    }
+}
+
+fn plural(count: usize, one: &str, many: &str) -> String {
+    match count {
+        1 => format!("1 {}", one),
+        _ => format!("{} {}", count, many),
+    }
+}
+
+fn show_references(title: String, uri: &Uri, range: Range, locations: Vec<Location>) -> CodeLens {
+    CodeLens {
+        range,
+        command: Some(Command {
+            title,
+            command: SHOW_REFERENCES.to_string(),
+            arguments: Some(vec![
+                serde_json::to_value(uri).unwrap(),
+                serde_json::to_value(range.start).unwrap(),
+                serde_json::to_value(locations).unwrap(),
+            ]),
+        }),
+        data: None,
+    }
+}
+
+/// A lens only showing text
+fn label(title: String, range: Range) -> CodeLens {
+    CodeLens {
+        range,
+        command: Some(Command {
+            title,
+            command: String::new(),
+            arguments: None,
+        }),
+        data: None,
+    }
+}
+
+/// Builds the lenses of one document
+struct CodeLensBuilder<'a> {
+    state: &'a GlobalState,
+    uri: &'a Uri,
+    dictionaries: FxHashMap<fpp_core::Node, ComponentDictionary>,
+    usages: FxHashMap<fpp_core::Node, ComponentUsage>,
+    lenses: Vec<CodeLens>,
+}
+
+impl CodeLensBuilder<'_> {
+    fn usage(&mut self, def: &DefComponent) -> &ComponentUsage {
+        let state = self.state;
+        self.usages
+            .entry(def.node_id)
+            .or_insert_with(|| component_usage(state, def))
+    }
+
+    fn references(&mut self, symbol: &Symbol) {
+        let mut locations: Vec<Location> = self
+            .state
+            .analysis
+            .use_def_map
+            .iter()
+            .filter(|(_, used)| used.node() == symbol.node())
+            .map(|(node, _)| node_to_location(self.state, *node))
+            .collect();
+        locations.sort_by(|a, b| {
+            (a.uri.as_str(), a.range.start.line, a.range.start.character).cmp(&(
+                b.uri.as_str(),
+                b.range.start.line,
+                b.range.start.character,
+            ))
+        });
+
+        let range = node_to_range(self.state, symbol.name().node_id);
+        let title = plural(locations.len(), "reference", "references");
+        self.lenses
+            .push(show_references(title, self.uri, range, locations));
+    }
+
+    fn component(&mut self, def: &DefComponent) {
+        let state = self.state;
+        let usage = self.usage(def);
+        let title = format!(
+            "used by {} in {}",
+            plural(usage.instances.len(), "instance", "instances"),
+            plural(usage.topologies.len(), "topology", "topologies")
+        );
+        let locations = usage
+            .instances
+            .iter()
+            .map(|(_, instance, _)| node_to_location(state, instance.name.node_id))
+            .collect();
+
+        let range = node_to_range(state, def.name.node_id);
+        self.lenses
+            .push(show_references(title, self.uri, range, locations));
+    }
+
+    /// The identifier of a command, event or channel relative to its
+    /// component, then the global identifier for each instance of the
+    /// component used in a topology
+    fn identifier(&mut self, component: &DefComponent, node: fpp_core::Node, name: fpp_core::Node) {
+        let state = self.state;
+        let dictionary = self
+            .dictionaries
+            .entry(component.node_id)
+            .or_insert_with(|| state.analysis.component_dictionary(component));
+
+        let (kind, id) = match dictionary.commands.iter().find(
+            |command| matches!(&command.kind, CommandKind::Command(spec) if spec.node_id == node),
+        ) {
+            Some(command) => ("opcode", command.opcode),
+            None => match dictionary
+                .events
+                .iter()
+                .map(|event| (event.node.node_id, event.id))
+                .chain(
+                    dictionary
+                        .channels
+                        .iter()
+                        .map(|channel| (channel.node.node_id, channel.id)),
+                )
+                .find(|(spec, _)| *spec == node)
+            {
+                Some((_, id)) => ("id", id),
+                None => return,
+            },
+        };
+
+        let mut title = format!("{} {:#x}", kind, id);
+        let usage = self.usage(component);
+        for (instance_name, instance, _) in usage.instances.iter().filter(|(_, _, used)| *used) {
+            if let Some(base_id) = state.analysis.integer_value(&instance.base_id) {
+                title.push_str(&format!(" · {} {:#x}", instance_name, base_id + id));
+            }
+        }
+
+        self.lenses
+            .push(label(title, node_to_range(self.state, name)));
+    }
+}
+
+/// The lenses of the definitions in the document at `uri`
+pub(crate) fn code_lenses(state: &GlobalState, uri: &Uri) -> Vec<CodeLens> {
+    let files = match state.files.get(uri.as_str()) {
+        None => return vec![],
+        Some(files) => files,
+    };
+
+    let mut builder = CodeLensBuilder {
+        state,
+        uri,
+        dictionaries: Default::default(),
+        usages: Default::default(),
+        lenses: vec![],
+    };
+
+    for file in files {
+        let cache = match state.cache.get(&state.parent_file(*file)) {
+            None => continue,
+            Some(cache) => cache,
+        };
+
+        let visitor = DefinitionVisitor {
+            source_file: *file,
+            context: &state.context,
+            component: None,
+        };
+
+        let mut definitions = vec![];
+        let _ = visitor.visit_trans_unit(&mut definitions, &cache.ast);
+
+        for (node, component) in definitions {
+            match (node, component) {
+                (Node::DefComponent(def), _) => builder.component(def),
+                (Node::DefTopology(def), _) => {
+                    let symbol = match state.analysis.symbol_map.get(&def.node_id) {
+                        None => continue,
+                        Some(symbol) => symbol,
+                    };
+
+                    let name = state.analysis.qualified_name(symbol);
+                    builder.lenses.push(CodeLens {
+                        range: node_to_range(state, def.name.node_id),
+                        command: Some(Command {
+                            title: "Show connection graph".to_string(),
+                            command: SHOW_TOPOLOGY_GRAPH.to_string(),
+                            arguments: Some(vec![serde_json::Value::String(name)]),
+                        }),
+                        data: None,
+                    });
+                }
+                (Node::SpecCommand(spec), Some(component)) => {
+                    builder.identifier(component, spec.node_id, spec.name.node_id)
+                }
+                (Node::SpecEvent(spec), Some(component)) => {
+                    builder.identifier(component, spec.node_id, spec.name.node_id)
+                }
+                (Node::SpecTlmChannel(spec), Some(component)) => {
+                    builder.identifier(component, spec.node_id, spec.name.node_id)
+                }
+                (
+                    Node::DefAbsType(_)
+                    | Node::DefAliasType(_)
+                    | Node::DefArray(_)
+                    | Node::DefEnum(_)
+                    | Node::DefStruct(_)
+                    | Node::DefConstant(_),
+                    _,
+                ) => {
+                    if let Some(symbol) = state.analysis.symbol_map.get(&node.id()) {
+                        builder.references(symbol)
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    builder.lenses
+}
@@ -1,6 +1,9 @@
-use fpp_core::{DiagnosticData, DiagnosticEmitter};
+use crate::include_context::IncludeContext;
+use fpp_analysis::semantics::Lint;
+use fpp_core::{DiagnosticData, DiagnosticEmitter, SourceFile};
 use lsp_types::{
-    Diagnostic, DiagnosticRelatedInformation, DiagnosticSeverity, Location, Position, Range, Uri,
+    Diagnostic, DiagnosticRelatedInformation, DiagnosticSeverity, DiagnosticTag, Location,
+    NumberOrString, Position, Range, Uri,
 };
 use rustc_hash::{FxHashMap, FxHashSet};
 use std::{
@@ -11,6 +14,8 @@ use std::{
 #[derive(Clone, Debug)]
 pub struct LspDiagnostic {
     id: usize,
+    /// The file the diagnostic was reported in, telling its inclusion context
+    file: SourceFile,
     diagnostic: Diagnostic,
 }
 
@@ -30,12 +35,38 @@ impl LspDiagnosticsEmitterInner {
     }
 
     /// Returns all diagnostics for a specific URI
-    pub fn get(&self, uri: &str) -> Vec<Diagnostic> {
-        self.diagnostics
-            .get(uri)
-            .map_or_else(|| vec![], |v| v.clone())
+    ///
+    /// Diagnostics reported identically in several `contexts` of the file are
+    /// merged. When the file has more than one context, the ones not reported
+    /// in every context are labeled with the contexts reporting them.
+    pub fn get(&self, uri: &str, contexts: &[IncludeContext]) -> Vec<Diagnostic> {
+        let mut merged: Vec<(Diagnostic, Vec<SourceFile>)> = vec![];
+        for d in self.diagnostics.get(uri).into_iter().flatten() {
+            match merged.iter_mut().find(|(other, _)| *other == d.diagnostic) {
+                Some((_, files)) => files.push(d.file),
+                None => merged.push((d.diagnostic.clone(), vec![d.file])),
+            }
+        }
+
+        merged
             .into_iter()
-            .map(|d| d.diagnostic)
+            .map(|(mut diagnostic, files)| {
+                let labels: Vec<String> = contexts
+                    .iter()
+                    .filter(|context| files.contains(&context.file))
+                    .map(|context| format!("`{}`", context.label))
+                    .collect();
+
+                if contexts.len() > 1 && !labels.is_empty() && labels.len() < contexts.len() {
+                    diagnostic.message = format!(
+                        "{}\nincluded from {}",
+                        diagnostic.message,
+                        labels.join(", ")
+                    );
+                }
+
+                diagnostic
+            })
             .collect()
     }
 }
@@ -45,9 +76,9 @@ impl LspDiagnosticsEmitter {
         self.0.lock().unwrap().clear();
     }
 
-    /// Returns all diagnostics for a specific URI
-    pub fn get(&self, uri: &str) -> Vec<Diagnostic> {
-        self.0.lock().unwrap().get(uri)
+    /// Returns all diagnostics for a specific URI, merged over its inclusion contexts
+    pub fn get(&self, uri: &str, contexts: &[IncludeContext]) -> Vec<Diagnostic> {
+        self.0.lock().unwrap().get(uri, contexts)
     }
 
     /// Start tracking all diagnostics
@@ -121,17 +152,30 @@ impl DiagnosticEmitter for LspDiagnosticsEmitter {
                 .collect(),
         );
 
+        // Fade out code reported by lints like `unused_definitions`
+        let tags = diagnostic
+            .code
+            .as_deref()
+            .and_then(Lint::find)
+            .filter(|lint| lint.unnecessary)
+            .map(|_| vec![DiagnosticTag::UNNECESSARY]);
+
         let mut state = self.0.lock().unwrap();
         let id = state.next_id;
 
         let lsp_diagnostic = LspDiagnostic {
             id,
+            file: file.as_ref().into(),
             diagnostic: Diagnostic {
                 range,
                 severity: Some(diagnostic_level_to_severity(diagnostic.level)),
+                code: diagnostic
+                    .code
+                    .map(|code| NumberOrString::String(code.into_owned())),
                 source: Some("fpp".to_owned()),
                 message: diagnostic.message,
                 related_information,
+                tags,
                 ..Diagnostic::default()
             },
         };
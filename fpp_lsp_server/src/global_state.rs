@@ -3,6 +3,7 @@ use crate::diagnostics::LspDiagnosticsEmitter;
 use crate::progress::Progress;
 use crate::{lsp, vfs};
 use crossbeam_channel::{Receiver, Sender};
+use fpp_analysis::semantics::ProjectLintConfig;
 use fpp_analysis::Analysis;
 use fpp_core::{CompilerContext, SourceFile};
 use lsp_server::RequestId;
@@ -19,6 +20,8 @@ pub struct TranslationUnitCache {
     pub file: SourceFile,
     pub ast: fpp_ast::TransUnit,
     pub include_context_map: FxHashMap<SourceFile, fpp_parser::IncludeParentKind>,
+    /// Syntax tree of the file and of each file it includes
+    pub parses: FxHashMap<SourceFile, fpp_lsp_parser::Parse>,
     pub gc: fpp_core::GarbageCollectionSet,
     pub diagnostics: FxHashSet<usize>,
 }
@@ -29,6 +32,8 @@ pub enum Workspace {
     None,
     LocsFile(Uri),
     FullWorkspace,
+    /// Projects described by the `fpp.toml` manifests of the workspace folders
+    Manifests(Vec<Uri>),
 }
 
 pub struct TaskWithReply {
@@ -49,12 +54,18 @@ pub struct GlobalState {
 
     pub(crate) workspace_folders: Option<Vec<WorkspaceFolder>>,
     pub(crate) workspace: Workspace,
+    /// Lint levels and style rules of each loaded project
+    pub(crate) project_lint_configs: Vec<ProjectLintConfig>,
+    /// URIs of the framework locations of the loaded projects
+    pub(crate) framework_dirs: Vec<String>,
 
     pub(crate) diagnostics: LspDiagnosticsEmitter,
     pub(crate) context: CompilerContext<LspDiagnosticsEmitter>,
     /// Top level files in project pointing to their translation unit
     pub(crate) cache: FxHashMap<SourceFile, Arc<TranslationUnitCache>>,
     pub(crate) files: FxHashMap<String, Vec<SourceFile>>,
+    /// Includers of the inclusion context selected for each document
+    pub(crate) active_include_contexts: FxHashMap<String, Vec<String>>,
     /// Computed compiler analysis
     pub(crate) analysis: Arc<Analysis>,
     pub(crate) analysis_diagnostics: FxHashSet<usize>,
@@ -83,10 +94,13 @@ impl GlobalState {
             shutdown_requested: false,
             workspace_folders,
             workspace: Workspace::None,
+            project_lint_configs: vec![],
+            framework_dirs: vec![],
             diagnostics: diagnostics.clone(),
             context: CompilerContext::new(diagnostics),
             cache: Default::default(),
             files: Default::default(),
+            active_include_contexts: Default::default(),
             analysis: Arc::new(Analysis::new()),
             analysis_diagnostics: Default::default(),
             capabilities: Arc::new(capabilities),
@@ -121,7 +135,6 @@ impl GlobalState {
         self.send(request.into());
     }
 
-    #[allow(dead_code)]
     pub(crate) fn send_notification<N: lsp_types::notification::Notification>(
         &self,
         params: N::Params,
@@ -204,6 +217,29 @@ impl GlobalState {
         Progress::begin(token, title, total, self.sender.clone())
     }
 
+    fn register_manifest_watcher(&mut self) {
+        let options = lsp_types::DidChangeWatchedFilesRegistrationOptions {
+            watchers: vec![lsp_types::FileSystemWatcher {
+                glob_pattern: lsp_types::GlobPattern::String(format!(
+                    "**/{}",
+                    fpp_fs::Manifest::FILE_NAME
+                )),
+                kind: None,
+            }],
+        };
+
+        self.send_request::<lsp_types::request::RegisterCapability>(
+            lsp_types::RegistrationParams {
+                registrations: vec![lsp_types::Registration {
+                    id: "fpp/manifests".to_string(),
+                    method: "workspace/didChangeWatchedFiles".to_string(),
+                    register_options: serde_json::to_value(options).ok(),
+                }],
+            },
+            |_, _| (),
+        );
+    }
+
     fn main_loop(&mut self, receiver: Receiver<lsp_server::Message>) {
         while !self.shutdown_requested {
             crossbeam_channel::select_biased! {
@@ -233,6 +269,16 @@ impl GlobalState {
         capabilities: lsp::capabilities::ClientCapabilities,
     ) {
         let mut state = GlobalState::new(workspace_folders, connection.sender, capabilities);
+
+        // Pick up changes to manifests that are not open in the editor
+        if state
+            .capabilities
+            .did_change_watched_files_dynamic_registration()
+        {
+            state.register_manifest_watcher();
+        }
+
+        state.task(Task::LoadManifests);
         state.main_loop(connection.receiver);
     }
 }
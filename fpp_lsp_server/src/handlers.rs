@@ -1,26 +1,36 @@
-use crate::global_state::{GlobalState, Task};
+use crate::call_hierarchy::CallHierarchyTarget;
+use crate::code_lens::code_lenses;
+use crate::global_state::{GlobalState, Task, Workspace};
+use crate::include_context::{merge_hovers, IncludeContext};
 use crate::lsp;
 use crate::lsp::utils::semantic_token_delta;
-use crate::lsp_ext::UriRequest;
+use crate::lsp_ext::{
+    IncludeContextItem, SetIncludeContextParams, TopologyGraphParams, UriRequest,
+};
 use crate::util::{
     completion_items_for_qual_ident, completion_items_in_name_group, hover_for_node,
-    hover_for_symbol, node_to_location, nodes_at_offset, position_to_offset, symbol_at_position,
-    symbol_to_completion_item,
+    hover_for_symbol, implementations, node_to_location, nodes_at_offset, nodes_at_offset_in,
+    position_to_offset, symbol_at_position, symbol_to_completion_item, type_definition,
+    use_or_def_symbol,
 };
 use anyhow::Result;
-use fpp_analysis::semantics::{NameGroup, SymbolInterface};
+use fpp_analysis::semantics::{NameGroup, Symbol, SymbolInterface};
 use fpp_ast::{AstNode, Node};
+use fpp_codegen::graph::GraphFormat;
 use fpp_core::{LineCol, LineIndex, SourceFile};
+use fpp_fs::{is_framework_uri, Manifest};
 use fpp_lsp_parser::{
     SyntaxKind, SyntaxNode, SyntaxToken, TextRange, TokenAtOffset, VisitorResult,
 };
 use lsp_types::{
-    CompletionItem, CompletionItemKind, CompletionParams, CompletionResponse,
-    DidChangeTextDocumentParams, DidChangeWatchedFilesParams, DidCloseTextDocumentParams,
-    DidOpenTextDocumentParams, DocumentDiagnosticReportResult, DocumentLink, FileChangeType,
-    GotoDefinitionParams, GotoDefinitionResponse, Hover, HoverParams, Location, Position, Range,
-    ReferenceParams, SemanticTokensFullDeltaResult, SemanticTokensRangeResult,
-    SemanticTokensResult, Uri,
+    CallHierarchyIncomingCall, CallHierarchyIncomingCallsParams, CallHierarchyItem,
+    CallHierarchyOutgoingCall, CallHierarchyOutgoingCallsParams, CallHierarchyPrepareParams,
+    CodeLens, CodeLensParams, CompletionItem, CompletionItemKind, CompletionParams,
+    CompletionResponse, DidChangeTextDocumentParams, DidChangeWatchedFilesParams,
+    DidCloseTextDocumentParams, DidOpenTextDocumentParams, DocumentDiagnosticReportResult,
+    DocumentLink, FileChangeType, GotoDefinitionParams, GotoDefinitionResponse, Hover, HoverParams,
+    Location, Position, Range, ReferenceParams, SemanticTokensFullDeltaResult,
+    SemanticTokensRangeResult, SemanticTokensResult, Uri,
 };
 use serde::{Deserialize, Serialize};
 use std::str::FromStr;
@@ -78,6 +88,16 @@ pub fn handle_did_change_watched_file(
                     state.task(Task::Update(file.uri));
                 }
             }
+            FileChangeType::CREATED | FileChangeType::DELETED
+                if file.uri.path().as_str().rsplit('/').next() == Some(Manifest::FILE_NAME) =>
+            {
+                // A project was added or removed, look for manifests again
+                // unless the client chose the workspace itself
+                match state.workspace {
+                    Workspace::None | Workspace::Manifests(_) => state.task(Task::LoadManifests),
+                    Workspace::LocsFile(_) | Workspace::FullWorkspace => {}
+                }
+            }
             FileChangeType::CREATED => {
                 // TODO(tumbar)
             }
@@ -122,10 +142,13 @@ fn parse_text_document(
     let text: String = state.vfs.read_sync(uri.as_str())?;
 
     let source_file = state
-        .files
-        .get(uri.as_str())
-        .map(|files| files.first().cloned())
-        .flatten();
+        .active_include_context(uri.as_str())
+        .map(|context| context.file);
+
+    // Reuse the syntax tree of the analysis unless the document changed since
+    if let Some(parse) = source_file.and_then(|file| state.cached_parse(file, &text)) {
+        return Ok((text, source_file, parse));
+    }
 
     let parse_kind = source_file
         .map(|f| state.analysis.include_context_map.get(&f).cloned())
@@ -211,7 +234,10 @@ pub fn handle_document_diagnostics(
     Ok(DocumentDiagnosticReportResult::Report(
         lsp_types::DocumentDiagnosticReport::Full(lsp_types::RelatedFullDocumentDiagnosticReport {
             full_document_diagnostic_report: lsp_types::FullDocumentDiagnosticReport {
-                items: state.diagnostics.get(&request.text_document.uri.as_str()),
+                items: state.diagnostics.get(
+                    request.text_document.uri.as_str(),
+                    &state.include_contexts(request.text_document.uri.as_str()),
+                ),
                 ..Default::default()
             },
             ..Default::default()
@@ -348,9 +374,16 @@ pub fn handle_document_link_resolve(
         Some(data) => serde_json::from_value(data)?,
     };
 
+    // Included files search the paths of the project of their includer
+    let origin_uri = data.origin_uri.as_str();
+    let top_uri = state
+        .active_include_context(origin_uri)
+        .and_then(|context| context.includers.into_iter().next())
+        .unwrap_or_else(|| origin_uri.to_string());
+
     let resolved = state
         .vfs
-        .resolve_uri_relative_path(data.origin_uri.as_str(), &data.relative_path)?;
+        .find_uri(origin_uri, &top_uri, &data.relative_path)?;
     Ok(DocumentLink {
         range: request.range,
         target: Some(Uri::from_str(&resolved)?),
@@ -359,6 +392,97 @@ pub fn handle_document_link_resolve(
     })
 }
 
+pub fn handle_framework_file(state: &GlobalState, request: UriRequest) -> Result<String> {
+    if !is_framework_uri(request.uri.as_str()) {
+        anyhow::bail!("{} is not a bundled framework file", request.uri.as_str());
+    }
+
+    state.vfs.read(request.uri.as_str())
+}
+
+pub fn handle_topology_graph(state: &GlobalState, request: TopologyGraphParams) -> Result<String> {
+    let analysis = &state.analysis;
+    let topology = analysis
+        .symbol_map
+        .values()
+        .find_map(|symbol| match symbol {
+            Symbol::Topology(def) if analysis.qualified_name(symbol) == request.topology => {
+                Some(def)
+            }
+            _ => None,
+        });
+
+    match topology {
+        None => anyhow::bail!("no topology named {}", request.topology),
+        Some(def) => Ok(fpp_codegen::graph::generate_topology(
+            analysis,
+            &request.topology,
+            def,
+            GraphFormat::Mermaid,
+        )),
+    }
+}
+
+pub fn handle_include_contexts(
+    state: &GlobalState,
+    request: UriRequest,
+) -> Result<Vec<IncludeContextItem>> {
+    let active = state.active_include_context(request.uri.as_str());
+
+    state
+        .include_contexts(request.uri.as_str())
+        .into_iter()
+        .map(|context| {
+            Ok(IncludeContextItem {
+                active: active
+                    .as_ref()
+                    .is_some_and(|active| active.file == context.file),
+                includers: context
+                    .includers
+                    .iter()
+                    .map(|uri| Uri::from_str(uri))
+                    .collect::<Result<_, _>>()?,
+                label: context.label,
+            })
+        })
+        .collect()
+}
+
+pub fn handle_set_include_context(
+    state: &mut GlobalState,
+    request: SetIncludeContextParams,
+) -> Result<()> {
+    let uri = request.uri.as_str();
+    let includers: Vec<String> = request
+        .includers
+        .iter()
+        .map(|uri| uri.as_str().to_string())
+        .collect();
+
+    if !state
+        .include_contexts(uri)
+        .iter()
+        .any(|context| context.includers == includers)
+    {
+        anyhow::bail!("{} is not included from {}", uri, includers.join(" › "));
+    }
+
+    state
+        .active_include_contexts
+        .insert(uri.to_string(), includers);
+
+    // The document may now be parsed from a different entry point
+    state.send_request::<lsp_types::request::SemanticTokensRefresh>((), |_, _| {});
+    Ok(())
+}
+
+pub fn handle_code_lens(
+    state: &GlobalState,
+    request: CodeLensParams,
+) -> Result<Option<Vec<CodeLens>>> {
+    Ok(Some(code_lenses(state, &request.text_document.uri)))
+}
+
 pub fn handle_goto_definition(
     state: &GlobalState,
     request: GotoDefinitionParams,
@@ -383,22 +507,72 @@ pub fn handle_goto_definition(
     }
 }
 
-pub fn handle_hover(state: &GlobalState, request: HoverParams) -> Result<Option<Hover>> {
-    let offset = position_to_offset(
-        state,
-        &request.text_document_position_params.text_document.uri,
-        &request.text_document_position_params.position,
-    );
+pub fn handle_goto_implementation(
+    state: &GlobalState,
+    request: GotoDefinitionParams,
+) -> Result<Option<GotoDefinitionResponse>> {
+    let uri = &request.text_document_position_params.text_document.uri;
+    let offset = position_to_offset(state, uri, &request.text_document_position_params.position);
 
-    let nodes = match nodes_at_offset(
-        state,
-        &request.text_document_position_params.text_document.uri,
-        offset,
-    ) {
+    let nodes = match nodes_at_offset(state, uri, offset) {
         None => return Ok(None),
         Some(nodes) => nodes,
     };
 
+    match use_or_def_symbol(state, &nodes) {
+        None => Ok(None),
+        Some(symbol) => Ok(Some(GotoDefinitionResponse::Array(implementations(
+            state, symbol,
+        )))),
+    }
+}
+
+pub fn handle_goto_type_definition(
+    state: &GlobalState,
+    request: GotoDefinitionParams,
+) -> Result<Option<GotoDefinitionResponse>> {
+    let uri = &request.text_document_position_params.text_document.uri;
+    let offset = position_to_offset(state, uri, &request.text_document_position_params.position);
+
+    let nodes = match nodes_at_offset(state, uri, offset) {
+        None => return Ok(None),
+        Some(nodes) => nodes,
+    };
+
+    Ok(type_definition(state, &nodes).map(GotoDefinitionResponse::Scalar))
+}
+
+pub fn handle_hover(state: &GlobalState, request: HoverParams) -> Result<Option<Hover>> {
+    let uri = &request.text_document_position_params.text_document.uri;
+    let offset = position_to_offset(state, uri, &request.text_document_position_params.position);
+
+    let contexts = state.include_contexts(uri.as_str());
+    if contexts.len() <= 1 {
+        return Ok(match nodes_at_offset(state, uri, offset) {
+            None => None,
+            Some(nodes) => hover_at(state, &nodes),
+        });
+    }
+
+    // The value or type of a symbol may differ between the places a file is
+    // included from, show the active context first
+    let active = state
+        .active_include_context(uri.as_str())
+        .map(|context| context.file);
+    let mut hovers: Vec<(&IncludeContext, Hover)> = contexts
+        .iter()
+        .filter_map(|context| {
+            let nodes = nodes_at_offset_in(state, context.file, offset);
+            hover_at(state, &nodes).map(|hover| (context, hover))
+        })
+        .collect();
+    hovers.sort_by_key(|(context, _)| Some(context.file) != active);
+
+    Ok(merge_hovers(hovers))
+}
+
+/// The hover for the innermost node first in `nodes`
+fn hover_at(state: &GlobalState, nodes: &[Node]) -> Option<Hover> {
     // Check if this node is a use/reference to definition
     if let Some((node, symbol)) = nodes.iter().find_map(|node| {
         if let Some(def) = state.analysis.use_def_map.get(&node.id()) {
@@ -407,7 +581,7 @@ pub fn handle_hover(state: &GlobalState, request: HoverParams) -> Result<Option<
             None
         }
     }) {
-        return Ok(Some(hover_for_symbol(state, node, symbol)));
+        return Some(hover_for_symbol(state, node, symbol));
     }
 
     // This is not a use/reference to another definition
@@ -415,11 +589,11 @@ pub fn handle_hover(state: &GlobalState, request: HoverParams) -> Result<Option<
     // the definition's name
     if let Some(Node::Name(name)) = nodes.first() {
         // We are hovering over a name
-        Ok(nodes
+        nodes
             .iter()
-            .find_map(|node| hover_for_node(state, name, *node)))
+            .find_map(|node| hover_for_node(state, name, *node))
     } else {
-        Ok(None)
+        None
     }
 }
 
@@ -484,13 +658,66 @@ pub fn handle_references(
     }
 }
 
+pub fn handle_call_hierarchy_prepare(
+    state: &GlobalState,
+    request: CallHierarchyPrepareParams,
+) -> Result<Option<Vec<CallHierarchyItem>>> {
+    let uri = &request.text_document_position_params.text_document.uri;
+    let offset = position_to_offset(state, uri, &request.text_document_position_params.position);
+
+    let nodes = match nodes_at_offset(state, uri, offset) {
+        None => return Ok(None),
+        Some(nodes) => nodes,
+    };
+
+    Ok(CallHierarchyTarget::at(state, &nodes)
+        .map(|(target, location)| vec![target.to_item(location)]))
+}
+
+pub fn handle_call_hierarchy_incoming(
+    state: &GlobalState,
+    request: CallHierarchyIncomingCallsParams,
+) -> Result<Option<Vec<CallHierarchyIncomingCall>>> {
+    let target = match CallHierarchyTarget::from_item(&request.item) {
+        None => return Ok(None),
+        Some(target) => target,
+    };
+
+    Ok(Some(
+        target
+            .calls(state, true)
+            .into_iter()
+            .map(|(from, from_ranges)| CallHierarchyIncomingCall { from, from_ranges })
+            .collect(),
+    ))
+}
+
+pub fn handle_call_hierarchy_outgoing(
+    state: &GlobalState,
+    request: CallHierarchyOutgoingCallsParams,
+) -> Result<Option<Vec<CallHierarchyOutgoingCall>>> {
+    let target = match CallHierarchyTarget::from_item(&request.item) {
+        None => return Ok(None),
+        Some(target) => target,
+    };
+
+    Ok(Some(
+        target
+            .calls(state, false)
+            .into_iter()
+            .map(|(to, from_ranges)| CallHierarchyOutgoingCall { to, from_ranges })
+            .collect(),
+    ))
+}
+
 pub fn handle_completion(
     state: &GlobalState,
     request: CompletionParams,
 ) -> Result<Option<CompletionResponse>> {
     let uri = request.text_document_position.text_document.uri;
 
-    let text: String = state.vfs.read_sync(uri.as_str())?;
+    // Files included in several places are completed in the context selected by the client
+    let (_, _, parse) = parse_text_document(state, &uri)?;
     let lines = state.vfs.get_lines(uri.as_str())?;
 
     let cursor_pos = match lines.offset(LineCol {
@@ -501,32 +728,6 @@ pub fn handle_completion(
         Some(p) => p,
     };
 
-    let parse_kind = match state.files.get(uri.as_str()) {
-        None => fpp_parser::IncludeParentKind::Module,
-        Some(source_files) => {
-            // This file may have been included in multiple spots
-            // We should choose the most 'permissive' syntax entry point
-            source_files
-                .iter()
-                .map(|f| match state.analysis.include_context_map.get(f) {
-                    Some(kind) => kind.clone(),
-                    None => fpp_parser::IncludeParentKind::Module,
-                })
-                .max()
-                .unwrap_or(fpp_parser::IncludeParentKind::Module)
-        }
-    };
-
-    let entry_kind = match parse_kind {
-        fpp_parser::IncludeParentKind::Component => fpp_lsp_parser::TopEntryPoint::Component,
-        fpp_parser::IncludeParentKind::Module => fpp_lsp_parser::TopEntryPoint::Module,
-        fpp_parser::IncludeParentKind::TlmPacket => fpp_lsp_parser::TopEntryPoint::TlmPacket,
-        fpp_parser::IncludeParentKind::TlmPacketSet => fpp_lsp_parser::TopEntryPoint::TlmPacketSet,
-        fpp_parser::IncludeParentKind::Topology => fpp_lsp_parser::TopEntryPoint::Topology,
-    };
-
-    let parse = fpp_lsp_parser::parse(&text, entry_kind);
-
     fn non_white_space_left(mut l: SyntaxToken) -> SyntaxToken {
         loop {
             match l.kind() {
@@ -0,0 +1,128 @@
+use crate::global_state::GlobalState;
+use fpp_core::SourceFile;
+use lsp_types::{Hover, HoverContents, MarkupContent, MarkupKind};
+
+/// One of the places a document is analyzed from
+///
+/// An `.fppi` file included from several places is parsed and analyzed once
+/// for each inclusion, each one being a separate `SourceFile` under a
+/// different parent. A file outside of any include is its own only context.
+#[derive(Debug, Clone)]
+pub(crate) struct IncludeContext {
+    pub file: SourceFile,
+    /// URIs of the files including this one, the outermost one first
+    pub includers: Vec<String>,
+    /// Names of the including files, `top.fpp › sub.fppi`
+    pub label: String,
+}
+
+fn file_name(uri: &str) -> &str {
+    &uri[(uri.rfind("/").map_or(0, |i| i + 1)).min(uri.len())..]
+}
+
+impl GlobalState {
+    fn include_context(&self, file: SourceFile) -> IncludeContext {
+        let mut includers = vec![];
+        let mut current = self.context.file_get(&file).parent;
+        while let Some(parent) = current {
+            let data = self.context.file_get(&parent);
+            includers.push(data.uri.clone());
+            current = data.parent;
+        }
+        includers.reverse();
+
+        let label = match includers.is_empty() {
+            true => file_name(&self.context.file_get(&file).uri).to_string(),
+            false => includers
+                .iter()
+                .map(|uri| file_name(uri))
+                .collect::<Vec<_>>()
+                .join(" › "),
+        };
+
+        IncludeContext {
+            file,
+            includers,
+            label,
+        }
+    }
+
+    /// The contexts the document at `uri` is analyzed in, sorted by their includers
+    pub(crate) fn include_contexts(&self, uri: &str) -> Vec<IncludeContext> {
+        let mut contexts: Vec<IncludeContext> = self
+            .files
+            .get(uri)
+            .into_iter()
+            .flatten()
+            .map(|file| self.include_context(*file))
+            .collect();
+
+        contexts.sort_by(|a, b| a.includers.cmp(&b.includers));
+        contexts
+    }
+
+    /// The context selected by the client for the document at `uri`, the
+    /// first one when none is selected or the selected one is gone
+    pub(crate) fn active_include_context(&self, uri: &str) -> Option<IncludeContext> {
+        let mut contexts = self.include_contexts(uri);
+        let index = self
+            .active_include_contexts
+            .get(uri)
+            .and_then(|includers| {
+                contexts
+                    .iter()
+                    .position(|context| context.includers == *includers)
+            })
+            .unwrap_or(0);
+
+        match index < contexts.len() {
+            true => Some(contexts.swap_remove(index)),
+            false => None,
+        }
+    }
+}
+
+fn hover_markdown(hover: &Hover) -> String {
+    match &hover.contents {
+        HoverContents::Markup(markup) => markup.value.clone(),
+        HoverContents::Scalar(_) | HoverContents::Array(_) => String::new(),
+    }
+}
+
+/// Combine the hovers computed in each context of a document
+///
+/// Contexts showing the same hover are listed together, and a single section
+/// is shown without labels when every context agrees.
+pub(crate) fn merge_hovers(hovers: Vec<(&IncludeContext, Hover)>) -> Option<Hover> {
+    let range = hovers.first()?.1.range;
+
+    let mut sections: Vec<(Vec<&str>, String)> = vec![];
+    for (context, hover) in &hovers {
+        let markdown = hover_markdown(hover);
+        match sections.iter_mut().find(|(_, other)| *other == markdown) {
+            Some((labels, _)) => labels.push(&context.label),
+            None => sections.push((vec![&context.label], markdown)),
+        }
+    }
+
+    if sections.len() == 1 {
+        return hovers.into_iter().next().map(|(_, hover)| hover);
+    }
+
+    let value = sections
+        .into_iter()
+        .map(|(labels, markdown)| {
+            let labels: Vec<String> = labels.iter().map(|label| format!("`{}`", label)).collect();
+            format!("**Included from** {}\n\n{}", labels.join(", "), markdown)
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n---\n\n");
+
+    Some(Hover {
+        contents: HoverContents::Markup(MarkupContent {
+            kind: MarkupKind::Markdown,
+            value,
+        }),
+        range,
+    })
+}
@@ -103,8 +103,14 @@ pub fn server_capabilities(caps: &ClientCapabilities) -> ServerCapabilities {
             },
         )),
         definition_provider: Some(OneOf::Left(true)),
+        implementation_provider: Some(lsp_types::ImplementationProviderCapability::Simple(true)),
+        type_definition_provider: Some(lsp_types::TypeDefinitionProviderCapability::Simple(true)),
         hover_provider: Some(lsp_types::HoverProviderCapability::Simple(true)),
         references_provider: Some(OneOf::Left(true)),
+        code_lens_provider: Some(lsp_types::CodeLensOptions {
+            resolve_provider: Some(false),
+        }),
+        call_hierarchy_provider: Some(lsp_types::CallHierarchyServerCapability::Simple(true)),
         // document_symbol_provider: Some(OneOf::Left(true)),
         // workspace_symbol_provider: Some(OneOf::Left(true)),
         completion_provider: Some(CompletionOptions {
@@ -242,17 +248,17 @@ impl ClientCapabilities {
         caps.did_save == Some(true) && caps.dynamic_registration == Some(true)
     }
 
-    // pub fn did_change_watched_files_dynamic_registration(&self) -> bool {
-    //     (|| -> _ {
-    //         self.0
-    //             .workspace
-    //             .as_ref()?
-    //             .did_change_watched_files
-    //             .as_ref()?
-    //             .dynamic_registration
-    //     })()
-    //     .unwrap_or_default()
-    // }
+    pub fn did_change_watched_files_dynamic_registration(&self) -> bool {
+        (|| -> _ {
+            self.0
+                .workspace
+                .as_ref()?
+                .did_change_watched_files
+                .as_ref()?
+                .dynamic_registration
+        })()
+        .unwrap_or_default()
+    }
 
     // pub fn did_change_watched_files_relative_pattern_support(&self) -> bool {
     //     (|| -> _ {
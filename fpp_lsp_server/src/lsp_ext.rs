@@ -32,6 +32,70 @@ impl Request for SetFullWorkspace {
     const METHOD: &'static str = "fpp/setFullWorkspace";
 }
 
+/// Content of a bundled framework file, so clients can show the
+/// `fpp-framework:` documents definitions point into
+pub enum FrameworkFile {}
+
+impl Request for FrameworkFile {
+    type Params = UriRequest;
+    type Result = String;
+    const METHOD: &'static str = "fpp/frameworkFile";
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TopologyGraphParams {
+    /// Fully qualified name of the topology
+    pub topology: String,
+}
+
+/// Connection graph of a topology as a Mermaid flowchart, opened by the
+/// code lens above each topology
+pub enum TopologyGraph {}
+
+impl Request for TopologyGraph {
+    type Params = TopologyGraphParams;
+    type Result = String;
+    const METHOD: &'static str = "fpp/topologyGraph";
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct IncludeContextItem {
+    /// Files including the document, the outermost one first
+    pub includers: Vec<lsp_types::Uri>,
+    pub label: String,
+    /// Whether this context is used where the document is handled in one context only
+    pub active: bool,
+}
+
+/// The places a document is included from, empty when it is not part of the project
+pub enum IncludeContexts {}
+
+impl Request for IncludeContexts {
+    type Params = UriRequest;
+    type Result = Vec<IncludeContextItem>;
+    const METHOD: &'static str = "fpp/includeContexts";
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SetIncludeContextParams {
+    pub uri: lsp_types::Uri,
+    /// Includers of one of the contexts listed by `fpp/includeContexts`
+    pub includers: Vec<lsp_types::Uri>,
+}
+
+/// Select the context a document included in several places is parsed and
+/// completed in, and which is listed first on hover
+pub enum SetIncludeContext {}
+
+impl Request for SetIncludeContext {
+    type Params = SetIncludeContextParams;
+    type Result = ();
+    const METHOD: &'static str = "fpp/setIncludeContext";
+}
+
 pub enum DumpSyntaxTree {}
 
 impl Notification for DumpSyntaxTree {
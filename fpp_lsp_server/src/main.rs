@@ -1,8 +1,11 @@
 mod analysis;
+mod call_hierarchy;
+mod code_lens;
 mod diagnostics;
 mod dispatcher;
 mod global_state;
 mod handlers;
+mod include_context;
 mod lsp_ext;
 mod notification;
 mod request;
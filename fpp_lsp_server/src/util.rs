@@ -1,7 +1,11 @@
 use crate::diagnostics::LspDiagnosticsEmitter;
 use crate::global_state::GlobalState;
-use fpp_analysis::semantics::{NameGroup, Symbol, SymbolInterface, Type};
-use fpp_ast::{AstNode, FormalParam, FormalParamKind, MoveWalkable, Name, Node, Visitor};
+use fpp_analysis::passes::is_lint_attribute;
+use fpp_analysis::semantics::{NameGroup, SerializedSize, Symbol, SymbolInterface, Type};
+use fpp_ast::{
+    AstNode, ComponentMember, FormalParam, FormalParamKind, InterfaceMember, MoveWalkable, Name,
+    Node, SpecPortInstance, Visitor,
+};
 use fpp_core::{BytePos, CompilerContext, LineCol, SourceFile};
 use fpp_lsp_parser::{SyntaxElement, SyntaxKind, SyntaxToken, TextSize};
 use lsp_types::{
@@ -83,23 +87,30 @@ pub fn nodes_at_offset<'a>(
     Some(
         files
             .into_iter()
-            .flat_map(|file| {
-                let cache = state.cache.get(&state.parent_file(*file)).unwrap();
-
-                let visitor = FindPositionVisitor {
-                    source_file: *file,
-                    looking_for: offset,
-                    context: &state.context,
-                };
-
-                let mut out = vec![];
-                let _ = visitor.visit_trans_unit(&mut out, &cache.ast);
-                out
-            })
+            .flat_map(|file| nodes_at_offset_in(state, *file, offset))
             .collect(),
     )
 }
 
+/// The nodes at `offset` in one inclusion context of a document
+pub(crate) fn nodes_at_offset_in<'a>(
+    state: &'a GlobalState,
+    file: SourceFile,
+    offset: BytePos,
+) -> Vec<Node<'a>> {
+    let cache = state.cache.get(&state.parent_file(file)).unwrap();
+
+    let visitor = FindPositionVisitor {
+        source_file: file,
+        looking_for: offset,
+        context: &state.context,
+    };
+
+    let mut out = vec![];
+    let _ = visitor.visit_trans_unit(&mut out, &cache.ast);
+    out
+}
+
 #[inline]
 pub fn position_to_offset(state: &GlobalState, document: &Uri, position: &Position) -> BytePos {
     state
@@ -133,7 +144,128 @@ pub(crate) fn symbol_at_position<'a>(
     })
 }
 
-fn node_to_range(state: &GlobalState, node: fpp_core::Node) -> Range {
+/// The symbol used or defined under the cursor, `nodes` being the innermost node first
+///
+/// Definitions are only found with the cursor on their name.
+pub(crate) fn use_or_def_symbol<'a>(state: &'a GlobalState, nodes: &[Node]) -> Option<&'a Symbol> {
+    nodes
+        .iter()
+        .find_map(|node| state.analysis.use_def_map.get(&node.id()))
+        .or_else(|| match nodes.first() {
+            Some(Node::Name(_)) => nodes
+                .iter()
+                .find_map(|node| state.analysis.symbol_map.get(&node.id())),
+            _ => None,
+        })
+}
+
+/// The locations implementing `symbol`
+///
+/// These are the instances of a component, the port instances using a port
+/// type, and the component of a component instance.
+pub(crate) fn implementations(state: &GlobalState, symbol: &Symbol) -> Vec<Location> {
+    let analysis = &state.analysis;
+
+    let mut out: Vec<Location> = match symbol {
+        Symbol::Component(def) => analysis
+            .symbol_map
+            .values()
+            .filter_map(|symbol| match symbol {
+                Symbol::ComponentInstance(instance) => {
+                    match analysis.use_def_map.get(&instance.component.id()) {
+                        Some(component) if component.node() == def.node_id => {
+                            Some(node_to_location(state, instance.name.node_id))
+                        }
+                        _ => None,
+                    }
+                }
+                _ => None,
+            })
+            .collect(),
+        Symbol::Port(_) => {
+            let port_type = analysis.qualified_name(symbol);
+            analysis
+                .symbol_map
+                .values()
+                .flat_map(|symbol| -> Vec<&SpecPortInstance> {
+                    match symbol {
+                        Symbol::Component(def) => def
+                            .members
+                            .iter()
+                            .filter_map(|member| match member {
+                                ComponentMember::SpecPortInstance(spec) => Some(spec),
+                                _ => None,
+                            })
+                            .collect(),
+                        Symbol::Interface(def) => def
+                            .members
+                            .iter()
+                            .filter_map(|member| match member {
+                                InterfaceMember::SpecPortInstance(spec) => Some(spec),
+                                _ => None,
+                            })
+                            .collect(),
+                        _ => vec![],
+                    }
+                })
+                .filter(|spec| analysis.port_instance(spec).port_type.as_ref() == Some(&port_type))
+                .map(|spec| {
+                    let name = match spec {
+                        SpecPortInstance::General(spec) => &spec.name,
+                        SpecPortInstance::Special(spec) => &spec.name,
+                    };
+
+                    node_to_location(state, name.node_id)
+                })
+                .collect()
+        }
+        Symbol::ComponentInstance(def) => match analysis.use_def_map.get(&def.component.id()) {
+            Some(component @ Symbol::Component(_)) => {
+                vec![node_to_location(state, component.name().node_id)]
+            }
+            _ => vec![],
+        },
+        _ => vec![],
+    };
+
+    out.sort_by(|a, b| {
+        (a.uri.as_str(), a.range.start.line, a.range.start.character).cmp(&(
+            b.uri.as_str(),
+            b.range.start.line,
+            b.range.start.character,
+        ))
+    });
+    out
+}
+
+/// The definition of the type of the constant, struct member, parameter,
+/// telemetry channel or formal parameter under the cursor
+pub(crate) fn type_definition(state: &GlobalState, nodes: &[Node]) -> Option<Location> {
+    let analysis = &state.analysis;
+
+    let ty = nodes.iter().find_map(|node| {
+        let typed = match node {
+            Node::DefConstant(def) => def.node_id,
+            Node::StructTypeMember(member) => member.type_name.node_id,
+            Node::SpecParam(spec) => spec.type_name.node_id,
+            Node::SpecTlmChannel(spec) => spec.type_name.node_id,
+            Node::FormalParam(param) => param.type_name.node_id,
+            Node::TypeName(type_name) => type_name.node_id,
+            _ => match analysis.use_def_map.get(&node.id())? {
+                Symbol::Constant(def) => def.node_id,
+                _ => return None,
+            },
+        };
+
+        analysis.type_map.get(&typed)
+    })?;
+
+    ty.def_node_id()
+        .and_then(|node| analysis.symbol_map.get(&node))
+        .map(|symbol| node_to_location(state, symbol.name().node_id))
+}
+
+pub fn node_to_range(state: &GlobalState, node: fpp_core::Node) -> Range {
     let span = state.context.span_get(&state.context.node_get_span(&node));
     let file = span.file.upgrade().unwrap();
 
@@ -222,12 +354,20 @@ pub fn hover_for_symbol(state: &GlobalState, hover_node: Node, symbol: &Symbol)
         |v| format!("({symbol_kind}) {qual_ident} = {v}"),
     );
 
-    let markdown_lines: Vec<String> = node_data
-        .pre_annotation
-        .clone()
-        .into_iter()
+    let serialized_size = match symbol {
+        Symbol::Port(def) => params_serialized_size(state, &def.params),
+        Symbol::AliasType(_) | Symbol::Array(_) | Symbol::Enum(_) | Symbol::Struct(_) => state
+            .analysis
+            .type_map
+            .get(&symbol.node())
+            .and_then(|ty| ty.serialized_size()),
+        _ => None,
+    };
+
+    let markdown_lines: Vec<String> = doc_lines(&node_data.pre_annotation)
         .chain(vec!["".to_string(), symbol_kind_line, "".to_string()])
-        .chain(node_data.post_annotation.clone().into_iter())
+        .chain(serialized_size_lines(serialized_size))
+        .chain(doc_lines(&node_data.post_annotation))
         .collect();
 
     Hover {
@@ -306,14 +446,30 @@ pub fn hover_for_node(state: &GlobalState, hover_node: &Name, def_node: Node) ->
         }
     };
 
-    let symbol_kind_line = format!("({symbol_kind}) {qual_ident}");
+    let symbol_kind_line = state.analysis.value_map.get(&def_node.id()).map_or_else(
+        || format!("({symbol_kind}) {qual_ident}"),
+        |v| format!("({symbol_kind}) {qual_ident} = {v}"),
+    );
 
-    let markdown_lines: Vec<String> = node_data
-        .pre_annotation
-        .clone()
-        .into_iter()
+    let serialized_size = match def_node {
+        Node::DefPort(def) => params_serialized_size(state, &def.params),
+        Node::FormalParam(param) => state
+            .analysis
+            .type_map
+            .get(&param.type_name.node_id)
+            .and_then(|ty| ty.serialized_size()),
+        Node::DefAliasType(_) | Node::DefArray(_) | Node::DefEnum(_) | Node::DefStruct(_) => state
+            .analysis
+            .type_map
+            .get(&def_node.id())
+            .and_then(|ty| ty.serialized_size()),
+        _ => None,
+    };
+
+    let markdown_lines: Vec<String> = doc_lines(&node_data.pre_annotation)
         .chain(vec!["".to_string(), symbol_kind_line, "".to_string()])
-        .chain(node_data.post_annotation.clone().into_iter())
+        .chain(serialized_size_lines(serialized_size))
+        .chain(doc_lines(&node_data.post_annotation))
         .collect();
 
     Some(Hover {
@@ -325,6 +481,31 @@ pub fn hover_for_node(state: &GlobalState, hover_node: &Name, def_node: Node) ->
     })
 }
 
+/// Combined serialized size of a list of port parameters
+fn params_serialized_size(state: &GlobalState, params: &[FormalParam]) -> Option<SerializedSize> {
+    params
+        .iter()
+        .try_fold(SerializedSize::fixed(0), |total, param| {
+            let ty = state.analysis.type_map.get(&param.type_name.node_id)?;
+            Some(total + ty.serialized_size()?)
+        })
+}
+
+/// Annotation lines shown as documentation, without the lint attributes
+fn doc_lines(annotation: &[String]) -> impl Iterator<Item = String> + '_ {
+    annotation
+        .iter()
+        .filter(|line| !is_lint_attribute(line))
+        .cloned()
+}
+
+fn serialized_size_lines(size: Option<SerializedSize>) -> Vec<String> {
+    match size {
+        None => vec![],
+        Some(size) => vec![format!("Serialized size: {size}"), "".to_string()],
+    }
+}
+
 fn formal_param_to_string(state: &GlobalState, param: &FormalParam) -> String {
     let kind_s = match param.kind {
         FormalParamKind::Ref => "ref ",
@@ -346,10 +527,11 @@ pub fn symbol_to_completion_item(state: &GlobalState, symbol: &Symbol) -> Comple
     let symbol_kind = symbol_kind_name(symbol);
     let description = {
         let node = state.context.node_get(&symbol.node());
-        if node.pre_annotation.is_empty() {
+        let lines: Vec<String> = doc_lines(&node.pre_annotation).collect();
+        if lines.is_empty() {
             None
         } else {
-            Some(node.pre_annotation.join(" "))
+            Some(lines.join(" "))
         }
     };
 
@@ -1,7 +1,8 @@
 mod file;
 pub use file::*;
 
-use fpp_core::{Error, LineIndex, SourceFile};
+use fpp_core::{uri_in_dir, Error, LineIndex, SearchPaths, SourceFile};
+use fpp_fs::{framework_file, is_framework_uri, normalize_path, resolve_framework_uri};
 use lsp_types::{
     DidChangeTextDocumentParams, DidCloseTextDocumentParams, DidOpenTextDocumentParams, Uri,
 };
@@ -12,15 +13,26 @@ use url::Url;
 
 use crate::lsp::capabilities::PositionEncoding;
 
+/// Where the files of one project search included and located files
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ProjectSearchPaths {
+    /// URIs of the directories and files holding the files of the project
+    pub dirs: Vec<String>,
+    pub search_paths: SearchPaths,
+}
+
 #[derive(Clone)]
 pub struct Vfs {
     files: Arc<RwLock<FxHashMap<String, File>>>,
+    /// Where included and located files are searched, for each project
+    pub(crate) search_paths: Vec<ProjectSearchPaths>,
 }
 
 impl Vfs {
     pub fn new() -> Vfs {
         Vfs {
             files: Default::default(),
+            search_paths: vec![],
         }
     }
 
@@ -52,9 +64,20 @@ impl Vfs {
             Some(file) => return Ok(file.content.text().to_string()),
         }
 
-        let path_uri = Uri::from_str(&path)?;
-        let fs_path = path_uri.path().to_string();
-        match std::fs::read_to_string(&fs_path) {
+        // Bundled framework files are not on disk but are tracked like
+        // the ones that are so their lines can be indexed
+        let text = match is_framework_uri(path) {
+            true => match framework_file(path) {
+                Some(text) => Ok(text.to_string()),
+                None => Err(std::io::Error::new(
+                    std::io::ErrorKind::NotFound,
+                    format!("not a bundled framework file: {}", path),
+                )),
+            },
+            false => std::fs::read_to_string(Uri::from_str(path)?.path().as_str()),
+        };
+
+        match text {
             Ok(text) => {
                 self.files.write().unwrap().insert(
                     path.to_string(),
@@ -70,6 +93,10 @@ impl Vfs {
     }
 
     pub(crate) fn update_fs(&self, path: &str) -> anyhow::Result<bool> {
+        if is_framework_uri(path) {
+            return Ok(false);
+        }
+
         match self.files.read().unwrap().get(path) {
             None => {}
             Some(file) => {
@@ -196,6 +223,10 @@ impl Vfs {
         base_file: &str,
         relative: &str,
     ) -> Result<String, Error> {
+        if let Some(uri) = resolve_framework_uri(base_file, relative) {
+            return Ok(uri);
+        }
+
         let uri = match Uri::from_str(base_file) {
             Ok(it) => it,
             Err(err) => return Err(err.to_string().into()),
@@ -206,7 +237,11 @@ impl Vfs {
         match parent_file_path.parent() {
             None => Err(format!("Cannot resolve parent directory of {}", &fs_path).into()),
             Some(parent_dir) => {
-                let final_path = parent_dir.join(relative).canonicalize()?;
+                // Missing files still resolve so every candidate can be reported
+                let final_path = parent_dir.join(relative);
+                let final_path = final_path
+                    .canonicalize()
+                    .unwrap_or_else(|_| normalize_path(&final_path));
                 match final_path.as_path().to_str() {
                     None => Err(format!(
                         "Failed to resolve path {} relative to {:?}",
@@ -224,6 +259,75 @@ impl Vfs {
             }
         }
     }
+
+    /// Search paths of the project holding `uri`, the innermost one when
+    /// projects are nested
+    fn search_paths_of(&self, uri: &str) -> Option<&SearchPaths> {
+        self.search_paths
+            .iter()
+            .filter_map(|project| {
+                let len = project
+                    .dirs
+                    .iter()
+                    .filter(|dir| uri_in_dir(uri, dir))
+                    .map(|dir| dir.len())
+                    .max()?;
+                Some((len, &project.search_paths))
+            })
+            .max_by_key(|(len, _)| *len)
+            .map(|(_, search_paths)| search_paths)
+    }
+
+    /// Every URI `relative` may refer to from `base_file`, in the order they
+    /// are tried: relative to the file, then in each search directory of the
+    /// project holding `top_file`, the outermost file including `base_file`
+    pub fn candidate_uris(
+        &self,
+        base_file: &str,
+        top_file: &str,
+        relative: &str,
+    ) -> Result<Vec<String>, Error> {
+        // Bundled framework files only refer to each other
+        if let Some(uri) = resolve_framework_uri(base_file, relative) {
+            return Ok(vec![uri]);
+        }
+
+        let search_paths = match self.search_paths_of(top_file) {
+            None => return Ok(vec![self.resolve_uri_relative_path(base_file, relative)?]),
+            Some(search_paths) => search_paths,
+        };
+
+        let relative = search_paths.remap(relative);
+        let mut candidates = vec![self.resolve_uri_relative_path(base_file, &relative)?];
+        if !std::path::Path::new(&relative).is_absolute() {
+            for dir in &search_paths.include {
+                let path = normalize_path(&dir.join(&relative));
+                if let Ok(uri) = Url::from_file_path(&path) {
+                    candidates.push(uri.as_str().to_string());
+                }
+            }
+        }
+
+        Ok(candidates)
+    }
+
+    /// The first candidate URI of `relative` that can be read
+    pub fn find_uri(
+        &self,
+        base_file: &str,
+        top_file: &str,
+        relative: &str,
+    ) -> anyhow::Result<String> {
+        let candidates = self.candidate_uris(base_file, top_file, relative)?;
+        match candidates.iter().find(|uri| self.read(uri).is_ok()) {
+            Some(uri) => Ok(uri.clone()),
+            None => Err(anyhow::anyhow!(
+                "cannot find `{}`, tried {}",
+                relative,
+                candidates.join(", ")
+            )),
+        }
+    }
 }
 
 impl fpp_core::FileReader for &Vfs {
@@ -231,6 +335,10 @@ impl fpp_core::FileReader for &Vfs {
         self.resolve_uri_relative_path(&current.uri(), include)
     }
 
+    fn candidates(&self, current: SourceFile, include: &str) -> Result<Vec<String>, Error> {
+        self.candidate_uris(&current.uri(), &current.top_file().uri(), include)
+    }
+
     fn read(&self, path: &str) -> Result<String, Error> {
         let mut this = (*self).clone();
         match Vfs::read(&mut this, path) {
@@ -48,6 +48,10 @@ pub(crate) fn annotated_struct(input: &ItemStruct) -> TokenStream {
                 if !post.is_empty() {
                     debug_struct.field("post_annotation", post);
                 }
+
+                if self.node_id.is_recovered() {
+                    debug_struct.field("recovered", &true);
+                }
                 debug_struct.finish()
             }
         }
@@ -60,6 +60,7 @@ impl<'a> Cursor<'a> {
                 Level::Error,
                 "syntax error: invalid token",
             )
+            .code("E0006")
             .annotation(err.msg.clone())
             .emit();
         })
@@ -104,6 +105,7 @@ impl<'a> Cursor<'a> {
                             self.content.as_bytes()[prev] as char
                         ),
                     )
+                    .code("E0006")
                     .emit();
                 }
                 TokenKind::Whitespace => {}
@@ -336,17 +338,27 @@ impl<'a> Cursor<'a> {
     /// Generate a new error while expecting a certain type of token
     /// Messages here are meant to only be simple literals, the full error message
     /// will be formatted given other context information.
+    /// The error is reported on the token found, or after the last token at
+    /// the end of the file.
     pub fn err_expected_token(
-        &self,
+        &mut self,
         msg: &'static str,
         expected: TokenKind,
         got: TokenKind,
     ) -> ParseError {
-        ParseError::ExpectedToken {
-            expected,
-            got,
-            last: self.last_consumed_span,
-            msg,
+        match self.peek_span(0) {
+            Some(span) => ParseError::ExpectedToken {
+                expected,
+                got,
+                span,
+                msg,
+            },
+            None => ParseError::ExpectedToken {
+                expected,
+                got,
+                span: self.last_consumed_span,
+                msg: "unexpected end of file",
+            },
         }
     }
 
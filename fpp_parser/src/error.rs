@@ -19,7 +19,8 @@ pub(crate) enum ParseError {
     ExpectedToken {
         expected: TokenKind,
         got: TokenKind,
-        last: Span,
+        /// Where the error is reported
+        span: Span,
         msg: &'static str,
     },
 
@@ -50,9 +51,23 @@ impl std::fmt::Display for TokenList {
     }
 }
 
+impl ParseError {
+    /// Stable code identifying this kind of error, see `fpp explain`
+    pub(crate) fn code(&self) -> &'static str {
+        match self {
+            ParseError::Syntax { .. } => "E0001",
+            ParseError::ExpectedOneOf { .. } => "E0002",
+            ParseError::ExpectedToken { .. } => "E0003",
+            ParseError::UnexpectedEof { .. } => "E0004",
+            ParseError::IncludeCycle { .. } => "E0005",
+        }
+    }
+}
+
 impl Into<Diagnostic> for ParseError {
     fn into(self) -> Diagnostic {
-        match self {
+        let code = self.code();
+        let diag = match self {
             ParseError::ExpectedOneOf {
                 got_kind,
                 got_span,
@@ -62,11 +77,11 @@ impl Into<Diagnostic> for ParseError {
                 .note(format!("expected one of {}", TokenList(expected)))
                 .note(format!("got {}", got_kind)),
             ParseError::ExpectedToken {
-                last,
+                span,
                 msg,
                 expected,
                 got,
-            } => Diagnostic::new(last, Level::Error, format!("syntax error: {msg}"))
+            } => Diagnostic::new(span, Level::Error, format!("syntax error: {msg}"))
                 .note(format!("expected {}", expected))
                 .note(format!("got {}", got)),
             ParseError::UnexpectedEof { last } => {
@@ -82,7 +97,9 @@ impl Into<Diagnostic> for ParseError {
                 })
             }
             ParseError::Syntax { last, msg } => Diagnostic::new(last, Level::Error, msg),
-        }
+        };
+
+        diag.code(code)
     }
 }
 
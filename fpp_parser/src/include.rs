@@ -1,5 +1,5 @@
 use crate::error::{ParseError, ParseResult};
-use crate::{parse, Parser};
+use crate::parse;
 use fpp_ast::*;
 use fpp_core::{FileReader, Position, SourceFile, Span, Spanned};
 use rustc_hash::FxHashMap;
@@ -16,13 +16,66 @@ pub enum IncludeParentKind {
 
 type ResolveIncludesState = FxHashMap<SourceFile, IncludeParentKind>;
 
-pub struct ResolveIncludes<Reader: FileReader> {
+/// Parses the members of an included file
+/// The span of the include specifier is attached to every node in the file
+pub trait IncludeParser {
+    fn component_members(&self, file: SourceFile, include_span: Span) -> Vec<ComponentMember>;
+    fn module_members(&self, file: SourceFile, include_span: Span) -> Vec<ModuleMember>;
+    fn topology_members(&self, file: SourceFile, include_span: Span) -> Vec<TopologyMember>;
+    fn tlm_packet_members(&self, file: SourceFile, include_span: Span) -> Vec<TlmPacketMember>;
+    fn tlm_packet_set_members(
+        &self,
+        file: SourceFile,
+        include_span: Span,
+    ) -> Vec<TlmPacketSetMember>;
+}
+
+/// Parses included files with [crate::Parser]
+pub struct DefaultIncludeParser;
+
+impl IncludeParser for DefaultIncludeParser {
+    fn component_members(&self, file: SourceFile, include_span: Span) -> Vec<ComponentMember> {
+        parse(file, |p| p.component_members(), Some(include_span))
+    }
+
+    fn module_members(&self, file: SourceFile, include_span: Span) -> Vec<ModuleMember> {
+        parse(file, |p| p.module_members(), Some(include_span))
+    }
+
+    fn topology_members(&self, file: SourceFile, include_span: Span) -> Vec<TopologyMember> {
+        parse(file, |p| p.topology_members(), Some(include_span))
+    }
+
+    fn tlm_packet_members(&self, file: SourceFile, include_span: Span) -> Vec<TlmPacketMember> {
+        parse(file, |p| p.tlm_packet_members(), Some(include_span))
+    }
+
+    fn tlm_packet_set_members(
+        &self,
+        file: SourceFile,
+        include_span: Span,
+    ) -> Vec<TlmPacketSetMember> {
+        parse(file, |p| p.tlm_packet_set_members(), Some(include_span))
+    }
+}
+
+pub struct ResolveIncludes<Reader: FileReader, P: IncludeParser = DefaultIncludeParser> {
     reader: Reader,
+    parser: P,
 }
 
 impl<Reader: FileReader> ResolveIncludes<Reader> {
     pub fn new(reader: Reader) -> ResolveIncludes<Reader> {
-        ResolveIncludes { reader }
+        ResolveIncludes {
+            reader,
+            parser: DefaultIncludeParser,
+        }
+    }
+}
+
+impl<Reader: FileReader, P: IncludeParser> ResolveIncludes<Reader, P> {
+    pub fn with_parser(reader: Reader, parser: P) -> ResolveIncludes<Reader, P> {
+        ResolveIncludes { reader, parser }
     }
 
     fn check_loc_for_cycle(
@@ -62,22 +115,19 @@ impl<Reader: FileReader> ResolveIncludes<Reader> {
         a: &mut ResolveIncludesState,
         kind: IncludeParentKind,
         spec_include: &SpecInclude,
-        parser: fn(&mut Parser) -> Vec<T>,
-        transformer: fn(&ResolveIncludes<Reader>, &mut ResolveIncludesState, T, &mut Vec<T>),
+        parser: fn(&P, SourceFile, Span) -> Vec<T>,
+        transformer: fn(&ResolveIncludes<Reader, P>, &mut ResolveIncludesState, T, &mut Vec<T>),
         out: &mut Vec<T>,
     ) {
-        let file_path = match self
+        let (file_path, content) = match self
             .reader
-            .resolve(spec_include.span().file(), &spec_include.file.data)
+            .find(spec_include.span().file(), &spec_include.file.data)
         {
-            Ok(file_path) => file_path,
+            Ok(found) => found,
             Err(err) => {
-                fpp_core::Diagnostic::new(
-                    spec_include.file.span(),
-                    fpp_core::Level::Error,
-                    err.to_string(),
-                )
-                .emit();
+                err.diagnostic(spec_include.file.span(), &spec_include.file.data)
+                    .code("E0007")
+                    .emit();
                 return;
             }
         };
@@ -90,24 +140,10 @@ impl<Reader: FileReader> ResolveIncludes<Reader> {
             }
         };
 
-        // Read the file
-        let content = match self.reader.read(&file_path) {
-            Ok(content) => content,
-            Err(err) => {
-                fpp_core::Diagnostic::new(
-                    spec_include.file.span(),
-                    fpp_core::Level::Error,
-                    err.to_string(),
-                )
-                .emit();
-                return;
-            }
-        };
-
         let file = SourceFile::new_with_parent(&file_path, content, spec_include.span().file());
         a.insert(file, kind);
 
-        let members = parse(file, parser, Some(spec_include.span()));
+        let members = parser(&self.parser, file, spec_include.span());
         for member in members {
             transformer(self, a, member, out);
         }
@@ -124,7 +160,7 @@ impl<Reader: FileReader> ResolveIncludes<Reader> {
                 a,
                 IncludeParentKind::Component,
                 spec_include,
-                |p| p.component_members(),
+                P::component_members,
                 Self::component_member,
                 out,
             ),
@@ -147,7 +183,7 @@ impl<Reader: FileReader> ResolveIncludes<Reader> {
                     a,
                     IncludeParentKind::Topology,
                     spec_include,
-                    |p| p.topology_members(),
+                    P::topology_members,
                     Self::topology_member,
                     out,
                 );
@@ -171,7 +207,7 @@ impl<Reader: FileReader> ResolveIncludes<Reader> {
                     a,
                     IncludeParentKind::Module,
                     spec_include,
-                    |p| p.module_members(),
+                    P::module_members,
                     Self::module_member,
                     out,
                 );
@@ -195,7 +231,7 @@ impl<Reader: FileReader> ResolveIncludes<Reader> {
                     a,
                     IncludeParentKind::TlmPacket,
                     spec_include,
-                    |p| p.tlm_packet_members(),
+                    P::tlm_packet_members,
                     Self::tlm_packet_member,
                     out,
                 );
@@ -219,7 +255,7 @@ impl<Reader: FileReader> ResolveIncludes<Reader> {
                     a,
                     IncludeParentKind::TlmPacketSet,
                     spec_include,
-                    |p| p.tlm_packet_set_members(),
+                    P::tlm_packet_set_members,
                     Self::tlm_packet_set_member,
                     out,
                 );
@@ -232,7 +268,7 @@ impl<Reader: FileReader> ResolveIncludes<Reader> {
     }
 }
 
-impl<Reader: FileReader> MutVisitor for ResolveIncludes<Reader> {
+impl<Reader: FileReader, P: IncludeParser> MutVisitor for ResolveIncludes<Reader, P> {
     type Break = ();
     type State = ResolveIncludesState;
 
@@ -12,4 +12,6 @@ pub use parser::parse;
 mod tests {
     mod cursor;
     mod parser;
+    mod search_paths;
+    mod unparse;
 }
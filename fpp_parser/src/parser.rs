@@ -10,6 +10,8 @@ use fpp_lexer::{KeywordKind, TokenKind};
 pub struct Parser<'a> {
     cursor: Cursor<'a>,
     include_span: Option<fpp_core::Span>,
+    /// Number of syntax errors reported so far
+    errors: usize,
 }
 
 enum ElementParsingResult<T> {
@@ -29,6 +31,7 @@ pub fn parse<T>(
     let mut parser = Parser {
         cursor: Cursor::new(source_file, content.as_ref(), include_span),
         include_span,
+        errors: 0,
     };
 
     let out = entry(&mut parser);
@@ -65,6 +68,57 @@ impl<'a> Parser<'a> {
         ))
     }
 
+    /// Create a node for a definition with a body
+    /// If any syntax errors were reported since `errors_before`, the
+    /// node is marked as recovered since it may be missing members.
+    fn body_node(&self, first_token: fpp_core::Span, errors_before: usize) -> fpp_core::Node {
+        let node = self.node(first_token);
+        if self.errors != errors_before {
+            node.mark_recovered();
+        }
+
+        node
+    }
+
+    fn emit_error(&mut self, err: ParseError) {
+        self.errors += 1;
+        let diagnostic: fpp_core::Diagnostic = err.into();
+        diagnostic.emit();
+    }
+
+    /// Consume the closing brace of a definition body
+    /// A missing closing brace is reported without failing the entire definition
+    fn close_body(&mut self) {
+        match self.consume(RightCurly) {
+            Ok(_) => {}
+            Err(err) => self.emit_error(err),
+        }
+    }
+
+    /// Skip tokens until the next member boundary at the current nesting level
+    /// Returns true if the end of the sequence was reached
+    fn recover_member(&mut self, punct: TokenKind, end: TokenKind) -> bool {
+        let mut depth = 0usize;
+        loop {
+            let current = self.peek(0);
+            if current == EOF || (depth == 0 && current == end) {
+                return true;
+            }
+
+            match current {
+                LeftCurly | LeftParen | LeftSquare => depth += 1,
+                RightCurly | RightParen | RightSquare => depth = depth.saturating_sub(1),
+                _ if depth == 0 && (current == punct || current == Eol) => {
+                    self.next();
+                    return false;
+                }
+                _ => {}
+            }
+
+            self.next();
+        }
+    }
+
     fn name(&mut self) -> ParseResult<Name> {
         let ident = self.consume(Identifier)?;
         Ok(Name {
@@ -201,11 +255,12 @@ impl<'a> Parser<'a> {
         let name = self.name()?;
 
         self.consume(LeftCurly)?;
-        let members = self.component_members();
-        self.consume(RightCurly)?;
+        let errors = self.errors;
+        let members = self.annotated_element_sequence(&Parser::component_member, Semi, RightCurly);
+        self.close_body();
 
         Ok(DefComponent {
-            node_id: self.node(first.span()),
+            node_id: self.body_node(first.span(), errors),
             kind,
             name,
             members,
@@ -217,7 +272,7 @@ impl<'a> Parser<'a> {
     }
 
     pub fn component_members(&mut self) -> Vec<ComponentMember> {
-        self.annotated_element_sequence(&Parser::component_member, Semi, RightCurly)
+        self.annotated_element_sequence(&Parser::component_member, Semi, EOF)
     }
 
     fn component_kind(&mut self) -> ParseResult<(ComponentKind, Token)> {
@@ -343,18 +398,19 @@ impl<'a> Parser<'a> {
         let first = self.consume_keyword(Module)?;
         let name = self.name()?;
         self.consume(LeftCurly)?;
-        let members = self.module_members();
-        self.consume(RightCurly)?;
+        let errors = self.errors;
+        let members = self.annotated_element_sequence(&Parser::module_member, Semi, RightCurly);
+        self.close_body();
 
         Ok(DefModule {
-            node_id: self.node(first.span()),
+            node_id: self.body_node(first.span(), errors),
             name,
             members,
         })
     }
 
     pub fn module_members(&mut self) -> Vec<ModuleMember> {
-        self.annotated_element_sequence(&Parser::module_member, Semi, RightCurly)
+        self.annotated_element_sequence(&Parser::module_member, Semi, EOF)
     }
 
     fn module_member(&mut self) -> ParseResult<ModuleMember> {
@@ -518,11 +574,12 @@ impl<'a> Parser<'a> {
         };
 
         self.consume(LeftCurly)?;
-        let members = self.topology_members();
-        self.consume(RightCurly)?;
+        let errors = self.errors;
+        let members = self.annotated_element_sequence(&Parser::topology_member, Semi, RightCurly);
+        self.close_body();
 
         Ok(DefTopology {
-            node_id: self.node(first.span()),
+            node_id: self.body_node(first.span(), errors),
             name,
             members,
             implements,
@@ -530,7 +587,7 @@ impl<'a> Parser<'a> {
     }
 
     pub fn topology_members(&mut self) -> Vec<TopologyMember> {
-        self.annotated_element_sequence(&Parser::topology_member, Semi, RightCurly)
+        self.annotated_element_sequence(&Parser::topology_member, Semi, EOF)
     }
 
     fn topology_member(&mut self) -> ParseResult<TopologyMember> {
@@ -716,8 +773,10 @@ impl<'a> Parser<'a> {
         self.consume_keyword(Packets)?;
         let name = self.name()?;
         self.consume(LeftCurly)?;
-        let members = self.tlm_packet_set_members();
-        self.consume(RightCurly)?;
+        let errors = self.errors;
+        let members =
+            self.annotated_element_sequence(&Parser::tlm_packet_set_member, Comma, RightCurly);
+        self.close_body();
 
         let omitted = match self.peek(0) {
             Keyword(Omit) => {
@@ -725,14 +784,14 @@ impl<'a> Parser<'a> {
                 self.consume(LeftCurly)?;
                 let omit =
                     self.element_sequence(&Parser::tlm_channel_identifier, Comma, RightCurly);
-                self.consume(RightCurly)?;
+                self.close_body();
                 omit
             }
             _ => vec![],
         };
 
         Ok(SpecTlmPacketSet {
-            node_id: self.node(first.span()),
+            node_id: self.body_node(first.span(), errors),
             name,
             members,
             omitted,
@@ -740,7 +799,7 @@ impl<'a> Parser<'a> {
     }
 
     pub fn tlm_packet_set_members(&mut self) -> Vec<TlmPacketSetMember> {
-        self.annotated_element_sequence(&Parser::tlm_packet_set_member, Comma, RightCurly)
+        self.annotated_element_sequence(&Parser::tlm_packet_set_member, Comma, EOF)
     }
 
     fn tlm_packet_set_member(&mut self) -> ParseResult<TlmPacketSetMember> {
@@ -761,11 +820,12 @@ impl<'a> Parser<'a> {
         self.consume_keyword(Group)?;
         let group = self.expr()?;
         self.consume(LeftCurly)?;
-        let members = self.tlm_packet_members();
-        self.consume(RightCurly)?;
+        let errors = self.errors;
+        let members = self.element_sequence(&Parser::tlm_packet_member, Comma, RightCurly);
+        self.close_body();
 
         Ok(SpecTlmPacket {
-            node_id: self.node(first.span()),
+            node_id: self.body_node(first.span(), errors),
             name,
             id,
             group,
@@ -774,7 +834,7 @@ impl<'a> Parser<'a> {
     }
 
     pub fn tlm_packet_members(&mut self) -> Vec<TlmPacketMember> {
-        self.element_sequence(&Parser::tlm_packet_member, Comma, RightCurly)
+        self.element_sequence(&Parser::tlm_packet_member, Comma, EOF)
     }
 
     fn tlm_packet_member(&mut self) -> ParseResult<TlmPacketMember> {
@@ -948,11 +1008,12 @@ impl<'a> Parser<'a> {
         let first = self.consume_keyword(Interface)?;
         let name = self.name()?;
         self.consume(LeftCurly)?;
+        let errors = self.errors;
         let members = self.annotated_element_sequence(&Parser::interface_member, Semi, RightCurly);
-        self.consume(RightCurly)?;
+        self.close_body();
 
         Ok(DefInterface {
-            node_id: self.node(first.span()),
+            node_id: self.body_node(first.span(), errors),
             name,
             members,
         })
@@ -997,9 +1058,10 @@ impl<'a> Parser<'a> {
         };
 
         self.consume(LeftCurly)?;
+        let errors = self.errors;
         let constants =
             self.annotated_element_sequence(&Parser::def_enum_constant, Comma, RightCurly);
-        self.consume(RightCurly)?;
+        self.close_body();
 
         let default = match self.peek(0) {
             Keyword(Default) => {
@@ -1010,7 +1072,7 @@ impl<'a> Parser<'a> {
         };
 
         Ok(DefEnum {
-            node_id: self.node(first.span()),
+            node_id: self.body_node(first.span(), errors),
             name,
             type_name,
             constants,
@@ -1084,6 +1146,7 @@ impl<'a> Parser<'a> {
         self.consume_keyword(Machine)?;
 
         let name = self.name()?;
+        let errors = self.errors;
         let members = match self.peek(0) {
             LeftCurly => {
                 self.next();
@@ -1093,7 +1156,7 @@ impl<'a> Parser<'a> {
                     RightCurly,
                 );
 
-                self.consume(RightCurly)?;
+                self.close_body();
 
                 Some(out)
             }
@@ -1101,7 +1164,7 @@ impl<'a> Parser<'a> {
         };
 
         Ok(DefStateMachine {
-            node_id: self.node(first.span()),
+            node_id: self.body_node(first.span(), errors),
             name,
             members,
         })
@@ -1633,9 +1696,10 @@ impl<'a> Parser<'a> {
         let name = self.name()?;
 
         self.consume(LeftCurly)?;
+        let errors = self.errors;
         let members =
             self.annotated_element_sequence(&Parser::struct_type_member, Comma, RightCurly);
-        self.consume(RightCurly)?;
+        self.close_body();
 
         let default = match self.peek(0) {
             Keyword(Default) => {
@@ -1646,7 +1710,7 @@ impl<'a> Parser<'a> {
         };
 
         Ok(DefStruct {
-            node_id: self.node(first.span()),
+            node_id: self.body_node(first.span(), errors),
             name,
             members,
             default,
@@ -1685,20 +1749,21 @@ impl<'a> Parser<'a> {
     fn def_state(&mut self) -> ParseResult<DefState> {
         let first = self.consume_keyword(State)?;
         let name = self.name()?;
+        let errors = self.errors;
 
         let members = match self.peek(0) {
             LeftCurly => {
                 self.next();
                 let members =
                     self.annotated_element_sequence(&Parser::state_member, Semi, RightCurly);
-                self.consume(RightCurly)?;
+                self.close_body();
                 members
             }
             _ => vec![],
         };
 
         Ok(DefState {
-            node_id: self.node(first.span()),
+            node_id: self.body_node(first.span(), errors),
             name,
             members,
         })
@@ -2131,25 +2196,30 @@ impl<'a> Parser<'a> {
                 }
                 ElementParsingResult::Unterminated(el) => {
                     out.push(el);
-                    break;
+
+                    // Elements must be separated unless this is the last element
+                    let current = self.peek(0);
+                    if current == end || current == EOF {
+                        break;
+                    }
+
+                    let err = self
+                        .cursor
+                        .err_expected_token("unexpected token", punct, current);
+                    self.emit_error(err);
+                    if self.recover_member(punct, end) {
+                        break;
+                    }
                 }
                 ElementParsingResult::None => {
                     break;
                 }
                 ElementParsingResult::Err(err) => {
-                    fpp_core::Diagnostic::from(err.into()).emit();
-
-                    // Recover to an anchor
-                    loop {
-                        let current = self.peek(0);
-                        if current == punct || current == Eol || current == EOF {
-                            self.next();
-                            break;
-                        } else if current == end {
-                            return out;
-                        }
+                    self.emit_error(err);
 
-                        self.next();
+                    // Recover to the next element
+                    if self.recover_member(punct, end) {
+                        break;
                     }
                 }
             }
@@ -2210,25 +2280,30 @@ impl<'a> Parser<'a> {
                 }
                 ElementParsingResult::Unterminated(el) => {
                     out.push(el);
-                    break;
+
+                    // Elements must be separated unless this is the last element
+                    let current = self.peek(0);
+                    if current == end || current == EOF {
+                        break;
+                    }
+
+                    let err = self
+                        .cursor
+                        .err_expected_token("unexpected token", punct, current);
+                    self.emit_error(err);
+                    if self.recover_member(punct, end) {
+                        break;
+                    }
                 }
                 ElementParsingResult::None => {
                     break;
                 }
                 ElementParsingResult::Err(err) => {
-                    fpp_core::Diagnostic::from(err.into()).emit();
-
-                    // Recover to an anchor
-                    loop {
-                        let current = self.peek(0);
-                        if current == punct || current == Eol || current == EOF {
-                            self.next();
-                            break;
-                        } else if current == end {
-                            return out;
-                        }
+                    self.emit_error(err);
 
-                        self.next();
+                    // Recover to the next element
+                    if self.recover_member(punct, end) {
+                        break;
                     }
                 }
             }
@@ -2256,7 +2331,7 @@ impl<'a> Parser<'a> {
                     ParseError::ExpectedToken {
                         expected: Identifier,
                         got: self.peek(0),
-                        last: dot_token.span,
+                        span: dot_token.span,
                         msg: "expected identifier",
                     }
                     .into(),
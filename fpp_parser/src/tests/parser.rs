@@ -7,6 +7,15 @@ use std::path::PathBuf;
 use std::{env, fs};
 
 fn run_test(file_path: &str) {
+    run_test_impl(file_path, false)
+}
+
+/// Parser recovery tests show both the diagnostics and the recovered AST
+fn run_recovery_test(file_path: &str) {
+    run_test_impl(file_path, true)
+}
+
+fn run_test_impl(file_path: &str, show_recovered_ast: bool) {
     let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
     path.push("src/tests");
 
@@ -18,7 +27,7 @@ fn run_test(file_path: &str) {
     ref_file.push(file_path);
     ref_file.set_extension("ref.txt");
 
-    let file_reader = FsReader {};
+    let file_reader = FsReader::default();
 
     let mut diagnostics_str = vec![];
     let mut ctx =
@@ -41,7 +50,13 @@ fn run_test(file_path: &str) {
     let output = if diagnostics_str.is_empty() {
         ast
     } else {
-        String::from_utf8(diagnostics_str).expect("failed to convert error message to string")
+        let diagnostics =
+            String::from_utf8(diagnostics_str).expect("failed to convert error message to string");
+        if show_recovered_ast {
+            format!("{}{}", diagnostics, ast)
+        } else {
+            diagnostics
+        }
     }
     .replace(path.to_str().unwrap(), "[ local path prefix ]");
 
@@ -138,6 +153,31 @@ fn parse_error() {
     run_test("parse-error")
 }
 
+#[test]
+fn recovery_component() {
+    run_recovery_test("recovery-component")
+}
+
+#[test]
+fn recovery_module() {
+    run_recovery_test("recovery-module")
+}
+
+#[test]
+fn recovery_state_machine() {
+    run_recovery_test("recovery-state-machine")
+}
+
+#[test]
+fn recovery_topology() {
+    run_recovery_test("recovery-topology")
+}
+
+#[test]
+fn recovery_tlm_packets() {
+    run_recovery_test("recovery-tlm-packets")
+}
+
 #[test]
 fn state_machine() {
     run_test("state-machine")
@@ -0,0 +1,132 @@
+use crate::{parse, ResolveIncludes};
+use fpp_ast::{ModuleMember, MutVisitor};
+use fpp_core::{FileReader, SearchPaths, SourceFile};
+use fpp_fs::{FsReader, OverlayReader};
+use pretty_assertions::assert_eq;
+use std::path::PathBuf;
+
+/// A model kept in memory, with the F Prime definitions vendored under `/lib/fprime`
+fn reader(search_paths: SearchPaths) -> OverlayReader {
+    let mut reader = OverlayReader::new(FsReader::new(search_paths));
+    reader.insert("/lib/fprime/Fw/Cmd.fppi", "constant cmdOpcode = 0");
+    reader.insert("/lib/fprime/Fw/Log.fppi", "constant logSeverity = 1");
+    reader.insert("/project/Ref/Consts.fppi", "constant local = 2");
+    reader
+}
+
+/// Resolve the includes of `content`, returning the names of the constants
+/// defined and the diagnostics reported
+fn resolve(reader: OverlayReader, content: &str) -> (Vec<String>, String) {
+    let mut diagnostics = vec![];
+    let mut ctx = fpp_core::CompilerContext::new(fpp_errors::WriteEmitter::new(&mut diagnostics));
+    let constants = fpp_core::run(&mut ctx, || {
+        let mut reader = reader;
+        reader.insert("/project/Ref/Top.fpp", content);
+        let file = SourceFile::new("/project/Ref/Top.fpp", content.to_string());
+        let mut ast = parse(file, |p| p.trans_unit(), None);
+        let mut source_files = Default::default();
+        let _ = ResolveIncludes::new(reader).visit_trans_unit(&mut source_files, &mut ast);
+
+        ast.0
+            .iter()
+            .filter_map(|member| match member {
+                ModuleMember::DefConstant(def) => Some(def.name.data.clone()),
+                _ => None,
+            })
+            .collect()
+    });
+
+    (constants, String::from_utf8(diagnostics).unwrap())
+}
+
+#[test]
+fn relative_to_including_file() {
+    let (constants, diagnostics) = resolve(reader(Default::default()), "include \"Consts.fppi\"");
+    assert_eq!(diagnostics, "");
+    assert_eq!(constants, vec!["local"]);
+}
+
+#[test]
+fn include_path() {
+    let search_paths = SearchPaths {
+        include: vec![PathBuf::from("/lib/fprime")],
+        remap: vec![],
+    };
+
+    let (constants, diagnostics) = resolve(reader(search_paths), "include \"Fw/Cmd.fppi\"");
+    assert_eq!(diagnostics, "");
+    assert_eq!(constants, vec!["cmdOpcode"]);
+}
+
+#[test]
+fn remap() {
+    let search_paths = SearchPaths {
+        include: vec![],
+        remap: vec![
+            ("fprime".to_string(), PathBuf::from("/lib/fprime")),
+            (
+                "fprime/Fw/Log.fppi".to_string(),
+                PathBuf::from("/lib/fprime/Fw/Cmd.fppi"),
+            ),
+        ],
+    };
+
+    let (constants, diagnostics) = resolve(
+        reader(search_paths),
+        "include \"fprime/Fw/Log.fppi\"\ninclude \"fprime/Fw/../Fw/Cmd.fppi\"",
+    );
+    assert_eq!(diagnostics, "");
+    assert_eq!(constants, vec!["cmdOpcode", "cmdOpcode"]);
+}
+
+#[test]
+fn remap_whole_components() {
+    let search_paths = SearchPaths {
+        include: vec![],
+        remap: vec![("fp".to_string(), PathBuf::from("/lib/fprime"))],
+    };
+
+    assert_eq!(
+        search_paths.remap("fp/Fw/Cmd.fppi"),
+        "/lib/fprime/Fw/Cmd.fppi"
+    );
+    assert_eq!(search_paths.remap("fp"), "/lib/fprime");
+    assert_eq!(
+        search_paths.remap("fprime/Fw/Cmd.fppi"),
+        "fprime/Fw/Cmd.fppi"
+    );
+}
+
+#[test]
+fn not_found() {
+    let search_paths = SearchPaths {
+        include: vec![PathBuf::from("/lib/fprime"), PathBuf::from("/lib/other")],
+        remap: vec![],
+    };
+
+    let (constants, diagnostics) = resolve(reader(search_paths), "include \"Svc/Health.fppi\"");
+    assert_eq!(constants, Vec::<String>::new());
+    assert_eq!(
+        diagnostics,
+        r#" --> /project/Ref/Top.fpp:1:9
+  |
+1 | include "Svc/Health.fppi"
+  |         ^^^^^^^^^^^^^^^^^ cannot find `Svc/Health.fppi` in any of the search paths [E0007]
+  |
+  = note: tried /project/Ref/Svc/Health.fppi
+  = note: tried /lib/fprime/Svc/Health.fppi
+  = note: tried /lib/other/Svc/Health.fppi
+
+"#
+    );
+}
+
+#[test]
+fn overlay_hides_disk() {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/src/tests/constant.fppi");
+    let mut reader = OverlayReader::new(FsReader::default());
+    assert!(reader.read(path).unwrap().contains("constant"));
+
+    reader.insert(path, "");
+    assert_eq!(reader.read(path).unwrap(), "");
+}
@@ -0,0 +1,290 @@
+use crate::{parse, IncludeParentKind, ResolveIncludes};
+use fpp_ast::unparse;
+use fpp_ast::{Expr, ExprKind, ModuleMember, MutVisitor, TransUnit};
+use fpp_core::SourceFile;
+use fpp_fs::FsReader;
+use pretty_assertions::assert_eq;
+use rustc_hash::FxHashMap;
+use std::fmt::Debug;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Collect the FPP test inputs of every crate in the workspace, included
+/// files among them
+fn test_inputs(dir: &Path, out: &mut Vec<PathBuf>) {
+    for entry in fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            if !path.ends_with("target") && !path.ends_with(".git") {
+                test_inputs(&path, out);
+            }
+        } else if path
+            .extension()
+            .is_some_and(|ext| ext == "fpp" || ext == "fppi")
+            && path.to_str().unwrap().contains("/src/test")
+        {
+            out.push(path);
+        }
+    }
+}
+
+/// Run `f` in a fresh compiler context, returning its result and the
+/// diagnostics reported
+fn with_context<T>(f: impl FnOnce() -> T) -> (T, String) {
+    let mut diagnostics = vec![];
+    let mut ctx = fpp_core::CompilerContext::new(fpp_errors::WriteEmitter::new(&mut diagnostics));
+    let out = fpp_core::run(&mut ctx, f);
+
+    (out, String::from_utf8(diagnostics).unwrap())
+}
+
+fn parse_str(uri: &str, content: String) -> TransUnit {
+    parse(SourceFile::new(uri, content), |p| p.trans_unit(), None)
+}
+
+/// The kind of members each file included by the translation units in
+/// `files` holds, keyed by canonical path
+fn include_kinds(files: &[PathBuf]) -> FxHashMap<PathBuf, IncludeParentKind> {
+    let mut kinds = FxHashMap::default();
+    for file in files
+        .iter()
+        .filter(|file| file.extension().unwrap() == "fpp")
+    {
+        let uri = file.to_str().unwrap();
+        let content = fs::read_to_string(file).unwrap();
+        let (included, _) = with_context(|| {
+            let mut ast = parse_str(uri, content);
+            let mut included = FxHashMap::default();
+            let _ =
+                ResolveIncludes::new(FsReader::default()).visit_trans_unit(&mut included, &mut ast);
+            included
+                .into_iter()
+                .map(|(file, kind)| (file.uri(), kind))
+                .collect::<Vec<_>>()
+        });
+
+        for (uri, kind) in included {
+            if let Ok(path) = fs::canonicalize(uri) {
+                kinds.insert(path, kind);
+            }
+        }
+    }
+
+    kinds
+}
+
+/// The debug rendering of an AST without source locations. Nodes compare by
+/// identity, so this is how two parses of different text are compared.
+/// Annotations are part of the rendering.
+fn without_spans<T: Debug>(ast: &T) -> String {
+    let mut text = format!("{:?}", ast);
+    while let Some(start) = text.find("Span { start: ") {
+        let end = start + text[start..].find(" }").unwrap() + 2;
+        text.replace_range(start..end, "Span");
+    }
+
+    text
+}
+
+/// Parse `content` as a translation unit or, given the kind of its includer,
+/// as the members of an included file. Returns the rendering of the AST
+/// without spans along with its printed source.
+fn render(uri: &str, content: String, kind: Option<IncludeParentKind>) -> (String, String) {
+    fn members<T: Debug>(members: Vec<T>, print: fn(&T) -> String) -> (String, String) {
+        let printed: Vec<String> = members.iter().map(print).collect();
+        (without_spans(&members), printed.join("\n\n"))
+    }
+
+    let file = SourceFile::new(uri, content);
+    match kind {
+        None => {
+            let ast = parse(file, |p| p.trans_unit(), None);
+            (without_spans(&ast), unparse::trans_unit(&ast))
+        }
+        Some(IncludeParentKind::Module) => members(
+            parse(file, |p| p.module_members(), None),
+            unparse::module_member,
+        ),
+        Some(IncludeParentKind::Component) => members(
+            parse(file, |p| p.component_members(), None),
+            unparse::component_member,
+        ),
+        Some(IncludeParentKind::Topology) => members(
+            parse(file, |p| p.topology_members(), None),
+            unparse::topology_member,
+        ),
+        Some(IncludeParentKind::TlmPacketSet) => members(
+            parse(file, |p| p.tlm_packet_set_members(), None),
+            unparse::tlm_packet_set_member,
+        ),
+        Some(IncludeParentKind::TlmPacket) => members(
+            parse(file, |p| p.tlm_packet_members(), None),
+            unparse::tlm_packet_member,
+        ),
+    }
+}
+
+#[test]
+fn round_trip() {
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("..");
+    let mut files = vec![];
+    test_inputs(&root, &mut files);
+    files.sort();
+    assert!(files.iter().any(|file| file.extension().unwrap() == "fppi"));
+
+    let kinds = include_kinds(&files);
+    let mut mismatched = vec![];
+    for file in &files {
+        let uri = file.to_str().unwrap();
+        let content = fs::read_to_string(file).unwrap();
+        let kind = match file.extension().unwrap() == "fpp" {
+            true => None,
+            // Files no test includes hold module members
+            false => Some(
+                kinds
+                    .get(&fs::canonicalize(file).unwrap())
+                    .copied()
+                    .unwrap_or(IncludeParentKind::Module),
+            ),
+        };
+
+        let (_, diagnostics) = with_context(|| render(uri, content.clone(), kind));
+        if !diagnostics.is_empty() {
+            // Syntax error tests
+            continue;
+        }
+
+        let ((expected, printed, actual), diagnostics) = with_context(|| {
+            let (expected, printed) = render(uri, content, kind);
+            let (actual, _) = render(&format!("{}.printed", uri), printed.clone(), kind);
+            (expected, printed, actual)
+        });
+        assert_eq!(diagnostics, "", "printing {} gave\n{}", uri, printed);
+
+        if expected != actual {
+            mismatched.push(uri.to_string());
+        }
+    }
+
+    assert_eq!(mismatched, Vec::<String>::new());
+}
+
+#[test]
+fn annotations() {
+    let src = r#"@ Module
+module M {
+  @ Constant
+  constant c = 1 @< after constant
+
+  @ Enum
+  enum E {
+    @ Enum constant
+    A @< after enum constant
+    B
+  }
+
+  struct S {
+    @ Struct member
+    x: U32 @< after struct member
+  }
+
+  port P(
+    @ Param
+    a: U32 @< after param
+  )
+
+  @ Component
+  passive component C {
+    @ Port instance
+    sync input port p: P @< after port instance
+  }
+
+  instance c1: C base id 0x100
+
+  topology T {
+    @ Instance
+    instance c1 @< after instance
+
+    @ Packets
+    telemetry packets Packets {
+      @ Packet
+      packet P1 group 0 {
+        c1.x
+      } @< after packet
+    }
+  }
+} @< after module
+"#;
+
+    let ((expected, actual), diagnostics) = with_context(|| {
+        let (expected, printed) = render("annotations.fpp", src.to_string(), None);
+        let (actual, _) = render("annotations.fpp.printed", printed, None);
+        (expected, actual)
+    });
+    assert_eq!(diagnostics, "");
+    assert_eq!(expected, actual);
+
+    for line in src.lines().filter_map(|line| line.split_once('@')) {
+        let text = line.1.trim_start_matches('<').trim();
+        assert!(
+            actual.contains(&format!("{:?}", text)),
+            "annotation `{}` was lost in\n{}",
+            text,
+            actual
+        );
+    }
+}
+
+fn strip_parens(e: &mut Expr) {
+    if let ExprKind::Paren(inner) = &mut e.kind {
+        *e = (**inner).clone();
+        strip_parens(e);
+        return;
+    }
+
+    match &mut e.kind {
+        ExprKind::Array(elements) => elements.iter_mut().for_each(strip_parens),
+        ExprKind::ArraySubscript { e1, e2 } => {
+            strip_parens(e1);
+            strip_parens(e2);
+        }
+        ExprKind::Binop { left, right, .. } => {
+            strip_parens(left);
+            strip_parens(right);
+        }
+        ExprKind::Dot { e, .. } | ExprKind::Unop { e, .. } => strip_parens(e),
+        ExprKind::Struct(members) => members
+            .iter_mut()
+            .for_each(|member| strip_parens(&mut member.value)),
+        _ => {}
+    }
+}
+
+#[test]
+fn parenthesization() {
+    let cases = [
+        ("a - (b - c)", "a - (b - c)"),
+        ("(a - b) - c", "a - b - c"),
+        ("(a + b) * (c / d)", "(a + b) * (c / d)"),
+        ("a * (b * c)", "a * (b * c)"),
+        ("-(a + b) * c", "-(a + b) * c"),
+        ("-(-a)", "-(-a)"),
+        ("((a))[(1 + 2)].b", "a[1 + 2].b"),
+        ("{ x = (1 + 2) * 3 }", "{ x = (1 + 2) * 3 }"),
+    ];
+
+    for (input, expected) in cases {
+        let (printed, diagnostics) = with_context(|| {
+            let ast = parse_str("expr.fpp", format!("constant c = {}", input));
+            let ModuleMember::DefConstant(def) = &ast.0[0] else {
+                panic!("expected a constant");
+            };
+            let mut value = def.value.clone();
+            strip_parens(&mut value);
+            unparse::expr(&value)
+        });
+
+        assert_eq!(diagnostics, "");
+        assert_eq!(printed, expected);
+    }
+}